@@ -3,26 +3,35 @@ use crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
 use fuzzy_matcher::skim::SkimMatcherV2;
 use fuzzy_matcher::FuzzyMatcher;
 use ratatui::DefaultTerminal;
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
 use std::time::Duration;
 use tokio::sync::mpsc;
 
 use crate::claude::commands::{self, CustomCommand};
-use crate::claude::conversation::Conversation;
-use crate::claude::events::StreamEvent;
+use crate::claude::conversation::{ContentBlock, Conversation, Message, Role};
+use crate::claude::events::{McpServer, StreamEvent};
 use crate::claude::process::{ClaudeProcess, SpawnOptions};
 use crate::claude::sessions;
 use crate::config::Config;
+use crate::diff::FileEdit;
 use crate::git::GitInfo;
 use crate::history::InputHistory;
+use crate::pty::PtyProcess;
+use crate::snippets::{self, SnipCommand, Snippets};
+use crate::terminal::{self, TerminalEmulator};
 use crate::theme::Theme;
 use crate::todo::TodoTracker;
 use crate::ui;
-use crate::ui::header::{COMPACT_HEADER_HEIGHT, HEADER_HEIGHT};
+use crate::ui::claude_pane::{LineCache, RenderOptions, ToolPreview};
 use crate::ui::input::InputEditor;
 use crate::ui::overlay::{OverlayItem, OverlayState};
 use crate::ui::toast::Toast;
+use crate::workflows::{self, Workflow};
 
-/// Built-in workflow templates: (name, description, prompt).
+/// Built-in workflow templates: (name, description, prompt). `{name}` slots
+/// in a prompt are asked for before it is sent.
 const WORKFLOW_TEMPLATES: &[(&str, &str, &str)] = &[
     (
         "Code Review",
@@ -74,8 +83,90 @@ const WORKFLOW_TEMPLATES: &[(&str, &str, &str)] = &[
         "Audit project dependencies",
         "Review the project's dependencies. Check for outdated packages, known vulnerabilities, unused dependencies, and suggest any that could be replaced or removed.",
     ),
+    (
+        "Review File",
+        "Review one file in depth",
+        "Review {file} in depth. Look for bugs, unclear code, missing error handling and untested paths, and suggest concrete improvements with line numbers.",
+    ),
+    (
+        "Explain Symbol",
+        "Explain how a function or type works",
+        "Explain how {symbol} works. Show where it is defined and used, walk through what it does step by step, and point out anything surprising.",
+    ),
+    (
+        "Fix Failing Test",
+        "Find and fix the cause of a failing test",
+        "The test {test} is failing. Run it, read the failure, find the root cause in the code under test, and fix it without weakening the test.",
+    ),
 ];
 
+/// Session picker rows, newest first, at most 50.
+fn session_items(sessions: Vec<sessions::SessionInfo>) -> Vec<OverlayItem> {
+    sessions
+        .into_iter()
+        .take(50)
+        .map(|s| {
+            let label = if s.preview.is_empty() {
+                format!("{} ({})", s.project_path, s.age_string())
+            } else {
+                format!("{} — {}", s.age_string(), s.preview)
+            };
+            OverlayItem {
+                label,
+                value: s.session_id,
+                hint: s.project_path,
+                is_header: false,
+            }
+        })
+        .collect()
+}
+
+/// Unified diff of two sessions' replies, headed by their short IDs, or
+/// `None` when they differ in too many lines to compare.
+fn session_diff_lines(first: &str, old: &str, second: &str, new: &str) -> Option<Vec<String>> {
+    let short = |id: &str| id.chars().take(8).collect::<String>();
+    let mut lines = vec![format!("--- {}", short(first)), format!("+++ {}", short(second))];
+    let ops = crate::diff::diff_texts_bounded(old, new)?;
+    lines.extend(crate::diff::format_unified(&ops).lines().map(String::from));
+    Some(lines)
+}
+
+/// Built-in workflows followed by custom ones from `workflows.toml` files,
+/// plus the errors of files that failed to load.
+pub fn all_workflows() -> (Vec<Workflow>, Vec<String>) {
+    let (custom, errors) = workflows::load_all();
+    (builtin_workflows().chain(custom).collect(), errors)
+}
+
+/// `WORKFLOW_TEMPLATES` as workflows, for merging with custom ones.
+fn builtin_workflows() -> impl Iterator<Item = Workflow> {
+    WORKFLOW_TEMPLATES.iter().map(|(name, description, prompt)| Workflow {
+        name: name.to_string(),
+        description: description.to_string(),
+        prompt: prompt.to_string(),
+        custom: false,
+    })
+}
+
+/// The `{name}` slots of a workflow prompt, in order of first appearance.
+fn workflow_placeholders(prompt: &str) -> Vec<String> {
+    let mut names: Vec<String> = Vec::new();
+    let mut rest = prompt;
+    while let Some(start) = rest.find('{') {
+        rest = &rest[start + 1..];
+        let Some(end) = rest.find('}') else { break };
+        let name = &rest[..end];
+        if !name.is_empty()
+            && name.chars().all(|c| c.is_alphanumeric() || c == '_')
+            && !names.iter().any(|n| n == name)
+        {
+            names.push(name.to_string());
+        }
+        rest = &rest[end + 1..];
+    }
+    names
+}
+
 /// All known vanilla Claude Code slash commands with descriptions.
 /// Used as fallback when system.init doesn't include all commands.
 const KNOWN_SLASH_COMMANDS: &[(&str, &str)] = &[
@@ -114,12 +205,33 @@ const KNOWN_SLASH_COMMANDS: &[(&str, &str)] = &[
 enum Msg {
     ClaudeEvent(StreamEvent),
     ClaudeExited,
+    /// Event from the transient "Ask Another Model" process, tagged with
+    /// the query it answers.
+    AltEvent(u64, StreamEvent),
+    /// Output bytes from the split-pane terminal.
+    PtyOutput(Vec<u8>),
+    /// The split-pane terminal's shell exited.
+    PtyExited,
+    /// Scheduled check that claude has sent `system.init` since startup.
+    InitTimeout,
+    /// Scheduled resend of the last prompt after a transient error (`auto_retry`).
+    RetryPrompt,
     Key(event::KeyEvent),
     Paste(String),
     Resize(u16, u16),
     Tick,
 }
 
+impl Msg {
+    /// Whether the screen should be redrawn right after this message.
+    /// Stream events only mark the screen dirty; bursts of them are coalesced
+    /// into a single redraw on the next tick to avoid flicker on fast output.
+    /// Input is still drawn immediately so typing stays responsive.
+    fn renders_immediately(&self) -> bool {
+        !matches!(self, Msg::ClaudeEvent(_) | Msg::AltEvent(..) | Msg::PtyOutput(_))
+    }
+}
+
 /// Actions for commands handled locally (not sent to Claude).
 enum LocalAction {
     Clear,
@@ -128,8 +240,10 @@ enum LocalAction {
     ShowModel,
     ShowMemory,
     ShowPlugins,
+    ShowStats,
     Exit,
     ChangeTheme,
+    Snippet(SnipCommand),
 }
 
 /// A parsed question from AskUserQuestion tool input.
@@ -140,6 +254,53 @@ struct UserQuestion {
     header: String,
     options: Vec<UserQuestionOption>,
     multi_select: bool,
+    /// Answered by typing rather than picking: no options were given, or
+    /// the question asks for free text (`"freeText": true` / `"type": "text"`).
+    free_text: bool,
+}
+
+/// One file's changes in the diff viewer, foldable down to its header.
+struct DiffSection {
+    path: String,
+    added: usize,
+    removed: usize,
+    lines: Vec<String>,
+    collapsed: bool,
+}
+
+/// The diff viewer's lines with each section's header (`▼ path [+a -r]`)
+/// and, unless collapsed, its hunks. Also returns the line of every header.
+fn diff_viewer_lines(sections: &[DiffSection]) -> (Vec<String>, Vec<usize>) {
+    let mut lines = Vec::new();
+    let mut headers = Vec::new();
+    for section in sections {
+        if !lines.is_empty() {
+            lines.push(String::new());
+        }
+        headers.push(lines.len());
+        let marker = if section.collapsed { '\u{25B6}' } else { '\u{25BC}' };
+        lines.push(format!("{marker} {} [+{} -{}]", section.path, section.added, section.removed));
+        if !section.collapsed {
+            lines.extend(section.lines.iter().cloned());
+        }
+    }
+    (lines, headers)
+}
+
+/// A config macro part-way through: the steps left to send and the number
+/// of the step whose turn is running.
+struct MacroRun {
+    name: String,
+    remaining: VecDeque<String>,
+    step: usize,
+    total: usize,
+}
+
+/// A pending `can_use_tool` request from the CLI awaiting an allow/deny decision.
+struct PermissionRequest {
+    request_id: String,
+    tool_name: String,
+    input: serde_json::Value,
 }
 
 /// A single option in a UserQuestion.
@@ -185,8 +346,105 @@ pub enum SplitContent {
     FilePreview(String, Vec<String>),
     /// Unified diff view.
     DiffView(Vec<String>),
+    /// Grep/Glob matches (tool and pattern, result lines, selected line).
+    /// Shift+Up/Down moves between the matches and Alt+O opens one.
+    SearchResults(String, Vec<String>, usize),
+    /// Raw stream events as they arrive, for debugging (bounded to `EVENT_LOG_MAX_LINES`).
+    EventLog(VecDeque<String>),
+    /// Answer to the last prompt from another model (model, text, finished).
+    AltAnswer(String, String, bool),
+    /// Screen of the interactive terminal running in a PTY.
+    Terminal(Vec<ui::claude_pane::StyledLine>),
+}
+
+/// An overlay that was closed and can be brought back with Ctrl+Shift+K.
+/// Pickers and browsers are rebuilt from current state; text viewers come
+/// back as they were, at the same scroll position.
+#[derive(Clone)]
+enum ClosedOverlay {
+    Rebuild(fn(&mut App)),
+    Viewer {
+        title: String,
+        lines: Vec<String>,
+        scroll: usize,
+    },
+}
+
+/// A respawn of Claude that failed to start, kept so it can be retried.
+#[derive(Debug, Clone, PartialEq)]
+enum Respawn {
+    Resume {
+        session_id: String,
+        add_dir: Option<std::path::PathBuf>,
+    },
+    Continue,
+}
+
+/// A shell running in a real PTY, shown in the split pane.
+struct PtySession {
+    process: PtyProcess,
+    emulator: TerminalEmulator,
 }
 
+/// Models offered by "Ask Another Model" (label, `--model` value).
+const ALT_MODELS: &[(&str, &str)] = &[
+    ("Opus", "opus"),
+    ("Sonnet", "sonnet"),
+    ("Haiku", "haiku"),
+];
+
+/// Maximum number of events kept in the split-pane event log.
+const EVENT_LOG_MAX_LINES: usize = 500;
+
+/// Number of opening messages shown when previewing a session.
+const SESSION_PREVIEW_MESSAGES: usize = 6;
+
+/// Maximum number of unrecognised stdout lines kept for debugging
+/// (unbounded with `--verbose-unknown`).
+const UNKNOWN_LINES_MAX: usize = 200;
+
+/// Number of past toasts kept for the notifications viewer (Ctrl+X).
+const TOAST_HISTORY_MAX: usize = 50;
+
+/// How long the tool call landed on by Tab / Shift+Tab stays highlighted.
+const TOOL_JUMP_HIGHLIGHT: Duration = Duration::from_millis(1500);
+
+/// Tick rate while nothing is streaming or animating, to save CPU.
+const IDLE_FPS: u32 = 2;
+
+/// Archived messages shown in full per "load older" (Ctrl+L).
+const LOAD_OLDER_STEP: usize = 50;
+
+/// How long to wait for `system.init` before suspecting a login/auth problem.
+const INIT_TIMEOUT: Duration = Duration::from_secs(8);
+
+/// How long after `/clear` Ctrl+Z can bring the conversation back.
+const CLEAR_UNDO_WINDOW: Duration = Duration::from_secs(5);
+
+/// Columns panned per Shift+Left/Right while line wrapping is off.
+const HORIZONTAL_STEP: usize = 8;
+
+/// Pause before `auto_retry` resends a prompt that hit a transient error.
+const RETRY_DELAY: Duration = Duration::from_secs(2);
+
+/// Lowercase fragments of error results worth retrying: dropped connections,
+/// timeouts and temporarily unavailable or overloaded APIs.
+const TRANSIENT_ERRORS: &[&str] = &[
+    "connection reset",
+    "connection refused",
+    "connection error",
+    "econnreset",
+    "econnrefused",
+    "etimedout",
+    "timed out",
+    "timeout",
+    "socket hang up",
+    "network error",
+    "overloaded",
+    "service unavailable",
+    "bad gateway",
+];
+
 /// Tracks a sub-agent spawned via the Task tool.
 pub struct AgentTask {
     /// tool_use_id that created this agent.
@@ -195,15 +453,40 @@ pub struct AgentTask {
     pub description: String,
     /// Sub-agent type (e.g. "Bash", "Explore", "Plan").
     pub agent_type: String,
+    /// The full prompt the agent was given.
+    pub prompt: String,
     /// When this agent was spawned.
     pub started: std::time::Instant,
     /// Whether this agent has completed.
     pub completed: bool,
+    /// Marked for cancellation from the dashboard. claude has no way to stop
+    /// a single sub-agent, so this only flags it.
+    pub cancel_requested: bool,
+}
+
+/// A shell command run by the Bash tool during this session.
+pub struct BashCommand {
+    /// tool_use_id of the Bash call.
+    pub id: String,
+    /// The `command` field from the Bash tool input.
+    pub command: String,
+    /// Outcome from the matching ToolResult: `Some(true)` on error,
+    /// `None` while the command is still running.
+    pub is_error: Option<bool>,
 }
 
 /// What to do when a TextInput overlay is confirmed.
 enum TextInputAction {
     RenameSession,
+    /// Fill the first of `remaining` in a workflow prompt, then ask for the
+    /// next or send it once all are filled.
+    WorkflowParam {
+        name: String,
+        prompt: String,
+        remaining: Vec<String>,
+    },
+    /// Answer a free-text AskUserQuestion question.
+    AnswerQuestion { question: String },
 }
 
 enum AppMode {
@@ -216,6 +499,11 @@ enum AppMode {
         lines: Vec<String>,
         scroll: usize,
     },
+    /// A text viewer for the session details, where `c` copies the session ID.
+    SessionInfo {
+        lines: Vec<String>,
+        scroll: usize,
+    },
     HistorySearch {
         query: String,
         matches: Vec<String>,
@@ -234,16 +522,48 @@ enum AppMode {
         cursor: usize,
         /// For multi-select: tracks which options are toggled on.
         selected: Vec<bool>,
+        /// Set when this question is a tool permission prompt; the answer
+        /// goes back to the CLI as a control response instead of a message.
+        permission: Option<PermissionRequest>,
     },
     PluginBrowser {
+        /// The plugins matching `query`, best match first.
         plugins: Vec<PluginInfo>,
+        /// Every discovered plugin.
+        all: Vec<PluginInfo>,
+        /// Type-to-filter text, fuzzy-matched against name and description.
+        query: String,
+        /// Typed characters go to `query` (after `/`) rather than acting as keys.
+        searching: bool,
         cursor: usize,
         scroll: usize,
     },
     WorkflowPicker(OverlayState),
+    FileRefPicker(OverlayState),
+    ModelPicker(OverlayState),
+    /// Where to run a session created in another directory.
+    ResumeDirPicker(OverlayState),
+    /// Two sessions, one after the other, whose replies to diff.
+    ComparePicker(OverlayState),
+    /// Claude could not be restarted; offers a retry.
+    RespawnFailed(OverlayState),
+    /// Files touched by tools this session; Enter opens one in the editor.
+    FileContext(OverlayState),
+    /// Code blocks of the last assistant message; Enter copies one.
+    CodeBlockPicker(OverlayState),
+    /// Files edited this session; Enter shows one's cumulative diff.
+    FileDiffPicker(OverlayState),
+    /// First-run welcome: key shortcuts and an offer to write a config file.
+    Welcome(OverlayState),
     AgentDashboard {
         scroll: usize,
     },
+    /// Session edits grouped by file; `selected` is the focused section.
+    DiffViewer {
+        sections: Vec<DiffSection>,
+        selected: usize,
+        scroll: usize,
+    },
 }
 
 /// A single item in the slash command completion popup.
@@ -299,8 +619,25 @@ pub struct App {
     theme_name: String,
     scroll_offset: usize,
     auto_scroll: bool,
+    /// Height of the conversation viewport at the last render, used for
+    /// half-page scrolling.
+    visible_height: usize,
+    /// Wrap width of the conversation at the last render.
+    conversation_width: usize,
+    /// Rendered line of the tool call last jumped to, highlighted briefly.
+    tool_jump: Option<(usize, std::time::Instant)>,
+    /// First message that arrived since the last highlight, and the frame its
+    /// fade started (None while it arrived out of view, scrolled back).
+    fresh_messages: Option<(usize, Option<u64>)>,
+    /// The overlay most recently closed, for Ctrl+Shift+K.
+    last_overlay: Option<ClosedOverlay>,
+    /// Messages removed by the last `/clear`, restorable with Ctrl+Z until
+    /// `CLEAR_UNDO_WINDOW` has passed.
+    cleared: Option<(Vec<Message>, std::time::Instant)>,
     command: String,
     slash_commands: Vec<String>,
+    /// MCP servers from the last `system.init` and their connection states.
+    mcp_servers: Vec<McpServer>,
     custom_commands: Vec<CustomCommand>,
     completion: Option<CompletionState>,
     /// Tracks the last slash command sent, so we can show feedback for empty results.
@@ -311,6 +648,10 @@ pub struct App {
     session_id: Option<String>,
     /// Main event sender, stored so we can forward events from resumed processes.
     event_tx: Option<mpsc::UnboundedSender<Msg>>,
+    /// Whether the startup process has yet to send a recognised event.
+    awaiting_init: bool,
+    /// Whether the `INIT_TIMEOUT` countdown has started (at the first send).
+    init_check_armed: bool,
     /// Cumulative token usage for this session.
     total_input_tokens: u64,
     total_output_tokens: u64,
@@ -324,10 +665,28 @@ pub struct App {
     budget_override: Option<f64>,
     /// Resume a specific session by ID from CLI args.
     resume_session_id: Option<String>,
+    /// Working directory the Claude process runs in, when a session was
+    /// resumed into the directory it was created in.
+    session_cwd: Option<std::path::PathBuf>,
+    /// Session awaiting a choice in the resume directory picker, with the
+    /// directory it was created in.
+    pending_resume: Option<(String, std::path::PathBuf)>,
+    /// Session picked first in the compare picker, while choosing the second.
+    compare_first: Option<String>,
+    /// The resume/continue that failed to spawn Claude, for Retry.
+    failed_respawn: Option<Respawn>,
+    /// Interactive terminal shown in the split pane.
+    pty_session: Option<PtySession>,
+    /// Whether keystrokes go to the split-pane terminal.
+    pty_focused: bool,
     /// Current git repo info (branch, dirty count).
     git_info: GitInfo,
-    /// Frame counter at last git refresh (refresh every ~5s).
-    git_last_refresh: u64,
+    /// When git info was last refreshed.
+    git_last_refresh: std::time::Instant,
+    /// How long the last git info refresh took.
+    git_gather_cost: std::time::Duration,
+    /// Time between git refreshes; backs off on repos where gathering is slow.
+    git_refresh_interval: std::time::Duration,
     /// Tracks Claude's todo list from TodoWrite tool calls.
     todo_tracker: TodoTracker,
     /// Model name detected from the most recent MessageStart event.
@@ -336,19 +695,100 @@ pub struct App {
     history: InputHistory,
     /// Current position when browsing history with Up/Down arrow (None = not browsing).
     history_browse_index: Option<usize>,
+    /// Named prompt snippets managed with `/snip`.
+    snippets: Snippets,
+    /// Workflow templates: the built-ins followed by custom ones from
+    /// `workflows.toml`.
+    workflows: Vec<Workflow>,
     /// Whether all tool result blocks are expanded (toggled with Ctrl+E).
     tools_expanded: bool,
+    /// Whether thinking blocks are shown (toggled with Ctrl+B).
+    show_thinking: bool,
+    /// Focus mode (Alt+F): show only Claude's prose, hiding tool calls,
+    /// their output and thinking.
+    prose_only: bool,
+    /// Word-wrap the conversation (toggled with Alt+W).
+    wrap_lines: bool,
+    /// Columns panned past while wrapping is off (Shift+Left/Right).
+    horizontal_offset: usize,
     /// Tracks AskUserQuestion tool_use blocks pending user interaction.
     /// Maps tool_use_id → accumulated input JSON string.
     pending_user_questions: std::collections::HashMap<String, String>,
+    /// Session picker previews read so far, by session ID, so moving the
+    /// selection doesn't re-read transcripts. Cleared when a picker opens.
+    session_previews: std::collections::HashMap<String, Vec<String>>,
     /// Whether split pane mode is active (Ctrl+S).
     split_pane: bool,
     /// Content displayed in the right split pane.
     split_content: SplitContent,
     /// Scroll offset for the right split pane.
     split_scroll: usize,
+    /// File the split pane is pinned to (Alt+P): only tool calls touching it
+    /// update the pane, re-reading the file from disk.
+    split_pinned: Option<String>,
     /// Tracks sub-agents spawned via the Task tool. Keyed by tool_use_id.
     agent_tasks: Vec<AgentTask>,
+    /// Transient process answering the last prompt with another model.
+    alt_claude: Option<ClaudeProcess>,
+    /// Id of the latest "Ask Another Model" query; events from earlier
+    /// queries are dropped.
+    alt_query: u64,
+    /// Every Bash command executed this session, in order.
+    command_log: Vec<BashCommand>,
+    /// When this app session started, for the stats overlay.
+    session_started: std::time::Instant,
+    /// Recent stdout lines from claude that didn't parse as a known event.
+    unknown_lines: VecDeque<String>,
+    /// Recent toast messages with when they were shown, oldest first.
+    toast_history: VecDeque<(std::time::Instant, String)>,
+    /// Clipboard images (temp PNG files) to attach to the next message (Ctrl+V).
+    pending_images: Vec<std::path::PathBuf>,
+    /// A message held back for its large `@file` content; submitting the
+    /// same text again sends it.
+    large_send_pending: Option<String>,
+    /// Folder for this session's pasted clipboard images, removed when the
+    /// app is dropped (the images stay until then for auto-retry).
+    paste_dir: std::path::PathBuf,
+    /// Images pasted so far, numbering the files in `paste_dir`.
+    paste_count: usize,
+    /// The last prompt sent as typed (after `@file` expansion) with its
+    /// images, for `auto_retry`.
+    last_prompt: Option<(String, Vec<std::path::PathBuf>)>,
+    /// Resends of `last_prompt` so far; reset by a successful turn.
+    retry_count: u32,
+    /// The config macro being run, if any.
+    running_macro: Option<MacroRun>,
+    /// Input editor snapshot taken when an overlay opens, restored on close.
+    input_draft: Option<InputEditor>,
+    /// Tool permission requests waiting to be shown.
+    permission_queue: VecDeque<PermissionRequest>,
+    /// The overlay a permission prompt was shown over, reopened once the
+    /// queue is answered.
+    suspended_mode: Option<AppMode>,
+    /// An ExitPlanMode request, answered from the plan block (Ctrl+Enter)
+    /// instead of a modal prompt.
+    pending_plan: Option<PermissionRequest>,
+    /// Editor command to launch after the current update (needs the terminal).
+    pending_editor: Option<Vec<String>>,
+    /// Read-only browsing: the input box is disabled so nothing can be sent.
+    review_mode: bool,
+    /// Wrapped conversation lines reused across frames until the conversation changes.
+    line_cache: LineCache,
+    /// Pauses the crossterm reader thread while an external program owns the terminal.
+    input_paused: Arc<AtomicBool>,
+    /// Whether `keyboard_enhancement()` was pushed, so it is popped and
+    /// pushed again around an external editor.
+    keyboard_enhanced: bool,
+    /// Puts text on the system clipboard; stubbed in tests.
+    copy_to_clipboard: fn(&str) -> Result<()>,
+    /// Tells the tick task to drop to `IDLE_FPS` (true) or run at `fps`.
+    tick_idle: tokio::sync::watch::Sender<bool>,
+}
+
+impl Drop for App {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.paste_dir);
+    }
 }
 
 impl App {
@@ -363,10 +803,31 @@ impl App {
         budget_override: Option<f64>,
         resume_session_id: Option<String>,
     ) -> Self {
+        let review_mode = config.review;
+        let show_thinking = config.show_thinking;
+        let snippets = Snippets::new();
+        // Problems worth knowing about before the first prompt
+        let mut problems = Vec::new();
+        let (_, missing_dirs) = config.partition_additional_dirs(None);
+        if !missing_dirs.is_empty() {
+            let dirs: Vec<String> = missing_dirs.iter().map(|d| d.display().to_string()).collect();
+            problems.push(format!("Additional directory not found, skipped: {}", dirs.join(", ")));
+        }
+        if let Some(e) = snippets.load_error() {
+            problems.push(format!("Snippets not loaded: {e}"));
+        }
+        let (workflows, workflow_errors) = all_workflows();
+        problems.extend(workflow_errors.iter().map(|e| format!("Workflows not loaded: {e}")));
+        let toast = (!problems.is_empty()).then(|| Toast::new(problems.join(" \u{00b7} ")));
+        let (git_info, git_gather_cost) = GitInfo::gather_timed();
+        let mut conversation = Conversation::new();
+        if let Some(banner) = config.startup_banner_text() {
+            conversation.push_system_message(banner);
+        }
         Self {
             config,
             theme,
-            conversation: Conversation::new(),
+            conversation,
             claude: None,
             input: InputEditor::new(),
             should_quit: false,
@@ -375,14 +836,23 @@ impl App {
             theme_name,
             scroll_offset: 0,
             auto_scroll: true,
+            visible_height: 20,
+            conversation_width: 80,
+            tool_jump: None,
+            fresh_messages: None,
+            last_overlay: None,
+            cleared: None,
             command,
             slash_commands: Vec::new(),
+            mcp_servers: Vec::new(),
             custom_commands: commands::load_all_commands(),
             completion: None,
             pending_slash_command: None,
-            toast: None,
+            toast,
             session_id: None,
             event_tx: None,
+            awaiting_init: false,
+            init_check_armed: false,
             total_input_tokens: 0,
             total_output_tokens: 0,
             continue_session,
@@ -390,18 +860,58 @@ impl App {
             effort_override,
             budget_override,
             resume_session_id,
-            git_info: GitInfo::gather(),
-            git_last_refresh: 0,
+            git_info,
+            git_last_refresh: std::time::Instant::now(),
+            git_gather_cost,
+            git_refresh_interval: crate::git::refresh_interval(git_gather_cost),
             todo_tracker: TodoTracker::new(),
             detected_model: None,
             history: InputHistory::new(),
             history_browse_index: None,
+            snippets,
+            workflows,
             tools_expanded: false,
+            show_thinking,
+            prose_only: false,
+            wrap_lines: true,
+            horizontal_offset: 0,
             pending_user_questions: std::collections::HashMap::new(),
+            session_previews: std::collections::HashMap::new(),
             split_pane: false,
             split_content: SplitContent::FileContext(Vec::new()),
             split_scroll: 0,
+            split_pinned: None,
             agent_tasks: Vec::new(),
+            alt_claude: None,
+            alt_query: 0,
+            command_log: Vec::new(),
+            session_started: std::time::Instant::now(),
+            unknown_lines: VecDeque::new(),
+            toast_history: VecDeque::new(),
+            pending_images: Vec::new(),
+            paste_count: 0,
+            large_send_pending: None,
+            paste_dir: std::env::temp_dir().join(format!("sexy-claude-paste-{}", std::process::id())),
+            last_prompt: None,
+            retry_count: 0,
+            running_macro: None,
+            input_draft: None,
+            permission_queue: VecDeque::new(),
+            suspended_mode: None,
+            pending_plan: None,
+            pending_editor: None,
+            review_mode,
+            session_cwd: None,
+            pending_resume: None,
+            compare_first: None,
+            failed_respawn: None,
+            pty_session: None,
+            pty_focused: false,
+            line_cache: LineCache::default(),
+            input_paused: Arc::new(AtomicBool::new(false)),
+            keyboard_enhanced: false,
+            copy_to_clipboard: crate::clipboard::copy_text,
+            tick_idle: tokio::sync::watch::Sender::new(false),
         }
     }
 
@@ -421,7 +931,12 @@ impl App {
             mcp_config: self.config.mcp_config.clone(),
             permission_mode: self.config.permission_mode.clone(),
             allowed_tools: self.config.allowed_tools.clone(),
+            add_dirs: self.config.partition_additional_dirs(self.session_cwd.as_deref()).0,
             resume_session_id: self.resume_session_id.clone(),
+            permission_prompt_stdio: !self.bypasses_permissions(),
+            extra_args: self.config.extra_args.clone(),
+            env: self.config.env.clone(),
+            cwd: self.session_cwd.clone(),
             ..Default::default()
         }
     }
@@ -436,22 +951,35 @@ impl App {
             ClaudeProcess::spawn_with_options(&self.command, options)?;
         self.claude = Some(claude_process);
         Self::forward_claude_events(event_rx, tx.clone());
+        self.awaiting_init = true;
 
         // Spawn crossterm event reader task
         let tx_event = tx.clone();
+        let paused = self.input_paused.clone();
         std::thread::spawn(move || {
-            event_reader_loop(tx_event);
+            event_reader_loop(tx_event, paused);
         });
 
-        // Spawn tick task
-        let tick_ms = 1000 / self.config.fps as u64;
+        // Spawn tick task: full fps while animating, IDLE_FPS otherwise
+        let tick = Duration::from_millis(1000 / self.config.fps as u64);
+        let idle_tick = Duration::from_millis(1000 / IDLE_FPS.min(self.config.fps) as u64);
+        let mut idle = self.tick_idle.subscribe();
         let tx_tick = tx.clone();
         tokio::spawn(async move {
-            let mut interval = tokio::time::interval(Duration::from_millis(tick_ms));
             loop {
-                interval.tick().await;
-                if tx_tick.send(Msg::Tick).is_err() {
-                    break;
+                let period = if *idle.borrow_and_update() { idle_tick } else { tick };
+                tokio::select! {
+                    _ = tokio::time::sleep(period) => {
+                        if tx_tick.send(Msg::Tick).is_err() {
+                            break;
+                        }
+                    }
+                    // Activity started or stopped: switch rate without waiting out an idle tick
+                    changed = idle.changed() => {
+                        if changed.is_err() {
+                            break;
+                        }
+                    }
                 }
             }
         });
@@ -461,21 +989,248 @@ impl App {
 
         // Event loop
         while let Some(msg) = rx.recv().await {
+            // While idle the next tick is far off, so draw everything right away
+            let render_now = msg.renders_immediately() || *self.tick_idle.borrow();
             self.update(msg).await?;
+            let idle = self.is_idle();
+            self.tick_idle.send_if_modified(|was_idle| std::mem::replace(was_idle, idle) != idle);
             if self.should_quit {
                 break;
             }
-            self.view(terminal)?;
+            if let Some(argv) = self.pending_editor.take() {
+                self.run_editor(terminal, &argv)?;
+            }
+            // Ticks always redraw (animations), so coalesced events show up within one frame
+            if render_now {
+                self.view(terminal)?;
+            }
         }
 
         // Cleanup
         if let Some(ref mut claude) = self.claude {
             let _ = claude.kill().await;
         }
+        if let Some(ref mut alt) = self.alt_claude {
+            let _ = alt.kill().await;
+        }
+
+        Ok(())
+    }
+
+    /// Suspend the TUI, run an editor in the foreground, then restore the TUI.
+    fn run_editor(&mut self, terminal: &mut DefaultTerminal, argv: &[String]) -> Result<()> {
+        let Some((program, args)) = argv.split_first() else {
+            return Ok(());
+        };
+
+        self.input_paused.store(true, Ordering::SeqCst);
+        let _ = crossterm::execute!(std::io::stdout(), crossterm::event::DisableBracketedPaste);
+        if self.keyboard_enhanced {
+            let _ = crossterm::execute!(std::io::stdout(), crossterm::event::PopKeyboardEnhancementFlags);
+        }
+        ratatui::restore();
+
+        let status = std::process::Command::new(program).args(args).status();
+
+        crossterm::terminal::enable_raw_mode()?;
+        crossterm::execute!(
+            std::io::stdout(),
+            crossterm::terminal::EnterAlternateScreen,
+            crossterm::event::EnableBracketedPaste
+        )?;
+        if self.keyboard_enhanced {
+            crossterm::execute!(std::io::stdout(), keyboard_enhancement())?;
+        }
+        terminal.clear()?;
+        self.input_paused.store(false, Ordering::SeqCst);
+
+        match status {
+            Ok(s) if s.success() => {}
+            Ok(s) => {
+                self.toast = Some(Toast::new(format!("{program} exited with {s}")));
+            }
+            Err(e) => {
+                self.toast = Some(Toast::new(format!("Failed to launch {program}: {e}")));
+            }
+        }
+        Ok(())
+    }
+
+    /// Start the `INIT_TIMEOUT` countdown on the first send: the CLI only
+    /// reports a session once it has input.
+    fn arm_init_check(&mut self) {
+        if self.awaiting_init && !self.init_check_armed {
+            self.init_check_armed = true;
+            self.schedule_init_check();
+        }
+    }
+
+    /// Send `Msg::InitTimeout` once `INIT_TIMEOUT` has passed.
+    fn schedule_init_check(&self) {
+        if let Some(tx) = self.event_tx.clone() {
+            tokio::spawn(async move {
+                tokio::time::sleep(INIT_TIMEOUT).await;
+                let _ = tx.send(Msg::InitTimeout);
+            });
+        }
+    }
+
+    /// After a failed turn, schedule a resend of the last prompt if the error
+    /// looks transient and `auto_retry` has attempts left. Returns whether a
+    /// resend was scheduled.
+    fn retry_if_transient(&mut self, text: &str, is_error: bool) -> bool {
+        if !is_error {
+            self.retry_count = 0;
+            return false;
+        }
+        if !self.config.auto_retry || self.last_prompt.is_none() || !is_transient_error(text) {
+            return false;
+        }
+        let max = self.config.max_retries;
+        if self.retry_count >= max {
+            self.toast = Some(Toast::new(format!("Giving up after {max} retries")));
+            self.retry_count = 0;
+            return false;
+        }
+        self.retry_count += 1;
+        self.toast = Some(Toast::new(format!("Retrying ({}/{max})...", self.retry_count)));
+        if let Some(tx) = self.event_tx.clone() {
+            tokio::spawn(async move {
+                tokio::time::sleep(RETRY_DELAY).await;
+                let _ = tx.send(Msg::RetryPrompt);
+            });
+        }
+        true
+    }
+
+    /// Run the config macro `name` from its first step.
+    async fn start_macro(&mut self, name: &str) -> Result<()> {
+        if self.blocked_by_review() {
+            return Ok(());
+        }
+        let Some(steps) = self.config.macros.get(name) else {
+            return Ok(());
+        };
+        if self.conversation.is_streaming() || self.running_macro.is_some() {
+            self.toast = Some(Toast::new("Wait for the current response to finish".to_string()));
+            return Ok(());
+        }
+        self.running_macro = Some(MacroRun {
+            name: name.to_string(),
+            remaining: steps.iter().cloned().collect(),
+            step: 0,
+            total: steps.len(),
+        });
+        self.advance_macro().await
+    }
+
+    /// Send the running macro's next step. Steps that don't start a turn
+    /// (local commands like `/clear`) are followed straight away, unless
+    /// they opened an overlay, which stops the macro.
+    async fn advance_macro(&mut self) -> Result<()> {
+        while let Some(run) = self.running_macro.as_mut() {
+            if self.review_mode {
+                let msg = format!("Macro \"{}\" stopped: review mode is on", run.name);
+                self.toast = Some(Toast::new(msg));
+                self.running_macro = None;
+                break;
+            }
+            let Some(step) = run.remaining.pop_front() else {
+                self.toast = Some(Toast::new(format!("Macro \"{}\" finished", run.name)));
+                self.running_macro = None;
+                break;
+            };
+            run.step += 1;
+            let (name, at) = (run.name.clone(), format!("step {}/{}", run.step, run.total));
+            self.toast = Some(Toast::new(format!("Macro \"{name}\": {at}")));
+            let stopped = |why: String| Some(Toast::new(format!("Macro \"{name}\" stopped: {why}")));
+            match self.submit_text(step).await {
+                Ok(true) => break,
+                Ok(false) => {}
+                Err(e) => {
+                    self.running_macro = None;
+                    self.toast = stopped(format!("{at} failed to send: {e:#}"));
+                    break;
+                }
+            }
+            if self.running_macro.is_none() {
+                break;
+            }
+            if self.large_send_pending.is_some() {
+                // The step is waiting in the input for the user to confirm it
+                self.running_macro = None;
+                self.toast = stopped(format!("{at} has large @file content, send it to confirm"));
+                break;
+            }
+            if !matches!(self.mode, AppMode::Normal) {
+                self.running_macro = None;
+                self.toast = stopped(format!("{at} opened a view"));
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    /// A turn ended: move the running macro on, or stop it when the turn
+    /// failed and isn't being retried.
+    async fn macro_turn_finished(&mut self, is_error: bool, retrying: bool) -> Result<()> {
+        let Some(run) = self.running_macro.as_ref() else {
+            return Ok(());
+        };
+        if retrying {
+            return Ok(());
+        }
+        if is_error {
+            let msg = format!("Macro \"{}\" stopped: step {}/{} failed", run.name, run.step, run.total);
+            self.toast = Some(Toast::new(msg));
+            self.running_macro = None;
+            return Ok(());
+        }
+        self.advance_macro().await
+    }
 
+    /// Resend `last_prompt`, unless a new turn started during the delay.
+    async fn retry_last_prompt(&mut self) -> Result<()> {
+        if self.retry_count == 0 || self.conversation.is_streaming() || self.blocked_by_review() {
+            return Ok(());
+        }
+        if let (Some((text, images)), Some(claude)) = (self.last_prompt.as_ref(), self.claude.as_mut()) {
+            claude.send_message_with_images(text, images).await?;
+        }
         Ok(())
     }
 
+    /// Explain a silent startup: nothing came back within `INIT_TIMEOUT` of
+    /// the first send.
+    fn check_init(&mut self) {
+        if !self.awaiting_init || !self.init_check_armed {
+            return;
+        }
+        self.awaiting_init = false;
+        let headline = format!("No response from {} — are you logged in? Try `claude /login`", self.command);
+        if !matches!(self.mode, AppMode::Normal) {
+            self.toast = Some(Toast::new(headline));
+            return;
+        }
+        let mut lines = vec![
+            headline,
+            String::new(),
+            format!("{} has not answered {}s after the first message.", self.command, INIT_TIMEOUT.as_secs()),
+            "  - Run `claude /login` in another terminal, then restart.".to_string(),
+            "  - If you use an API key, check that ANTHROPIC_API_KEY is valid.".to_string(),
+        ];
+        if !self.unknown_lines.is_empty() {
+            lines.push(String::new());
+            lines.push("Unrecognised output from claude:".to_string());
+            lines.extend(self.unknown_lines.iter().map(|l| format!("  {l}")));
+        }
+        self.mode = AppMode::TextViewer {
+            title: "Connection".to_string(),
+            lines,
+            scroll: 0,
+        };
+    }
+
     /// Forward Claude events from a process receiver to the main event channel.
     fn forward_claude_events(
         mut event_rx: mpsc::UnboundedReceiver<StreamEvent>,
@@ -491,75 +1246,591 @@ impl App {
         });
     }
 
-    /// Resume a session: kill current process, reset state, spawn with --resume.
-    async fn resume_session(&mut self, session_id: &str) -> Result<()> {
-        // Kill the current process
-        if let Some(ref mut claude) = self.claude {
-            let _ = claude.kill().await;
-        }
-        self.claude = None;
+    /// Forward events from the "Ask Another Model" process to the main event channel.
+    fn forward_alt_events(
+        query: u64,
+        mut event_rx: mpsc::UnboundedReceiver<StreamEvent>,
+        tx: mpsc::UnboundedSender<Msg>,
+    ) {
+        tokio::spawn(async move {
+            while let Some(event) = event_rx.recv().await {
+                if tx.send(Msg::AltEvent(query, event)).is_err() {
+                    break;
+                }
+            }
+        });
+    }
 
-        // Reset conversation state
-        self.conversation = Conversation::new();
-        self.scroll_offset = 0;
-        self.auto_scroll = true;
-        self.slash_commands.clear();
-        self.session_id = None;
+    /// Whether unknown stdout lines should be dumped on exit.
+    pub fn verbose_unknown(&self) -> bool {
+        self.config.verbose_unknown
+    }
 
-        // Spawn new process with --resume + config options
-        let mut options = self.build_spawn_options();
-        options.resume_session_id = Some(session_id.to_string());
-        options.continue_session = false;
-        let (claude_process, event_rx) =
-            ClaudeProcess::spawn_with_options(&self.command, options)?;
-        self.claude = Some(claude_process);
+    /// Unrecognised stdout lines collected this session.
+    pub fn unknown_lines(&self) -> impl Iterator<Item = &String> {
+        self.unknown_lines.iter()
+    }
 
-        // Forward events from the new process
-        if let Some(ref tx) = self.event_tx {
-            Self::forward_claude_events(event_rx, tx.clone());
+    /// Record an unrecognised stdout line. Lines that look like
+    /// human-readable output (warnings, update notices) rather than JSON
+    /// are surfaced as a dim toast instead of being dropped.
+    fn record_unknown_line(&mut self, line: &str) {
+        self.unknown_lines.push_back(line.to_string());
+        if !self.config.verbose_unknown {
+            while self.unknown_lines.len() > UNKNOWN_LINES_MAX {
+                self.unknown_lines.pop_front();
+            }
         }
 
-        self.toast = Some(Toast::new("Resuming session...".to_string()));
-
-        Ok(())
+        let text = line.trim();
+        if !text.is_empty() && !text.starts_with('{') {
+            let message = if text.chars().count() > 80 {
+                format!("{}…", text.chars().take(79).collect::<String>())
+            } else {
+                text.to_string()
+            };
+            self.toast = Some(Toast::dim(message));
+        }
     }
 
-    /// Continue the most recent session using --continue.
-    async fn continue_last_session(&mut self) -> Result<()> {
-        if let Some(ref mut claude) = self.claude {
-            let _ = claude.kill().await;
+    fn open_unknown_lines_viewer(&mut self) {
+        if self.unknown_lines.is_empty() {
+            self.toast = Some(Toast::new("No unrecognised CLI output".to_string()));
+            return;
         }
-        self.claude = None;
-        self.conversation = Conversation::new();
-        self.scroll_offset = 0;
-        self.auto_scroll = true;
-        self.slash_commands.clear();
-        self.session_id = None;
-
-        let (claude_process, event_rx) =
-            ClaudeProcess::spawn_with_continue(&self.command)?;
-        self.claude = Some(claude_process);
+        self.mode = AppMode::TextViewer {
+            title: "Unrecognised CLI Output".to_string(),
+            lines: self.unknown_lines.iter().cloned().collect(),
+            scroll: 0,
+        };
+    }
 
-        if let Some(ref tx) = self.event_tx {
-            Self::forward_claude_events(event_rx, tx.clone());
+    /// Keep the current toast in the notification history, once.
+    fn record_toast(&mut self) {
+        let Some(toast) = &self.toast else {
+            return;
+        };
+        if self.toast_history.back().is_some_and(|(at, _)| *at == toast.created_at) {
+            return;
         }
+        self.toast_history.push_back((toast.created_at, toast.message.clone()));
+        while self.toast_history.len() > TOAST_HISTORY_MAX {
+            self.toast_history.pop_front();
+        }
+    }
 
-        self.toast = Some(Toast::new("Continuing last session...".to_string()));
+    fn open_notifications_viewer(&mut self) {
+        let lines = if self.toast_history.is_empty() {
+            vec!["No notifications yet.".to_string()]
+        } else {
+            self.toast_history
+                .iter()
+                .rev()
+                .map(|(at, message)| {
+                    format!("{:>9}  {message}", crate::claude::sessions::format_age(at.elapsed().as_secs()))
+                })
+                .collect()
+        };
+        self.mode = AppMode::TextViewer {
+            title: "Notifications".to_string(),
+            lines,
+            scroll: 0,
+        };
+    }
 
-        Ok(())
+    /// Connected and failed MCP servers as reported at startup.
+    fn open_mcp_status(&mut self) {
+        let lines = if self.mcp_servers.is_empty() {
+            vec!["No MCP servers configured.".to_string()]
+        } else {
+            let connected = self.mcp_servers.iter().filter(|s| s.is_connected()).count();
+            let failed = self.mcp_servers.len() - connected;
+            let mut lines = vec![format!("{connected} connected, {failed} not connected"), String::new()];
+            lines.extend(self.mcp_servers.iter().map(|server| {
+                let mark = if server.is_connected() { "✓" } else { "✗" };
+                format!("  {mark} {}  ({})", server.name, server.status)
+            }));
+            if failed > 0 {
+                lines.push(String::new());
+                lines.push("Tools from servers that are not connected are unavailable this session.".to_string());
+            }
+            lines
+        };
+        self.mode = AppMode::TextViewer {
+            title: "MCP Status".to_string(),
+            lines,
+            scroll: 0,
+        };
     }
 
-    async fn update(&mut self, msg: Msg) -> Result<()> {
-        match msg {
+    /// Show the directories Claude can access: the working directory and
+    /// any `additional_dirs`, with the ones that were not found marked.
+    fn open_directories(&mut self) {
+        let cwd = self
+            .session_cwd
+            .clone()
+            .or_else(|| std::env::current_dir().ok())
+            .map_or_else(|| ".".to_string(), |d| d.display().to_string());
+        let mut lines = vec![format!("  ✓ {cwd}  (working directory)")];
+        let (found, missing) = self.config.partition_additional_dirs(self.session_cwd.as_deref());
+        lines.extend(found.iter().map(|dir| format!("  ✓ {}", dir.display())));
+        lines.extend(missing.iter().map(|dir| format!("  ✗ {}  (not found, skipped)", dir.display())));
+        if self.config.additional_dirs.is_empty() {
+            lines.push(String::new());
+            lines.push("Add more with --add-dir <path> or additional_dirs in the config.".to_string());
+        }
+        self.mode = AppMode::TextViewer {
+            title: "Directories in Scope".to_string(),
+            lines,
+            scroll: 0,
+        };
+    }
+
+    /// Put the output of the most recent failed tool call into the input box
+    /// at the cursor, for the user to add context before sending.
+    fn insert_last_tool_error(&mut self) {
+        let error = self.conversation.messages.iter().rev().find_map(|m| {
+            m.content.iter().rev().find_map(|block| match block {
+                ContentBlock::ToolResult { content, is_error: true, .. } if !content.trim().is_empty() => {
+                    Some(content.trim_end().to_string())
+                }
+                _ => None,
+            })
+        });
+        match error {
+            Some(error) => {
+                self.input.insert_str(&error);
+                self.input.insert_newline();
+                self.toast = Some(Toast::new("Inserted the last tool error".to_string()));
+            }
+            None => self.toast = Some(Toast::new("No tool errors in this conversation".to_string())),
+        }
+    }
+
+    fn open_model_picker(&mut self) {
+        if self.last_prompt.is_none() {
+            self.toast = Some(Toast::new("No prompt to ask about yet".to_string()));
+            return;
+        }
+        let items = ALT_MODELS
+            .iter()
+            .map(|(label, model)| OverlayItem {
+                label: label.to_string(),
+                value: model.to_string(),
+                hint: format!("--model {model}"),
+                is_header: false,
+            })
+            .collect();
+        self.mode = AppMode::ModelPicker(OverlayState::new(items, None));
+    }
+
+    /// Send the last prompt, as it was sent with its images, to a second,
+    /// transient claude process running `model` and stream its answer into
+    /// the split pane. The main conversation is untouched. The transient
+    /// process never prompts for permissions, so tools that would need
+    /// approval are denied.
+    async fn ask_another_model(&mut self, model: &str) -> Result<()> {
+        let Some((prompt, images)) = self.last_prompt.clone() else {
+            return Ok(());
+        };
+        if let Some(ref mut alt) = self.alt_claude {
+            let _ = alt.kill().await;
+        }
+        self.alt_claude = None;
+
+        let mut options = self.build_spawn_options();
+        options.model = Some(model.to_string());
+        options.continue_session = false;
+        options.resume_session_id = None;
+        options.permission_mode = None;
+        options.allowed_tools = None;
+        options.permission_prompt_stdio = false;
+        let (mut alt, event_rx) = match ClaudeProcess::spawn_with_options(&self.command, options) {
+            Ok(spawned) => spawned,
+            Err(e) => {
+                self.toast = Some(Toast::new(format!("Could not ask {model}: {e:#}")));
+                return Ok(());
+            }
+        };
+        if let Err(e) = alt.send_message_with_images(&prompt, &images).await {
+            let _ = alt.kill().await;
+            self.toast = Some(Toast::new(format!("Could not ask {model}: {e:#}")));
+            return Ok(());
+        }
+        self.alt_claude = Some(alt);
+        self.alt_query += 1;
+        if let Some(ref tx) = self.event_tx {
+            Self::forward_alt_events(self.alt_query, event_rx, tx.clone());
+        }
+
+        self.split_pane = true;
+        self.split_content = SplitContent::AltAnswer(model.to_string(), String::new(), false);
+        self.split_scroll = 0;
+        self.toast = Some(Toast::new(format!("Asking {model}...")));
+        Ok(())
+    }
+
+    /// Apply an event from the "Ask Another Model" process to the split pane.
+    /// Events from an earlier query, or arriving after the answer finished,
+    /// are dropped.
+    async fn apply_alt_event(&mut self, query: u64, event: StreamEvent) -> Result<()> {
+        use crate::claude::events::{ContentBlockType, Delta};
+
+        if query != self.alt_query {
+            return Ok(());
+        }
+        let SplitContent::AltAnswer(_, ref mut text, ref mut finished) = self.split_content else {
+            return Ok(());
+        };
+        if *finished {
+            return Ok(());
+        }
+        match event {
+            StreamEvent::ContentBlockDelta { delta: Delta::TextDelta(ref t), .. } => {
+                text.push_str(t);
+            }
+            StreamEvent::ContentBlockStart {
+                block_type: ContentBlockType::ToolUse { ref name, .. },
+                ..
+            } => {
+                text.push_str(&format!("\n[{name}]\n"));
+            }
+            StreamEvent::Result { text: ref result, is_error, .. } => {
+                if is_error || text.trim().is_empty() {
+                    text.push_str(result);
+                }
+                *finished = true;
+                if let Some(mut alt) = self.alt_claude.take() {
+                    let _ = alt.kill().await;
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Resume a session: kill current process, reset state, spawn with --resume.
+    /// Resume `session_id`, first asking where to run it when it was created
+    /// in a different directory than ours.
+    async fn start_resume(&mut self, session_id: &str) -> Result<()> {
+        let here = std::env::current_dir().unwrap_or_default();
+        match sessions::session_cwd(session_id) {
+            Some(dir) if dir != here && dir.is_dir() => {
+                self.open_resume_dir_picker(session_id, dir);
+                Ok(())
+            }
+            Some(dir) if dir != here => {
+                self.session_cwd = None;
+                self.resume_session(session_id, None).await?;
+                self.toast = Some(Toast::new(format!(
+                    "This session was created in {}",
+                    dir.display()
+                )));
+                Ok(())
+            }
+            _ => {
+                self.session_cwd = None;
+                self.resume_session(session_id, None).await
+            }
+        }
+    }
+
+    fn open_resume_dir_picker(&mut self, session_id: &str, dir: std::path::PathBuf) {
+        let shown = dir.display().to_string();
+        let items = vec![
+            OverlayItem {
+                label: format!("Resume in {shown}"),
+                value: "chdir".to_string(),
+                hint: "recommended".to_string(),
+                is_header: false,
+            },
+            OverlayItem {
+                label: format!("Resume here, adding {shown}"),
+                value: "adddir".to_string(),
+                hint: "--add-dir".to_string(),
+                is_header: false,
+            },
+            OverlayItem {
+                label: "Resume here".to_string(),
+                value: "here".to_string(),
+                hint: String::new(),
+                is_header: false,
+            },
+        ];
+        self.pending_resume = Some((session_id.to_string(), dir));
+        self.mode = AppMode::ResumeDirPicker(OverlayState::new(items, None));
+    }
+
+    /// Move into the directory a resumed session was created in, so git
+    /// info, file pickers and `@file` mentions follow the Claude process.
+    fn enter_session_dir(&mut self, dir: std::path::PathBuf) {
+        if let Err(e) = std::env::set_current_dir(&dir) {
+            self.toast = Some(Toast::new(format!("Could not enter {}: {e}", dir.display())));
+        }
+        self.session_cwd = Some(dir);
+        self.refresh_git_info();
+        // Paths completed or pinned in the old directory no longer apply
+        self.completion = None;
+        self.split_pinned = None;
+    }
+
+    /// Respawn Claude resuming `session_id`, optionally granting access to
+    /// an extra directory with `--add-dir`.
+    async fn resume_session(&mut self, session_id: &str, add_dir: Option<&std::path::Path>) -> Result<()> {
+        // Kill the current process
+        if let Some(ref mut claude) = self.claude {
+            let _ = claude.kill().await;
+        }
+        self.claude = None;
+
+        // Reset conversation state
+        self.conversation = Conversation::new();
+        self.fresh_messages = None;
+        self.scroll_offset = 0;
+        self.auto_scroll = true;
+        self.slash_commands.clear();
+        self.session_id = None;
+
+        // Spawn new process with --resume + config options
+        let mut options = self.build_spawn_options();
+        options.resume_session_id = Some(session_id.to_string());
+        options.continue_session = false;
+        if let Some(dir) = add_dir {
+            options.add_dirs.push(dir.to_path_buf());
+        }
+        let (claude_process, event_rx) = match ClaudeProcess::spawn_with_options(&self.command, options) {
+            Ok(spawned) => spawned,
+            Err(e) => {
+                let respawn = Respawn::Resume {
+                    session_id: session_id.to_string(),
+                    add_dir: add_dir.map(std::path::Path::to_path_buf),
+                };
+                self.show_respawn_failed(respawn, &e);
+                return Ok(());
+            }
+        };
+        self.claude = Some(claude_process);
+
+        // Forward events from the new process
+        if let Some(ref tx) = self.event_tx {
+            Self::forward_claude_events(event_rx, tx.clone());
+        }
+
+        let msg = match self.session_cwd {
+            Some(ref dir) => format!("Resuming session in {}...", dir.display()),
+            None => "Resuming session...".to_string(),
+        };
+        self.toast = Some(Toast::new(msg));
+
+        Ok(())
+    }
+
+    /// Continue the most recent session using --continue.
+    async fn continue_last_session(&mut self) -> Result<()> {
+        if let Some(ref mut claude) = self.claude {
+            let _ = claude.kill().await;
+        }
+        self.claude = None;
+        self.conversation = Conversation::new();
+        self.fresh_messages = None;
+        self.scroll_offset = 0;
+        self.auto_scroll = true;
+        self.slash_commands.clear();
+        self.session_id = None;
+
+        let (claude_process, event_rx) = match ClaudeProcess::spawn_with_continue(&self.command) {
+            Ok(spawned) => spawned,
+            Err(e) => {
+                self.show_respawn_failed(Respawn::Continue, &e);
+                return Ok(());
+            }
+        };
+        self.claude = Some(claude_process);
+
+        if let Some(ref tx) = self.event_tx {
+            Self::forward_claude_events(event_rx, tx.clone());
+        }
+
+        self.toast = Some(Toast::new("Continuing last session...".to_string()));
+
+        Ok(())
+    }
+
+    /// Keep the app running after Claude failed to restart (e.g. the binary
+    /// vanished during an npm update) and offer to try again.
+    fn show_respawn_failed(&mut self, respawn: Respawn, error: &anyhow::Error) {
+        let not_found = error
+            .chain()
+            .filter_map(|cause| cause.downcast_ref::<std::io::Error>())
+            .any(|e| e.kind() == std::io::ErrorKind::NotFound);
+        let reason = if not_found {
+            format!("'{}' not found (is it being updated?)", self.command)
+        } else {
+            format!("{error:#}")
+        };
+        let items = vec![
+            OverlayItem {
+                label: "Retry".to_string(),
+                value: "retry".to_string(),
+                hint: reason.clone(),
+                is_header: false,
+            },
+            OverlayItem {
+                label: "Dismiss".to_string(),
+                value: "dismiss".to_string(),
+                hint: String::new(),
+                is_header: false,
+            },
+        ];
+        self.failed_respawn = Some(respawn);
+        self.mode = AppMode::RespawnFailed(OverlayState::new(items, None));
+        self.toast = Some(Toast::new(reason));
+    }
+
+    /// Run the failed resume/continue again.
+    async fn retry_respawn(&mut self) -> Result<()> {
+        match self.failed_respawn.take() {
+            Some(Respawn::Resume { session_id, add_dir }) => {
+                self.resume_session(&session_id, add_dir.as_deref()).await
+            }
+            Some(Respawn::Continue) => self.continue_last_session().await,
+            None => Ok(()),
+        }
+    }
+
+    async fn update(&mut self, msg: Msg) -> Result<()> {
+        let was_normal = matches!(self.mode, AppMode::Normal);
+        let result = self.handle_msg(msg).await;
+        self.record_toast();
+        self.show_next_permission_prompt();
+        self.sync_input_draft(was_normal);
+        result
+    }
+
+    /// Whether nothing on screen changes by itself: no streaming, running
+    /// tool, animated header, toast, highlight or terminal pane.
+    fn is_idle(&self) -> bool {
+        let full_header = self.conversation.messages.is_empty() && !self.config.minimal_ui();
+        !(ui::claude_pane::shows_spinner(&self.conversation)
+            || full_header
+            || self.toast.is_some()
+            || self.tool_jump.is_some_and(|(_, at)| at.elapsed() < TOOL_JUMP_HIGHLIGHT)
+            || self.fresh_messages.is_some_and(|(_, start)| start.is_some())
+            || self.pty_session.is_some())
+    }
+
+    /// Whether the session runs with `bypassPermissions` (no prompts at all).
+    fn bypasses_permissions(&self) -> bool {
+        self.config.permission_mode.as_deref() == Some("bypassPermissions")
+    }
+
+    /// Show the next queued permission request as an Allow/Deny question.
+    /// An open overlay is set aside so Claude isn't blocked behind it, and
+    /// reopened once the queue is answered.
+    fn show_next_permission_prompt(&mut self) {
+        if matches!(self.mode, AppMode::UserQuestion { .. }) {
+            return;
+        }
+        let Some(request) = self.permission_queue.pop_front() else {
+            if matches!(self.mode, AppMode::Normal) {
+                if let Some(mode) = self.suspended_mode.take() {
+                    self.mode = mode;
+                }
+            }
+            return;
+        };
+        if !matches!(self.mode, AppMode::Normal) {
+            self.suspended_mode = Some(std::mem::replace(&mut self.mode, AppMode::Normal));
+        }
+        let detail = ui::claude_pane::extract_primary_arg(&request.tool_name, &request.input.to_string())
+            .unwrap_or_default();
+        // Flag destructive Bash commands and preselect Deny for them
+        let danger = if request.tool_name == "Bash" {
+            request
+                .input
+                .get("command")
+                .and_then(|c| c.as_str())
+                .and_then(|c| self.config.danger_pattern(c))
+                .map(String::from)
+        } else {
+            None
+        };
+        let (question, header, cursor) = match danger {
+            Some(pattern) => (
+                format!("\u{26A0} Destructive command (matches \"{pattern}\"). Allow Claude to run it?"),
+                "Danger",
+                1,
+            ),
+            None => (format!("Allow Claude to use {}?", request.tool_name), "Permission", 0),
+        };
+        let question = UserQuestion {
+            question,
+            header: header.to_string(),
+            options: vec![
+                UserQuestionOption {
+                    label: "Allow".to_string(),
+                    description: detail,
+                },
+                UserQuestionOption {
+                    label: "Deny".to_string(),
+                    description: "Claude will be told the tool was denied".to_string(),
+                },
+            ],
+            multi_select: false,
+            free_text: false,
+        };
+        self.mode = AppMode::UserQuestion {
+            questions: vec![question],
+            current_question: 0,
+            cursor,
+            selected: vec![false; 2],
+            permission: Some(request),
+        };
+    }
+
+    /// Snapshot the input draft when an overlay opens and restore it when
+    /// the app returns to normal mode, so overlays never lose typed text.
+    /// Overlays that intentionally replace the input clear `input_draft`.
+    fn sync_input_draft(&mut self, was_normal: bool) {
+        let is_normal = matches!(self.mode, AppMode::Normal);
+        if was_normal && !is_normal {
+            self.input_draft = Some(self.input.clone());
+        } else if !was_normal && is_normal {
+            if let Some(draft) = self.input_draft.take() {
+                self.input = draft;
+            }
+        }
+    }
+
+    async fn handle_msg(&mut self, msg: Msg) -> Result<()> {
+        match msg {
             Msg::ClaudeEvent(event) => {
+                // Any recognised event means claude is up; unrecognised output
+                // is what the startup diagnostic reports
+                if let StreamEvent::Unknown(ref line) = event {
+                    self.record_unknown_line(line);
+                } else {
+                    self.awaiting_init = false;
+                }
+
                 // Extract slash commands and session ID from SystemInit
                 if let StreamEvent::SystemInit {
                     ref slash_commands,
                     ref session_id,
+                    ref mcp_servers,
                 } = event
                 {
                     self.slash_commands = slash_commands.clone();
                     self.session_id = session_id.clone();
+                    self.awaiting_init = false;
+                    self.mcp_servers = mcp_servers.clone();
+                    let failed = mcp_servers.iter().filter(|s| !s.is_connected()).count();
+                    if failed > 0 {
+                        let servers = if failed == 1 { "server" } else { "servers" };
+                        self.toast = Some(Toast::new(format!(
+                            "{failed} MCP {servers} not connected (Ctrl+K → MCP Status)"
+                        )));
+                    }
                 }
 
                 // Show toast for empty slash command results, clear tracking
@@ -579,6 +1850,8 @@ impl App {
                         }
                     }
                     self.pending_slash_command.take();
+                    let retrying = self.retry_if_transient(text, is_error);
+                    self.macro_turn_finished(is_error, retrying).await?;
                 }
 
                 // Capture model name and clear pending command on new message
@@ -633,6 +1906,18 @@ impl App {
                                 self.pending_user_questions
                                     .insert(id.clone(), input.clone());
                             }
+                            if name == "Bash" {
+                                if let Some(command) = serde_json::from_str::<serde_json::Value>(input)
+                                    .ok()
+                                    .and_then(|v| v.get("command")?.as_str().map(String::from))
+                                {
+                                    self.command_log.push(BashCommand {
+                                        id: id.clone(),
+                                        command,
+                                        is_error: None,
+                                    });
+                                }
+                            }
                             // Track sub-agent spawning via Task tool
                             if name == "Task" {
                                 if let Ok(value) = serde_json::from_str::<serde_json::Value>(input) {
@@ -646,12 +1931,19 @@ impl App {
                                         .and_then(|v| v.as_str())
                                         .unwrap_or("unknown")
                                         .to_string();
+                                    let prompt = value
+                                        .get("prompt")
+                                        .and_then(|v| v.as_str())
+                                        .unwrap_or_default()
+                                        .to_string();
                                     self.agent_tasks.push(AgentTask {
                                         id: id.clone(),
                                         description,
                                         agent_type,
+                                        prompt,
                                         started: std::time::Instant::now(),
                                         completed: false,
+                                        cancel_requested: false,
                                     });
                                 }
                             }
@@ -668,20 +1960,40 @@ impl App {
                     }
                 }
 
+                // Record the outcome of logged Bash commands
+                if let StreamEvent::ToolResult { ref tool_use_id, is_error, .. } = event {
+                    if let Some(entry) = self.command_log.iter_mut().find(|c| c.id == *tool_use_id) {
+                        entry.is_error = Some(is_error);
+                    }
+                }
+
                 // Intercept ToolResult for AskUserQuestion — show interactive overlay
                 if let StreamEvent::ToolResult { ref tool_use_id, .. } = event {
                     if let Some(input_json) = self.pending_user_questions.remove(tool_use_id) {
                         if let Some(questions) = parse_ask_user_questions(&input_json) {
-                            if !questions.is_empty() {
-                                let num_options = questions[0].options.len();
-                                self.mode = AppMode::UserQuestion {
-                                    questions,
-                                    current_question: 0,
-                                    cursor: 0,
-                                    selected: vec![false; num_options],
-                                };
-                            }
+                            self.open_user_questions(questions);
+                        }
+                    }
+                }
+
+                // Tool permission prompts — queued and shown one at a time
+                if let StreamEvent::PermissionRequest { ref request_id, ref tool_name, ref input } = event {
+                    if self.bypasses_permissions() {
+                        if let Some(ref mut claude) = self.claude {
+                            claude.send_permission_response(request_id, true, input, "").await?;
                         }
+                    } else if tool_name == "ExitPlanMode" {
+                        self.pending_plan = Some(PermissionRequest {
+                            request_id: request_id.clone(),
+                            tool_name: tool_name.clone(),
+                            input: input.clone(),
+                        });
+                    } else {
+                        self.permission_queue.push_back(PermissionRequest {
+                            request_id: request_id.clone(),
+                            tool_name: tool_name.clone(),
+                            input: input.clone(),
+                        });
                     }
                 }
 
@@ -690,14 +2002,36 @@ impl App {
                     self.update_split_content_from_event(&event);
                 }
 
+                let before = self.conversation.messages.len();
                 self.conversation.apply_event(&event);
+                if self.conversation.messages.len() > before {
+                    self.mark_fresh_messages(before);
+                }
+                if matches!(event, StreamEvent::Result { .. }) && self.config.max_rendered_messages > 0 {
+                    self.conversation.archive(self.config.max_rendered_messages);
+                }
                 if self.auto_scroll {
                     self.scroll_to_bottom();
                 }
             }
             Msg::ClaudeExited => {
                 // Claude process ended
+                if let Some(run) = self.running_macro.take() {
+                    self.toast = Some(Toast::new(format!("Macro \"{}\" stopped: claude exited", run.name)));
+                }
             }
+            Msg::AltEvent(query, event) => {
+                self.apply_alt_event(query, event).await?;
+            }
+            Msg::PtyOutput(bytes) => {
+                if let Some(ref mut session) = self.pty_session {
+                    session.emulator.process(&bytes);
+                    self.refresh_terminal_pane();
+                }
+            }
+            Msg::PtyExited => self.close_terminal("Terminal exited".to_string()),
+            Msg::InitTimeout => self.check_init(),
+            Msg::RetryPrompt => self.retry_last_prompt().await?,
             Msg::Key(key) => {
                 if key.kind != KeyEventKind::Press {
                     return Ok(());
@@ -705,16 +2039,19 @@ impl App {
                 self.handle_key(key).await?;
             }
             Msg::Paste(text) => {
-                if matches!(self.mode, AppMode::Normal) {
+                if matches!(self.mode, AppMode::Normal) && self.terminal_has_focus() {
+                    self.write_to_terminal(text.as_bytes());
+                } else if matches!(self.mode, AppMode::Normal) && !self.review_mode {
                     self.input.insert_str(&text);
                     self.history_browse_index = None;
                     self.update_completions();
                 }
             }
-            Msg::Resize(_width, _height) => {
+            Msg::Resize(width, height) => {
                 if self.auto_scroll {
                     self.scroll_to_bottom();
                 }
+                self.fit_terminal_to_pane(width, height);
             }
             Msg::Tick => {
                 self.frame_count = self.frame_count.wrapping_add(1);
@@ -722,11 +2059,16 @@ impl App {
                 if self.toast.as_ref().is_some_and(|t| t.is_expired()) {
                     self.toast = None;
                 }
-                // Refresh git info every ~5 seconds
-                let refresh_interval = (self.config.fps as u64) * 5;
-                if self.frame_count - self.git_last_refresh >= refresh_interval {
-                    self.git_info = GitInfo::gather();
-                    self.git_last_refresh = self.frame_count;
+                if self.cleared.as_ref().is_some_and(|(_, at)| at.elapsed() >= CLEAR_UNDO_WINDOW) {
+                    self.cleared = None;
+                }
+                if self.fresh_messages.is_some_and(|(_, start)| start.is_some()) && self.fresh_tint().is_none() {
+                    self.fresh_messages = None;
+                }
+                // Refresh git info every 5s, or every 30s when gathering is slow
+                if self.git_last_refresh.elapsed() >= self.git_refresh_interval {
+                    self.refresh_git_info();
+                    self.git_last_refresh = std::time::Instant::now();
                 }
             }
         }
@@ -734,19 +2076,81 @@ impl App {
     }
 
     async fn handle_key(&mut self, key: event::KeyEvent) -> Result<()> {
+        let open_overlay = self.reopenable_overlay();
+        let result = self.dispatch_key(key).await;
+        if open_overlay.is_some() && matches!(self.mode, AppMode::Normal) {
+            self.last_overlay = open_overlay;
+        }
+        result
+    }
+
+    /// How to bring back the current overlay once it's closed. `None` when
+    /// no overlay is open, or for mid-flow ones (text input, questions,
+    /// two-step pickers) that can't be rebuilt.
+    fn reopenable_overlay(&self) -> Option<ClosedOverlay> {
+        let open: fn(&mut App) = match &self.mode {
+            AppMode::ActionMenu(_) => Self::open_action_menu,
+            AppMode::ThemePicker(_) => Self::open_theme_picker,
+            AppMode::SessionPicker(_) => Self::open_session_picker,
+            AppMode::CheckpointTimeline(_) => Self::open_checkpoint_timeline,
+            AppMode::WorkflowPicker(_) => Self::open_workflow_picker,
+            AppMode::FileRefPicker(_) => Self::open_file_ref_picker,
+            AppMode::ModelPicker(_) => Self::open_model_picker,
+            AppMode::FileContext(_) => Self::open_file_context_panel,
+            AppMode::CodeBlockPicker(_) => Self::open_code_block_picker,
+            AppMode::FileDiffPicker(_) => Self::open_file_diff_picker,
+            AppMode::PluginBrowser { .. } => Self::open_plugin_browser,
+            AppMode::AgentDashboard { .. } => Self::open_agent_dashboard,
+            AppMode::DiffViewer { .. } => Self::open_diff_viewer,
+            AppMode::HistorySearch { .. } => Self::open_history_search,
+            AppMode::SessionInfo { .. } => Self::show_session_info,
+            AppMode::TextViewer { title, lines, scroll } => {
+                return Some(ClosedOverlay::Viewer {
+                    title: title.clone(),
+                    lines: lines.clone(),
+                    scroll: *scroll,
+                });
+            }
+            _ => return None,
+        };
+        Some(ClosedOverlay::Rebuild(open))
+    }
+
+    /// Bring back the overlay closed last (Ctrl+Shift+K).
+    fn reopen_last_overlay(&mut self) {
+        match self.last_overlay.clone() {
+            Some(ClosedOverlay::Rebuild(open)) => open(self),
+            Some(ClosedOverlay::Viewer { title, lines, scroll }) => {
+                self.mode = AppMode::TextViewer { title, lines, scroll };
+            }
+            None => self.toast = Some(Toast::new("No overlay to reopen".to_string())),
+        }
+    }
+
+    async fn dispatch_key(&mut self, key: event::KeyEvent) -> Result<()> {
         match &self.mode {
             AppMode::Normal => self.handle_key_normal(key).await,
             AppMode::ActionMenu(_)
             | AppMode::ThemePicker(_)
             | AppMode::SessionPicker(_)
             | AppMode::CheckpointTimeline(_)
-            | AppMode::WorkflowPicker(_) => self.handle_key_overlay(key).await,
-            AppMode::TextViewer { .. } => self.handle_key_text_viewer(key),
+            | AppMode::WorkflowPicker(_)
+            | AppMode::FileRefPicker(_)
+            | AppMode::ModelPicker(_)
+            | AppMode::ResumeDirPicker(_)
+            | AppMode::ComparePicker(_)
+            | AppMode::RespawnFailed(_)
+            | AppMode::FileContext(_)
+            | AppMode::CodeBlockPicker(_)
+            | AppMode::FileDiffPicker(_)
+            | AppMode::Welcome(_) => self.handle_key_overlay(key).await,
+            AppMode::TextViewer { .. } | AppMode::SessionInfo { .. } => self.handle_key_text_viewer(key),
             AppMode::HistorySearch { .. } => self.handle_key_history_search(key),
             AppMode::TextInput { .. } => self.handle_key_text_input(key).await,
             AppMode::UserQuestion { .. } => self.handle_key_user_question(key).await,
             AppMode::PluginBrowser { .. } => self.handle_key_plugin_browser(key).await,
             AppMode::AgentDashboard { .. } => self.handle_key_agent_dashboard(key),
+            AppMode::DiffViewer { .. } => self.handle_key_diff_viewer(key),
         }
     }
 
@@ -759,6 +2163,26 @@ impl App {
             return Ok(());
         }
 
+        // Keystrokes go to the split-pane terminal until Ctrl+] hands focus back
+        if self.terminal_has_focus() {
+            if ctrl && matches!(key.code, KeyCode::Char(']') | KeyCode::Char('5')) {
+                self.pty_focused = false;
+                self.refresh_terminal_pane();
+                self.toast = Some(Toast::new("Terminal unfocused".to_string()));
+            } else if let Some(bytes) = terminal::key_to_bytes(&key) {
+                self.write_to_terminal(&bytes);
+            }
+            return Ok(());
+        }
+
+        // Ctrl+Shift+K arrives as 'K' (or 'k' with Shift, on terminals that report it);
+        // terminals that drop the Shift get Alt+K
+        let alt_k = key.modifiers.contains(KeyModifiers::ALT) && key.code == KeyCode::Char('k');
+        if alt_k || (ctrl && (key.code == KeyCode::Char('K') || (shift && key.code == KeyCode::Char('k')))) {
+            self.reopen_last_overlay();
+            return Ok(());
+        }
+
         if ctrl && key.code == KeyCode::Char('k') {
             self.open_action_menu();
             return Ok(());
@@ -789,6 +2213,29 @@ impl App {
             return Ok(());
         }
 
+        // Readline-style editing on Alt, clear of the Ctrl+W / Ctrl+U bindings
+        if key.modifiers.contains(KeyModifiers::ALT) && !self.review_mode {
+            let edited = match key.code {
+                KeyCode::Backspace => {
+                    self.input.delete_prev_word();
+                    true
+                }
+                KeyCode::Char('u') => {
+                    self.input.delete_to_line_start();
+                    true
+                }
+                KeyCode::Char('c') => {
+                    self.input.take_content();
+                    true
+                }
+                _ => false,
+            };
+            if edited {
+                self.update_completions();
+                return Ok(());
+            }
+        }
+
         if ctrl && key.code == KeyCode::Char('w') {
             self.open_workflow_picker();
             return Ok(());
@@ -799,7 +2246,7 @@ impl App {
             return Ok(());
         }
 
-        if ctrl && key.code == KeyCode::Char('d') {
+        if ctrl && key.code == KeyCode::Char('g') {
             self.open_diff_viewer();
             return Ok(());
         }
@@ -811,22 +2258,140 @@ impl App {
             return Ok(());
         }
 
+        if ctrl && key.code == KeyCode::Char('b') {
+            self.toggle_thinking();
+            return Ok(());
+        }
+
+        if ctrl && key.code == KeyCode::Char('x') {
+            self.open_notifications_viewer();
+            return Ok(());
+        }
+
+        if ctrl && key.code == KeyCode::Char('z') {
+            self.undo_clear();
+            return Ok(());
+        }
+
         if ctrl && key.code == KeyCode::Char('a') {
             self.open_agent_dashboard();
             return Ok(());
         }
 
-        if ctrl && key.code == KeyCode::Char('s') {
-            self.split_pane = !self.split_pane;
-            let msg = if self.split_pane { "Split pane enabled" } else { "Split pane closed" };
-            self.toast = Some(Toast::new(msg.to_string()));
+        if ctrl && key.code == KeyCode::Char('v') {
+            self.paste_count += 1;
+            match crate::clipboard::paste_image(&self.paste_dir, self.paste_count) {
+                Ok(path) => {
+                    self.pending_images.push(path);
+                    let n = self.pending_images.len();
+                    let msg = if n == 1 { "Image attached".to_string() } else { format!("{n} images attached") };
+                    self.toast = Some(Toast::new(msg));
+                }
+                Err(e) => {
+                    self.toast = Some(Toast::new(format!("{e:#}")));
+                }
+            }
             return Ok(());
         }
 
-        // Scrolling — Shift+PageUp/Down scrolls split pane, plain PageUp/Down scrolls conversation
-        if self.split_pane && shift {
-            match key.code {
-                KeyCode::PageUp => {
+        if key.modifiers.contains(KeyModifiers::ALT) && key.code == KeyCode::Char('v') {
+            self.remove_pending_image();
+            return Ok(());
+        }
+
+        if ctrl && key.code == KeyCode::Char('o') {
+            self.open_file_ref_picker();
+            return Ok(());
+        }
+
+        if ctrl && key.code == KeyCode::Char('y') {
+            self.open_code_block_picker();
+            return Ok(());
+        }
+
+        if ctrl && key.code == KeyCode::Char('n') {
+            if self.conversation.last_response_truncated() && !self.review_mode {
+                self.continue_truncated_response().await?;
+            }
+            return Ok(());
+        }
+
+        if ctrl && key.code == KeyCode::Char('l') {
+            self.load_older_messages();
+            return Ok(());
+        }
+
+        // With submit_key = "ctrl+enter" it sends the message unless a plan awaits approval
+        if ctrl && key.code == KeyCode::Enter && (self.pending_plan.is_some() || !self.ctrl_enter_submits()) {
+            if !self.review_mode {
+                self.answer_plan(true).await?;
+            }
+            return Ok(());
+        }
+
+        // Alt+Y/Alt+N answer a pending plan in any terminal, without taking
+        // keys that start a typed reply
+        if self.pending_plan.is_some() && key.modifiers.contains(KeyModifiers::ALT) && !ctrl {
+            match key.code {
+                KeyCode::Char('y') => return self.answer_plan(true).await,
+                KeyCode::Char('n') => return self.answer_plan(false).await,
+                _ => {}
+            }
+        }
+
+        if key.modifiers.contains(KeyModifiers::ALT) && key.code == KeyCode::Char('s') {
+            if !self.review_mode {
+                self.summarize_visible().await?;
+            }
+            return Ok(());
+        }
+
+        if key.modifiers.contains(KeyModifiers::ALT) && key.code == KeyCode::Char('e') {
+            if !self.review_mode {
+                self.insert_last_tool_error();
+            }
+            return Ok(());
+        }
+
+        if key.modifiers.contains(KeyModifiers::ALT) && key.code == KeyCode::Char('p') {
+            self.toggle_split_pin();
+            return Ok(());
+        }
+
+        if ctrl && key.code == KeyCode::Char('s') {
+            self.split_pane = !self.split_pane;
+            let msg = if self.split_pane { "Split pane enabled" } else { "Split pane closed" };
+            self.toast = Some(Toast::new(msg.to_string()));
+            return Ok(());
+        }
+
+        if key.modifiers.contains(KeyModifiers::ALT) && key.code == KeyCode::Char('f') {
+            self.toggle_prose_only();
+            return Ok(());
+        }
+
+        if key.modifiers.contains(KeyModifiers::ALT) && key.code == KeyCode::Char('w') {
+            self.toggle_wrap();
+            return Ok(());
+        }
+
+        if key.modifiers.contains(KeyModifiers::ALT) && key.code == KeyCode::Char('o') {
+            self.open_search_hit();
+            return Ok(());
+        }
+
+        // Scrolling — Shift+PageUp/Down scrolls split pane, plain PageUp/Down scrolls conversation
+        if self.split_pane && shift {
+            match key.code {
+                KeyCode::Up if matches!(self.split_content, SplitContent::SearchResults(..)) => {
+                    self.move_search_selection(false);
+                    return Ok(());
+                }
+                KeyCode::Down if matches!(self.split_content, SplitContent::SearchResults(..)) => {
+                    self.move_search_selection(true);
+                    return Ok(());
+                }
+                KeyCode::PageUp => {
                     self.split_scroll = self.split_scroll.saturating_sub(10);
                     return Ok(());
                 }
@@ -837,20 +2402,92 @@ impl App {
                 _ => {}
             }
         }
+        // Shift+Left/Right pan unwrapped content instead of moving the cursor
+        if shift && !self.wrap_lines {
+            match key.code {
+                KeyCode::Left => {
+                    self.horizontal_offset = self.horizontal_offset.saturating_sub(HORIZONTAL_STEP);
+                    return Ok(());
+                }
+                KeyCode::Right => {
+                    self.pan_right();
+                    return Ok(());
+                }
+                _ => {}
+            }
+        }
         match key.code {
             KeyCode::PageUp => {
-                self.auto_scroll = false;
-                self.scroll_offset = self.scroll_offset.saturating_sub(10);
+                self.scroll_up(10);
                 return Ok(());
             }
             KeyCode::PageDown => {
-                self.scroll_offset += 10;
-                self.clamp_scroll();
+                self.scroll_down(10);
+                return Ok(());
+            }
+            KeyCode::Char('u') if ctrl => {
+                self.scroll_up(self.half_page());
+                return Ok(());
+            }
+            KeyCode::Char('d') if ctrl => {
+                self.scroll_down(self.half_page());
                 return Ok(());
             }
             _ => {}
         }
 
+        // Tab / Shift+Tab jump between tool calls while the input is empty
+        if self.input.is_empty() && self.completion.is_none() {
+            match key.code {
+                KeyCode::Tab if !shift => {
+                    self.jump_to_tool_call(true);
+                    return Ok(());
+                }
+                KeyCode::BackTab => {
+                    self.jump_to_tool_call(false);
+                    return Ok(());
+                }
+                _ => {}
+            }
+        }
+
+        // Line-by-line scrolling with Up/Down while scrolled back and the input is empty
+        if !self.auto_scroll && self.input.is_empty() && self.completion.is_none() && self.history_browse_index.is_none() {
+            match key.code {
+                KeyCode::Up => {
+                    self.scroll_up(1);
+                    return Ok(());
+                }
+                KeyCode::Down => {
+                    self.scroll_down(1);
+                    return Ok(());
+                }
+                _ => {}
+            }
+        }
+
+        // Enter on the tool call just jumped to (Tab) folds or unfolds its output
+        if key.code == KeyCode::Enter && self.input.is_empty() && self.pending_images.is_empty() {
+            if let Some(id) = self.jumped_tool_use_id() {
+                self.toggle_tool_result(&id);
+                return Ok(());
+            }
+        }
+
+        // Review mode: the input box is disabled, arrow keys only scroll
+        if self.review_mode {
+            match key.code {
+                KeyCode::Up => self.scroll_up(1),
+                KeyCode::Down => self.scroll_down(1),
+                KeyCode::End => self.resume_auto_scroll(),
+                KeyCode::Enter => {
+                    self.toast = Some(Toast::new("Review mode: input disabled (Ctrl+K to exit)".to_string()));
+                }
+                _ => {}
+            }
+            return Ok(());
+        }
+
         // Completion navigation (when popup is visible)
         if self.completion.is_some() {
             match key.code {
@@ -923,75 +2560,20 @@ impl App {
 
         // Input handling
         match key.code {
-            KeyCode::Enter if !shift => {
-                if !self.input.is_empty() && !self.conversation.is_streaming() {
+            KeyCode::Enter if self.is_submit_key(key) => {
+                let has_input = !self.input.is_empty() || !self.pending_images.is_empty();
+                if has_input && !self.conversation.is_streaming() {
                     let text = self.input.take_content();
-                    self.history.push(text.clone());
                     self.history_browse_index = None;
 
-                    if let Some(action) = self.handle_local_command(&text) {
-                        // Command handled locally
-                        match action {
-                            LocalAction::Clear => {
-                                self.conversation = Conversation::new();
-                                self.scroll_offset = 0;
-                                self.auto_scroll = true;
-                            }
-                            LocalAction::Help => {
-                                self.show_help_viewer();
-                            }
-                            LocalAction::ShowConfig => {
-                                self.show_config_viewer();
-                            }
-                            LocalAction::ShowModel => {
-                                let model = self.detected_model.as_deref()
-                                    .or(self.model_override.as_deref())
-                                    .or(self.config.model.as_deref())
-                                    .unwrap_or("(default)");
-                                self.toast = Some(Toast::new(format!("Model: {model}")));
-                            }
-                            LocalAction::ShowMemory => {
-                                self.open_memory_viewer();
-                            }
-                            LocalAction::ShowPlugins => {
-                                self.open_plugin_browser();
-                            }
-                            LocalAction::Exit => {
-                                self.should_quit = true;
-                            }
-                            LocalAction::ChangeTheme => {
-                                self.open_theme_picker();
-                            }
-                        }
-                    } else if let Some(prompt) = self.resolve_custom_command(&text) {
-                        // Custom command — substitute args and send as user message
-                        self.conversation.push_user_message(prompt.clone());
-                        self.auto_scroll = true;
-                        self.scroll_to_bottom();
-                        if let Some(ref mut claude) = self.claude {
-                            claude.send_message(&prompt).await?;
-                        }
-                    } else if text.starts_with('/') {
-                        // Slash command — send to Claude but don't add as user message
-                        self.pending_slash_command = Some(text.clone());
-                        self.auto_scroll = true;
-                        self.scroll_to_bottom();
-                        if let Some(ref mut claude) = self.claude {
-                            claude.send_message(&text).await?;
-                        }
-                    } else {
-                        // Normal user message — expand @file mentions before sending
-                        self.conversation.push_user_message(text.clone());
-                        self.auto_scroll = true;
-                        self.scroll_to_bottom();
-                        let expanded = expand_file_mentions(&text);
-                        if let Some(ref mut claude) = self.claude {
-                            claude.send_message(&expanded).await?;
-                        }
+                    self.submit_text(text.clone()).await?;
+                    // A send held back for confirmation is remembered once it goes out
+                    if self.large_send_pending.as_deref() != Some(text.as_str()) {
+                        self.history.push(text);
                     }
                 }
             }
-            KeyCode::Enter if shift => {
+            KeyCode::Enter => {
                 self.input.insert_newline();
             }
             KeyCode::Char(c) if !ctrl => {
@@ -1013,6 +2595,9 @@ impl App {
             KeyCode::Home => {
                 self.input.move_home();
             }
+            KeyCode::End if self.input.is_empty() => {
+                self.resume_auto_scroll();
+            }
             KeyCode::End => {
                 self.input.move_end();
             }
@@ -1035,17 +2620,19 @@ impl App {
             }
             KeyCode::Up => {
                 self.overlay_state_mut(|s| s.move_up());
-                self.preview_theme();
+                self.update_overlay_preview();
             }
             KeyCode::Down => {
                 self.overlay_state_mut(|s| s.move_down());
-                self.preview_theme();
+                self.update_overlay_preview();
             }
             KeyCode::Backspace => {
                 self.overlay_state_mut(|s| s.backspace());
+                self.update_overlay_preview();
             }
             KeyCode::Char(c) => {
                 self.overlay_state_mut(|s| s.type_char(c));
+                self.update_overlay_preview();
             }
             _ => {}
         }
@@ -1059,8 +2646,17 @@ impl App {
             | AppMode::ThemePicker(ref mut state)
             | AppMode::SessionPicker(ref mut state)
             | AppMode::CheckpointTimeline(ref mut state)
-            | AppMode::WorkflowPicker(ref mut state) => f(state),
-            AppMode::Normal | AppMode::TextViewer { .. } | AppMode::HistorySearch { .. } | AppMode::TextInput { .. } | AppMode::UserQuestion { .. } | AppMode::PluginBrowser { .. } | AppMode::AgentDashboard { .. } => {}
+            | AppMode::WorkflowPicker(ref mut state)
+            | AppMode::FileRefPicker(ref mut state)
+            | AppMode::ModelPicker(ref mut state)
+            | AppMode::ResumeDirPicker(ref mut state)
+            | AppMode::ComparePicker(ref mut state)
+            | AppMode::RespawnFailed(ref mut state)
+            | AppMode::FileContext(ref mut state)
+            | AppMode::CodeBlockPicker(ref mut state)
+            | AppMode::FileDiffPicker(ref mut state)
+            | AppMode::Welcome(ref mut state) => f(state),
+            AppMode::Normal | AppMode::TextViewer { .. } | AppMode::SessionInfo { .. } | AppMode::HistorySearch { .. } | AppMode::TextInput { .. } | AppMode::UserQuestion { .. } | AppMode::PluginBrowser { .. } | AppMode::AgentDashboard { .. } | AppMode::DiffViewer { .. } => {}
         }
     }
 
@@ -1108,13 +2704,26 @@ impl App {
             });
         }
 
+        // Add saved snippets as `/snip <name>`
+        for (name, text) in self.snippets.iter() {
+            items.push(CompletionItem {
+                name: format!("snip {name}"),
+                description: text.lines().next().unwrap_or("").to_string(),
+                score: 0,
+            });
+        }
+
         items
     }
 
     /// Update slash command completions based on current input text using fuzzy matching.
     fn update_completions(&mut self) {
         let content = self.input.content();
-        if !content.starts_with('/') || content.contains(' ') || content.contains('\n') {
+        // Spaces end completion, except inside `/snip <name>`
+        if !content.starts_with('/')
+            || (content.contains(' ') && !content.starts_with("/snip "))
+            || content.contains('\n')
+        {
             self.completion = None;
             return;
         }
@@ -1183,6 +2792,9 @@ impl App {
     /// Check if the input is a command that should be handled locally.
     fn handle_local_command(&self, text: &str) -> Option<LocalAction> {
         let trimmed = text.trim();
+        if let Some(command) = snippets::parse_command(trimmed) {
+            return Some(LocalAction::Snippet(command));
+        }
         match trimmed {
             "/clear" => Some(LocalAction::Clear),
             "/help" => Some(LocalAction::Help),
@@ -1190,155 +2802,766 @@ impl App {
             "/model" => Some(LocalAction::ShowModel),
             "/memory" => Some(LocalAction::ShowMemory),
             "/plugins" => Some(LocalAction::ShowPlugins),
+            "/stats" => Some(LocalAction::ShowStats),
             "/exit" | "/quit" => Some(LocalAction::Exit),
             "/theme" => Some(LocalAction::ChangeTheme),
             _ => None,
         }
     }
 
+    /// Save, delete, expand or list `/snip` prompt snippets.
+    fn run_snippet_command(&mut self, command: SnipCommand) {
+        let msg = match command {
+            SnipCommand::Save(name) => {
+                // The `/snip save` line itself is already in history, so skip commands
+                let last_prompt = (0..self.history.len())
+                    .filter_map(|i| self.history.get_reverse(i))
+                    .find(|entry| !entry.starts_with('/'))
+                    .map(String::from);
+                match last_prompt {
+                    Some(text) => match self.snippets.insert(&name, &text) {
+                        Ok(()) => format!("Saved snippet \"{name}\""),
+                        Err(e) => format!("Failed to save snippet: {e:#}"),
+                    },
+                    None => "No prompt to save yet".to_string(),
+                }
+            }
+            SnipCommand::Delete(name) => match self.snippets.remove(&name) {
+                Ok(true) => format!("Deleted snippet \"{name}\""),
+                Ok(false) => format!("No snippet named \"{name}\""),
+                Err(e) => format!("Failed to delete snippet: {e:#}"),
+            },
+            SnipCommand::Expand(name) => match self.snippets.get(&name) {
+                Some(text) => {
+                    self.input.set_content(text);
+                    return;
+                }
+                None => format!("No snippet named \"{name}\""),
+            },
+            SnipCommand::List => {
+                self.show_snippets_viewer();
+                return;
+            }
+        };
+        self.toast = Some(Toast::new(msg));
+    }
+
+    fn show_snippets_viewer(&mut self) {
+        let mut lines = vec![
+            "Usage:".to_string(),
+            "  /snip save <name>     Save your last prompt".to_string(),
+            "  /snip <name>          Put a snippet in the input box".to_string(),
+            "  /snip delete <name>   Delete a snippet".to_string(),
+            String::new(),
+        ];
+        if self.snippets.iter().next().is_none() {
+            lines.push("No snippets saved yet.".to_string());
+        }
+        for (name, text) in self.snippets.iter() {
+            let first = text.lines().next().unwrap_or("");
+            lines.push(format!("  {name:<20} {first}"));
+        }
+        self.mode = AppMode::TextViewer {
+            title: format!("Snippets ({})", self.snippets.path().display()),
+            lines,
+            scroll: 0,
+        };
+    }
+
     fn scroll_to_bottom(&mut self) {
-        self.scroll_offset = usize::MAX;
+        self.scroll_offset = self.bottom_offset();
     }
 
-    fn clamp_scroll(&mut self) {
-        let total = ui::claude_pane::total_lines_with_options(&self.conversation, 80, &self.theme, self.tools_expanded);
-        let max_scroll = total.saturating_sub(10);
-        if self.scroll_offset >= max_scroll {
-            self.scroll_offset = max_scroll;
-            self.auto_scroll = true;
+    /// The scroll offset that puts the last rendered line (the spinner, while
+    /// one shows) at the bottom of the viewport.
+    fn bottom_offset(&self) -> usize {
+        let total = self.line_cache.total_lines(
+            &self.conversation,
+            self.conversation_width,
+            &self.theme,
+            self.render_options(),
+        ) + usize::from(crate::ui::claude_pane::shows_spinner(&self.conversation));
+        total.saturating_sub(self.visible_height)
+    }
+
+    /// Keep the bottom line pinned while following output, computing the
+    /// exact offset each frame so a growing message extends the view
+    /// smoothly rather than snapping from a sentinel.
+    fn anchor_scroll(&mut self) {
+        let bottom = self.bottom_offset();
+        if self.auto_scroll || self.scroll_offset > bottom {
+            self.scroll_offset = bottom;
+        }
+        // Messages that arrived out of view start fading once the view is back on them
+        if self.auto_scroll {
+            if let Some((_, start @ None)) = &mut self.fresh_messages {
+                *start = Some(self.frame_count);
+            }
         }
     }
 
-    fn open_theme_picker(&mut self) {
-        let themes = crate::theme::Theme::list_available();
-        let items: Vec<OverlayItem> = themes
-            .iter()
-            .map(|name| {
-                let display = crate::theme::Theme::load(name)
-                    .map(|t| t.name)
-                    .unwrap_or_else(|_| name.clone());
-                OverlayItem {
-                    label: display,
-                    value: name.clone(),
-                    hint: String::new(),
-                }
-            })
-            .collect();
+    /// Highlight messages from `first` on as newly arrived: right away while
+    /// following, or when the view next returns to the bottom.
+    fn mark_fresh_messages(&mut self, first: usize) {
+        if !self.config.highlight_new_messages {
+            return;
+        }
+        let first = match self.fresh_messages {
+            // Still waiting to be seen: keep the earliest unseen message
+            Some((earlier, None)) => earlier.min(first),
+            _ => first,
+        };
+        let start = self.auto_scroll.then_some(self.frame_count);
+        self.fresh_messages = Some((first, start));
+    }
 
-        let current_idx = items
-            .iter()
-            .position(|i| i.value == self.theme_name)
-            .unwrap_or(0);
-        let mut state = OverlayState::new(items, Some(self.theme_name.clone()));
-        state.selected = current_idx;
-        self.mode = AppMode::ThemePicker(state);
+    /// First message to tint and how strongly, fading linearly to nothing
+    /// over a second's worth of frames.
+    fn fresh_tint(&self) -> Option<(usize, f64)> {
+        let (first, start) = self.fresh_messages?;
+        let fade_frames = self.config.fps.max(1) as u64;
+        let age = self.frame_count.wrapping_sub(start?);
+        (age < fade_frames).then(|| (first, 1.0 - age as f64 / fade_frames as f64))
     }
 
-    /// Check whether a given slash command is available from Claude CLI.
-    fn has_slash_command(&self, name: &str) -> bool {
-        self.slash_commands.iter().any(|c| c == name)
+    fn scroll_up(&mut self, lines: usize) {
+        self.auto_scroll = false;
+        self.scroll_offset = self.scroll_offset.saturating_sub(lines);
     }
 
-    fn open_action_menu(&mut self) {
-        let mut items = vec![
-            OverlayItem {
-                label: "Continue Last Session".to_string(),
-                value: "continue".to_string(),
-                hint: String::new(),
-            },
-            OverlayItem {
-                label: "Resume Session".to_string(),
-                value: "resume".to_string(),
-                hint: String::new(),
-            },
-        ];
+    fn scroll_down(&mut self, lines: usize) {
+        self.scroll_offset = self.scroll_offset.saturating_add(lines);
+        self.clamp_scroll();
+    }
 
-        // Only show commands that are actually available in stream-json mode
-        if self.has_slash_command("rename") {
-            items.push(OverlayItem {
-                label: "Rename Session".to_string(),
-                value: "rename".to_string(),
-                hint: String::new(),
-            });
-        }
-        if self.has_slash_command("compact") {
-            items.push(OverlayItem {
-                label: "Compact Context".to_string(),
-                value: "compact".to_string(),
-                hint: String::new(),
-            });
-        }
-        if self.has_slash_command("rewind") {
-            items.push(OverlayItem {
-                label: "Rewind to Checkpoint".to_string(),
-                value: "rewind".to_string(),
-                hint: String::new(),
-            });
+    /// Re-gather git info and adapt the refresh interval to how long it took.
+    fn refresh_git_info(&mut self) {
+        let (info, cost) = GitInfo::gather_timed();
+        self.git_info = info;
+        self.git_gather_cost = cost;
+        self.git_refresh_interval = crate::git::refresh_interval(cost);
+    }
+
+    /// Empty the displayed conversation, keeping the messages briefly so an
+    /// accidental `/clear` can be undone with Ctrl+Z.
+    fn clear_conversation(&mut self) {
+        let messages = std::mem::take(&mut self.conversation.messages);
+        self.conversation = Conversation::new();
+        self.fresh_messages = None;
+        self.scroll_offset = 0;
+        self.auto_scroll = true;
+        if !messages.is_empty() {
+            self.cleared = Some((messages, std::time::Instant::now()));
+            self.toast = Some(Toast::new("Cleared — press Ctrl+Z to undo".to_string()));
         }
+    }
 
-        items.push(OverlayItem {
-            label: "Workflow Templates".to_string(),
-            value: "workflows".to_string(),
-            hint: "Ctrl+W".to_string(),
-        });
-        items.push(OverlayItem {
-            label: if self.split_pane { "Close Split Pane".to_string() } else { "Split Pane".to_string() },
-            value: "split".to_string(),
-            hint: "Ctrl+S".to_string(),
-        });
-        {
-            let active = self.agent_tasks.iter().filter(|t| !t.completed).count();
-            let total = self.agent_tasks.len();
-            let label = if total == 0 {
-                "Agent Dashboard".to_string()
-            } else {
-                format!("Agent Dashboard ({active} active / {total} total)")
-            };
-            items.push(OverlayItem {
-                label,
-                value: "agents".to_string(),
-                hint: "Ctrl+A".to_string(),
-            });
+    /// Put back the messages removed by a recent `/clear`, ahead of anything
+    /// sent since. Only the display is restored: Claude's own context isn't.
+    fn undo_clear(&mut self) {
+        match self.cleared.take() {
+            Some((mut messages, at)) if at.elapsed() < CLEAR_UNDO_WINDOW => {
+                messages.append(&mut self.conversation.messages);
+                self.conversation.messages = messages;
+                self.scroll_to_bottom();
+                self.toast = Some(Toast::new("Conversation restored".to_string()));
+            }
+            _ => self.toast = Some(Toast::new("Nothing to undo".to_string())),
         }
-        items.push(OverlayItem {
-            label: "Switch Theme".to_string(),
-            value: "theme".to_string(),
-            hint: "Ctrl+T".to_string(),
-        });
-        items.push(OverlayItem {
-            label: "Quit".to_string(),
-            value: "quit".to_string(),
-            hint: "Ctrl+Q".to_string(),
-        });
+    }
 
-        self.mode = AppMode::ActionMenu(OverlayState::new(items, None));
+    /// Ask Claude to pick up where a `max_tokens`-truncated response stopped.
+    async fn continue_truncated_response(&mut self) -> Result<()> {
+        self.send_prompt("continue".to_string(), "continue".to_string(), Vec::new()).await?;
+        Ok(())
     }
 
-    fn open_session_picker(&mut self) {
-        let all_sessions = sessions::discover_sessions();
-        let items: Vec<OverlayItem> = all_sessions
-            .into_iter()
-            .take(50)
-            .map(|s| {
-                let label = if s.preview.is_empty() {
-                    format!("{} ({})", s.project_path, s.age_string())
-                } else {
-                    format!("{} — {}", s.age_string(), s.preview)
-                };
-                OverlayItem {
-                    label,
-                    value: s.session_id,
-                    hint: s.project_path,
-                }
-            })
-            .collect();
+    /// Ask Claude to summarize the conversation lines currently on screen,
+    /// e.g. the visible part of a huge log.
+    async fn summarize_visible(&mut self) -> Result<()> {
+        let lines = self.line_cache.visible_text(
+            &self.conversation,
+            self.conversation_width,
+            &self.theme,
+            self.render_options(),
+            self.scroll_offset,
+            self.visible_height,
+        );
+        let text = lines.join("\n");
+        if text.trim().is_empty() {
+            self.toast = Some(Toast::new("Nothing on screen to summarize".to_string()));
+            return Ok(());
+        }
+        let prompt = format!("Summarize this output:\n\n{}", text.trim_matches('\n'));
+        self.send_prompt(prompt.clone(), prompt, Vec::new()).await?;
+        Ok(())
+    }
 
-        if items.is_empty() {
-            self.toast = Some(Toast::new("No sessions found".to_string()));
+    /// Show the next `LOAD_OLDER_STEP` archived messages in full and scroll
+    /// to the top so they are in view.
+    fn load_older_messages(&mut self) {
+        let loaded = self.conversation.load_older(LOAD_OLDER_STEP);
+        if loaded == 0 {
+            self.toast = Some(Toast::new("No older messages to load".to_string()));
             return;
         }
+        self.auto_scroll = false;
+        self.scroll_offset = 0;
+        let left = self.conversation.summarized();
+        self.toast = Some(Toast::new(format!("Loaded {loaded} older messages ({left} still archived)")));
+    }
 
-        self.mode = AppMode::SessionPicker(OverlayState::new(items, None));
+    /// Whether `submit_key = "ctrl+enter"` swaps Enter and Ctrl+Enter (or
+    /// Alt+Enter, for terminals that can't tell Ctrl+Enter from Enter).
+    fn ctrl_enter_submits(&self) -> bool {
+        self.config.submit_key == "ctrl+enter"
+    }
+
+    /// Whether `key` (an Enter press) sends the input rather than inserting a newline.
+    fn is_submit_key(&self, key: event::KeyEvent) -> bool {
+        if self.ctrl_enter_submits() {
+            key.modifiers.intersects(KeyModifiers::CONTROL | KeyModifiers::ALT)
+        } else {
+            !key.modifiers.contains(KeyModifiers::SHIFT)
+        }
+    }
+
+    /// Answer the pending ExitPlanMode request. Approving lets Claude start
+    /// implementing; rejecting keeps it in plan mode.
+    async fn answer_plan(&mut self, approve: bool) -> Result<()> {
+        if self.blocked_by_review() {
+            return Ok(());
+        }
+        let Some(request) = self.pending_plan.take() else {
+            self.toast = Some(Toast::new("No plan awaiting approval".to_string()));
+            return Ok(());
+        };
+        // The plan answer is not a prompt; a later transient error must not
+        // resend whatever was typed before it.
+        self.last_prompt = None;
+        if let Some(ref mut claude) = self.claude {
+            claude
+                .send_permission_response(&request.request_id, approve, &request.input, "Plan rejected by user")
+                .await?;
+        }
+        let msg = if approve { "Plan accepted" } else { "Plan rejected" };
+        self.toast = Some(Toast::new(msg.to_string()));
+        Ok(())
+    }
+
+    /// Scroll so the next (or previous) tool call sits at the top of the
+    /// viewport, and highlight it briefly.
+    fn jump_to_tool_call(&mut self, forward: bool) {
+        let tool_lines = self.line_cache.tool_use_lines(
+            &self.conversation,
+            self.conversation_width,
+            &self.theme,
+            self.render_options(),
+        );
+        let target = if forward {
+            tool_lines.into_iter().find(|&line| line > self.scroll_offset)
+        } else {
+            tool_lines.into_iter().rev().find(|&line| line < self.scroll_offset)
+        };
+        match target {
+            Some(line) => {
+                self.auto_scroll = false;
+                self.scroll_offset = line;
+                self.tool_jump = Some((line, std::time::Instant::now()));
+            }
+            None => {
+                let msg = if forward { "No later tool calls" } else { "No earlier tool calls" };
+                self.toast = Some(Toast::new(msg.to_string()));
+            }
+        }
+    }
+
+    /// The tool call last jumped to with Tab, while it is still at the top
+    /// of the screen.
+    fn jumped_tool_use_id(&self) -> Option<String> {
+        let (line, _) = self.tool_jump.filter(|&(line, _)| line == self.scroll_offset)?;
+        let tool_lines = self.line_cache.tool_use_lines(
+            &self.conversation,
+            self.conversation_width,
+            &self.theme,
+            self.render_options(),
+        );
+        let index = tool_lines.iter().position(|&l| l == line)?;
+        // Tool calls render in order, except in summarized messages
+        self.conversation.messages[self.conversation.summarized()..]
+            .iter()
+            .flat_map(|msg| &msg.content)
+            .filter_map(|block| match block {
+                ContentBlock::ToolUse { id, .. } => Some(id.clone()),
+                _ => None,
+            })
+            .nth(index)
+    }
+
+    fn toggle_tool_result(&mut self, tool_use_id: &str) {
+        let msg = match self.conversation.toggle_tool_result(tool_use_id) {
+            None => "No output yet",
+            Some(_) if self.tools_expanded => "All tool output is expanded (Ctrl+E)",
+            Some(true) => "Tool output collapsed",
+            Some(false) => "Tool output expanded",
+        };
+        self.toast = Some(Toast::new(msg.to_string()));
+    }
+
+    /// Display toggles for rendering the conversation.
+    fn render_options(&self) -> RenderOptions {
+        RenderOptions {
+            tools_expanded: self.tools_expanded,
+            show_timestamps: self.config.show_timestamps,
+            show_thinking: self.show_thinking,
+            tool_preview: ToolPreview::from_config(&self.config.tool_preview),
+            tool_glyphs: self.config.use_tool_glyphs,
+            tool_header_width: self.config.tool_header_width,
+            padding: self.config.conversation_padding,
+            prose_only: self.prose_only,
+            wrap: self.wrap_lines,
+            plan_pending: self.pending_plan.is_some(),
+        }
+    }
+
+    fn toggle_thinking(&mut self) {
+        self.show_thinking = !self.show_thinking;
+        let msg = if self.show_thinking { "Thinking shown" } else { "Thinking hidden" };
+        self.toast = Some(Toast::new(msg.to_string()));
+    }
+
+    fn toggle_prose_only(&mut self) {
+        self.prose_only = !self.prose_only;
+        let msg = if self.prose_only { "Focus mode: prose only" } else { "Focus mode off" };
+        self.toast = Some(Toast::new(msg.to_string()));
+        self.clamp_scroll();
+    }
+
+    fn toggle_wrap(&mut self) {
+        self.wrap_lines = !self.wrap_lines;
+        self.horizontal_offset = 0;
+        let msg = if self.wrap_lines { "Line wrapping on" } else { "Line wrapping off: Shift+Left/Right to pan" };
+        self.toast = Some(Toast::new(msg.to_string()));
+        self.clamp_scroll();
+    }
+
+    /// Pan unwrapped content right, stopping once the widest line's end is in view.
+    fn pan_right(&mut self) {
+        let widest = self.line_cache.max_line_width(
+            &self.conversation,
+            self.conversation_width,
+            &self.theme,
+            self.render_options(),
+        );
+        let max_offset = widest.saturating_sub(self.conversation_width);
+        self.horizontal_offset = (self.horizontal_offset + HORIZONTAL_STEP).min(max_offset);
+    }
+
+    /// Half the conversation viewport, for Ctrl+U / Ctrl+D.
+    fn half_page(&self) -> usize {
+        (self.visible_height / 2).max(1)
+    }
+
+    /// Stop a downward scroll at the bottom of the conversation, as laid out
+    /// in the last frame.
+    fn clamp_scroll(&mut self) {
+        let bottom = self.bottom_offset();
+        if self.scroll_offset >= bottom {
+            self.scroll_offset = bottom;
+            if self.config.follow_on_scroll() {
+                self.auto_scroll = true;
+            }
+        }
+    }
+
+    /// Send `text` as if it was typed and submitted: a local command, a
+    /// custom command, a slash command or a message. Returns whether it went
+    /// to Claude, so a turn is under way.
+    async fn submit_text(&mut self, text: String) -> Result<bool> {
+        if let Some(action) = self.handle_local_command(&text) {
+            // Command handled locally
+            match action {
+                LocalAction::Clear => self.clear_conversation(),
+                LocalAction::Help => {
+                    self.show_help_viewer();
+                }
+                LocalAction::ShowConfig => {
+                    self.show_config_viewer();
+                }
+                LocalAction::ShowModel => {
+                    let model = self.detected_model.as_deref()
+                        .or(self.model_override.as_deref())
+                        .or(self.config.model.as_deref())
+                        .unwrap_or("(default)");
+                    self.toast = Some(Toast::new(format!("Model: {model}")));
+                }
+                LocalAction::ShowMemory => {
+                    self.open_memory_viewer();
+                }
+                LocalAction::ShowPlugins => {
+                    self.open_plugin_browser();
+                }
+                LocalAction::ShowStats => {
+                    self.show_session_stats();
+                }
+                LocalAction::Exit => {
+                    self.should_quit = true;
+                }
+                LocalAction::ChangeTheme => {
+                    self.open_theme_picker();
+                }
+                LocalAction::Snippet(command) => {
+                    self.run_snippet_command(command);
+                }
+            }
+            Ok(false)
+        } else if let Some(prompt) = self.resolve_custom_command(&text) {
+            // Custom command — substitute args and send as user message
+            self.send_prompt(prompt.clone(), prompt, Vec::new()).await
+        } else if text.starts_with('/') {
+            // Slash command — send to Claude but don't add as user message
+            self.auto_scroll = true;
+            self.scroll_to_bottom();
+            self.send_slash_command(text).await
+        } else {
+            // Normal user message — expand @file mentions before sending
+            let max_bytes = self.config.max_mention_bytes;
+            let expansion = expand_file_mentions(&text, max_bytes);
+            if expansion.injected_bytes > LARGE_MENTION_WARN_BYTES
+                && self.large_send_pending.as_deref() != Some(text.as_str())
+            {
+                // Hold it back in the input; sending it again confirms
+                let size = format_bytes(expansion.injected_bytes);
+                self.toast = Some(Toast::new(format!("Large context: {size} of @file content · send again to confirm")));
+                self.input.set_content(&text);
+                self.large_send_pending = Some(text);
+                return Ok(false);
+            }
+            self.large_send_pending = None;
+            let images = std::mem::take(&mut self.pending_images);
+            let mut display = "[Image attached]\n".repeat(images.len());
+            display.push_str(&text);
+            if let Some(notice) = expansion.notice(max_bytes) {
+                self.toast = Some(Toast::new(notice));
+            }
+            self.send_prompt(display.trim_end().to_string(), expansion.text, images).await
+        }
+    }
+
+    /// Show `display` as the user's message and send `text` (plus any
+    /// images) to Claude. Every prompt goes through here so the last one is
+    /// remembered for `auto_retry`. Returns whether it went to Claude.
+    async fn send_prompt(&mut self, display: String, text: String, images: Vec<std::path::PathBuf>) -> Result<bool> {
+        if self.blocked_by_review() {
+            return Ok(false);
+        }
+        self.conversation.push_user_message(display);
+        self.auto_scroll = true;
+        self.scroll_to_bottom();
+        self.retry_count = 0;
+        let Some(ref mut claude) = self.claude else {
+            self.last_prompt = None;
+            return Ok(false);
+        };
+        claude.send_message_with_images(&text, &images).await?;
+        self.last_prompt = Some((text, images));
+        self.arm_init_check();
+        Ok(true)
+    }
+
+    /// In review mode nothing may reach Claude; say so and return true.
+    fn blocked_by_review(&mut self) -> bool {
+        if self.review_mode {
+            self.toast = Some(Toast::new("Review mode: input disabled (Ctrl+K to exit)".to_string()));
+        }
+        self.review_mode
+    }
+
+    /// Send a slash command such as `/compact`. Slash commands are not
+    /// retried on a transient error, so the remembered prompt is cleared.
+    async fn send_slash_command(&mut self, cmd: String) -> Result<bool> {
+        if self.blocked_by_review() {
+            return Ok(false);
+        }
+        self.last_prompt = None;
+        let Some(ref mut claude) = self.claude else {
+            return Ok(false);
+        };
+        claude.send_message(&cmd).await?;
+        self.pending_slash_command = Some(cmd);
+        self.arm_init_check();
+        Ok(true)
+    }
+
+    /// Jump to the bottom and follow streaming output again.
+    fn resume_auto_scroll(&mut self) {
+        self.auto_scroll = true;
+        self.scroll_to_bottom();
+    }
+
+    fn open_theme_picker(&mut self) {
+        let themes = crate::theme::Theme::list_available();
+        let items: Vec<OverlayItem> = themes
+            .iter()
+            .map(|name| {
+                let display = crate::theme::Theme::load(name)
+                    .map(|t| t.name)
+                    .unwrap_or_else(|_| name.clone());
+                OverlayItem {
+                    label: display,
+                    value: name.clone(),
+                    hint: String::new(),
+                    is_header: false,
+                }
+            })
+            .collect();
+
+        let current_idx = items
+            .iter()
+            .position(|i| i.value == self.theme_name)
+            .unwrap_or(0);
+        let mut state = OverlayState::new(items, Some(self.theme_name.clone()));
+        state.selected = current_idx;
+        self.mode = AppMode::ThemePicker(state);
+    }
+
+    /// Check whether a given slash command is available from Claude CLI.
+    fn has_slash_command(&self, name: &str) -> bool {
+        self.slash_commands.iter().any(|c| c == name)
+    }
+
+    /// Open the command palette: every action grouped by category, with
+    /// keybinding hints and fuzzy filtering.
+    fn open_action_menu(&mut self) {
+        fn action(label: impl Into<String>, value: &str, hint: &str) -> OverlayItem {
+            OverlayItem {
+                label: label.into(),
+                value: value.to_string(),
+                hint: hint.to_string(),
+                is_header: false,
+            }
+        }
+
+        let mut items = vec![
+            OverlayItem::header("Session"),
+            action("Continue Last Session", "continue", ""),
+            action("Resume Session", "resume", ""),
+            action("Compare Sessions", "compare", ""),
+        ];
+
+        // Only show commands that are actually available in stream-json mode
+        if self.has_slash_command("rename") {
+            items.push(action("Rename Session", "rename", ""));
+        }
+        if self.has_slash_command("compact") {
+            items.push(action("Compact Context", "compact", ""));
+        }
+        if self.has_slash_command("rewind") {
+            items.push(action("Rewind to Checkpoint", "rewind", ""));
+        }
+        if self.pending_plan.is_some() {
+            items.push(action("Accept Plan", "acceptplan", "Alt+Y"));
+            items.push(action("Reject Plan", "rejectplan", "Alt+N"));
+        }
+        if !self.pending_images.is_empty() {
+            items.push(action("Remove Attached Image", "dropimage", "Alt+V"));
+        }
+        if self.conversation.summarized() > 0 {
+            items.push(action("Load Older Messages", "loadolder", "Ctrl+L"));
+        }
+        items.push(action("Clear Conversation", "clear", "/clear"));
+        items.push(action("Search History", "history", "Ctrl+R"));
+        items.push(action("Ask Another Model", "askmodel", ""));
+        if matches!(self.split_content, SplitContent::AltAnswer(..)) {
+            items.push(action("Hide Other Model's Answer", "althide", ""));
+        }
+
+        items.push(OverlayItem::header("View"));
+        if self.last_overlay.is_some() {
+            items.push(action("Reopen Last Overlay", "reopen", "Alt+K"));
+        }
+        items.push(action(
+            if self.split_pane { "Close Split Pane" } else { "Split Pane" },
+            "split",
+            "Ctrl+S",
+        ));
+        items.push(action(
+            if self.split_pinned.is_some() { "Unpin Split Pane" } else { "Pin Split Pane to File" },
+            "pin",
+            "Alt+P",
+        ));
+        items.push(action(
+            if self.review_mode { "Exit Review Mode" } else { "Review Mode (Read-Only)" },
+            "review",
+            "",
+        ));
+        items.push(action(
+            if self.auto_scroll { "Pause Auto-Scroll" } else { "Resume Auto-Scroll" },
+            "autoscroll",
+            "End",
+        ));
+        {
+            let active = self.agent_tasks.iter().filter(|t| !t.completed).count();
+            let total = self.agent_tasks.len();
+            let label = if total == 0 {
+                "Agent Dashboard".to_string()
+            } else {
+                format!("Agent Dashboard ({active} active / {total} total)")
+            };
+            items.push(action(label, "agents", "Ctrl+A"));
+        }
+        items.push(action("Diff Viewer", "diff", "Ctrl+G"));
+        items.push(action("Session Diff for File...", "filediff", ""));
+        if !self.review_mode {
+            items.push(action("Summarize Visible Output", "summarize", "Alt+S"));
+            items.push(action("Insert Last Tool Error", "inserterror", "Alt+E"));
+        }
+        items.push(action("Export Diff as Patch", "exportpatch", ""));
+        items.push(action(
+            format!("Command Log ({})", self.command_log.len()),
+            "commandlog",
+            "",
+        ));
+        items.push(action(
+            if matches!(self.split_content, SplitContent::EventLog(_)) { "Hide Event Log" } else { "Raw Event Log" },
+            "eventlog",
+            "",
+        ));
+        items.push(action(
+            format!("Unrecognised CLI Output ({})", self.unknown_lines.len()),
+            "unknown",
+            "",
+        ));
+        items.push(action("Notifications", "notifications", "Ctrl+X"));
+        items.push(action(
+            if self.pty_session.is_some() { "Focus Terminal" } else { "Open Terminal" },
+            "terminal",
+            "",
+        ));
+        items.push(action("Open File Reference", "filerefs", "Ctrl+O"));
+        items.push(action("File Context", "files", "Ctrl+F"));
+        items.push(action("Copy Code Block", "copycode", "Ctrl+Y"));
+        items.push(action(
+            if self.tools_expanded { "Collapse Tool Output" } else { "Expand Tool Output" },
+            "tools",
+            "Ctrl+E",
+        ));
+        items.push(action(
+            if self.show_thinking { "Hide Thinking" } else { "Show Thinking" },
+            "thinking",
+            "Ctrl+B",
+        ));
+        items.push(action(
+            if self.prose_only { "Exit Focus Mode" } else { "Focus Mode (Prose Only)" },
+            "prose",
+            "Alt+F",
+        ));
+        items.push(action(
+            if self.wrap_lines { "Disable Line Wrapping" } else { "Enable Line Wrapping" },
+            "wrap",
+            "Alt+W",
+        ));
+        items.push(action("CLAUDE.md Instructions", "instructions", "Ctrl+I"));
+        items.push(action("Auto-Memory", "memory", "Ctrl+M"));
+        items.push(action("Session Stats", "stats", "/stats"));
+        items.push(action("Session Info", "sessioninfo", ""));
+        items.push(action("Config", "config", "/config"));
+        items.push(action("Help", "help", "/help"));
+
+        items.push(OverlayItem::header("Plugins"));
+        items.push(action("Plugin Browser", "plugins", "Ctrl+P"));
+        {
+            let connected = self.mcp_servers.iter().filter(|s| s.is_connected()).count();
+            let label = if self.mcp_servers.is_empty() {
+                "MCP Status".to_string()
+            } else {
+                format!("MCP Status ({connected}/{} connected)", self.mcp_servers.len())
+            };
+            items.push(action(label, "mcp", ""));
+        }
+        items.push(action(
+            format!("Directories in Scope ({})", 1 + self.config.partition_additional_dirs(self.session_cwd.as_deref()).0.len()),
+            "dirs",
+            "",
+        ));
+
+        items.push(OverlayItem::header("Theme"));
+        items.push(action("Switch Theme", "theme", "Ctrl+T"));
+
+        items.push(OverlayItem::header("Workflows"));
+        items.push(action("Workflow Templates", "workflows", "Ctrl+W"));
+        for (idx, workflow) in self.workflows.iter().enumerate() {
+            items.push(action(format!("Workflow: {}", workflow.name), &format!("workflow:{idx}"), ""));
+        }
+
+        let show_macros = !self.config.macros.is_empty() && !self.review_mode;
+        if show_macros || self.running_macro.is_some() {
+            items.push(OverlayItem::header("Macros"));
+            if let Some(run) = &self.running_macro {
+                items.push(action(format!("Stop Macro \"{}\" ({}/{})", run.name, run.step, run.total), "stopmacro", ""));
+            }
+            let mut names: Vec<&String> = self.config.macros.keys().collect();
+            names.sort();
+            for name in names.into_iter().filter(|_| show_macros) {
+                let steps = self.config.macros[name].len();
+                let hint = if steps == 1 { "1 step".to_string() } else { format!("{steps} steps") };
+                items.push(action(format!("Macro: {name}"), &format!("macro:{name}"), &hint));
+            }
+        }
+
+        items.push(OverlayItem::header("App"));
+        items.push(action("Quit", "quit", "Ctrl+Q"));
+
+        self.mode = AppMode::ActionMenu(OverlayState::fuzzy(items));
+    }
+
+    fn open_session_picker(&mut self) {
+        self.show_session_picker(sessions::discover_sessions());
+    }
+
+    /// Open the session picker on just these sessions, e.g. the several
+    /// that an ambiguous `--resume` prefix matched.
+    pub fn show_session_picker(&mut self, sessions: Vec<sessions::SessionInfo>) {
+        let items = session_items(sessions);
+        if items.is_empty() {
+            self.toast = Some(Toast::new("No sessions found".to_string()));
+            return;
+        }
+
+        self.session_previews.clear();
+        self.mode = AppMode::SessionPicker(OverlayState::new(items, None));
+        self.update_overlay_preview();
+    }
+
+    /// Pick two sessions to diff their assistant replies in the split pane.
+    fn open_compare_picker(&mut self) {
+        self.compare_first = None;
+        let items = session_items(sessions::discover_sessions());
+        if items.len() < 2 {
+            self.toast = Some(Toast::new("Need at least two sessions to compare".to_string()));
+            return;
+        }
+        self.session_previews.clear();
+        self.mode = AppMode::ComparePicker(OverlayState::new(items, None));
+        self.update_overlay_preview();
+    }
+
+    /// Show a unified diff of the assistant replies of two sessions in the
+    /// split pane.
+    fn compare_sessions(&mut self, first: &str, second: &str) {
+        let old = sessions::assistant_replies(first).join("\n\n");
+        let new = sessions::assistant_replies(second).join("\n\n");
+        if old.is_empty() && new.is_empty() {
+            self.toast = Some(Toast::new("No assistant replies to compare".to_string()));
+            return;
+        }
+        let Some(lines) = session_diff_lines(first, &old, second, &new) else {
+            self.toast = Some(Toast::new("These sessions differ too much to compare".to_string()));
+            return;
+        };
+        self.split_content = SplitContent::DiffView(lines);
+        self.split_scroll = 0;
+        self.split_pane = true;
     }
 
     fn open_history_search(&mut self) {
@@ -1380,6 +3603,7 @@ impl App {
                 };
                 self.mode = AppMode::Normal;
                 if let Some(text) = selected_text {
+                    self.input_draft = None;
                     self.input.set_content(&text);
                 }
             }
@@ -1429,22 +3653,19 @@ impl App {
             }
             KeyCode::Backspace => {
                 if let AppMode::TextInput { ref mut value, ref mut cursor, .. } = self.mode {
-                    if *cursor > 0 {
-                        value.remove(*cursor - 1);
-                        *cursor -= 1;
-                    }
+                    let prev = ui::input::prev_grapheme(value, *cursor);
+                    value.drain(prev..*cursor);
+                    *cursor = prev;
                 }
             }
             KeyCode::Left => {
-                if let AppMode::TextInput { ref mut cursor, .. } = self.mode {
-                    *cursor = cursor.saturating_sub(1);
+                if let AppMode::TextInput { ref value, ref mut cursor, .. } = self.mode {
+                    *cursor = ui::input::prev_grapheme(value, *cursor);
                 }
             }
             KeyCode::Right => {
                 if let AppMode::TextInput { ref value, ref mut cursor, .. } = self.mode {
-                    if *cursor < value.len() {
-                        *cursor += 1;
-                    }
+                    *cursor = ui::input::next_grapheme(value, *cursor);
                 }
             }
             KeyCode::Home => {
@@ -1460,7 +3681,7 @@ impl App {
             KeyCode::Char(c) => {
                 if let AppMode::TextInput { ref mut value, ref mut cursor, .. } = self.mode {
                     value.insert(*cursor, c);
-                    *cursor += 1;
+                    *cursor += c.len_utf8();
                 }
             }
             _ => {}
@@ -1471,8 +3692,16 @@ impl App {
     async fn handle_key_user_question(&mut self, key: event::KeyEvent) -> Result<()> {
         match key.code {
             KeyCode::Esc => {
-                // Dismiss without answering — Claude already got an error result
-                self.mode = AppMode::Normal;
+                // Dismiss without answering — Claude already got an error result.
+                // A permission prompt must still be answered, so Esc denies.
+                let mode = std::mem::replace(&mut self.mode, AppMode::Normal);
+                if let AppMode::UserQuestion { permission: Some(request), .. } = mode {
+                    if let Some(ref mut claude) = self.claude {
+                        claude
+                            .send_permission_response(&request.request_id, false, &request.input, "Denied by user")
+                            .await?;
+                    }
+                }
             }
             KeyCode::Up => {
                 if let AppMode::UserQuestion { ref mut cursor, ref questions, current_question, .. } = self.mode {
@@ -1506,8 +3735,18 @@ impl App {
             }
             KeyCode::Enter => {
                 let mode = std::mem::replace(&mut self.mode, AppMode::Normal);
-                if let AppMode::UserQuestion { questions, current_question, cursor, selected } = mode {
-                    if let Some(q) = questions.get(current_question) {
+                if let AppMode::UserQuestion { questions, current_question, cursor, selected, permission } = mode {
+                    if let Some(request) = permission {
+                        let allow = cursor == 0;
+                        if let Some(ref mut claude) = self.claude {
+                            claude
+                                .send_permission_response(&request.request_id, allow, &request.input, "Denied by user")
+                                .await?;
+                        }
+                        if !allow {
+                            self.toast = Some(Toast::new(format!("Denied {}", request.tool_name)));
+                        }
+                    } else if let Some(q) = questions.into_iter().nth(current_question) {
                         let answer = if q.multi_select {
                             // Collect all toggled options
                             let answers: Vec<&str> = q.options.iter()
@@ -1526,13 +3765,7 @@ impl App {
                         };
 
                         if !answer.is_empty() {
-                            // Send the user's answer as a regular message
-                            let response = format!("{}: {}", q.question, answer);
-                            self.conversation.push_user_message(response.clone());
-                            if let Some(ref mut claude) = self.claude {
-                                claude.send_message(&response).await?;
-                            }
-                            self.scroll_to_bottom();
+                            self.send_question_answer(&q.question, &answer).await?;
                         }
                     }
                 }
@@ -1542,31 +3775,107 @@ impl App {
         Ok(())
     }
 
+    /// Show AskUserQuestion questions: a choice list, or a text field for
+    /// free-text questions.
+    fn open_user_questions(&mut self, questions: Vec<UserQuestion>) {
+        let Some(first) = questions.first() else {
+            return;
+        };
+        if first.free_text {
+            self.mode = AppMode::TextInput {
+                prompt: first.question.clone(),
+                value: String::new(),
+                cursor: 0,
+                action: TextInputAction::AnswerQuestion { question: first.question.clone() },
+            };
+            return;
+        }
+        let num_options = first.options.len();
+        self.mode = AppMode::UserQuestion {
+            questions,
+            current_question: 0,
+            cursor: 0,
+            selected: vec![false; num_options],
+            permission: None,
+        };
+    }
+
+    /// Send the answer to an AskUserQuestion question as a regular message.
+    async fn send_question_answer(&mut self, question: &str, answer: &str) -> Result<()> {
+        let response = format!("{question}: {answer}");
+        self.send_prompt(response.clone(), response, Vec::new()).await?;
+        Ok(())
+    }
+
     async fn execute_text_input_action(&mut self, action: TextInputAction, value: &str) -> Result<()> {
         match action {
+            TextInputAction::AnswerQuestion { question } => {
+                self.send_question_answer(&question, value.trim()).await?;
+            }
             TextInputAction::RenameSession => {
                 if !self.has_slash_command("rename") {
                     self.toast = Some(Toast::new("/rename not available".to_string()));
                     return Ok(());
                 }
-                let cmd = format!("/rename {}", value);
-                self.pending_slash_command = Some(cmd.clone());
-                if let Some(ref mut claude) = self.claude {
-                    claude.send_message(&cmd).await?;
-                }
+                self.send_slash_command(format!("/rename {}", value)).await?;
                 self.toast = Some(Toast::new(format!("Renamed session to \"{}\"", value)));
             }
+            TextInputAction::WorkflowParam { name, prompt, mut remaining } => {
+                let slot = remaining.remove(0);
+                let prompt = prompt.replace(&format!("{{{slot}}}"), value.trim());
+                self.ask_workflow_params(name, prompt, remaining).await?;
+            }
         }
         Ok(())
     }
 
-    fn preview_theme(&mut self) {
-        if let AppMode::ThemePicker(ref state) = self.mode {
-            if let Some(value) = state.selected_value() {
-                if let Ok(new_theme) = crate::theme::Theme::load(&value) {
-                    self.theme = new_theme;
+    /// Run a workflow, first asking for each `{placeholder}` in its prompt.
+    async fn start_workflow(&mut self, name: &str, prompt: &str) -> Result<()> {
+        let placeholders = workflow_placeholders(prompt);
+        self.ask_workflow_params(name.to_string(), prompt.to_string(), placeholders).await
+    }
+
+    async fn ask_workflow_params(&mut self, name: String, prompt: String, remaining: Vec<String>) -> Result<()> {
+        let Some(slot) = remaining.first() else {
+            self.send_prompt(prompt.clone(), prompt, Vec::new()).await?;
+            return Ok(());
+        };
+        self.mode = AppMode::TextInput {
+            prompt: format!("{name}: {slot}"),
+            value: String::new(),
+            cursor: 0,
+            action: TextInputAction::WorkflowParam { name, prompt, remaining },
+        };
+        Ok(())
+    }
+
+    /// Preview the selected overlay item without committing to it: themes
+    /// are applied live (restored on cancel), while workflow and session
+    /// pickers show the prompt text / opening messages in a side panel.
+    fn update_overlay_preview(&mut self) {
+        match self.mode {
+            AppMode::ThemePicker(ref state) => {
+                if let Some(value) = state.selected_value() {
+                    if let Ok(new_theme) = crate::theme::Theme::load(&value) {
+                        self.theme = new_theme;
+                    }
                 }
             }
+            AppMode::WorkflowPicker(ref mut state) => {
+                state.preview = state
+                    .selected_value()
+                    .map(|prompt| prompt.lines().map(String::from).collect());
+            }
+            AppMode::SessionPicker(ref mut state) | AppMode::ComparePicker(ref mut state) => {
+                let previews = &mut self.session_previews;
+                state.preview = state.selected_value().map(|id| {
+                    previews
+                        .entry(id)
+                        .or_insert_with_key(|id| sessions::transcript_preview(id, SESSION_PREVIEW_MESSAGES))
+                        .clone()
+                });
+            }
+            _ => {}
         }
     }
 
@@ -1590,8 +3899,7 @@ impl App {
                     if let Ok(new_theme) = crate::theme::Theme::load(&value) {
                         self.theme = new_theme;
                         self.theme_name = value.clone();
-                        let config_path = crate::config::Config::default_path();
-                        let _ = crate::config::save_theme(&value, &config_path);
+                        let _ = crate::config::save_theme(&value, &self.config.path);
                     }
                 }
             }
@@ -1600,6 +3908,7 @@ impl App {
                     match value.as_str() {
                         "continue" => self.continue_last_session().await?,
                         "resume" => self.open_session_picker(),
+                        "compare" => self.open_compare_picker(),
                         "rename" => {
                             self.mode = AppMode::TextInput {
                                 prompt: "Session name".to_string(),
@@ -1609,13 +3918,13 @@ impl App {
                             };
                         }
                         "compact" => {
-                            self.pending_slash_command = Some("/compact".to_string());
-                            if let Some(ref mut claude) = self.claude {
-                                claude.send_message("/compact").await?;
-                            }
+                            self.send_slash_command("/compact".to_string()).await?;
                             self.toast = Some(Toast::new("Compacting context...".to_string()));
                         }
                         "rewind" => self.open_checkpoint_timeline(),
+                        "loadolder" => self.load_older_messages(),
+                        "acceptplan" => self.answer_plan(true).await?,
+                        "rejectplan" => self.answer_plan(false).await?,
                         "workflows" => self.open_workflow_picker(),
                         "split" => {
                             self.split_pane = !self.split_pane;
@@ -1623,40 +3932,228 @@ impl App {
                             self.toast = Some(Toast::new(msg.to_string()));
                         }
                         "agents" => self.open_agent_dashboard(),
+                        "diff" => self.open_diff_viewer(),
+                        "filediff" => self.open_file_diff_picker(),
+                        "files" => self.open_file_context_panel(),
+                        "copycode" => self.open_code_block_picker(),
+                        "commandlog" => self.open_command_log(),
+                        "eventlog" => self.toggle_event_log(),
+                        "pin" => self.toggle_split_pin(),
+                        "summarize" => self.summarize_visible().await?,
+                        "inserterror" => self.insert_last_tool_error(),
+                        "exportpatch" => self.export_patch(),
+                        "terminal" => self.open_terminal(),
+                        "unknown" => self.open_unknown_lines_viewer(),
+                        "notifications" => self.open_notifications_viewer(),
+                        "askmodel" => self.open_model_picker(),
+                        "dropimage" => self.remove_pending_image(),
+                        "althide" => {
+                            self.split_content = SplitContent::FileContext(Vec::new());
+                            self.split_scroll = 0;
+                        }
+                        "filerefs" => self.open_file_ref_picker(),
+                        "tools" => {
+                            self.tools_expanded = !self.tools_expanded;
+                            let msg = if self.tools_expanded { "Tool output expanded" } else { "Tool output collapsed" };
+                            self.toast = Some(Toast::new(msg.to_string()));
+                        }
+                        "thinking" => self.toggle_thinking(),
+                        "prose" => self.toggle_prose_only(),
+                        "wrap" => self.toggle_wrap(),
+                        "instructions" => self.open_instructions_viewer(),
+                        "memory" => self.open_memory_viewer(),
+                        "config" => self.show_config_viewer(),
+                        "help" => self.show_help_viewer(),
+                        "stats" => self.show_session_stats(),
+                        "sessioninfo" => self.show_session_info(),
+                        "history" => self.open_history_search(),
+                        "review" => {
+                            self.review_mode = !self.review_mode;
+                            let msg = if self.review_mode { "Review mode on: input disabled" } else { "Review mode off" };
+                            self.toast = Some(Toast::new(msg.to_string()));
+                            if self.review_mode && self.running_macro.take().is_some() {
+                                self.toast = Some(Toast::new("Review mode on: macro stopped".to_string()));
+                            }
+                        }
+                        "autoscroll" => {
+                            if self.auto_scroll {
+                                self.auto_scroll = false;
+                                self.toast = Some(Toast::new("Auto-scroll paused".to_string()));
+                            } else {
+                                self.resume_auto_scroll();
+                                self.toast = Some(Toast::new("Auto-scroll resumed".to_string()));
+                            }
+                        }
+                        "clear" => self.clear_conversation(),
+                        "plugins" => self.open_plugin_browser(),
+                        "mcp" => self.open_mcp_status(),
+                        "dirs" => self.open_directories(),
                         "theme" => self.open_theme_picker(),
                         "quit" => self.should_quit = true,
-                        _ => {}
+                        "reopen" => self.reopen_last_overlay(),
+                        "stopmacro" => {
+                            if let Some(run) = self.running_macro.take() {
+                                let msg = format!("Macro \"{}\" stopped after step {}/{}", run.name, run.step, run.total);
+                                self.toast = Some(Toast::new(msg));
+                            }
+                        }
+                        other if other.starts_with("macro:") => {
+                            self.start_macro(&other["macro:".len()..]).await?;
+                        }
+                        other => {
+                            let template = other
+                                .strip_prefix("workflow:")
+                                .and_then(|idx| idx.parse::<usize>().ok())
+                                .and_then(|idx| self.workflows.get(idx))
+                                .cloned();
+                            if let Some(workflow) = template {
+                                self.start_workflow(&workflow.name, &workflow.prompt).await?;
+                            }
+                        }
                     }
                 }
             }
             AppMode::SessionPicker(state) => {
                 if let Some(session_id) = state.selected_value() {
-                    self.resume_session(&session_id).await?;
+                    self.start_resume(&session_id).await?;
                 }
             }
-            AppMode::CheckpointTimeline(state) => {
-                if let Some(value) = state.selected_value() {
-                    // value is the turn number (1-based)
-                    let cmd = format!("/rewind {}", value);
-                    self.pending_slash_command = Some(cmd.clone());
-                    if let Some(ref mut claude) = self.claude {
-                        claude.send_message(&cmd).await?;
+            AppMode::ComparePicker(state) => {
+                if let Some(session_id) = state.selected_value() {
+                    match self.compare_first.take() {
+                        Some(first) => self.compare_sessions(&first, &session_id),
+                        None => {
+                            // Same list minus the first pick, for choosing the second
+                            let items = state
+                                .items
+                                .into_iter()
+                                .filter(|item| item.value != session_id)
+                                .collect();
+                            self.compare_first = Some(session_id);
+                            self.mode = AppMode::ComparePicker(OverlayState::new(items, None));
+                            self.update_overlay_preview();
+                            self.toast = Some(Toast::new("Pick a session to compare with".to_string()));
+                        }
                     }
+                }
+            }
+            AppMode::RespawnFailed(state) => {
+                if state.selected_value().as_deref() == Some("retry") {
+                    self.retry_respawn().await?;
+                } else {
+                    self.failed_respawn = None;
+                }
+            }
+            AppMode::ResumeDirPicker(state) => {
+                if let (Some(choice), Some((session_id, dir))) =
+                    (state.selected_value(), self.pending_resume.take())
+                {
+                    match choice.as_str() {
+                        "chdir" => {
+                            self.enter_session_dir(dir);
+                            self.resume_session(&session_id, None).await?;
+                        }
+                        "adddir" => {
+                            self.session_cwd = None;
+                            self.resume_session(&session_id, Some(&dir)).await?;
+                        }
+                        _ => {
+                            self.session_cwd = None;
+                            self.resume_session(&session_id, None).await?;
+                        }
+                    }
+                }
+            }
+            AppMode::CheckpointTimeline(state) => {
+                if let Some(value) = state.selected_value() {
+                    // value is the turn number (1-based)
+                    self.send_slash_command(format!("/rewind {}", value)).await?;
                     self.toast = Some(Toast::new(format!("Rewinding to turn {}...", value)));
                 }
             }
+            AppMode::FileRefPicker(state) => {
+                if let Some(value) = state.selected_value() {
+                    // value is "path:line"
+                    if let Some((path, line)) = value.rsplit_once(':') {
+                        let file_ref = crate::filerefs::FileRef {
+                            path: path.to_string(),
+                            line: line.parse().unwrap_or(1),
+                        };
+                        self.pending_editor = Some(crate::filerefs::editor_command(
+                            self.config.editor_command.as_deref(),
+                            &file_ref,
+                        ));
+                    }
+                }
+            }
+            AppMode::FileContext(state) => {
+                if let Some(path) = state.selected_value() {
+                    let file_ref = crate::filerefs::FileRef { path, line: 1 };
+                    self.pending_editor = Some(crate::filerefs::editor_command(
+                        self.config.editor_command.as_deref(),
+                        &file_ref,
+                    ));
+                }
+            }
+            AppMode::Welcome(state) => {
+                let config_path = self.config.path.clone();
+                match state.selected_value().as_deref() {
+                    Some("write") => {
+                        let msg = match crate::config::write_default(&config_path) {
+                            Ok(()) => format!("Wrote {}", config_path.display()),
+                            Err(e) => format!("Failed to write config: {e}"),
+                        };
+                        self.toast = Some(Toast::new(msg));
+                    }
+                    Some("dismiss") => {
+                        if let Err(e) = crate::config::save_welcomed(&config_path) {
+                            self.toast = Some(Toast::new(format!("Failed to save config: {e}")));
+                        }
+                    }
+                    Some("palette") => self.open_action_menu(),
+                    Some("theme") => self.open_theme_picker(),
+                    Some("split") => self.split_pane = true,
+                    Some("history") => self.open_history_search(),
+                    Some("help") => self.show_help_viewer(),
+                    _ => {}
+                }
+            }
+            AppMode::CodeBlockPicker(state) => {
+                let block = state
+                    .selected_value()
+                    .and_then(|idx| idx.parse::<usize>().ok())
+                    .and_then(|idx| self.last_code_blocks().into_iter().nth(idx));
+                if let Some(block) = block {
+                    let msg = match (self.copy_to_clipboard)(&block.code) {
+                        Ok(()) => format!("Copied {} lines", block.code.lines().count()),
+                        Err(e) => format!("Copy failed: {e}"),
+                    };
+                    self.toast = Some(Toast::new(msg));
+                }
+            }
+            AppMode::FileDiffPicker(state) => {
+                if let Some(path) = state.selected_value() {
+                    self.open_file_diff(&path);
+                }
+            }
+            AppMode::ModelPicker(state) => {
+                if let Some(model) = state.selected_value() {
+                    self.ask_another_model(&model).await?;
+                }
+            }
             AppMode::WorkflowPicker(state) => {
                 if let Some(value) = state.selected_value() {
                     // value is the workflow prompt text
-                    self.conversation.push_user_message(value.clone());
-                    self.auto_scroll = true;
-                    self.scroll_to_bottom();
-                    if let Some(ref mut claude) = self.claude {
-                        claude.send_message(&value).await?;
-                    }
+                    let name = state
+                        .items
+                        .iter()
+                        .find(|item| item.value == value)
+                        .map(|item| item.label.clone())
+                        .unwrap_or_default();
+                    self.start_workflow(&name, &value).await?;
                 }
             }
-            AppMode::Normal | AppMode::TextViewer { .. } | AppMode::HistorySearch { .. } | AppMode::TextInput { .. } | AppMode::UserQuestion { .. } | AppMode::PluginBrowser { .. } | AppMode::AgentDashboard { .. } => {}
+            AppMode::Normal | AppMode::TextViewer { .. } | AppMode::SessionInfo { .. } | AppMode::HistorySearch { .. } | AppMode::TextInput { .. } | AppMode::UserQuestion { .. } | AppMode::PluginBrowser { .. } | AppMode::AgentDashboard { .. } | AppMode::DiffViewer { .. } => {}
         }
         Ok(())
     }
@@ -1687,9 +4184,16 @@ impl App {
             }
         }
         lines.push(String::new());
+        lines.push("## Snippets".to_string());
+        lines.push("   /snip save <name>    Save your last prompt as a snippet".to_string());
+        lines.push("   /snip <name>         Put a snippet in the input box".to_string());
+        lines.push("   /snip delete <name>  Delete a snippet".to_string());
+        lines.push("   /snip                List snippets".to_string());
+        lines.push(String::new());
         lines.push("## Keyboard Shortcuts".to_string());
         lines.push("   Ctrl+Q              Quit".to_string());
-        lines.push("   Ctrl+K              Action menu".to_string());
+        lines.push("   Ctrl+K              Command palette".to_string());
+        lines.push("   Ctrl+Shift+K/Alt+K  Reopen the last closed overlay".to_string());
         lines.push("   Ctrl+T              Theme picker".to_string());
         lines.push("   Ctrl+R              History search".to_string());
         lines.push("   Ctrl+I              CLAUDE.md viewer".to_string());
@@ -1697,12 +4201,44 @@ impl App {
         lines.push("   Ctrl+P              Plugin browser".to_string());
         lines.push("   Ctrl+W              Workflow templates".to_string());
         lines.push("   Ctrl+S              Toggle split pane".to_string());
+        lines.push("   Alt+P               Pin/unpin split pane to its file".to_string());
+        lines.push("   Shift+Up/Down       Select a Grep/Glob match in the split pane".to_string());
+        lines.push("   Alt+O               Open the selected match in your editor".to_string());
+        lines.push("   Alt+S               Ask Claude to summarize what's on screen".to_string());
+        lines.push("   Alt+E               Insert the last tool error into the input".to_string());
         lines.push("   Ctrl+A              Agent dashboard".to_string());
-        lines.push("   Ctrl+F              File context panel".to_string());
-        lines.push("   Ctrl+D              Diff viewer".to_string());
+        lines.push("   Ctrl+F              File context panel (Enter opens in editor)".to_string());
+        lines.push("   Ctrl+G              Diff viewer".to_string());
         lines.push("   Ctrl+E              Toggle tool blocks".to_string());
+        lines.push("   Ctrl+B              Show/hide thinking blocks".to_string());
+        lines.push("   Alt+F               Focus mode: show only prose".to_string());
+        lines.push("   Alt+W               Toggle line wrapping (Shift+Left/Right pans when off)".to_string());
+        lines.push("   Ctrl+X              Recent notifications".to_string());
+        lines.push("   Ctrl+Z              Undo /clear (for a few seconds)".to_string());
+        lines.push("   Ctrl+V              Attach clipboard image".to_string());
+        lines.push("   Alt+V               Remove the last attached image".to_string());
+        lines.push("   Ctrl+O              Open file:line reference".to_string());
+        lines.push("   Ctrl+N              Continue a truncated response".to_string());
+        lines.push("   Alt+Y / Alt+N       Accept / reject the plan awaiting approval".to_string());
+        lines.push("   Ctrl+Enter          Accept the plan awaiting approval".to_string());
+        lines.push("   Ctrl+L              Load older archived messages".to_string());
+        lines.push("   Ctrl+Y              Copy a code block from the last response".to_string());
         lines.push("   PageUp/PageDown     Scroll conversation".to_string());
-        lines.push("   Shift+Enter         Insert newline".to_string());
+        lines.push("   Ctrl+U/Ctrl+D       Scroll half a page".to_string());
+        lines.push("   Alt+Backspace       Delete the word before the cursor".to_string());
+        lines.push("   Alt+U               Delete to the start of the line (again to join lines)".to_string());
+        lines.push("   Alt+C               Clear the whole input".to_string());
+        lines.push("   Tab/Shift+Tab       Jump to next/previous tool call".to_string());
+        lines.push("   Enter               Fold/unfold the jumped-to tool call's output".to_string());
+        lines.push("   Ctrl+]              Leave the split-pane terminal".to_string());
+        lines.push("   Up/Down             Scroll a line (when scrolled back)".to_string());
+        lines.push("   End                 Follow live output (empty input)".to_string());
+        if self.ctrl_enter_submits() {
+            lines.push("   Ctrl/Alt+Enter      Send message (submit_key = \"ctrl+enter\")".to_string());
+            lines.push("   Enter               Insert newline".to_string());
+        } else {
+            lines.push("   Shift+Enter         Insert newline (run /terminal-setup if it sends instead)".to_string());
+        }
         lines.push(String::new());
         lines.push("? = may not be available in stream-json mode".to_string());
 
@@ -1714,8 +4250,8 @@ impl App {
     }
 
     fn show_config_viewer(&mut self) {
-        let config_path = crate::config::Config::default_path();
-        let content = std::fs::read_to_string(&config_path).unwrap_or_else(|_| {
+        let config_path = &self.config.path;
+        let content = std::fs::read_to_string(config_path).unwrap_or_else(|_| {
             format!(
                 "# Config file not found\n# Create it at: {}\n#\n# Example:\n# command = \"claude\"\n# theme = \"catppuccin-mocha\"\n# fps = 30\n# model = \"claude-sonnet-4-5-20250929\"\n# permission_mode = \"default\"",
                 config_path.display()
@@ -1892,15 +4428,128 @@ impl App {
     }
 
     fn open_workflow_picker(&mut self) {
-        let items: Vec<OverlayItem> = WORKFLOW_TEMPLATES
+        let items: Vec<OverlayItem> = self
+            .workflows
             .iter()
-            .map(|(name, desc, prompt)| OverlayItem {
-                label: name.to_string(),
-                value: prompt.to_string(),
-                hint: desc.to_string(),
+            .map(|workflow| OverlayItem {
+                label: workflow.name.clone(),
+                value: workflow.prompt.clone(),
+                hint: if workflow.custom {
+                    format!("[custom] {}", workflow.description)
+                } else {
+                    workflow.description.clone()
+                },
+                is_header: false,
             })
             .collect();
         self.mode = AppMode::WorkflowPicker(OverlayState::new(items, None));
+        self.update_overlay_preview();
+    }
+
+    /// List `path:line` references from assistant text and tool output,
+    /// most recent first. Enter opens the selected one in the editor.
+    fn open_file_ref_picker(&mut self) {
+        let mut seen = std::collections::HashSet::new();
+        let mut items = Vec::new();
+        for msg in self.conversation.messages.iter().rev() {
+            for block in msg.content.iter().rev() {
+                let text = match block {
+                    ContentBlock::Text(text) if matches!(msg.role, Role::Assistant) => text,
+                    ContentBlock::ToolResult { content, .. } => content,
+                    _ => continue,
+                };
+                for (_, file_ref) in crate::filerefs::find_file_refs(text).into_iter().rev() {
+                    let value = file_ref.display();
+                    if seen.insert(value.clone()) {
+                        items.push(OverlayItem {
+                            label: value.clone(),
+                            value,
+                            hint: String::new(),
+                            is_header: false,
+                        });
+                    }
+                }
+            }
+        }
+
+        if items.is_empty() {
+            self.toast = Some(Toast::new("No file references in conversation".to_string()));
+            return;
+        }
+        self.mode = AppMode::FileRefPicker(OverlayState::new(items, None));
+    }
+
+    /// Record that the terminal reports enhanced keys (see `keyboard_enhancement`).
+    pub fn set_keyboard_enhanced(&mut self, enhanced: bool) {
+        self.keyboard_enhanced = enhanced;
+    }
+
+    /// Show the first-run welcome overlay.
+    pub fn open_welcome(&mut self) {
+        let item = |label: &str, value: &str, hint: &str| OverlayItem {
+            label: label.to_string(),
+            value: value.to_string(),
+            hint: hint.to_string(),
+            is_header: false,
+        };
+        let config_path = &self.config.path;
+        let mut items = vec![OverlayItem::header("Get started")];
+        if !config_path.exists() {
+            items.push(item("Write a default config file", "write", &config_path.display().to_string()));
+        }
+        items.push(item("Don't show this again", "dismiss", ""));
+        items.push(item("Maybe later", "close", "Esc"));
+        items.push(OverlayItem::header("Key shortcuts (select to try)"));
+        items.push(item("Command palette", "palette", "Ctrl+K"));
+        items.push(item("Switch theme", "theme", "Ctrl+T"));
+        items.push(item("Split pane", "split", "Ctrl+S"));
+        items.push(item("Search input history", "history", "Ctrl+R"));
+        items.push(item("All shortcuts and commands", "help", "/help"));
+        self.mode = AppMode::Welcome(OverlayState::new(items, None));
+    }
+
+    /// Code blocks in the most recent assistant message, in order.
+    fn last_code_blocks(&self) -> Vec<ui::markdown::CodeBlock> {
+        self.conversation
+            .messages
+            .iter()
+            .rev()
+            .find(|m| m.role == Role::Assistant)
+            .map(|msg| {
+                msg.content
+                    .iter()
+                    .filter_map(|block| match block {
+                        ContentBlock::Text(text) => Some(ui::markdown::code_blocks(text)),
+                        _ => None,
+                    })
+                    .flatten()
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    fn open_code_block_picker(&mut self) {
+        let blocks = self.last_code_blocks();
+        if blocks.is_empty() {
+            self.toast = Some(Toast::new("No code blocks in the last response".to_string()));
+            return;
+        }
+        let items = blocks
+            .iter()
+            .enumerate()
+            .map(|(i, block)| {
+                let first = block.code.lines().find(|l| !l.trim().is_empty()).unwrap_or("").trim();
+                let first: String = first.chars().take(50).collect();
+                let lang = if block.lang.is_empty() { "text" } else { block.lang.as_str() };
+                OverlayItem {
+                    label: format!("{}. [{lang}] {first}", i + 1),
+                    value: i.to_string(),
+                    hint: format!("{} lines", block.code.lines().count()),
+                    is_header: false,
+                }
+            })
+            .collect();
+        self.mode = AppMode::CodeBlockPicker(OverlayState::new(items, None));
     }
 
     fn open_agent_dashboard(&mut self) {
@@ -1926,11 +4575,72 @@ impl App {
                     *scroll = (*scroll + 1).min(self.agent_tasks.len().saturating_sub(1));
                 }
             }
+            KeyCode::Enter => {
+                if let AppMode::AgentDashboard { scroll } = self.mode {
+                    self.open_agent_details(scroll);
+                }
+            }
+            KeyCode::Char('x') if key.modifiers.is_empty() => {
+                if let AppMode::AgentDashboard { scroll } = self.mode {
+                    self.request_agent_cancel(scroll);
+                }
+            }
+            // Type-ahead: jump to the next task whose description starts with c
+            KeyCode::Char(c) if key.modifiers.is_empty() || key.modifiers == KeyModifiers::SHIFT => {
+                if let AppMode::AgentDashboard { ref mut scroll } = self.mode {
+                    let names = self.agent_tasks.iter().map(|t| t.description.as_str());
+                    if let Some(i) = ui::overlay::find_by_initial(names, *scroll, c) {
+                        *scroll = i;
+                    }
+                }
+            }
             _ => {}
         }
         Ok(())
     }
 
+    /// Show the full description and prompt of the agent at `index`.
+    fn open_agent_details(&mut self, index: usize) {
+        let Some(task) = self.agent_tasks.get(index) else {
+            return;
+        };
+        let status = match (task.completed, task.cancel_requested) {
+            (true, _) => "done",
+            (false, true) => "running (marked for cancellation)",
+            (false, false) => "running",
+        };
+        let mut lines = vec![
+            format!("  Type:       {}", task.agent_type),
+            format!("  Status:     {status}"),
+            format!("  Elapsed:    {}", format_duration(task.started.elapsed().as_secs())),
+            format!("  Tool ID:    {}", task.id),
+            String::new(),
+            task.description.clone(),
+            String::new(),
+        ];
+        lines.extend(task.prompt.lines().map(String::from));
+        self.mode = AppMode::TextViewer {
+            title: "Agent Details".to_string(),
+            lines,
+            scroll: 0,
+        };
+    }
+
+    /// Flag the running agent at `index` for cancellation. claude can't
+    /// interrupt one sub-agent, so the mark is a reminder and the toast says so.
+    fn request_agent_cancel(&mut self, index: usize) {
+        let Some(task) = self.agent_tasks.get_mut(index) else {
+            return;
+        };
+        let message = if task.completed {
+            "Agent already finished".to_string()
+        } else {
+            task.cancel_requested = true;
+            "Marked for cancellation — claude can't stop a single agent yet".to_string()
+        };
+        self.toast = Some(Toast::new(message));
+    }
+
     fn open_plugin_browser(&mut self) {
         let plugins = Self::discover_plugins();
         if plugins.is_empty() {
@@ -1938,17 +4648,83 @@ impl App {
             return;
         }
         self.mode = AppMode::PluginBrowser {
-            plugins,
+            plugins: plugins.clone(),
+            all: plugins,
+            query: String::new(),
+            searching: false,
             cursor: 0,
             scroll: 0,
         };
     }
 
+    /// Replace the plugin browser's list after an install, uninstall or
+    /// toggle, keeping the cursor on `full_name` wherever the re-sort put it.
+    fn show_refreshed_plugins(&mut self, all: Vec<PluginInfo>, full_name: &str) {
+        let (old_cursor, scroll, query, searching) = match std::mem::replace(&mut self.mode, AppMode::Normal) {
+            AppMode::PluginBrowser { cursor, scroll, query, searching, .. } => (cursor, scroll, query, searching),
+            _ => (0, 0, String::new(), false),
+        };
+        let plugins = filter_plugins(&all, &query);
+        let cursor = plugins
+            .iter()
+            .position(|p| p.full_name() == full_name)
+            .unwrap_or_else(|| old_cursor.min(plugins.len().saturating_sub(1)));
+        self.mode = AppMode::PluginBrowser { plugins, all, query, searching, cursor, scroll };
+    }
+
+    /// Apply `edit` to the plugin browser's filter and re-filter the list.
+    fn edit_plugin_query(&mut self, edit: impl FnOnce(&mut String)) {
+        if let AppMode::PluginBrowser { ref mut plugins, ref all, ref mut query, ref mut cursor, .. } = self.mode {
+            edit(query);
+            *plugins = filter_plugins(all, query);
+            *cursor = 0;
+        }
+    }
+
+    /// Edit the plugin browser's filter while it is being typed. Returns
+    /// whether the key was consumed; navigation keys fall through.
+    fn handle_key_plugin_search(&mut self, key: event::KeyEvent) -> bool {
+        let AppMode::PluginBrowser { ref mut searching, .. } = self.mode else {
+            return false;
+        };
+        if !*searching {
+            return false;
+        }
+        match key.code {
+            KeyCode::Char(c) if !key.modifiers.contains(KeyModifiers::CONTROL) => self.edit_plugin_query(|q| q.push(c)),
+            KeyCode::Backspace => self.edit_plugin_query(|q| {
+                q.pop();
+            }),
+            KeyCode::Enter => *searching = false,
+            KeyCode::Esc => {
+                *searching = false;
+                self.edit_plugin_query(String::clear);
+            }
+            _ => return false,
+        }
+        true
+    }
+
     async fn handle_key_plugin_browser(&mut self, key: event::KeyEvent) -> Result<()> {
+        if self.handle_key_plugin_search(key) {
+            return Ok(());
+        }
         match key.code {
+            // Esc drops an active filter before closing
+            KeyCode::Esc if matches!(self.mode, AppMode::PluginBrowser { ref query, .. } if !query.is_empty()) => {
+                self.edit_plugin_query(String::clear);
+            }
             KeyCode::Esc | KeyCode::Char('q') => {
                 self.mode = AppMode::Normal;
             }
+            KeyCode::Backspace => self.edit_plugin_query(|q| {
+                q.pop();
+            }),
+            KeyCode::Char('/') => {
+                if let AppMode::PluginBrowser { ref mut searching, .. } = self.mode {
+                    *searching = true;
+                }
+            }
             KeyCode::Up | KeyCode::Char('k') => {
                 if let AppMode::PluginBrowser { ref mut cursor, .. } = self.mode {
                     *cursor = cursor.saturating_sub(1);
@@ -2001,8 +4777,7 @@ impl App {
                         Ok(o) if o.status.success() => {
                             self.toast = Some(Toast::new(format!("Plugin {action}d: {name}")));
                             // Refresh the plugin list
-                            let plugins = Self::discover_plugins();
-                            self.mode = AppMode::PluginBrowser { plugins, cursor: 0, scroll: 0 };
+                            self.show_refreshed_plugins(Self::discover_plugins(), &name);
                         }
                         Ok(o) => {
                             let err = String::from_utf8_lossy(&o.stderr);
@@ -2031,8 +4806,7 @@ impl App {
                     match output {
                         Ok(o) if o.status.success() => {
                             self.toast = Some(Toast::new(format!("Installed: {name}")));
-                            let plugins = Self::discover_plugins();
-                            self.mode = AppMode::PluginBrowser { plugins, cursor: 0, scroll: 0 };
+                            self.show_refreshed_plugins(Self::discover_plugins(), &name);
                         }
                         Ok(o) => {
                             let err = String::from_utf8_lossy(&o.stderr);
@@ -2060,8 +4834,7 @@ impl App {
                     match output {
                         Ok(o) if o.status.success() => {
                             self.toast = Some(Toast::new(format!("Uninstalled: {name}")));
-                            let plugins = Self::discover_plugins();
-                            self.mode = AppMode::PluginBrowser { plugins, cursor: 0, scroll: 0 };
+                            self.show_refreshed_plugins(Self::discover_plugins(), &name);
                         }
                         Ok(o) => {
                             let err = String::from_utf8_lossy(&o.stderr);
@@ -2073,6 +4846,15 @@ impl App {
                     }
                 }
             }
+            // Type-ahead on the letters not bound above
+            KeyCode::Char(c) if key.modifiers.is_empty() || key.modifiers == KeyModifiers::SHIFT => {
+                if let AppMode::PluginBrowser { ref plugins, ref mut cursor, .. } = self.mode {
+                    let names = plugins.iter().map(|p| p.name.as_str());
+                    if let Some(i) = ui::overlay::find_by_initial(names, *cursor, c) {
+                        *cursor = i;
+                    }
+                }
+            }
             _ => {}
         }
         Ok(())
@@ -2081,60 +4863,273 @@ impl App {
     fn open_diff_viewer(&mut self) {
         use crate::claude::conversation::ContentBlock;
 
-        // Collect all Edit tool diffs from the conversation
-        let mut diff_text = String::new();
+        // Collect Edit and Write changes, one section per file in the order
+        // they were first touched
+        let mut sections: Vec<DiffSection> = Vec::new();
         for msg in &self.conversation.messages {
             for block in &msg.content {
-                if let ContentBlock::ToolUse { name, input, .. } = block {
-                    if name == "Edit" {
-                        if let Ok(value) = serde_json::from_str::<serde_json::Value>(input) {
-                            let file_path = value
-                                .get("file_path")
-                                .and_then(|v| v.as_str())
-                                .unwrap_or("unknown");
-                            let old = value
-                                .get("old_string")
-                                .and_then(|v| v.as_str())
-                                .unwrap_or("");
-                            let new = value
-                                .get("new_string")
-                                .and_then(|v| v.as_str())
-                                .unwrap_or("");
-
-                            if !old.is_empty() || !new.is_empty() {
-                                diff_text.push_str(&format!("--- {file_path}\n+++ {file_path}\n"));
-                                let ops = crate::diff::diff_lines(old, new);
-                                diff_text.push_str(&crate::diff::format_unified(&ops));
-                                diff_text.push('\n');
-                            }
-                        }
-                    } else if name == "Write" {
-                        if let Ok(value) = serde_json::from_str::<serde_json::Value>(input) {
-                            let file_path = value
-                                .get("file_path")
-                                .and_then(|v| v.as_str())
-                                .unwrap_or("unknown");
-                            let content = value
-                                .get("content")
-                                .and_then(|v| v.as_str())
-                                .unwrap_or("");
-                            let line_count = content.lines().count();
-                            diff_text
-                                .push_str(&format!("+++ {file_path} (new file, {line_count} lines)\n\n"));
-                        }
+                let ContentBlock::ToolUse { name, input, .. } = block else {
+                    continue;
+                };
+                if name != "Edit" && name != "Write" {
+                    continue;
+                }
+                let Ok(value) = serde_json::from_str::<serde_json::Value>(input) else {
+                    continue;
+                };
+                let file_path = value
+                    .get("file_path")
+                    .and_then(|v| v.as_str())
+                    .unwrap_or("unknown");
+                let field = |key: &str| value.get(key).and_then(|v| v.as_str()).unwrap_or("");
+                let index = match sections.iter().position(|s| s.path == file_path) {
+                    Some(index) => index,
+                    None => {
+                        sections.push(DiffSection {
+                            path: file_path.to_string(),
+                            added: 0,
+                            removed: 0,
+                            lines: Vec::new(),
+                            collapsed: false,
+                        });
+                        sections.len() - 1
                     }
+                };
+                let section = &mut sections[index];
+                if name == "Edit" {
+                    let (old, new) = (field("old_string"), field("new_string"));
+                    if old.is_empty() && new.is_empty() {
+                        continue;
+                    }
+                    // Locate the edit in the file as it is now so the
+                    // hunk headers carry real line numbers
+                    let first_line = std::fs::read_to_string(file_path)
+                        .ok()
+                        .and_then(|content| crate::diff::snippet_line(&content, new));
+                    let ops = crate::diff::diff_lines(old, new);
+                    let (added, removed) = crate::diff::change_counts(old, new);
+                    section.added += added;
+                    section.removed += removed;
+                    section
+                        .lines
+                        .extend(crate::diff::format_hunks(&ops, 3, first_line).lines().map(String::from));
+                } else {
+                    let line_count = field("content").lines().count();
+                    section.added += line_count;
+                    section.lines.push(format!("  (new file, {line_count} lines)"));
+                }
+            }
+        }
+        sections.retain(|s| !s.lines.is_empty());
+
+        if sections.is_empty() {
+            self.toast = Some(Toast::new("No file changes in this session".to_string()));
+            return;
+        }
+
+        self.mode = AppMode::DiffViewer {
+            sections,
+            selected: 0,
+            scroll: 0,
+        };
+    }
+
+    /// Up/Down move between files, Enter/Space fold the focused one, and
+    /// PageUp/PageDown scroll through the hunks.
+    fn handle_key_diff_viewer(&mut self, key: event::KeyEvent) -> Result<()> {
+        let AppMode::DiffViewer { ref mut sections, ref mut selected, ref mut scroll } = self.mode else {
+            return Ok(());
+        };
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') => {
+                self.mode = AppMode::Normal;
+                return Ok(());
+            }
+            KeyCode::Up | KeyCode::Char('k') => *selected = selected.saturating_sub(1),
+            KeyCode::Down | KeyCode::Char('j') => *selected = (*selected + 1).min(sections.len() - 1),
+            KeyCode::Enter | KeyCode::Char(' ') => {
+                sections[*selected].collapsed = !sections[*selected].collapsed;
+            }
+            // Fold or unfold every file at once
+            KeyCode::Char('a') => {
+                let collapse = sections.iter().any(|s| !s.collapsed);
+                for section in sections.iter_mut() {
+                    section.collapsed = collapse;
+                }
+            }
+            KeyCode::PageUp => {
+                *scroll = scroll.saturating_sub(20);
+                return Ok(());
+            }
+            KeyCode::PageDown => {
+                *scroll += 20;
+                return Ok(());
+            }
+            _ => return Ok(()),
+        }
+        // Keep the focused file's header at the top
+        *scroll = diff_viewer_lines(sections).1[*selected];
+        Ok(())
+    }
+
+    /// Write every file change made this session to a `.patch` file in the
+    /// current directory that `git apply` can consume.
+    fn export_patch(&mut self) {
+        let cwd = std::env::current_dir().unwrap_or_default();
+        let Some((patch, skipped)) = self.session_patch(&cwd) else {
+            self.toast = Some(Toast::new("No file changes in this session".to_string()));
+            return;
+        };
+        if patch.is_empty() {
+            self.toast = Some(Toast::new("Could not reconstruct any session changes".to_string()));
+            return;
+        }
+
+        let stamp = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        let out = cwd.join(format!("sexy-claude-{stamp}.patch"));
+        let msg = match std::fs::write(&out, patch) {
+            Ok(()) if skipped.is_empty() => format!("Saved {}", out.display()),
+            Ok(()) => format!("Saved {} (skipped changed files: {})", out.display(), skipped.join(", ")),
+            Err(e) => format!("Failed to write patch: {e}"),
+        };
+        self.toast = Some(Toast::new(msg));
+    }
+
+    /// Patch of the session's successful Edit/Write calls, with paths relative
+    /// to `root`, plus the files whose changes couldn't be reconstructed.
+    /// Returns `None` when nothing was edited.
+    fn session_patch(&self, root: &std::path::Path) -> Option<(String, Vec<String>)> {
+        let inputs = self.successful_edit_inputs();
+        let edits = edits_by_file(&inputs);
+        if edits.is_empty() {
+            return None;
+        }
+
+        let mut patch = String::new();
+        let mut skipped = Vec::new();
+        for (path, file_edits) in &edits {
+            let relative = std::path::Path::new(path)
+                .strip_prefix(root)
+                .unwrap_or(std::path::Path::new(path));
+            let current = std::fs::read_to_string(path).ok();
+            match current.as_deref().and_then(|c| crate::diff::original_content(c, file_edits).map(|o| (c, o))) {
+                Some((current, (original, is_new))) if relative.is_relative() => {
+                    patch.push_str(&crate::diff::format_patch(
+                        &relative.display().to_string(),
+                        &original,
+                        current,
+                        is_new,
+                    ));
                 }
+                _ => skipped.push(relative.display().to_string()),
             }
         }
+        Some((patch, skipped))
+    }
+
+    /// Inputs of the session's successful Edit/Write calls with their result
+    /// text, in the order they ran.
+    fn successful_edit_inputs(&self) -> Vec<(serde_json::Value, String)> {
+        let results: std::collections::HashMap<&str, (&str, bool)> = self
+            .conversation
+            .messages
+            .iter()
+            .flat_map(|msg| &msg.content)
+            .filter_map(|block| match block {
+                ContentBlock::ToolResult { tool_use_id, content, is_error, .. } => {
+                    Some((tool_use_id.as_str(), (content.as_str(), *is_error)))
+                }
+                _ => None,
+            })
+            .collect();
+
+        self.conversation
+            .messages
+            .iter()
+            .flat_map(|msg| &msg.content)
+            .filter_map(|block| match block {
+                ContentBlock::ToolUse { id, name, input } if name == "Edit" || name == "Write" => {
+                    let (result, is_error) = results.get(id.as_str()).copied().unwrap_or_default();
+                    if is_error {
+                        return None;
+                    }
+                    Some((serde_json::from_str(input).ok()?, result.to_string()))
+                }
+                _ => None,
+            })
+            .collect()
+    }
 
-        if diff_text.is_empty() {
+    /// Pick one of the files edited this session to see its cumulative diff.
+    fn open_file_diff_picker(&mut self) {
+        let inputs = self.successful_edit_inputs();
+        let edits = edits_by_file(&inputs);
+        if edits.is_empty() {
             self.toast = Some(Toast::new("No file changes in this session".to_string()));
             return;
         }
+        let cwd = std::env::current_dir().unwrap_or_default();
+        let items = edits
+            .iter()
+            .map(|(path, file_edits)| OverlayItem {
+                label: std::path::Path::new(path).strip_prefix(&cwd).unwrap_or(std::path::Path::new(path)).display().to_string(),
+                value: path.to_string(),
+                hint: format!("{} change{}", file_edits.len(), if file_edits.len() == 1 { "" } else { "s" }),
+                is_header: false,
+            })
+            .collect();
+        self.mode = AppMode::FileDiffPicker(OverlayState::new(items, None));
+    }
+
+    /// Show everything this session changed in `path` as one diff, after a
+    /// list of the individual changes. The file's content before the session
+    /// is recovered by undoing the edits on the file as it is now (or is empty
+    /// when Claude created it), then the edits are replayed in order.
+    fn open_file_diff(&mut self, path: &str) {
+        let inputs = self.successful_edit_inputs();
+        let edits = edits_by_file(&inputs);
+        let Some(file_edits) = edits.get(path) else {
+            return;
+        };
+        let original = std::fs::read_to_string(path)
+            .ok()
+            .and_then(|current| crate::diff::original_content(&current, file_edits))
+            .map(|(original, _)| original)
+            .or_else(|| matches!(file_edits.first(), Some(FileEdit::Write { created: true, .. })).then(String::new));
+        let Some((original, states)) =
+            original.and_then(|original| crate::diff::replay_edits(&original, file_edits).map(|s| (original, s)))
+        else {
+            let reason = if file_edits.iter().any(|e| matches!(e, FileEdit::Write { created: false, .. })) {
+                "a Write replaced content from before the session"
+            } else {
+                "it changed outside Claude's edits"
+            };
+            self.toast = Some(Toast::new(format!("Can't reconstruct {path}: {reason}")));
+            return;
+        };
 
-        let lines: Vec<String> = diff_text.lines().map(|l| l.to_string()).collect();
+        let mut lines = vec![format!("{} changes to {path} this session:", file_edits.len())];
+        let mut before = original.as_str();
+        for (i, (edit, after)) in file_edits.iter().zip(&states).enumerate() {
+            let kind = if matches!(edit, FileEdit::Write { .. }) { "Write" } else { "Edit" };
+            let (added, removed) = crate::diff::change_counts(before, after);
+            lines.push(format!("  {}. {kind:<6} +{added} -{removed}", i + 1));
+            before = after;
+        }
+        lines.push(String::new());
+        let last = states.last().map(String::as_str).unwrap_or_default();
+        if original == last {
+            lines.push("No net change: the edits cancel out.".to_string());
+        } else {
+            lines.push(format!("--- {path} (before the session)"));
+            lines.push(format!("+++ {path} (after the last change)"));
+            lines.extend(crate::diff::format_file_hunks(&original, last, 3).lines().map(String::from));
+        }
         self.mode = AppMode::TextViewer {
-            title: "Session Diffs".to_string(),
+            title: format!("Session Diff: {path}"),
             lines,
             scroll: 0,
         };
@@ -2145,10 +5140,36 @@ impl App {
     fn update_split_content_from_event(&mut self, event: &StreamEvent) {
         use crate::claude::conversation::ContentBlock;
 
+        // Another model's answer and the terminal stay until replaced
+        if matches!(self.split_content, SplitContent::AltAnswer(..) | SplitContent::Terminal(_)) {
+            return;
+        }
+
+        // The event log sticks until toggled off and records every event
+        if let SplitContent::EventLog(ref mut log) = self.split_content {
+            let following = self.split_scroll + 1 >= log.len();
+            log.push_back(format!("{event:?}"));
+            while log.len() > EVENT_LOG_MAX_LINES {
+                log.pop_front();
+            }
+            if following {
+                self.split_scroll = log.len();
+            }
+            return;
+        }
+
+        if self.split_pinned.is_some() {
+            self.refresh_pinned_file(event);
+            return;
+        }
+
         // When a tool is about to execute (MessageStop with ToolUse), update the split pane
         if let StreamEvent::MessageStop = event {
             if let Some(msg) = self.conversation.messages.last() {
                 if let Some(ContentBlock::ToolUse { name, input, .. }) = msg.content.last() {
+                    if !self.config.split_auto_tools.contains(name) {
+                        return;
+                    }
                     if let Ok(value) = serde_json::from_str::<serde_json::Value>(input) {
                         match name.as_str() {
                             "Edit" => {
@@ -2199,6 +5220,19 @@ impl App {
                                 self.split_content = SplitContent::FilePreview(file_path, lines);
                                 self.split_scroll = 0;
                             }
+                            "Grep" | "Glob" => {
+                                let pattern = value
+                                    .get("pattern")
+                                    .and_then(|v| v.as_str())
+                                    .unwrap_or("");
+                                // Matches will appear in tool result; show placeholder
+                                self.split_content = SplitContent::SearchResults(
+                                    format!("{name}: {pattern}"),
+                                    vec!["Searching...".to_string()],
+                                    0,
+                                );
+                                self.split_scroll = 0;
+                            }
                             _ => {}
                         }
                     }
@@ -2206,36 +5240,313 @@ impl App {
             }
         }
 
-        // When a ToolResult arrives for a Read, populate with the actual content
+        // When a ToolResult arrives for a Read/Grep/Glob, populate with the actual
+        // content, but only into the placeholder that same call put up
         if let StreamEvent::ToolResult { ref tool_use_id, ref content, .. } = event {
-            // Find the matching ToolUse to check if it was a Read
+            // Find the matching ToolUse to check which tool it was
             for msg in self.conversation.messages.iter().rev() {
                 for block in msg.content.iter().rev() {
-                    if let ContentBlock::ToolUse { id, name, .. } = block {
-                        if id == tool_use_id && name == "Read" {
-                            if let SplitContent::FilePreview(ref path, _) = self.split_content {
-                                let path = path.clone();
-                                let lines: Vec<String> = content.lines().map(|l| l.to_string()).collect();
-                                self.split_content = SplitContent::FilePreview(path, lines);
+                    if let ContentBlock::ToolUse { id, name, input } = block {
+                        if id != tool_use_id {
+                            continue;
+                        }
+                        if !self.config.split_auto_tools.contains(name) {
+                            return;
+                        }
+                        let value = serde_json::from_str::<serde_json::Value>(input).unwrap_or_default();
+                        let field = |key: &str| value.get(key).and_then(|v| v.as_str()).unwrap_or("").to_string();
+                        let lines: Vec<String> = content.lines().map(|l| l.to_string()).collect();
+                        match (name.as_str(), &self.split_content) {
+                            ("Read", SplitContent::FilePreview(path, _)) if *path == field("file_path") => {
+                                self.split_content = SplitContent::FilePreview(path.clone(), lines);
                                 self.split_scroll = 0;
                             }
-                            return;
+                            ("Grep" | "Glob", SplitContent::SearchResults(query, _, _))
+                                if *query == format!("{name}: {}", field("pattern")) =>
+                            {
+                                let selected = lines
+                                    .iter()
+                                    .position(|l| crate::filerefs::parse_search_line(l).is_some())
+                                    .unwrap_or(0);
+                                self.split_content = SplitContent::SearchResults(query.clone(), lines, selected);
+                                self.split_scroll = 0;
+                            }
+                            _ => {}
                         }
+                        return;
                     }
                 }
             }
         }
     }
 
-    fn open_file_context_panel(&mut self) {
-        use crate::claude::conversation::ContentBlock;
-        use std::collections::BTreeMap;
-
-        // Collect file operations from conversation tool uses
-        let file_tools = ["Read", "Write", "Edit", "Glob", "Grep"];
-        let mut file_ops: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    /// Select the next (or previous) Grep/Glob match in the split pane,
+    /// scrolling it into view.
+    fn move_search_selection(&mut self, forward: bool) {
+        let SplitContent::SearchResults(_, ref lines, ref mut selected) = self.split_content else {
+            return;
+        };
+        let is_hit = |i: &usize| crate::filerefs::parse_search_line(&lines[*i]).is_some();
+        let next = if forward {
+            (*selected + 1..lines.len()).find(is_hit)
+        } else {
+            (0..*selected).rev().find(is_hit)
+        };
+        let Some(next) = next else {
+            return;
+        };
+        *selected = next;
+        // The split pane is as tall as the conversation, less its borders
+        let height = self.visible_height.saturating_sub(2).max(1);
+        if next < self.split_scroll {
+            self.split_scroll = next;
+        } else if next >= self.split_scroll + height {
+            self.split_scroll = next + 1 - height;
+        }
+    }
 
-        for msg in &self.conversation.messages {
+    /// Open the selected Grep/Glob match in the editor.
+    fn open_search_hit(&mut self) {
+        let hit = match self.split_content {
+            SplitContent::SearchResults(_, ref lines, selected) if self.split_pane => {
+                lines.get(selected).and_then(|l| crate::filerefs::parse_search_line(l))
+            }
+            _ => None,
+        };
+        match hit {
+            Some(hit) => {
+                self.pending_editor = Some(crate::filerefs::editor_command(
+                    self.config.editor_command.as_deref(),
+                    &hit.file_ref(),
+                ));
+            }
+            None => self.toast = Some(Toast::new("No search result selected".to_string())),
+        }
+    }
+
+    /// Pin the split pane to the file it shows, or unpin it.
+    fn toggle_split_pin(&mut self) {
+        if let Some(path) = self.split_pinned.take() {
+            self.toast = Some(Toast::new(format!("Split pane unpinned from {path}")));
+            return;
+        }
+        let path = match self.split_content {
+            SplitContent::FilePreview(ref path, _) => Some(path.clone()),
+            SplitContent::DiffView(ref lines) => {
+                lines.first().and_then(|l| l.strip_prefix("--- ")).map(String::from)
+            }
+            _ => None,
+        };
+        match path.filter(|p| std::path::Path::new(p).is_file()) {
+            Some(path) => {
+                self.toast = Some(Toast::new(format!("Split pane pinned to {path}")));
+                self.split_pinned = Some(path);
+                self.split_scroll = 0;
+                self.split_pane = true;
+                self.show_pinned_file();
+            }
+            None => {
+                self.toast = Some(Toast::new("No file in the split pane to pin".to_string()));
+            }
+        }
+    }
+
+    /// Re-read the pinned file into the split pane, keeping the scroll position.
+    fn show_pinned_file(&mut self) {
+        let Some(ref path) = self.split_pinned else {
+            return;
+        };
+        let lines = match std::fs::read_to_string(path) {
+            Ok(content) => content.lines().map(String::from).collect(),
+            Err(e) => vec![format!("Failed to read file: {e}")],
+        };
+        self.split_content = SplitContent::FilePreview(path.clone(), lines);
+    }
+
+    /// Refresh the pinned file once a tool call on it (Edit, Write, ...)
+    /// finishes; every other tool call leaves the pane alone.
+    fn refresh_pinned_file(&mut self, event: &StreamEvent) {
+        let StreamEvent::ToolResult { ref tool_use_id, .. } = event else {
+            return;
+        };
+        let touches_pinned = self
+            .conversation
+            .messages
+            .iter()
+            .rev()
+            .flat_map(|msg| msg.content.iter())
+            .find_map(|block| match block {
+                ContentBlock::ToolUse { id, input, .. } if id == tool_use_id => Some(input),
+                _ => None,
+            })
+            .and_then(|input| serde_json::from_str::<serde_json::Value>(input).ok())
+            .and_then(|value| value.get("file_path")?.as_str().map(String::from))
+            .is_some_and(|path| Some(path) == self.split_pinned);
+        if touches_pinned {
+            self.show_pinned_file();
+        }
+    }
+
+    /// Drop the most recently attached image before it is sent.
+    fn remove_pending_image(&mut self) {
+        let Some(path) = self.pending_images.pop() else {
+            self.toast = Some(Toast::new("No images attached".to_string()));
+            return;
+        };
+        let _ = std::fs::remove_file(path);
+        let msg = match self.pending_images.len() {
+            0 => "Image removed".to_string(),
+            n => format!("Image removed, {n} still attached"),
+        };
+        self.toast = Some(Toast::new(msg));
+    }
+
+    /// Show the split-pane terminal and give it keyboard focus, starting a
+    /// shell in a PTY if none is running.
+    fn open_terminal(&mut self) {
+        if self.pty_session.is_none() {
+            let (width, height) = crossterm::terminal::size().unwrap_or((120, 40));
+            let (cols, rows) = self.terminal_pane_size(width, height);
+            let shell = std::env::var("SHELL").unwrap_or_else(|_| "sh".to_string());
+            let process = match PtyProcess::spawn(&shell, cols, rows) {
+                Ok(process) => process,
+                Err(e) => {
+                    self.toast = Some(Toast::new(format!("Failed to start terminal: {e}")));
+                    return;
+                }
+            };
+            if let (Ok(reader), Some(tx)) = (process.take_reader(), self.event_tx.clone()) {
+                Self::forward_pty_output(reader, tx);
+            }
+            self.pty_session = Some(PtySession {
+                process,
+                emulator: TerminalEmulator::new(rows, cols),
+            });
+        }
+        self.split_pane = true;
+        self.pty_focused = true;
+        self.refresh_terminal_pane();
+        self.toast = Some(Toast::new("Terminal focused — Ctrl+] to return".to_string()));
+    }
+
+    /// Pump PTY output into the event loop from a blocking reader thread.
+    fn forward_pty_output(mut reader: Box<dyn std::io::Read + Send>, tx: mpsc::UnboundedSender<Msg>) {
+        std::thread::spawn(move || {
+            let mut buf = [0u8; 4096];
+            loop {
+                match reader.read(&mut buf) {
+                    Ok(0) | Err(_) => break,
+                    Ok(n) => {
+                        if tx.send(Msg::PtyOutput(buf[..n].to_vec())).is_err() {
+                            return;
+                        }
+                    }
+                }
+            }
+            let _ = tx.send(Msg::PtyExited);
+        });
+    }
+
+    /// Redraw the split pane from the terminal emulator's screen.
+    fn refresh_terminal_pane(&mut self) {
+        let Some(ref session) = self.pty_session else {
+            return;
+        };
+        if self.pty_focused || matches!(self.split_content, SplitContent::Terminal(_)) {
+            self.split_content = SplitContent::Terminal(terminal::converter::screen_to_lines(
+                session.emulator.screen(),
+                self.theme.background,
+                self.pty_focused,
+            ));
+        }
+    }
+
+    /// Inner areas of the conversation pane and the split pane (when
+    /// `split`) for a terminal of the given size, as `ui::render` lays them out.
+    fn pane_areas(&self, width: u16, height: u16, split: bool) -> (ratatui::layout::Rect, Option<ratatui::layout::Rect>) {
+        let compact = !self.conversation.messages.is_empty() || self.config.minimal_ui();
+        let compact_header = ui::header::is_compact(width, compact);
+        ui::pane_areas(ratatui::layout::Rect::new(0, 0, width, height), &self.input, compact_header, split)
+    }
+
+    /// Take the conversation's visible height and wrap width from the
+    /// pane it will be drawn in.
+    fn fit_conversation_to_pane(&mut self, width: u16, height: u16) {
+        let (pane, _) = self.pane_areas(width, height, self.split_pane);
+        self.visible_height = pane.height as usize;
+        let padding = ui::claude_pane::effective_padding(pane.width as usize, self.config.conversation_padding);
+        self.conversation_width = pane.width as usize - 2 * padding;
+    }
+
+    /// Columns and rows inside the split pane for a terminal of the given
+    /// size, as `ui::render` lays it out.
+    fn terminal_pane_size(&self, width: u16, height: u16) -> (u16, u16) {
+        let inner = self.pane_areas(width, height, true).1.unwrap_or_default();
+        (inner.width.max(10), inner.height.max(3))
+    }
+
+    /// Resize the PTY and its emulator when the split pane changed size (the
+    /// terminal was resized, the header collapsed or the input grew).
+    fn fit_terminal_to_pane(&mut self, width: u16, height: u16) {
+        let (cols, rows) = self.terminal_pane_size(width, height);
+        let Some(ref mut session) = self.pty_session else {
+            return;
+        };
+        if (session.emulator.rows(), session.emulator.cols()) == (rows, cols) {
+            return;
+        }
+        let _ = session.process.resize(cols, rows);
+        session.emulator.resize(rows, cols);
+        self.refresh_terminal_pane();
+    }
+
+    /// Send input to the split-pane terminal, closing it if the PTY is gone.
+    fn write_to_terminal(&mut self, bytes: &[u8]) {
+        let Some(ref session) = self.pty_session else {
+            return;
+        };
+        if let Err(e) = session.process.write(bytes) {
+            self.close_terminal(format!("Terminal closed: {e:#}"));
+        }
+    }
+
+    /// Drop the split-pane terminal and its shell, showing `message`.
+    fn close_terminal(&mut self, message: String) {
+        self.pty_session = None;
+        self.pty_focused = false;
+        if matches!(self.split_content, SplitContent::Terminal(_)) {
+            self.split_content = SplitContent::FileContext(Vec::new());
+        }
+        self.toast = Some(Toast::new(message));
+    }
+
+    /// Whether keystrokes should currently go to the split-pane terminal.
+    fn terminal_has_focus(&self) -> bool {
+        self.pty_focused
+            && self.split_pane
+            && self.pty_session.is_some()
+            && matches!(self.split_content, SplitContent::Terminal(_))
+    }
+
+    /// Toggle the raw stream event log in the split pane.
+    fn toggle_event_log(&mut self) {
+        if matches!(self.split_content, SplitContent::EventLog(_)) {
+            self.split_content = SplitContent::FileContext(Vec::new());
+            self.toast = Some(Toast::new("Event log closed".to_string()));
+        } else {
+            self.split_pane = true;
+            self.split_content = SplitContent::EventLog(VecDeque::new());
+            self.split_scroll = 0;
+            self.toast = Some(Toast::new("Event log enabled".to_string()));
+        }
+    }
+
+    /// Files touched by tool calls this conversation, with the tools used on each.
+    fn file_operations(&self) -> BTreeMap<String, Vec<String>> {
+        let file_tools = ["Read", "Write", "Edit", "Glob", "Grep"];
+        let mut file_ops: BTreeMap<String, Vec<String>> = BTreeMap::new();
+
+        for msg in &self.conversation.messages {
             for block in &msg.content {
                 if let ContentBlock::ToolUse { name, input, .. } = block {
                     if !file_tools.contains(&name.as_str()) {
@@ -2257,28 +5568,162 @@ impl App {
                 }
             }
         }
+        file_ops
+    }
 
+    fn open_file_context_panel(&mut self) {
+        let file_ops = self.file_operations();
         if file_ops.is_empty() {
             self.toast = Some(Toast::new("No file operations in this session".to_string()));
             return;
         }
 
+        let mut items = vec![OverlayItem::header(&format!("{} files accessed", file_ops.len()))];
+        for (path, ops) in file_ops {
+            items.push(OverlayItem {
+                label: path.clone(),
+                value: path,
+                hint: ops.join(", "),
+                is_header: false,
+            });
+        }
+        self.mode = AppMode::FileContext(OverlayState::new(items, None));
+    }
+
+    fn open_command_log(&mut self) {
+        if self.command_log.is_empty() {
+            self.toast = Some(Toast::new("No shell commands run this session".to_string()));
+            return;
+        }
+
+        let failed = self.command_log.iter().filter(|c| c.is_error == Some(true)).count();
         let mut lines: Vec<String> = Vec::new();
-        lines.push(format!("{} files accessed", file_ops.len()));
+        let count = self.command_log.len();
+        let noun = if count == 1 { "command" } else { "commands" };
+        lines.push(format!("{count} {noun} run, {failed} failed"));
         lines.push(String::new());
 
-        for (path, ops) in &file_ops {
-            let summary: Vec<&str> = ops.iter().map(|s| s.as_str()).collect();
-            lines.push(format!("  {} [{}]", path, summary.join(", ")));
+        for (i, entry) in self.command_log.iter().enumerate() {
+            let status = match entry.is_error {
+                Some(false) => "\u{2713}",
+                Some(true) => "\u{2717}",
+                None => "\u{2026}",
+            };
+            let mut command_lines = entry.command.lines();
+            let first = command_lines.next().unwrap_or_default();
+            lines.push(format!("{:>3}. {} $ {}", i + 1, status, first));
+            for line in command_lines {
+                lines.push(format!("          {}", line));
+            }
+        }
+
+        self.mode = AppMode::TextViewer {
+            title: "Command Log".to_string(),
+            lines,
+            scroll: 0,
+        };
+    }
+
+    fn show_session_stats(&mut self) {
+        let mut user_messages = 0;
+        let mut assistant_messages = 0;
+        let mut tool_calls: BTreeMap<&str, usize> = BTreeMap::new();
+        for msg in &self.conversation.messages {
+            match msg.role {
+                Role::User => user_messages += 1,
+                Role::Assistant => assistant_messages += 1,
+            }
+            for block in &msg.content {
+                if let ContentBlock::ToolUse { name, .. } = block {
+                    *tool_calls.entry(name.as_str()).or_default() += 1;
+                }
+            }
+        }
+        let total_tool_calls: usize = tool_calls.values().sum();
+        let file_ops = self.file_operations();
+
+        let model = self.detected_model.as_deref()
+            .or(self.model_override.as_deref())
+            .or(self.config.model.as_deref());
+        let pricing = crate::cost::pricing_for_model(model.unwrap_or("sonnet"));
+        let session_cost = pricing.calculate_cost(self.total_input_tokens, self.total_output_tokens);
+
+        let mut lines = vec![
+            "# Session".to_string(),
+            format!("  Duration:   {}", format_duration(self.session_started.elapsed().as_secs())),
+            format!("  Model:      {}", model.unwrap_or("(default)")),
+            format!(
+                "  Git status: {}ms, refreshed every {}s",
+                self.git_gather_cost.as_millis(),
+                self.git_refresh_interval.as_secs()
+            ),
+            String::new(),
+            "# Messages".to_string(),
+            format!("  You:        {user_messages}"),
+            format!("  Claude:     {assistant_messages}"),
+            String::new(),
+            "# Usage".to_string(),
+            format!("  Input:      {} tokens", self.total_input_tokens),
+            format!("  Output:     {} tokens", self.total_output_tokens),
+            format!("  Est. cost:  {}", crate::cost::format_cost(session_cost)),
+            String::new(),
+            format!("# Tool Calls ({total_tool_calls})"),
+        ];
+        let mut by_count: Vec<(&str, usize)> = tool_calls.into_iter().collect();
+        by_count.sort_by_key(|&(_, count)| std::cmp::Reverse(count));
+        for (name, count) in by_count {
+            lines.push(format!("  {name:<18} {count:>4}"));
+        }
+        lines.push(String::new());
+        lines.push(format!("# Files Touched ({})", file_ops.len()));
+        for path in file_ops.keys() {
+            lines.push(format!("  {path}"));
         }
 
         self.mode = AppMode::TextViewer {
-            title: "File Context".to_string(),
+            title: "Session Stats".to_string(),
             lines,
             scroll: 0,
         };
     }
 
+    /// Identifying details for bug reports and sharing; `c` copies the session ID.
+    fn show_session_info(&mut self) {
+        let model = self.detected_model.as_deref()
+            .or(self.model_override.as_deref())
+            .or(self.config.model.as_deref())
+            .unwrap_or("(default)");
+        let cwd = self
+            .session_cwd
+            .clone()
+            .or_else(|| std::env::current_dir().ok())
+            .map_or_else(|| "(unknown)".to_string(), |dir| dir.display().to_string());
+        let elapsed = self.session_started.elapsed().as_secs();
+        let mut lines = vec![
+            format!("  Session ID: {}", self.session_id.as_deref().unwrap_or("(waiting for claude)")),
+            format!("  Model:      {model}"),
+            format!("  Directory:  {cwd}"),
+            format!("  Started:    {}", sessions::format_age(elapsed)),
+            format!("  Tokens:     {} in, {} out", self.total_input_tokens, self.total_output_tokens),
+        ];
+        if self.session_id.is_some() {
+            lines.push(String::new());
+            lines.push("  Press c to copy the session ID".to_string());
+        }
+        self.mode = AppMode::SessionInfo { lines, scroll: 0 };
+    }
+
+    fn copy_session_id(&mut self) {
+        let Some(ref id) = self.session_id else {
+            return;
+        };
+        let msg = match (self.copy_to_clipboard)(id) {
+            Ok(()) => "Session ID copied".to_string(),
+            Err(e) => format!("Copy failed: {e}"),
+        };
+        self.toast = Some(Toast::new(msg));
+    }
+
     fn open_checkpoint_timeline(&mut self) {
         use crate::claude::conversation::{ContentBlock, Role};
 
@@ -2310,6 +5755,7 @@ impl App {
                 label: format!("Turn {} — {}", turn_number, preview),
                 value: turn_number.to_string(),
                 hint: String::new(),
+                is_header: false,
             });
         }
 
@@ -2327,28 +5773,31 @@ impl App {
             KeyCode::Esc | KeyCode::Char('q') => {
                 self.mode = AppMode::Normal;
             }
+            KeyCode::Char('c') if matches!(self.mode, AppMode::SessionInfo { .. }) => {
+                self.copy_session_id();
+            }
             KeyCode::Up | KeyCode::Char('k') => {
-                if let AppMode::TextViewer { ref mut scroll, .. } = self.mode {
+                if let AppMode::TextViewer { ref mut scroll, .. } | AppMode::SessionInfo { ref mut scroll, .. } = self.mode {
                     *scroll = scroll.saturating_sub(1);
                 }
             }
             KeyCode::Down | KeyCode::Char('j') => {
-                if let AppMode::TextViewer { ref mut scroll, .. } = self.mode {
+                if let AppMode::TextViewer { ref mut scroll, .. } | AppMode::SessionInfo { ref mut scroll, .. } = self.mode {
                     *scroll += 1;
                 }
             }
             KeyCode::PageUp => {
-                if let AppMode::TextViewer { ref mut scroll, .. } = self.mode {
+                if let AppMode::TextViewer { ref mut scroll, .. } | AppMode::SessionInfo { ref mut scroll, .. } = self.mode {
                     *scroll = scroll.saturating_sub(20);
                 }
             }
             KeyCode::PageDown => {
-                if let AppMode::TextViewer { ref mut scroll, .. } = self.mode {
+                if let AppMode::TextViewer { ref mut scroll, .. } | AppMode::SessionInfo { ref mut scroll, .. } = self.mode {
                     *scroll += 20;
                 }
             }
             KeyCode::Home => {
-                if let AppMode::TextViewer { ref mut scroll, .. } = self.mode {
+                if let AppMode::TextViewer { ref mut scroll, .. } | AppMode::SessionInfo { ref mut scroll, .. } = self.mode {
                     *scroll = 0;
                 }
             }
@@ -2358,32 +5807,35 @@ impl App {
     }
 
     fn view(&mut self, terminal: &mut DefaultTerminal) -> Result<()> {
+        // Clamp scroll before rendering
+        let term_size = terminal.size()?;
+        let minimal_ui = self.config.minimal_ui();
+        self.fit_conversation_to_pane(term_size.width, term_size.height);
+        self.fit_terminal_to_pane(term_size.width, term_size.height);
+        self.anchor_scroll();
+
         let theme = &self.theme;
         let frame_count = self.frame_count;
         let overlay = match &self.mode {
-            AppMode::ActionMenu(state) => Some(("Actions", state)),
+            AppMode::ActionMenu(state) => Some(("Command Palette", state)),
             AppMode::ThemePicker(state) => Some(("Select Theme", state)),
             AppMode::SessionPicker(state) => Some(("Resume Session", state)),
             AppMode::CheckpointTimeline(state) => Some(("Rewind to Checkpoint", state)),
             AppMode::WorkflowPicker(state) => Some(("Workflow Templates", state)),
-            AppMode::Normal | AppMode::TextViewer { .. } | AppMode::HistorySearch { .. } | AppMode::TextInput { .. } | AppMode::UserQuestion { .. } | AppMode::PluginBrowser { .. } | AppMode::AgentDashboard { .. } => None,
+            AppMode::FileRefPicker(state) => Some(("File References", state)),
+            AppMode::ModelPicker(state) => Some(("Ask Another Model", state)),
+            AppMode::ResumeDirPicker(state) => Some(("Session Directory", state)),
+            AppMode::ComparePicker(state) => Some(("Compare Sessions", state)),
+            AppMode::RespawnFailed(state) => Some(("Claude Failed to Start", state)),
+            AppMode::FileContext(state) => Some(("File Context", state)),
+            AppMode::CodeBlockPicker(state) => Some(("Copy Code Block", state)),
+            AppMode::FileDiffPicker(state) => Some(("Session Diff for File", state)),
+            AppMode::Welcome(state) => Some(("Welcome to sexy-claude", state)),
+            AppMode::Normal | AppMode::TextViewer { .. } | AppMode::SessionInfo { .. } | AppMode::HistorySearch { .. } | AppMode::TextInput { .. } | AppMode::UserQuestion { .. } | AppMode::PluginBrowser { .. } | AppMode::AgentDashboard { .. } | AppMode::DiffViewer { .. } => None,
         };
 
-        // Clamp scroll before rendering
-        let term_size = terminal.size()?;
-        let header_h = if self.conversation.messages.is_empty() { HEADER_HEIGHT } else { COMPACT_HEADER_HEIGHT };
-        let visible_height = term_size.height.saturating_sub(header_h + 4) as usize;
-        let total_conv_lines = ui::claude_pane::total_lines_with_options(
-            &self.conversation,
-            term_size.width.saturating_sub(4) as usize,
-            &self.theme,
-            self.tools_expanded,
-        );
-        if self.auto_scroll || self.scroll_offset > total_conv_lines {
-            self.scroll_offset = total_conv_lines.saturating_sub(visible_height);
-        }
-
         let conversation = &self.conversation;
+        let line_cache = &self.line_cache;
         let input = &self.input;
         let scroll_offset = self.scroll_offset;
         let is_streaming = self.conversation.is_streaming();
@@ -2396,13 +5848,23 @@ impl App {
             .or(self.model_override.as_deref())
             .or(self.config.model.as_deref());
         let permission_mode = self.config.permission_mode.as_deref();
-        let tools_expanded = self.tools_expanded;
+        let effort = self.effort_override.as_deref()
+            .or(self.config.effort.as_deref());
+        let render_options = self.render_options();
         let text_viewer = match &self.mode {
             AppMode::TextViewer {
                 title,
                 lines,
                 scroll,
             } => Some((title.as_str(), lines.as_slice(), *scroll)),
+            AppMode::SessionInfo { lines, scroll } => Some(("Session Info", lines.as_slice(), *scroll)),
+            _ => None,
+        };
+        let diff_viewer = match &self.mode {
+            AppMode::DiffViewer { sections, selected, scroll } => {
+                let (lines, headers) = diff_viewer_lines(sections);
+                Some((lines, headers[*selected], *scroll))
+            }
             _ => None,
         };
         let history_search = match &self.mode {
@@ -2418,14 +5880,14 @@ impl App {
             _ => None,
         };
         let user_question = match &self.mode {
-            AppMode::UserQuestion { questions, current_question, cursor, selected } => {
+            AppMode::UserQuestion { questions, current_question, cursor, selected, .. } => {
                 questions.get(*current_question).map(|q| (q, *cursor, selected.as_slice()))
             }
             _ => None,
         };
         let plugin_browser = match &self.mode {
-            AppMode::PluginBrowser { plugins, cursor, scroll } => {
-                Some((plugins.as_slice(), *cursor, *scroll))
+            AppMode::PluginBrowser { plugins, all, query, searching, cursor, .. } => {
+                Some((plugins.as_slice(), all.len(), query.as_str(), *searching, *cursor))
             }
             _ => None,
         };
@@ -2435,6 +5897,15 @@ impl App {
         };
         let split_content = if self.split_pane { Some(&self.split_content) } else { None };
         let split_scroll = self.split_scroll;
+        let review_mode = self.review_mode;
+        let ctrl_enter_submits = self.ctrl_enter_submits();
+        let horizontal_offset = if self.wrap_lines { 0 } else { self.horizontal_offset };
+        let highlight_line = self
+            .tool_jump
+            .filter(|(_, at)| at.elapsed() < TOOL_JUMP_HIGHLIGHT)
+            .map(|(line, _)| line);
+        let fresh_messages = self.fresh_tint();
+        let session_elapsed = self.config.show_clock.then(|| self.session_started.elapsed().as_secs());
 
         terminal.draw(|frame| {
             let active_tool = conversation.active_tool_name()
@@ -2454,16 +5925,29 @@ impl App {
                 todo_summary.as_deref(),
                 model_name,
                 permission_mode,
-                tools_expanded,
+                effort,
+                render_options,
                 active_tool,
                 split_content,
                 split_scroll,
+                highlight_line,
+                fresh_messages,
+                minimal_ui,
+                review_mode,
+                session_elapsed,
+                ctrl_enter_submits,
+                horizontal_offset,
+                line_cache,
             );
             if let Some((title, state)) = overlay {
                 ui::render_overlay(frame, title, state, theme);
             }
             if let Some((title, lines, scroll)) = text_viewer {
-                ui::render_text_viewer(frame, title, lines, scroll, theme);
+                ui::render_text_viewer(frame, title, lines, scroll, None, theme);
+            }
+            if let Some((ref lines, header, scroll)) = diff_viewer {
+                let title = "Session Diffs \u{2014} Enter: fold file, a: fold all";
+                ui::render_text_viewer(frame, title, lines, scroll, Some(header), theme);
             }
             if let Some((query, matches, selected)) = history_search {
                 ui::render_history_search(frame, query, matches, selected, theme);
@@ -2485,8 +5969,8 @@ impl App {
                     theme,
                 );
             }
-            if let Some((plugins, cursor, scroll)) = plugin_browser {
-                ui::render_plugin_browser(frame, plugins, cursor, scroll, theme);
+            if let Some((plugins, total, query, searching, cursor)) = plugin_browser {
+                ui::render_plugin_browser(frame, plugins, total, query, searching, cursor, theme);
             }
             if let Some((tasks, scroll)) = agent_dashboard {
                 ui::render_agent_dashboard(frame, tasks, scroll, theme);
@@ -2505,12 +5989,18 @@ impl App {
 /// - `@` must be preceded by whitespace or be at the start of the text
 /// - The path extends until the next whitespace or end of text
 /// - Only existing files are expanded; non-existent paths are left as-is
-fn expand_file_mentions(text: &str) -> String {
+fn expand_file_mentions(text: &str, max_bytes: usize) -> MentionExpansion {
     use std::path::Path;
 
+    let mut expansion = MentionExpansion {
+        text: text.to_string(),
+        truncated: Vec::new(),
+        injected_bytes: 0,
+    };
+
     // Quick bail — no @ means nothing to expand
     if !text.contains('@') {
-        return text.to_string();
+        return expansion;
     }
 
     let mut file_contents: Vec<(String, String)> = Vec::new();
@@ -2534,12 +6024,18 @@ fn expand_file_mentions(text: &str) -> String {
                     let path = Path::new(&path_str);
                     if path.exists() && path.is_file() {
                         if let Ok(content) = std::fs::read_to_string(path) {
-                            // Limit to 100KB to avoid massive context injection
-                            let truncated = if content.len() > 100_000 {
-                                format!("{}...\n[truncated, file is {} bytes]", &content[..100_000], content.len())
+                            // Limit the size to avoid massive context injection
+                            let truncated = if content.len() > max_bytes {
+                                let mut cut = max_bytes;
+                                while !content.is_char_boundary(cut) {
+                                    cut -= 1;
+                                }
+                                expansion.truncated.push((path_str.clone(), content.len()));
+                                format!("{}...\n[truncated, file is {} bytes]", &content[..cut], content.len())
                             } else {
                                 content
                             };
+                            expansion.injected_bytes += truncated.len();
                             file_contents.push((path_str, truncated));
                         }
                     }
@@ -2550,7 +6046,7 @@ fn expand_file_mentions(text: &str) -> String {
     }
 
     if file_contents.is_empty() {
-        return text.to_string();
+        return expansion;
     }
 
     // Build expanded text: file contents first, then original message
@@ -2559,7 +6055,125 @@ fn expand_file_mentions(text: &str) -> String {
         expanded.push_str(&format!("<file path=\"{path}\">\n{content}\n</file>\n\n"));
     }
     expanded.push_str(text);
-    expanded
+    expansion.text = expanded;
+    expansion
+}
+
+/// Result of expanding `@file` mentions in a message.
+struct MentionExpansion {
+    /// Message text with file contents prepended.
+    text: String,
+    /// Mentioned files that were cut to the size limit, with their full size in bytes.
+    truncated: Vec<(String, usize)>,
+    /// Total bytes of file content injected.
+    injected_bytes: usize,
+}
+
+impl MentionExpansion {
+    /// Toast text describing truncated files, if any. Oversized injections
+    /// are confirmed before sending instead.
+    fn notice(&self, max_bytes: usize) -> Option<String> {
+        let (path, size) = self.truncated.first()?;
+        let name = std::path::Path::new(path)
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or(path);
+        let mut notice = format!("Truncated {name} ({} \u{2192} {})", format_bytes(*size), format_bytes(max_bytes));
+        if self.truncated.len() > 1 {
+            notice.push_str(&format!(" and {} more", self.truncated.len() - 1));
+        }
+        Some(notice)
+    }
+}
+
+/// Combined `@file` content above which sending needs confirming.
+const LARGE_MENTION_WARN_BYTES: usize = 250_000;
+
+/// Whether an error result is a network hiccup worth retrying rather than a
+/// real failure (bad request, auth, budget).
+fn is_transient_error(text: &str) -> bool {
+    let text = text.to_lowercase();
+    TRANSIENT_ERRORS.iter().any(|pattern| text.contains(pattern))
+}
+
+/// Format a byte count compactly (e.g. "512B", "100KB", "1.5MB").
+fn format_bytes(bytes: usize) -> String {
+    if bytes >= 1_000_000 {
+        format!("{:.1}MB", bytes as f64 / 1_000_000.0)
+    } else if bytes >= 1_000 {
+        format!("{}KB", bytes / 1_000)
+    } else {
+        format!("{bytes}B")
+    }
+}
+
+/// Edit/Write inputs (with their result text) grouped by file as the
+/// changes they made, in order.
+fn edits_by_file(inputs: &[(serde_json::Value, String)]) -> BTreeMap<&str, Vec<FileEdit<'_>>> {
+    let mut edits: BTreeMap<&str, Vec<FileEdit<'_>>> = BTreeMap::new();
+    for (value, result) in inputs {
+        let Some(path) = value.get("file_path").and_then(|v| v.as_str()) else {
+            continue;
+        };
+        let edit = match value.get("content") {
+            Some(content) => FileEdit::Write {
+                content: content.as_str().unwrap_or(""),
+                created: result.starts_with("File created successfully"),
+            },
+            None => FileEdit::Replace {
+                old: value.get("old_string").and_then(|v| v.as_str()).unwrap_or(""),
+                new: value.get("new_string").and_then(|v| v.as_str()).unwrap_or(""),
+                all: value.get("replace_all").and_then(|v| v.as_bool()).unwrap_or(false),
+                lines: edited_lines(result),
+            },
+        };
+        edits.entry(path).or_default().push(edit);
+    }
+    edits
+}
+
+/// The 0-based lines an Edit may have started on, from the `cat -n` snippet
+/// in its result. The snippet opens four lines above the change, or at the
+/// top of the file when the change is within its first five lines.
+fn edited_lines(result: &str) -> std::ops::Range<usize> {
+    let first = result.lines().find_map(|line| {
+        let (number, _) = line.trim_start().split_once(['\u{2192}', '\t'])?;
+        number.parse::<usize>().ok()
+    });
+    match first {
+        Some(1) => 0..5,
+        Some(n) => n + 3..n + 4,
+        None => crate::diff::ANY_LINE,
+    }
+}
+
+/// The plugins fuzzy-matching `query` on name and description, best match
+/// first; all of them for an empty query.
+fn filter_plugins(all: &[PluginInfo], query: &str) -> Vec<PluginInfo> {
+    if query.is_empty() {
+        return all.to_vec();
+    }
+    let matcher = SkimMatcherV2::default();
+    let mut scored: Vec<(i64, &PluginInfo)> = all
+        .iter()
+        .filter_map(|p| {
+            let score = matcher.fuzzy_match(&format!("{} {}", p.name, p.description), query)?;
+            Some((score, p))
+        })
+        .collect();
+    scored.sort_by_key(|&(score, _)| std::cmp::Reverse(score));
+    scored.into_iter().map(|(_, p)| p.clone()).collect()
+}
+
+/// Format a duration in seconds as e.g. "42s", "3m 05s" or "1h 02m".
+fn format_duration(secs: u64) -> String {
+    if secs < 60 {
+        format!("{secs}s")
+    } else if secs < 3600 {
+        format!("{}m {:02}s", secs / 60, secs % 60)
+    } else {
+        format!("{}h {:02}m", secs / 3600, (secs % 3600) / 60)
+    }
 }
 
 /// Parse AskUserQuestion tool input JSON into structured questions.
@@ -2571,16 +6185,20 @@ fn parse_ask_user_questions(input_json: &str) -> Option<Vec<UserQuestion>> {
         let question = q.get("question")?.as_str()?.to_string();
         let header = q.get("header").and_then(|h| h.as_str()).unwrap_or("").to_string();
         let multi_select = q.get("multiSelect").and_then(|m| m.as_bool()).unwrap_or(false);
-        let options_arr = q.get("options")?.as_array()?;
         let mut options = Vec::new();
-        for opt in options_arr {
-            let label = opt.get("label")?.as_str()?.to_string();
-            let description = opt.get("description").and_then(|d| d.as_str()).unwrap_or("").to_string();
-            options.push(UserQuestionOption { label, description });
+        if let Some(options_arr) = q.get("options") {
+            for opt in options_arr.as_array()? {
+                let label = opt.get("label")?.as_str()?.to_string();
+                let description = opt.get("description").and_then(|d| d.as_str()).unwrap_or("").to_string();
+                options.push(UserQuestionOption { label, description });
+            }
         }
+        let flagged = q.get("freeText").and_then(|f| f.as_bool()).unwrap_or(false)
+            || q.get("type").and_then(|t| t.as_str()) == Some("text");
         result.push(UserQuestion {
             question,
             header,
+            free_text: flagged || options.is_empty(),
             options,
             multi_select,
         });
@@ -2588,8 +6206,29 @@ fn parse_ask_user_questions(input_json: &str) -> Option<Vec<UserQuestion>> {
     Some(result)
 }
 
-fn event_reader_loop(tx: mpsc::UnboundedSender<Msg>) {
+/// Ask the terminal to report keys like Ctrl+Enter and Ctrl+Shift+K
+/// distinctly (the kitty keyboard protocol), where it supports that.
+pub fn keyboard_enhancement() -> crossterm::event::PushKeyboardEnhancementFlags {
+    crossterm::event::PushKeyboardEnhancementFlags(
+        crossterm::event::KeyboardEnhancementFlags::DISAMBIGUATE_ESCAPE_CODES,
+    )
+}
+
+fn event_reader_loop(tx: mpsc::UnboundedSender<Msg>, paused: Arc<AtomicBool>) {
     loop {
+        // Leave stdin alone while an external program (editor) owns the terminal
+        if paused.load(Ordering::SeqCst) {
+            std::thread::sleep(Duration::from_millis(50));
+            continue;
+        }
+        match event::poll(Duration::from_millis(50)) {
+            Ok(true) => {}
+            Ok(false) => continue,
+            Err(_) => break,
+        }
+        if paused.load(Ordering::SeqCst) {
+            continue;
+        }
         match event::read() {
             Ok(Event::Key(key)) => {
                 if tx.send(Msg::Key(key)).is_err() {
@@ -2618,14 +6257,14 @@ mod tests {
 
     #[test]
     fn test_expand_file_mentions_no_mentions() {
-        assert_eq!(expand_file_mentions("hello world"), "hello world");
+        assert_eq!(expand_file_mentions("hello world", 100_000).text, "hello world");
     }
 
     #[test]
     fn test_expand_file_mentions_nonexistent_file() {
         // Non-existent file should be left as-is
         assert_eq!(
-            expand_file_mentions("check @/nonexistent/path/xyz.rs"),
+            expand_file_mentions("check @/nonexistent/path/xyz.rs", 100_000).text,
             "check @/nonexistent/path/xyz.rs"
         );
     }
@@ -2634,7 +6273,7 @@ mod tests {
     fn test_expand_file_mentions_email_not_expanded() {
         // Email addresses should NOT be treated as file mentions
         assert_eq!(
-            expand_file_mentions("send to user@example.com"),
+            expand_file_mentions("send to user@example.com", 100_000).text,
             "send to user@example.com"
         );
     }
@@ -2647,7 +6286,7 @@ mod tests {
         let path_str = file_path.to_str().unwrap();
 
         let input = format!("read @{path_str} please");
-        let expanded = expand_file_mentions(&input);
+        let expanded = expand_file_mentions(&input, 100_000).text;
 
         assert!(expanded.contains("<file path="), "Expected file tag");
         assert!(expanded.contains("file contents here"), "Expected file contents");
@@ -2662,11 +6301,69 @@ mod tests {
         let path_str = file_path.to_str().unwrap();
 
         let input = format!("@{path_str}");
-        let expanded = expand_file_mentions(&input);
+        let expanded = expand_file_mentions(&input, 100_000).text;
 
         assert!(expanded.contains("start content"), "Expected file contents");
     }
 
+    #[test]
+    fn test_expand_file_mentions_reports_truncation() {
+        let dir = tempfile::tempdir().unwrap();
+        let file_path = dir.path().join("large.log");
+        std::fs::write(&file_path, "é".repeat(3_000)).unwrap();
+        let path_str = file_path.to_str().unwrap();
+
+        let expansion = expand_file_mentions(&format!("see @{path_str}"), 1_001);
+        assert_eq!(expansion.truncated, vec![(path_str.to_string(), 6_000)]);
+        assert!(expansion.text.contains("[truncated, file is 6000 bytes]"));
+        assert_eq!(
+            expansion.notice(1_001).as_deref(),
+            Some("Truncated large.log (6KB \u{2192} 1KB)")
+        );
+
+        let small = expand_file_mentions(&format!("see @{path_str}"), 100_000);
+        assert!(small.truncated.is_empty());
+        assert_eq!(small.injected_bytes, 6_000);
+        assert_eq!(small.notice(100_000), None);
+    }
+
+    #[test]
+    fn test_large_mentions_need_confirming_before_sending() {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        rt.block_on(async {
+            let dir = tempfile::tempdir().unwrap();
+            let file_path = dir.path().join("huge.log");
+            std::fs::write(&file_path, "x".repeat(LARGE_MENTION_WARN_BYTES + 1)).unwrap();
+            let text = format!("explain @{}", file_path.display());
+            let mut app = test_app();
+            app.history = InputHistory::with_path(dir.path().join("history.jsonl"));
+            app.config.max_mention_bytes = LARGE_MENTION_WARN_BYTES * 2;
+
+            app.input.set_content(&text);
+            app.update(key(KeyCode::Enter, KeyModifiers::NONE)).await.unwrap();
+            assert!(app.conversation.messages.is_empty());
+            assert_eq!(app.input.content(), text);
+            assert_eq!(
+                app.toast.as_ref().unwrap().message,
+                "Large context: 250KB of @file content · send again to confirm"
+            );
+            assert_eq!(app.history.len(), 0);
+
+            app.update(key(KeyCode::Enter, KeyModifiers::NONE)).await.unwrap();
+            assert_eq!(app.conversation.messages.len(), 1);
+            assert!(app.input.is_empty());
+            assert!(app.large_send_pending.is_none());
+            assert_eq!(app.history.len(), 1);
+        });
+    }
+
+    #[test]
+    fn test_format_bytes() {
+        assert_eq!(format_bytes(512), "512B");
+        assert_eq!(format_bytes(100_000), "100KB");
+        assert_eq!(format_bytes(1_500_000), "1.5MB");
+    }
+
     #[test]
     fn test_parse_ask_user_questions_single() {
         let json = r#"{"questions":[{"question":"Which approach?","header":"Approach","options":[{"label":"Option A","description":"First option"},{"label":"Option B","description":"Second option"}],"multiSelect":false}]}"#;
@@ -2688,9 +6385,2335 @@ mod tests {
         assert_eq!(questions[0].options.len(), 3);
     }
 
+    #[test]
+    fn test_free_text_question_answered_by_typing() {
+        let json = r#"{"questions":[{"question":"What should the branch be called?","header":"Branch"}]}"#;
+        let questions = parse_ask_user_questions(json).unwrap();
+        assert!(questions[0].free_text);
+        let flagged = r#"{"questions":[{"question":"Name?","options":[{"label":"x"}],"type":"text"}]}"#;
+        assert!(parse_ask_user_questions(flagged).unwrap()[0].free_text);
+
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        rt.block_on(async {
+            let mut app = test_app();
+            app.open_user_questions(questions);
+            assert!(matches!(app.mode, AppMode::TextInput { action: TextInputAction::AnswerQuestion { .. }, .. }));
+            for c in "fix/login".chars() {
+                app.update(key(KeyCode::Char(c), KeyModifiers::NONE)).await.unwrap();
+            }
+            app.update(key(KeyCode::Enter, KeyModifiers::NONE)).await.unwrap();
+            assert!(matches!(app.mode, AppMode::Normal));
+            let last = app.conversation.messages.last().unwrap();
+            assert!(matches!(
+                last.content.first(),
+                Some(ContentBlock::Text(text)) if text == "What should the branch be called?: fix/login"
+            ));
+        });
+    }
+
     #[test]
     fn test_parse_ask_user_questions_invalid() {
         assert!(parse_ask_user_questions("not json").is_none());
         assert!(parse_ask_user_questions(r#"{"questions":[]}"#).unwrap().is_empty());
     }
+
+    fn test_app() -> App {
+        App::new(
+            Config::default(),
+            Theme::default_theme(),
+            "catppuccin-mocha".to_string(),
+            "claude".to_string(),
+            false,
+            None,
+            None,
+            None,
+            None,
+        )
+    }
+
+    fn key(code: KeyCode, modifiers: KeyModifiers) -> Msg {
+        Msg::Key(event::KeyEvent::new(code, modifiers))
+    }
+
+    #[test]
+    fn test_is_transient_error() {
+        assert!(is_transient_error("API Error: Connection error (ECONNRESET)"));
+        assert!(is_transient_error("Request timed out."));
+        assert!(is_transient_error("API Error: 529 {\"type\":\"overloaded_error\"}"));
+        assert!(!is_transient_error("Invalid API key · Please run /login"));
+        assert!(!is_transient_error("Exceeded USD budget (5)"));
+    }
+
+    #[test]
+    fn test_auto_retry_resends_until_limit() {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        rt.block_on(async {
+            let mut app = test_app();
+            app.config.auto_retry = true;
+            app.last_prompt = Some(("fix the build".to_string(), Vec::new()));
+            let result = |text: &str, is_error| {
+                Msg::ClaudeEvent(StreamEvent::Result {
+                    text: text.to_string(),
+                    is_error,
+                    permission_denials: Vec::new(),
+                })
+            };
+
+            app.update(result("Invalid API key", true)).await.unwrap();
+            assert_eq!(app.retry_count, 0);
+
+            app.update(result("API Error: Connection error (ECONNRESET)", true)).await.unwrap();
+            assert_eq!(app.toast.as_ref().unwrap().message, "Retrying (1/2)...");
+            app.update(Msg::RetryPrompt).await.unwrap();
+            app.update(result("Request timed out", true)).await.unwrap();
+            assert_eq!(app.toast.as_ref().unwrap().message, "Retrying (2/2)...");
+            app.update(result("Request timed out", true)).await.unwrap();
+            assert_eq!(app.toast.as_ref().unwrap().message, "Giving up after 2 retries");
+            assert_eq!(app.retry_count, 0);
+
+            // A successful turn resets the budget
+            app.update(result("API Error: Connection error", true)).await.unwrap();
+            app.update(result("", false)).await.unwrap();
+            assert_eq!(app.retry_count, 0);
+
+            // Off by default
+            app.config.auto_retry = false;
+            app.update(result("API Error: Connection error", true)).await.unwrap();
+            assert_eq!(app.retry_count, 0);
+        });
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_every_prompt_is_remembered_but_slash_commands_are_not() {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        rt.block_on(async {
+            let mut app = test_app();
+            let (process, _events) = ClaudeProcess::spawn("sh -c cat>/dev/null").unwrap();
+            app.claude = Some(process);
+
+            app.continue_truncated_response().await.unwrap();
+            assert_eq!(app.last_prompt.as_ref().unwrap().0, "continue");
+
+            app.send_question_answer("Which db?", "sqlite").await.unwrap();
+            assert_eq!(app.last_prompt.as_ref().unwrap().0, "Which db?: sqlite");
+
+            app.start_workflow("review", "Review the diff").await.unwrap();
+            assert_eq!(app.last_prompt.as_ref().unwrap().0, "Review the diff");
+
+            // A failed slash command is not resent
+            assert!(app.submit_text("/compact".to_string()).await.unwrap());
+            assert!(app.last_prompt.is_none());
+            app.config.auto_retry = true;
+            app.update(Msg::ClaudeEvent(StreamEvent::Result {
+                text: "API Error: Connection error".to_string(),
+                is_error: true,
+                permission_denials: Vec::new(),
+            }))
+            .await
+            .unwrap();
+            assert_eq!(app.retry_count, 0);
+        });
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_macro_stops_at_overlays_and_failed_sends() {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        rt.block_on(async {
+            let mut app = test_app();
+            let (process, _events) = ClaudeProcess::spawn("sh -c cat>/dev/null").unwrap();
+            app.claude = Some(process);
+            app.config.macros.insert("look".to_string(), vec!["/help".to_string(), "after".to_string()]);
+            app.start_macro("look").await.unwrap();
+            assert!(app.running_macro.is_none());
+            assert!(matches!(app.mode, AppMode::TextViewer { .. }));
+            assert_eq!(app.toast.as_ref().unwrap().message, "Macro \"look\" stopped: step 1/2 opened a view");
+            assert!(app.conversation.messages.iter().all(|m| m.role != Role::User));
+
+            // A send that fails stops the macro instead of ending the app
+            app.mode = AppMode::Normal;
+            let (mut process, _events) = ClaudeProcess::spawn("true").unwrap();
+            let _ = process.kill().await;
+            app.claude = Some(process);
+            app.config.macros.insert("send".to_string(), vec!["first".to_string()]);
+            tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+            app.start_macro("send").await.unwrap();
+            assert!(app.running_macro.is_none());
+            assert!(app.toast.as_ref().unwrap().message.starts_with("Macro \"send\" stopped: step 1/1 failed to send"));
+        });
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_macro_sends_steps_turn_by_turn() {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        rt.block_on(async {
+            let mut app = test_app();
+            // Stands in for claude: accepts messages and never answers
+            let (process, _events) = ClaudeProcess::spawn("sh -c cat>/dev/null").unwrap();
+            app.claude = Some(process);
+            app.config.macros.insert(
+                "chain".to_string(),
+                vec!["first".to_string(), "/clear".to_string(), "second".to_string()],
+            );
+            let result = |is_error| {
+                Msg::ClaudeEvent(StreamEvent::Result {
+                    text: if is_error { "boom".to_string() } else { String::new() },
+                    is_error,
+                    permission_denials: Vec::new(),
+                })
+            };
+            let user_texts = |app: &App| -> Vec<String> {
+                app.conversation.messages.iter()
+                    .filter(|m| m.role == Role::User)
+                    .filter_map(|m| match m.content.first() {
+                        Some(ContentBlock::Text(text)) => Some(text.clone()),
+                        _ => None,
+                    })
+                    .collect()
+            };
+
+            app.open_action_menu();
+            let AppMode::ActionMenu(ref state) = app.mode else { panic!("expected the palette") };
+            assert!(state.items.iter().any(|i| i.label == "Macro: chain" && i.value == "macro:chain"));
+            app.mode = AppMode::Normal;
+
+            app.start_macro("chain").await.unwrap();
+            assert_eq!(user_texts(&app), ["first"]);
+            assert_eq!(app.toast.as_ref().unwrap().message, "Macro \"chain\": step 1/3");
+
+            // The local /clear runs straight after the first turn, then the last step waits
+            app.update(result(false)).await.unwrap();
+            assert_eq!(user_texts(&app), ["second"]);
+            assert_eq!(app.running_macro.as_ref().unwrap().step, 3);
+            app.update(result(false)).await.unwrap();
+            assert!(app.running_macro.is_none());
+            assert_eq!(app.toast.as_ref().unwrap().message, "Macro \"chain\" finished");
+
+            // A failed step stops the chain
+            app.start_macro("chain").await.unwrap();
+            app.update(result(true)).await.unwrap();
+            assert!(app.running_macro.is_none());
+            assert_eq!(app.toast.as_ref().unwrap().message, "Macro \"chain\" stopped: step 1/3 failed");
+            assert_eq!(user_texts(&app).last().unwrap(), "first");
+        });
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_review_mode_stops_and_hides_macros() {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        rt.block_on(async {
+            let mut app = test_app();
+            let (process, _events) = ClaudeProcess::spawn("sh -c cat>/dev/null").unwrap();
+            app.claude = Some(process);
+            app.config.macros.insert("one".to_string(), vec!["first".to_string()]);
+            app.config.macros.insert("two".to_string(), vec!["first".to_string(), "second".to_string()]);
+
+            app.open_action_menu();
+            let AppMode::ActionMenu(ref state) = app.mode else { panic!("expected the palette") };
+            assert!(state.items.iter().any(|i| i.value == "macro:one" && i.hint == "1 step"));
+            assert!(state.items.iter().any(|i| i.value == "macro:two" && i.hint == "2 steps"));
+            app.mode = AppMode::Normal;
+
+            // Turning review mode on mid-run stops the macro before its next step
+            app.start_macro("two").await.unwrap();
+            app.review_mode = true;
+            app.update(Msg::ClaudeEvent(StreamEvent::Result {
+                text: String::new(),
+                is_error: false,
+                permission_denials: Vec::new(),
+            }))
+            .await
+            .unwrap();
+            assert!(app.running_macro.is_none());
+            assert_eq!(app.conversation.messages.iter().filter(|m| m.role == Role::User).count(), 1);
+
+            app.start_macro("one").await.unwrap();
+            assert!(app.running_macro.is_none());
+            app.open_action_menu();
+            let AppMode::ActionMenu(ref state) = app.mode else { panic!("expected the palette") };
+            assert!(!state.items.iter().any(|i| i.value.starts_with("macro:")));
+        });
+    }
+
+    #[test]
+    fn test_session_info_shows_and_copies_session_id() {
+        thread_local! {
+            static COPIED: std::cell::RefCell<Option<String>> = const { std::cell::RefCell::new(None) };
+        }
+        let mut app = test_app();
+        app.show_session_info();
+        let AppMode::SessionInfo { ref lines, .. } = app.mode else {
+            panic!("expected the session info viewer");
+        };
+        assert_eq!(lines[0], "  Session ID: (waiting for claude)");
+        assert!(!lines.iter().any(|l| l.contains("Press c")));
+
+        app.session_id = Some("5f0c6a2e-1b7d-4c1e-9a53-2d8e7f4b6c10".to_string());
+        app.session_cwd = Some(std::path::PathBuf::from("/work/project"));
+        app.total_input_tokens = 1200;
+        app.total_output_tokens = 340;
+        app.show_session_info();
+        let AppMode::SessionInfo { ref lines, .. } = app.mode else {
+            panic!("expected the session info viewer");
+        };
+        assert_eq!(lines[0], "  Session ID: 5f0c6a2e-1b7d-4c1e-9a53-2d8e7f4b6c10");
+        assert_eq!(lines[2], "  Directory:  /work/project");
+        assert_eq!(lines[4], "  Tokens:     1200 in, 340 out");
+
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        app.copy_to_clipboard = |text| {
+            COPIED.with(|copied| *copied.borrow_mut() = Some(text.to_string()));
+            Ok(())
+        };
+        rt.block_on(app.update(key(KeyCode::Char('c'), KeyModifiers::NONE))).unwrap();
+        assert_eq!(app.toast.as_ref().unwrap().message, "Session ID copied");
+        assert_eq!(COPIED.with(|copied| copied.borrow().clone()).as_deref(), app.session_id.as_deref());
+
+        app.copy_to_clipboard = |_| anyhow::bail!("no display");
+        rt.block_on(app.update(key(KeyCode::Char('c'), KeyModifiers::NONE))).unwrap();
+        assert_eq!(app.toast.as_ref().unwrap().message, "Copy failed: no display");
+
+        // A text viewer titled the same doesn't copy
+        app.mode = AppMode::TextViewer { title: "Session Info".to_string(), lines: Vec::new(), scroll: 0 };
+        app.toast = None;
+        rt.block_on(app.update(key(KeyCode::Char('c'), KeyModifiers::NONE))).unwrap();
+        assert!(app.toast.is_none());
+    }
+
+    #[test]
+    fn test_alt_keys_edit_the_input() {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        rt.block_on(async {
+            let mut app = test_app();
+            app.input.set_content("first line\nexplain the parser");
+            app.update(key(KeyCode::Backspace, KeyModifiers::ALT)).await.unwrap();
+            assert_eq!(app.input.content(), "first line\nexplain the ");
+
+            app.update(key(KeyCode::Char('u'), KeyModifiers::ALT)).await.unwrap();
+            assert_eq!(app.input.content(), "first line\n");
+            app.update(key(KeyCode::Char('c'), KeyModifiers::ALT)).await.unwrap();
+            assert!(app.input.is_empty());
+
+            // Ctrl+U and Ctrl+W keep their bindings while typing
+            app.input.set_content("draft");
+            app.visible_height = 30;
+            app.scroll_offset = 50;
+            app.update(key(KeyCode::Char('u'), KeyModifiers::CONTROL)).await.unwrap();
+            assert_eq!(app.scroll_offset, 35);
+            app.update(key(KeyCode::Char('w'), KeyModifiers::CONTROL)).await.unwrap();
+            assert!(matches!(app.mode, AppMode::WorkflowPicker(_)));
+            assert_eq!(app.input.content(), "draft");
+        });
+    }
+
+    #[test]
+    fn test_half_page_and_line_scrolling() {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        rt.block_on(async {
+            let mut app = test_app();
+            app.visible_height = 30;
+            app.scroll_offset = 50;
+
+            app.update(key(KeyCode::Char('u'), KeyModifiers::CONTROL)).await.unwrap();
+            assert_eq!(app.scroll_offset, 35);
+            assert!(!app.auto_scroll);
+
+            app.update(key(KeyCode::Up, KeyModifiers::NONE)).await.unwrap();
+            assert_eq!(app.scroll_offset, 34);
+            assert!(app.input.is_empty());
+
+            // Nothing below the viewport: scrolling down snaps back to live output
+            app.update(key(KeyCode::Char('d'), KeyModifiers::CONTROL)).await.unwrap();
+            assert!(app.auto_scroll);
+        });
+    }
+
+    #[test]
+    fn test_pasted_images_are_removed_with_the_app() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut app = test_app();
+        app.paste_dir = dir.path().join("paste");
+        std::fs::create_dir(&app.paste_dir).unwrap();
+        std::fs::write(app.paste_dir.join("paste-1.png"), b"png").unwrap();
+        let paste_dir = app.paste_dir.clone();
+        drop(app);
+        assert!(!paste_dir.exists());
+    }
+
+    #[test]
+    fn test_attached_images_can_be_removed_before_sending() {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        rt.block_on(async {
+            let dir = tempfile::tempdir().unwrap();
+            let mut app = test_app();
+            let images: Vec<_> = (1..=2).map(|i| dir.path().join(format!("paste-{i}.png"))).collect();
+            for image in &images {
+                std::fs::write(image, b"png").unwrap();
+            }
+            app.pending_images = images.clone();
+
+            app.open_action_menu();
+            let AppMode::ActionMenu(ref state) = app.mode else { panic!("expected the palette") };
+            assert!(state.items.iter().any(|i| i.value == "dropimage" && i.hint == "Alt+V"));
+            app.mode = AppMode::Normal;
+
+            app.update(key(KeyCode::Char('v'), KeyModifiers::ALT)).await.unwrap();
+            assert_eq!(app.pending_images, images[..1]);
+            assert!(!images[1].exists());
+            assert_eq!(app.toast.as_ref().unwrap().message, "Image removed, 1 still attached");
+            app.update(key(KeyCode::Char('v'), KeyModifiers::ALT)).await.unwrap();
+            app.update(key(KeyCode::Char('v'), KeyModifiers::ALT)).await.unwrap();
+            assert!(app.pending_images.is_empty());
+            assert_eq!(app.toast.as_ref().unwrap().message, "No images attached");
+        });
+    }
+
+    #[test]
+    fn test_scrolling_down_stops_at_the_last_line() {
+        let mut app = test_app();
+        app.visible_height = 5;
+        app.conversation.push_user_message((0..30).map(|i| format!("line {i}")).collect::<Vec<_>>().join("\n"));
+        app.auto_scroll = false;
+        app.scroll_down(1000);
+        let total = app.line_cache.total_lines(&app.conversation, app.conversation_width, &app.theme, app.render_options());
+        assert_eq!(app.scroll_offset, total - 5);
+        assert_eq!(app.scroll_offset, app.bottom_offset());
+    }
+
+    #[test]
+    fn test_conversation_is_fitted_to_the_rendered_pane() {
+        let mut app = test_app();
+        app.config.conversation_padding = 0;
+        app.conversation.push_user_message("hi".to_string());
+        // Header, input (3 rows with its border) and status bar, then the pane's borders
+        let rows = 40 - crate::ui::header::COMPACT_HEADER_HEIGHT as usize - 3 - 1 - 2;
+        app.fit_conversation_to_pane(120, 40);
+        assert_eq!((app.visible_height, app.conversation_width), (rows, 118));
+
+        // A growing input and the split pane take room from the conversation
+        app.input.set_content("one\ntwo\nthree");
+        app.split_pane = true;
+        app.fit_conversation_to_pane(120, 40);
+        assert_eq!((app.visible_height, app.conversation_width), (rows - 3, 70));
+    }
+
+    #[test]
+    fn test_auto_scroll_pins_bottom_line_while_streaming() {
+        use crate::claude::events::{ContentBlockType, Delta};
+
+        let mut app = test_app();
+        app.visible_height = 5;
+        app.conversation.push_user_message("question".to_string());
+        app.conversation.apply_event(&StreamEvent::MessageStart {
+            message_id: "msg_1".to_string(),
+            model: "claude-sonnet-4-5".to_string(),
+            usage: None,
+        });
+        app.conversation.apply_event(&StreamEvent::ContentBlockStart {
+            index: 0,
+            block_type: ContentBlockType::Text,
+        });
+        let total = |app: &App| app.line_cache.total_lines(&app.conversation, 80, &app.theme, app.render_options());
+        let delta = |text: &str| StreamEvent::ContentBlockDelta {
+            index: 0,
+            delta: Delta::TextDelta(text.to_string()),
+        };
+
+        for i in 0..20 {
+            app.conversation.apply_event(&delta(&format!("line {i}\n\n")));
+            app.anchor_scroll();
+            // The last line and the spinner below it end exactly at the bottom
+            assert_eq!(app.scroll_offset, (total(&app) + 1).saturating_sub(app.visible_height));
+        }
+
+        // Scrolled up: growth below doesn't move the view
+        app.scroll_up(3);
+        let pinned = app.scroll_offset;
+        app.conversation.apply_event(&delta("more\n\nmore"));
+        app.anchor_scroll();
+        assert_eq!(app.scroll_offset, pinned);
+    }
+
+    #[test]
+    fn test_manual_auto_scroll_stays_pinned_until_end() {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        rt.block_on(async {
+            let mut app = test_app();
+            app.config.auto_scroll = "manual".to_string();
+            app.visible_height = 30;
+            app.scroll_offset = 50;
+
+            app.update(key(KeyCode::Char('u'), KeyModifiers::CONTROL)).await.unwrap();
+            assert!(!app.auto_scroll);
+
+            // Scrolling back to the bottom does not re-engage following
+            app.update(key(KeyCode::Char('d'), KeyModifiers::CONTROL)).await.unwrap();
+            assert!(!app.auto_scroll);
+
+            app.update(key(KeyCode::End, KeyModifiers::NONE)).await.unwrap();
+            assert!(app.auto_scroll);
+        });
+    }
+
+    #[test]
+    fn test_tab_jumps_between_tool_calls() {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        rt.block_on(async {
+            let mut app = test_app();
+            let tool = |id: &str| ContentBlock::ToolUse {
+                id: id.to_string(),
+                name: "Bash".to_string(),
+                input: "{\"command\":\"ls\"}".to_string(),
+            };
+            app.conversation.messages.push(crate::claude::conversation::Message {
+                role: Role::Assistant,
+                timestamp: None,
+                stop_reason: None,
+                content: vec![
+                    ContentBlock::Text("intro".to_string()),
+                    tool("t1"),
+                    ContentBlock::Text("middle\n\nmore prose".to_string()),
+                    tool("t2"),
+                ],
+            });
+            let lines = app.line_cache.tool_use_lines(&app.conversation, 80, &app.theme, app.render_options());
+            app.scroll_offset = 0;
+
+            app.update(key(KeyCode::Tab, KeyModifiers::NONE)).await.unwrap();
+            assert_eq!(app.scroll_offset, lines[0]);
+            assert!(!app.auto_scroll);
+            app.update(key(KeyCode::Tab, KeyModifiers::NONE)).await.unwrap();
+            assert_eq!(app.scroll_offset, lines[1]);
+            assert_eq!(app.tool_jump.map(|(line, _)| line), Some(lines[1]));
+            app.update(key(KeyCode::BackTab, KeyModifiers::SHIFT)).await.unwrap();
+            assert_eq!(app.scroll_offset, lines[0]);
+            assert!(app.input.is_empty());
+        });
+    }
+
+    #[test]
+    fn test_messages_arriving_out_of_view_highlight_on_return() {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        rt.block_on(async {
+            let mut app = test_app();
+            app.conversation.push_user_message("hi".to_string());
+            app.auto_scroll = false;
+            app.update(Msg::ClaudeEvent(StreamEvent::MessageStart {
+                message_id: "m1".to_string(),
+                model: "claude".to_string(),
+                usage: None,
+            }))
+            .await
+            .unwrap();
+            // Scrolled back: nothing fades until the view returns
+            assert_eq!(app.fresh_messages, Some((1, None)));
+            assert_eq!(app.fresh_tint(), None);
+
+            app.update(key(KeyCode::End, KeyModifiers::NONE)).await.unwrap();
+            app.anchor_scroll();
+            assert_eq!(app.fresh_tint(), Some((1, 1.0)));
+
+            for _ in 0..app.config.fps {
+                app.update(Msg::Tick).await.unwrap();
+            }
+            assert_eq!(app.fresh_messages, None);
+
+            // Disabled: no highlight at all
+            app.config.highlight_new_messages = false;
+            app.update(Msg::ClaudeEvent(StreamEvent::MessageStart {
+                message_id: "m2".to_string(),
+                model: "claude".to_string(),
+                usage: None,
+            }))
+            .await
+            .unwrap();
+            assert_eq!(app.fresh_messages, None);
+        });
+    }
+
+    #[test]
+    fn test_enter_on_jumped_tool_call_toggles_its_output() {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        rt.block_on(async {
+            let mut app = test_app();
+            let long = (0..30).map(|i| format!("line {i}")).collect::<Vec<_>>().join("\n");
+            for id in ["t1", "t2"] {
+                app.conversation.messages.push(crate::claude::conversation::Message {
+                    role: Role::Assistant,
+                    timestamp: None,
+                    stop_reason: None,
+                    content: vec![
+                        ContentBlock::ToolUse {
+                            id: id.to_string(),
+                            name: "Bash".to_string(),
+                            input: "{\"command\":\"ls\"}".to_string(),
+                        },
+                        ContentBlock::ToolResult {
+                            tool_use_id: id.to_string(),
+                            content: long.clone(),
+                            is_error: false,
+                            collapsed: true,
+                        },
+                    ],
+                });
+            }
+            let total = |app: &App| app.line_cache.total_lines(&app.conversation, app.conversation_width, &app.theme, app.render_options());
+            let before = total(&app);
+            app.scroll_offset = 0;
+
+            // Jump to the first (older) tool call and expand it
+            app.update(key(KeyCode::BackTab, KeyModifiers::SHIFT)).await.unwrap();
+            app.update(key(KeyCode::Tab, KeyModifiers::NONE)).await.unwrap();
+            app.update(key(KeyCode::Enter, KeyModifiers::NONE)).await.unwrap();
+            assert_eq!(app.toast.as_ref().unwrap().message, "Tool output expanded");
+            let expanded = total(&app);
+            assert!(expanded > before, "older message re-rendered");
+
+            // Stays expanded when later events arrive
+            app.update(Msg::ClaudeEvent(StreamEvent::MessageStart {
+                message_id: "m3".to_string(),
+                model: "claude".to_string(),
+                usage: None,
+            }))
+            .await
+            .unwrap();
+            let ContentBlock::ToolResult { collapsed, .. } = &app.conversation.messages[0].content[1] else {
+                panic!("expected the tool result");
+            };
+            assert!(!collapsed);
+
+            // With no tool call jumped to, Enter on an empty input does nothing
+            app.scroll_offset += 1;
+            app.update(key(KeyCode::Enter, KeyModifiers::NONE)).await.unwrap();
+            assert_eq!(app.toast.as_ref().unwrap().message, "Tool output expanded");
+        });
+    }
+
+    #[test]
+    fn test_input_draft_survives_action_menu() {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        rt.block_on(async {
+            let mut app = test_app();
+            for c in "half a prompt".chars() {
+                app.update(key(KeyCode::Char(c), KeyModifiers::NONE)).await.unwrap();
+            }
+            app.update(key(KeyCode::Left, KeyModifiers::NONE)).await.unwrap();
+            let cursor = app.input.cursor_position();
+
+            app.update(key(KeyCode::Char('k'), KeyModifiers::CONTROL)).await.unwrap();
+            assert!(matches!(app.mode, AppMode::ActionMenu(_)));
+            app.update(key(KeyCode::Char('x'), KeyModifiers::NONE)).await.unwrap();
+            app.update(key(KeyCode::Esc, KeyModifiers::NONE)).await.unwrap();
+
+            assert!(matches!(app.mode, AppMode::Normal));
+            assert_eq!(app.input.content(), "half a prompt");
+            assert_eq!(app.input.cursor_position(), cursor);
+        });
+    }
+
+    #[test]
+    fn test_stream_events_are_coalesced_until_tick() {
+        assert!(!Msg::ClaudeEvent(StreamEvent::MessageStop).renders_immediately());
+        assert!(Msg::Tick.renders_immediately());
+        assert!(key(KeyCode::Char('a'), KeyModifiers::NONE).renders_immediately());
+        assert!(Msg::Resize(80, 24).renders_immediately());
+    }
+
+    #[test]
+    fn test_event_log_is_bounded_and_sticky() {
+        let mut app = test_app();
+        app.toggle_event_log();
+        assert!(app.split_pane);
+        for i in 0..EVENT_LOG_MAX_LINES + 20 {
+            app.update_split_content_from_event(&StreamEvent::Unknown(format!("line {i}")));
+        }
+        app.update_split_content_from_event(&StreamEvent::MessageStop);
+        let SplitContent::EventLog(ref log) = app.split_content else {
+            panic!("event log should stay active");
+        };
+        assert_eq!(log.len(), EVENT_LOG_MAX_LINES);
+        assert_eq!(log.back().map(String::as_str), Some("MessageStop"));
+        assert_eq!(app.split_scroll, EVENT_LOG_MAX_LINES);
+    }
+
+    #[test]
+    fn test_command_log_records_bash_outcome() {
+        use crate::claude::events::{ContentBlockType, Delta};
+
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        rt.block_on(async {
+            let mut app = test_app();
+            let events = vec![
+                StreamEvent::MessageStart {
+                    message_id: "msg_1".to_string(),
+                    model: "claude-sonnet-4-5".to_string(),
+                    usage: None,
+                },
+                StreamEvent::ContentBlockStart {
+                    index: 0,
+                    block_type: ContentBlockType::ToolUse {
+                        id: "tool_1".to_string(),
+                        name: "Bash".to_string(),
+                    },
+                },
+                StreamEvent::ContentBlockDelta {
+                    index: 0,
+                    delta: Delta::InputJsonDelta(r#"{"command":"cargo test"}"#.to_string()),
+                },
+                StreamEvent::ContentBlockStop { index: 0 },
+            ];
+            for event in events {
+                app.update(Msg::ClaudeEvent(event)).await.unwrap();
+            }
+            assert_eq!(app.command_log.len(), 1);
+            assert_eq!(app.command_log[0].command, "cargo test");
+            assert_eq!(app.command_log[0].is_error, None);
+
+            app.update(Msg::ClaudeEvent(StreamEvent::ToolResult {
+                tool_use_id: "tool_1".to_string(),
+                content: "error: test failed".to_string(),
+                is_error: true,
+                images: Vec::new(),
+            }))
+            .await
+            .unwrap();
+            assert_eq!(app.command_log[0].is_error, Some(true));
+
+            app.open_command_log();
+            match app.mode {
+                AppMode::TextViewer { ref lines, .. } => {
+                    assert_eq!(lines[0], "1 command run, 1 failed");
+                    assert!(lines[2].contains("\u{2717} $ cargo test"));
+                }
+                _ => panic!("expected the command log viewer"),
+            }
+        });
+    }
+
+    #[test]
+    fn test_alt_answer_accumulates_and_sticks() {
+        use crate::claude::events::Delta;
+
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        rt.block_on(async {
+            let mut app = test_app();
+            app.open_model_picker();
+            assert!(matches!(app.mode, AppMode::Normal), "no prompt yet");
+
+            app.last_prompt = Some(("explain lifetimes".to_string(), Vec::new()));
+            app.open_model_picker();
+            assert!(matches!(app.mode, AppMode::ModelPicker(_)));
+
+            app.split_pane = true;
+            app.split_content = SplitContent::AltAnswer("haiku".to_string(), String::new(), false);
+            for t in ["Lifetimes ", "are scopes."] {
+                app.update(Msg::AltEvent(0, StreamEvent::ContentBlockDelta {
+                    index: 0,
+                    delta: Delta::TextDelta(t.to_string()),
+                }))
+                .await
+                .unwrap();
+            }
+            app.update_split_content_from_event(&StreamEvent::MessageStop);
+            app.update(Msg::AltEvent(0, StreamEvent::Result {
+                text: "Lifetimes are scopes.".to_string(),
+                is_error: false,
+                permission_denials: Vec::new(),
+            }))
+            .await
+            .unwrap();
+            // Nothing lands after the answer finished
+            app.update(Msg::AltEvent(0, StreamEvent::ContentBlockDelta {
+                index: 0,
+                delta: Delta::TextDelta(" More.".to_string()),
+            }))
+            .await
+            .unwrap();
+
+            match app.split_content {
+                SplitContent::AltAnswer(ref model, ref text, finished) => {
+                    assert_eq!(model, "haiku");
+                    assert_eq!(text, "Lifetimes are scopes.");
+                    assert!(finished);
+                }
+                _ => panic!("alternate answer should stay in the split pane"),
+            }
+        });
+    }
+
+    #[test]
+    fn test_entering_the_session_dir_refreshes_dir_state() {
+        // Entering the directory we are already in keeps other tests' cwd intact
+        let here = std::env::current_dir().unwrap();
+        let mut app = test_app();
+        app.split_pinned = Some("src/main.rs".to_string());
+        app.completion = Some(CompletionState::new(Vec::new()));
+        app.git_info = GitInfo::default();
+
+        app.enter_session_dir(here.clone());
+        assert_eq!(app.session_cwd.as_deref(), Some(here.as_path()));
+        assert_eq!(std::env::current_dir().unwrap(), here);
+        assert!(app.split_pinned.is_none());
+        assert!(app.completion.is_none());
+        assert_eq!(app.git_info.branch, GitInfo::gather().branch);
+    }
+
+    #[test]
+    fn test_ask_another_model_failure_is_a_toast() {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        rt.block_on(async {
+            let mut app = test_app();
+            app.command = "/nonexistent/sexy-claude-test-binary".to_string();
+            app.last_prompt = Some(("explain lifetimes".to_string(), Vec::new()));
+            app.ask_another_model("haiku").await.unwrap();
+            assert!(app.alt_claude.is_none());
+            assert!(app.toast.as_ref().unwrap().message.starts_with("Could not ask haiku: "));
+            assert!(!matches!(app.split_content, SplitContent::AltAnswer(..)));
+        });
+    }
+
+    #[test]
+    fn test_alt_events_from_an_earlier_query_are_dropped() {
+        use crate::claude::events::Delta;
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        rt.block_on(async {
+            let mut app = test_app();
+            app.alt_query = 2;
+            app.split_content = SplitContent::AltAnswer("opus".to_string(), String::new(), false);
+            for (query, t) in [(1, "stale"), (2, "fresh")] {
+                app.update(Msg::AltEvent(query, StreamEvent::ContentBlockDelta {
+                    index: 0,
+                    delta: Delta::TextDelta(t.to_string()),
+                }))
+                .await
+                .unwrap();
+            }
+            app.update(Msg::AltEvent(1, StreamEvent::Result {
+                text: "old answer".to_string(),
+                is_error: true,
+                permission_denials: Vec::new(),
+            }))
+            .await
+            .unwrap();
+
+            match app.split_content {
+                SplitContent::AltAnswer(_, ref text, finished) => {
+                    assert_eq!(text, "fresh");
+                    assert!(!finished, "a stale Result must not finish the new answer");
+                }
+                _ => panic!("alternate answer should stay in the split pane"),
+            }
+        });
+    }
+
+    #[test]
+    fn test_format_duration() {
+        assert_eq!(format_duration(42), "42s");
+        assert_eq!(format_duration(185), "3m 05s");
+        assert_eq!(format_duration(3720), "1h 02m");
+    }
+
+    #[test]
+    fn test_session_stats_counts_messages_and_tools() {
+        let mut app = test_app();
+        app.conversation.push_user_message("hi".to_string());
+        app.conversation.messages.push(crate::claude::conversation::Message {
+            role: Role::Assistant,
+            timestamp: None,
+            stop_reason: None,
+            content: vec![
+                ContentBlock::ToolUse {
+                    id: "t1".to_string(),
+                    name: "Read".to_string(),
+                    input: r#"{"file_path":"src/main.rs"}"#.to_string(),
+                },
+                ContentBlock::ToolUse {
+                    id: "t2".to_string(),
+                    name: "Read".to_string(),
+                    input: r#"{"file_path":"src/app.rs"}"#.to_string(),
+                },
+            ],
+        });
+        app.total_input_tokens = 1200;
+
+        app.show_session_stats();
+        let AppMode::TextViewer { ref lines, .. } = app.mode else {
+            panic!("expected the stats viewer");
+        };
+        assert!(lines.contains(&"  You:        1".to_string()));
+        assert!(lines.contains(&"  Claude:     1".to_string()));
+        assert!(lines.contains(&"  Input:      1200 tokens".to_string()));
+        assert!(lines.contains(&"# Tool Calls (2)".to_string()));
+        assert!(lines.iter().any(|l| l.starts_with("  Read") && l.ends_with("2")));
+        assert!(lines.contains(&"# Files Touched (2)".to_string()));
+    }
+
+    #[test]
+    fn test_workflow_picker_previews_selected_prompt() {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        rt.block_on(async {
+            let mut app = test_app();
+            app.open_workflow_picker();
+            let first = match app.mode {
+                AppMode::WorkflowPicker(ref state) => state.preview.clone(),
+                _ => panic!("expected the workflow picker"),
+            };
+            assert_eq!(first.as_ref().map(|p| p.join("\n")), Some(WORKFLOW_TEMPLATES[0].2.to_string()));
+
+            app.update(key(KeyCode::Down, KeyModifiers::NONE)).await.unwrap();
+            match app.mode {
+                AppMode::WorkflowPicker(ref state) => {
+                    assert_eq!(
+                        state.preview.as_ref().map(|p| p.join("\n")),
+                        Some(WORKFLOW_TEMPLATES[1].2.to_string())
+                    );
+                }
+                _ => panic!("expected the workflow picker"),
+            }
+        });
+    }
+
+    #[test]
+    fn test_custom_workflows_are_marked_in_picker() {
+        let mut app = test_app();
+        app.workflows.push(Workflow {
+            name: "Standup".to_string(),
+            description: "Summarize yesterday".to_string(),
+            prompt: "What did I do yesterday?".to_string(),
+            custom: true,
+        });
+        app.open_workflow_picker();
+        match app.mode {
+            AppMode::WorkflowPicker(ref state) => {
+                assert_eq!(state.items.len(), WORKFLOW_TEMPLATES.len() + 1);
+                assert_eq!(state.items[0].hint, WORKFLOW_TEMPLATES[0].1);
+                let custom = state.items.last().unwrap();
+                assert_eq!(custom.label, "Standup");
+                assert_eq!(custom.hint, "[custom] Summarize yesterday");
+            }
+            _ => panic!("expected the workflow picker"),
+        }
+    }
+
+    #[test]
+    fn test_missing_binary_on_respawn_offers_retry() {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        rt.block_on(async {
+            let mut app = test_app();
+            app.command = "/nonexistent/claude".to_string();
+            app.resume_session("abc-123", None).await.unwrap();
+            assert!(matches!(app.mode, AppMode::RespawnFailed(_)));
+            assert!(app.claude.is_none());
+            assert_eq!(
+                app.toast.as_ref().map(|t| t.message.as_str()),
+                Some("'/nonexistent/claude' not found (is it being updated?)")
+            );
+            assert_eq!(
+                app.failed_respawn,
+                Some(Respawn::Resume { session_id: "abc-123".to_string(), add_dir: None })
+            );
+
+            // Retrying while the binary is still missing shows the prompt again
+            app.update(key(KeyCode::Enter, KeyModifiers::NONE)).await.unwrap();
+            assert!(matches!(app.mode, AppMode::RespawnFailed(_)));
+
+            app.update(key(KeyCode::Down, KeyModifiers::NONE)).await.unwrap();
+            app.update(key(KeyCode::Enter, KeyModifiers::NONE)).await.unwrap();
+            assert!(matches!(app.mode, AppMode::Normal));
+            assert_eq!(app.failed_respawn, None);
+        });
+    }
+
+    #[test]
+    fn test_session_diff_lines() {
+        let lines = session_diff_lines("aaaaaaaa-1111", "Use a map.\n\nDone.", "bbbbbbbb-2222", "Use a set.\n\nDone.");
+        assert_eq!(
+            lines.unwrap(),
+            vec!["--- aaaaaaaa", "+++ bbbbbbbb", "- Use a map.", "+ Use a set.", "  ", "  Done."]
+        );
+
+        // Long but mostly shared transcripts are trimmed; wholly different ones give up
+        let shared = "same\n".repeat(10_000);
+        let lines = session_diff_lines("a", &format!("{shared}old\n{shared}"), "b", &format!("{shared}new\n{shared}"));
+        assert_eq!(lines.unwrap().len(), 2 + 20_002);
+        let old: String = (0..3000).map(|i| format!("a{i}\n")).collect();
+        let new: String = (0..3000).map(|i| format!("b{i}\n")).collect();
+        assert!(session_diff_lines("a", &old, "b", &new).is_none());
+    }
+
+    #[test]
+    fn test_session_previews_are_read_once_per_session() {
+        let mut app = test_app();
+        let item = |id: &str| OverlayItem {
+            label: id.to_string(),
+            value: id.to_string(),
+            hint: String::new(),
+            is_header: false,
+        };
+        app.session_previews.insert("first".to_string(), vec!["You: cached".to_string()]);
+        app.mode = AppMode::SessionPicker(OverlayState::new(vec![item("first"), item("second")], None));
+        app.update_overlay_preview();
+        let AppMode::SessionPicker(ref mut state) = app.mode else { panic!("expected the session picker") };
+        assert_eq!(state.preview.as_deref(), Some(&["You: cached".to_string()][..]));
+
+        state.selected = 1;
+        app.update_overlay_preview();
+        assert!(app.session_previews.contains_key("second"));
+    }
+
+    #[test]
+    fn test_compare_picker_asks_for_second_session() {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        rt.block_on(async {
+            let mut app = test_app();
+            let item = |id: &str| OverlayItem {
+                label: id.to_string(),
+                value: id.to_string(),
+                hint: String::new(),
+                is_header: false,
+            };
+            app.mode = AppMode::ComparePicker(OverlayState::new(vec![item("first"), item("second")], None));
+            app.update(key(KeyCode::Enter, KeyModifiers::NONE)).await.unwrap();
+            assert_eq!(app.compare_first.as_deref(), Some("first"));
+            match app.mode {
+                AppMode::ComparePicker(ref state) => {
+                    assert_eq!(state.items.len(), 1);
+                    assert_eq!(state.items[0].value, "second");
+                }
+                _ => panic!("expected the compare picker"),
+            }
+
+            // Neither session exists on disk, so there is nothing to diff
+            app.update(key(KeyCode::Enter, KeyModifiers::NONE)).await.unwrap();
+            assert!(matches!(app.mode, AppMode::Normal));
+            assert_eq!(app.compare_first, None);
+            assert_eq!(app.toast.as_ref().map(|t| t.message.as_str()), Some("No assistant replies to compare"));
+        });
+    }
+
+    #[test]
+    fn test_workflow_placeholders() {
+        assert_eq!(workflow_placeholders("Fix {test} in {file}, then rerun {test}"), vec!["test", "file"]);
+        assert!(workflow_placeholders("Use a map like {} or { a: 1 }").is_empty());
+        assert!(workflow_placeholders(WORKFLOW_TEMPLATES[0].2).is_empty());
+    }
+
+    #[test]
+    fn test_workflow_asks_for_each_placeholder() {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        rt.block_on(async {
+            let mut app = test_app();
+            app.start_workflow("Review Pair", "Compare {left} with {right}").await.unwrap();
+            match app.mode {
+                AppMode::TextInput { ref prompt, .. } => assert_eq!(prompt, "Review Pair: left"),
+                _ => panic!("expected a text input"),
+            }
+            app.update(key(KeyCode::Char('a'), KeyModifiers::NONE)).await.unwrap();
+            app.update(key(KeyCode::Enter, KeyModifiers::NONE)).await.unwrap();
+            match app.mode {
+                AppMode::TextInput { ref prompt, .. } => assert_eq!(prompt, "Review Pair: right"),
+                _ => panic!("expected a second text input"),
+            }
+            app.update(key(KeyCode::Char('b'), KeyModifiers::NONE)).await.unwrap();
+            app.update(key(KeyCode::Enter, KeyModifiers::NONE)).await.unwrap();
+
+            assert!(matches!(app.mode, AppMode::Normal));
+            let sent = app.conversation.messages.last().unwrap();
+            assert!(matches!(&sent.content[0], ContentBlock::Text(text) if text == "Compare a with b"));
+        });
+    }
+
+    #[test]
+    fn test_unknown_lines_are_buffered_and_warnings_toasted() {
+        let mut app = test_app();
+        app.record_unknown_line(r#"{"type":"assistant"}"#);
+        assert!(app.toast.is_none());
+
+        app.record_unknown_line("Update available: 1.2.3 -> 1.3.0");
+        let toast = app.toast.as_ref().expect("warning should be toasted");
+        assert!(toast.dim);
+        assert_eq!(toast.message, "Update available: 1.2.3 -> 1.3.0");
+
+        for i in 0..UNKNOWN_LINES_MAX {
+            app.record_unknown_line(&format!("{{\"n\":{i}}}"));
+        }
+        assert_eq!(app.unknown_lines.len(), UNKNOWN_LINES_MAX);
+
+        app.config.verbose_unknown = true;
+        app.record_unknown_line("{}");
+        assert_eq!(app.unknown_lines().count(), UNKNOWN_LINES_MAX + 1);
+    }
+
+    fn permission_event(id: &str) -> Msg {
+        Msg::ClaudeEvent(StreamEvent::PermissionRequest {
+            request_id: id.to_string(),
+            tool_name: "Bash".to_string(),
+            input: serde_json::json!({ "command": "cargo test" }),
+        })
+    }
+
+    #[test]
+    fn test_destructive_bash_preselects_deny() {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        rt.block_on(async {
+            let mut app = test_app();
+            app.update(Msg::ClaudeEvent(StreamEvent::PermissionRequest {
+                request_id: "req-1".to_string(),
+                tool_name: "Bash".to_string(),
+                input: serde_json::json!({ "command": "git  reset --hard HEAD~3" }),
+            }))
+            .await
+            .unwrap();
+            match app.mode {
+                AppMode::UserQuestion { ref questions, cursor, permission: Some(_), .. } => {
+                    assert!(questions[0].question.contains("git reset --hard"));
+                    assert_eq!(cursor, 1);
+                }
+                _ => panic!("expected a permission prompt"),
+            }
+        });
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_init_timeout_waits_for_first_message() {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        rt.block_on(async {
+            let mut app = test_app();
+            let (process, _events) = ClaudeProcess::spawn("sh -c cat>/dev/null").unwrap();
+            app.claude = Some(process);
+            app.awaiting_init = true;
+            app.update(Msg::InitTimeout).await.unwrap();
+            assert!(matches!(app.mode, AppMode::Normal), "nothing sent yet, keep waiting");
+
+            // The countdown starts at the first send
+            app.submit_text("hello".to_string()).await.unwrap();
+            assert!(app.init_check_armed);
+            app.update(Msg::InitTimeout).await.unwrap();
+            match app.mode {
+                AppMode::TextViewer { ref lines, .. } => assert!(lines[0].contains("claude /login")),
+                _ => panic!("expected the connection diagnostic"),
+            }
+            assert!(!app.awaiting_init);
+        });
+    }
+
+    #[test]
+    fn test_any_event_clears_pending_timeout() {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        rt.block_on(async {
+            let mut app = test_app();
+            app.awaiting_init = true;
+            app.init_check_armed = true;
+            app.update(Msg::ClaudeEvent(StreamEvent::Unknown("warming up".to_string()))).await.unwrap();
+            assert!(app.awaiting_init, "unrecognised output doesn't count");
+            app.update(Msg::ClaudeEvent(StreamEvent::MessageStop)).await.unwrap();
+            app.update(Msg::InitTimeout).await.unwrap();
+            assert!(matches!(app.mode, AppMode::Normal));
+        });
+    }
+
+    #[test]
+    fn test_mcp_status_from_init() {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        rt.block_on(async {
+            let mut app = test_app();
+            let server = |name: &str, status: &str| McpServer { name: name.to_string(), status: status.to_string() };
+            app.update(Msg::ClaudeEvent(StreamEvent::SystemInit {
+                slash_commands: Vec::new(),
+                session_id: None,
+                mcp_servers: vec![server("github", "connected"), server("linear", "failed")],
+            }))
+            .await
+            .unwrap();
+            assert_eq!(
+                app.toast.as_ref().map(|t| t.message.as_str()),
+                Some("1 MCP server not connected (Ctrl+K → MCP Status)")
+            );
+
+            app.open_mcp_status();
+            match app.mode {
+                AppMode::TextViewer { ref title, ref lines, .. } => {
+                    assert_eq!(title, "MCP Status");
+                    assert_eq!(lines[0], "1 connected, 1 not connected");
+                    assert!(lines.contains(&"  ✓ github  (connected)".to_string()));
+                    assert!(lines.contains(&"  ✗ linear  (failed)".to_string()));
+                }
+                _ => panic!("expected the MCP status viewer"),
+            }
+        });
+    }
+
+    #[test]
+    fn test_snippet_expands_and_completes() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut app = test_app();
+        app.snippets = Snippets::load_from(dir.path().join("snippets.toml"));
+        app.snippets.insert("fix", "Fix the failing test\nand explain why").unwrap();
+
+        app.input.set_content("/snip f");
+        app.update_completions();
+        let names: Vec<String> = app.completion.as_ref().unwrap().matches.iter().map(|m| m.name.clone()).collect();
+        assert!(names.contains(&"snip fix".to_string()));
+
+        match app.handle_local_command("/snip fix") {
+            Some(LocalAction::Snippet(command)) => app.run_snippet_command(command),
+            _ => panic!("expected a snippet command"),
+        }
+        assert_eq!(app.input.content(), "Fix the failing test\nand explain why");
+
+        app.run_snippet_command(SnipCommand::Expand("missing".to_string()));
+        assert_eq!(app.toast.as_ref().map(|t| t.message.as_str()), Some("No snippet named \"missing\""));
+    }
+
+    #[test]
+    fn test_plan_approval_skips_modal_prompt() {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        rt.block_on(async {
+            let mut app = test_app();
+            app.update(Msg::ClaudeEvent(StreamEvent::PermissionRequest {
+                request_id: "plan-1".to_string(),
+                tool_name: "ExitPlanMode".to_string(),
+                input: serde_json::json!({ "plan": "1. Do the thing" }),
+            }))
+            .await
+            .unwrap();
+            assert!(matches!(app.mode, AppMode::Normal));
+            assert!(app.permission_queue.is_empty());
+            assert_eq!(app.pending_plan.as_ref().map(|r| r.request_id.as_str()), Some("plan-1"));
+
+            app.update(key(KeyCode::Enter, KeyModifiers::CONTROL)).await.unwrap();
+            assert!(app.pending_plan.is_none());
+            assert_eq!(app.toast.as_ref().map(|t| t.message.as_str()), Some("Plan accepted"));
+
+            // Alt+Y/Alt+N work in terminals that can't send Ctrl+Enter
+            let plan = || PermissionRequest {
+                request_id: "plan-2".to_string(),
+                tool_name: "ExitPlanMode".to_string(),
+                input: serde_json::json!({ "plan": "1. Do the thing" }),
+            };
+            app.pending_plan = Some(plan());
+            app.update(key(KeyCode::Char('n'), KeyModifiers::ALT)).await.unwrap();
+            assert!(app.pending_plan.is_none());
+            assert_eq!(app.toast.as_ref().map(|t| t.message.as_str()), Some("Plan rejected"));
+            assert!(app.input.is_empty());
+
+            // ...while plain keys start a typed reply, even from an empty input
+            app.pending_plan = Some(plan());
+            for c in "no, instead".chars() {
+                app.update(key(KeyCode::Char(c), KeyModifiers::NONE)).await.unwrap();
+            }
+            assert_eq!(app.input.content(), "no, instead");
+            assert!(app.pending_plan.is_some());
+        });
+    }
+
+    #[test]
+    fn test_permission_requests_prompt_one_at_a_time() {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        rt.block_on(async {
+            let mut app = test_app();
+            app.update(permission_event("req-1")).await.unwrap();
+            app.update(permission_event("req-2")).await.unwrap();
+            match app.mode {
+                AppMode::UserQuestion { ref questions, permission: Some(ref request), .. } => {
+                    assert_eq!(request.request_id, "req-1");
+                    assert_eq!(questions[0].options[0].label, "Allow");
+                    assert_eq!(questions[0].options[0].description, "cargo test");
+                }
+                _ => panic!("expected a permission prompt"),
+            }
+            app.update(key(KeyCode::Esc, KeyModifiers::NONE)).await.unwrap();
+            match app.mode {
+                AppMode::UserQuestion { permission: Some(ref request), .. } => {
+                    assert_eq!(request.request_id, "req-2");
+                }
+                _ => panic!("expected the second permission prompt"),
+            }
+        });
+    }
+
+    #[test]
+    fn test_permission_prompt_shows_over_an_open_overlay() {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        rt.block_on(async {
+            let mut app = test_app();
+            app.show_help_viewer();
+            app.update(permission_event("req-1")).await.unwrap();
+            assert!(matches!(app.mode, AppMode::UserQuestion { permission: Some(_), .. }));
+            app.update(key(KeyCode::Esc, KeyModifiers::NONE)).await.unwrap();
+            assert!(matches!(app.mode, AppMode::TextViewer { .. }));
+            assert!(app.suspended_mode.is_none());
+        });
+    }
+
+    #[test]
+    fn test_ctrl_b_toggles_thinking() {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        rt.block_on(async {
+            let mut app = test_app();
+            assert!(app.render_options().show_thinking);
+            app.update(key(KeyCode::Char('b'), KeyModifiers::CONTROL)).await.unwrap();
+            assert!(!app.render_options().show_thinking);
+            assert_eq!(app.toast.as_ref().map(|t| t.message.as_str()), Some("Thinking hidden"));
+            app.update(key(KeyCode::Char('b'), KeyModifiers::CONTROL)).await.unwrap();
+            assert!(app.render_options().show_thinking);
+        });
+    }
+
+    #[test]
+    fn test_idle_only_when_nothing_animates() {
+        let mut app = test_app();
+        // The full header animates until the first message
+        assert!(!app.is_idle());
+        app.conversation.push_user_message("hi".to_string());
+        assert!(app.is_idle());
+
+        app.toast = Some(Toast::new("Copied".to_string()));
+        assert!(!app.is_idle());
+        app.toast = None;
+
+        app.conversation.apply_event(&StreamEvent::MessageStart {
+            message_id: "msg_1".to_string(),
+            model: "claude-sonnet-4-5".to_string(),
+            usage: None,
+        });
+        assert!(!app.is_idle());
+    }
+
+    #[test]
+    fn test_plugin_refresh_keeps_cursor_on_plugin() {
+        let plugin = |name: &str, installed: bool| PluginInfo {
+            name: name.to_string(),
+            marketplace: "official".to_string(),
+            description: String::new(),
+            is_mcp: false,
+            installed,
+            enabled: installed,
+        };
+        let cursor = |app: &App| match app.mode {
+            AppMode::PluginBrowser { cursor, .. } => cursor,
+            _ => panic!("expected the plugin browser"),
+        };
+        let mut app = test_app();
+        let plugins = vec![plugin("github", true), plugin("linear", false), plugin("sentry", false)];
+        app.mode = AppMode::PluginBrowser {
+            plugins: plugins.clone(),
+            all: plugins,
+            query: String::new(),
+            searching: false,
+            cursor: 2,
+            scroll: 0,
+        };
+
+        // Installing sentry sorts it up among the installed plugins
+        app.show_refreshed_plugins(
+            vec![plugin("github", true), plugin("sentry", true), plugin("linear", false)],
+            "sentry@official",
+        );
+        assert_eq!(cursor(&app), 1);
+
+        // A plugin that vanished leaves the cursor where it was, within bounds
+        app.show_refreshed_plugins(vec![plugin("github", true)], "sentry@official");
+        assert_eq!(cursor(&app), 0);
+    }
+
+    #[test]
+    fn test_plugin_browser_type_ahead() {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        rt.block_on(async {
+            let mut app = test_app();
+            let plugin = |name: &str| PluginInfo {
+                name: name.to_string(),
+                marketplace: "official".to_string(),
+                description: String::new(),
+                is_mcp: false,
+                installed: false,
+                enabled: false,
+            };
+            let plugins = vec![plugin("github"), plugin("linear"), plugin("gitlab"), plugin("sentry")];
+            app.mode = AppMode::PluginBrowser {
+                plugins: plugins.clone(),
+                all: plugins,
+                query: String::new(),
+                searching: false,
+                cursor: 0,
+                scroll: 0,
+            };
+            let cursor = |app: &App| match app.mode {
+                AppMode::PluginBrowser { cursor, .. } => cursor,
+                _ => panic!("expected the plugin browser"),
+            };
+
+            app.update(key(KeyCode::Char('s'), KeyModifiers::NONE)).await.unwrap();
+            assert_eq!(cursor(&app), 3);
+            app.update(key(KeyCode::Char('g'), KeyModifiers::NONE)).await.unwrap();
+            assert_eq!(cursor(&app), 0);
+            app.update(key(KeyCode::Char('G'), KeyModifiers::SHIFT)).await.unwrap();
+            assert_eq!(cursor(&app), 2);
+            // Bound letters keep their action
+            app.update(key(KeyCode::Char('k'), KeyModifiers::NONE)).await.unwrap();
+            assert_eq!(cursor(&app), 1);
+        });
+    }
+
+    #[test]
+    fn test_plugin_browser_search() {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        rt.block_on(async {
+            let mut app = test_app();
+            let plugin = |name: &str, description: &str| PluginInfo {
+                name: name.to_string(),
+                marketplace: "official".to_string(),
+                description: description.to_string(),
+                is_mcp: false,
+                installed: false,
+                enabled: false,
+            };
+            let plugins = vec![
+                plugin("github", "Issues and pull requests"),
+                plugin("linear", "Issue tracking"),
+                plugin("sentry", "Error monitoring"),
+            ];
+            app.mode = AppMode::PluginBrowser {
+                plugins: plugins.clone(),
+                all: plugins,
+                query: String::new(),
+                searching: false,
+                cursor: 2,
+                scroll: 0,
+            };
+            let shown = |app: &App| match app.mode {
+                AppMode::PluginBrowser { ref plugins, .. } => plugins.iter().map(|p| p.name.clone()).collect::<Vec<_>>(),
+                _ => panic!("expected the plugin browser"),
+            };
+
+            // Letters that are otherwise bound (i, u, q) go into the query
+            app.update(key(KeyCode::Char('/'), KeyModifiers::NONE)).await.unwrap();
+            for c in "issu".chars() {
+                app.update(key(KeyCode::Char(c), KeyModifiers::NONE)).await.unwrap();
+            }
+            assert_eq!(shown(&app).len(), 2);
+            assert!(!shown(&app).contains(&"sentry".to_string()));
+            app.update(key(KeyCode::Backspace, KeyModifiers::NONE)).await.unwrap();
+            app.update(key(KeyCode::Char('x'), KeyModifiers::NONE)).await.unwrap();
+            assert!(shown(&app).is_empty());
+            app.update(key(KeyCode::Backspace, KeyModifiers::NONE)).await.unwrap();
+
+            // Enter keeps the filter and hands keys back to the browser
+            app.update(key(KeyCode::Enter, KeyModifiers::NONE)).await.unwrap();
+            app.update(key(KeyCode::Down, KeyModifiers::NONE)).await.unwrap();
+            assert!(matches!(app.mode, AppMode::PluginBrowser { cursor: 1, searching: false, .. }));
+
+            // Esc clears the filter first, then closes
+            app.update(key(KeyCode::Esc, KeyModifiers::NONE)).await.unwrap();
+            assert_eq!(shown(&app).len(), 3);
+            app.update(key(KeyCode::Esc, KeyModifiers::NONE)).await.unwrap();
+            assert!(matches!(app.mode, AppMode::Normal));
+        });
+    }
+
+    #[test]
+    fn test_toasts_are_kept_in_notification_history() {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        rt.block_on(async {
+            let mut app = test_app();
+            app.update(key(KeyCode::Char('b'), KeyModifiers::CONTROL)).await.unwrap();
+            // The same toast is recorded once however many messages pass
+            app.update(Msg::Tick).await.unwrap();
+            app.update(key(KeyCode::Char('b'), KeyModifiers::CONTROL)).await.unwrap();
+            app.toast = None;
+            app.update(Msg::Tick).await.unwrap();
+
+            app.update(key(KeyCode::Char('x'), KeyModifiers::CONTROL)).await.unwrap();
+            match app.mode {
+                AppMode::TextViewer { ref title, ref lines, .. } => {
+                    assert_eq!(title, "Notifications");
+                    assert_eq!(lines.len(), 2);
+                    assert!(lines[0].ends_with("  Thinking shown"));
+                    assert!(lines[1].contains("just now"));
+                    assert!(lines[1].ends_with("  Thinking hidden"));
+                }
+                _ => panic!("expected the notifications viewer"),
+            }
+        });
+    }
+
+    #[test]
+    fn test_terminal_is_sized_to_the_rendered_split_pane() {
+        let mut app = test_app();
+        app.conversation.push_user_message("hi".to_string());
+        // Header, input (3) and status bar (1) rows, then the pane's borders
+        let rows = 40 - crate::ui::header::COMPACT_HEADER_HEIGHT - 4 - 2;
+        assert_eq!(app.terminal_pane_size(120, 40), (46, rows));
+
+        app.input.set_content("one\ntwo\nthree");
+        assert_eq!(app.terminal_pane_size(120, 40), (46, rows - 3));
+    }
+
+    #[test]
+    fn test_terminal_pane_shows_pty_output_and_releases_focus() {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        rt.block_on(async {
+            let mut app = test_app();
+            app.pty_session = Some(PtySession {
+                process: PtyProcess::spawn("cat", 20, 4).unwrap(),
+                emulator: TerminalEmulator::new(4, 20),
+            });
+            app.split_pane = true;
+            app.pty_focused = true;
+            app.refresh_terminal_pane();
+            assert!(app.terminal_has_focus());
+
+            app.update(Msg::PtyOutput(b"hello".to_vec())).await.unwrap();
+            match app.split_content {
+                SplitContent::Terminal(ref lines) => {
+                    let first: String = lines[0].spans.iter().map(|s| s.text.as_str()).collect();
+                    assert!(first.starts_with("hello"));
+                }
+                _ => panic!("expected the terminal in the split pane"),
+            }
+
+            app.update(key(KeyCode::Char(']'), KeyModifiers::CONTROL)).await.unwrap();
+            assert!(!app.terminal_has_focus());
+            assert!(matches!(app.split_content, SplitContent::Terminal(_)));
+
+            app.update(Msg::PtyExited).await.unwrap();
+            assert!(app.pty_session.is_none());
+            assert!(matches!(app.split_content, SplitContent::FileContext(_)));
+        });
+    }
+
+    #[test]
+    fn test_session_patch_skips_failed_edits() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("lib.rs");
+        std::fs::write(&file, "fn a() {}\nfn renamed() {}\n").unwrap();
+        let edit = |id: &str, old: &str, new: &str| ContentBlock::ToolUse {
+            id: id.to_string(),
+            name: "Edit".to_string(),
+            input: serde_json::json!({
+                "file_path": file.display().to_string(),
+                "old_string": old,
+                "new_string": new,
+            })
+            .to_string(),
+        };
+
+        let mut app = test_app();
+        assert!(app.session_patch(dir.path()).is_none());
+        app.conversation.messages.push(crate::claude::conversation::Message {
+            role: Role::Assistant,
+            timestamp: None,
+            stop_reason: None,
+            content: vec![
+                edit("t1", "fn b() {}", "fn renamed() {}"),
+                edit("t2", "missing", "never applied"),
+                ContentBlock::ToolResult {
+                    tool_use_id: "t2".to_string(),
+                    content: "old_string not found".to_string(),
+                    is_error: true,
+                    collapsed: false,
+                },
+            ],
+        });
+
+        let (patch, skipped) = app.session_patch(dir.path()).unwrap();
+        assert!(skipped.is_empty());
+        assert!(patch.starts_with("diff --git a/lib.rs b/lib.rs\n"));
+        assert!(patch.contains("@@ -1,2 +1,2 @@\n fn a() {}\n-fn b() {}\n+fn renamed() {}\n"));
+    }
+
+    #[test]
+    fn test_session_patch_undoes_deletions_and_overwrites() {
+        let dir = tempfile::tempdir().unwrap();
+        let lib = dir.path().join("lib.rs");
+        let main = dir.path().join("main.rs");
+        std::fs::write(&lib, "a\nb\nc\nd\ne\nf\ng\n").unwrap();
+        std::fs::write(&main, "fn main() {}\n").unwrap();
+        let tool = |id: &str, name: &str, input: serde_json::Value| ContentBlock::ToolUse {
+            id: id.to_string(),
+            name: name.to_string(),
+            input: input.to_string(),
+        };
+        let result = |id: &str, content: &str| ContentBlock::ToolResult {
+            tool_use_id: id.to_string(),
+            content: content.to_string(),
+            is_error: false,
+            collapsed: false,
+        };
+
+        let mut app = test_app();
+        app.conversation.messages.push(crate::claude::conversation::Message {
+            role: Role::Assistant,
+            timestamp: None,
+            stop_reason: None,
+            content: vec![
+                tool("t1", "Edit", serde_json::json!({
+                    "file_path": lib.display().to_string(), "old_string": "x\n", "new_string": "",
+                })),
+                result(
+                    "t1",
+                    "The file lib.rs has been updated. Here's the result of running `cat -n` on a snippet of the edited file:\n\
+                     \x20    2\u{2192}b\n     3\u{2192}c\n     4\u{2192}d\n     5\u{2192}e\n     6\u{2192}f",
+                ),
+                tool("t2", "Write", serde_json::json!({
+                    "file_path": main.display().to_string(), "content": "fn main() {}\n",
+                })),
+                result("t2", "The file main.rs has been updated."),
+            ],
+        });
+
+        let (patch, skipped) = app.session_patch(dir.path()).unwrap();
+        assert_eq!(skipped, ["main.rs"]);
+        assert!(patch.contains(" e\n-x\n f\n"), "{patch}");
+        assert_eq!(edited_lines("no snippet"), crate::diff::ANY_LINE);
+    }
+
+    #[test]
+    fn test_file_diff_combines_edits_to_one_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("lib.rs");
+        let path = file.display().to_string();
+        std::fs::write(&file, "fn a() {}\nfn b2() {}\nfn c() {}\n").unwrap();
+        let tool = |id: &str, name: &str, input: serde_json::Value| ContentBlock::ToolUse {
+            id: id.to_string(),
+            name: name.to_string(),
+            input: input.to_string(),
+        };
+
+        let mut app = test_app();
+        app.conversation.messages.push(crate::claude::conversation::Message {
+            role: Role::Assistant,
+            timestamp: None,
+            stop_reason: None,
+            content: vec![
+                tool("t1", "Write", serde_json::json!({ "file_path": path, "content": "fn a() {}\n" })),
+                tool("t2", "Edit", serde_json::json!({ "file_path": path, "old_string": "fn a() {}", "new_string": "fn a() {}\nfn b() {}" })),
+                tool("t3", "Edit", serde_json::json!({ "file_path": path, "old_string": "fn b() {}", "new_string": "fn b2() {}\nfn c() {}" })),
+                tool("t4", "Edit", serde_json::json!({ "file_path": "/elsewhere.rs", "old_string": "x", "new_string": "y" })),
+                ContentBlock::ToolResult {
+                    tool_use_id: "t1".to_string(),
+                    content: format!("File created successfully at: {path}"),
+                    is_error: false,
+                    collapsed: false,
+                },
+            ],
+        });
+
+        app.open_file_diff_picker();
+        match app.mode {
+            AppMode::FileDiffPicker(ref state) => assert_eq!(state.items.len(), 2),
+            _ => panic!("expected the file picker"),
+        }
+
+        app.open_file_diff(&path);
+        match app.mode {
+            AppMode::TextViewer { ref lines, .. } => {
+                assert_eq!(lines[0], format!("3 changes to {path} this session:"));
+                assert_eq!(lines[1], "  1. Write  +1 -0");
+                assert_eq!(lines[3], "  3. Edit   +2 -1");
+                let hunk = lines.iter().position(|l| l.starts_with("@@")).unwrap();
+                assert_eq!(lines[hunk..], ["@@ -0,0 +1,3 @@", "+ fn a() {}", "+ fn b2() {}", "+ fn c() {}"]);
+            }
+            _ => panic!("expected the diff viewer"),
+        }
+
+        // Another file whose edits no longer match what's on disk
+        app.mode = AppMode::Normal;
+        app.open_file_diff("/elsewhere.rs");
+        assert!(matches!(app.mode, AppMode::Normal));
+        assert!(app.toast.as_ref().unwrap().message.starts_with("Can't reconstruct /elsewhere.rs"));
+
+        // A Write over an existing file doesn't pass for a new one
+        let ContentBlock::ToolResult { content, .. } = &mut app.conversation.messages[0].content[4] else {
+            panic!("expected the Write result");
+        };
+        *content = format!("The file {path} has been updated.");
+        app.open_file_diff(&path);
+        assert!(matches!(app.mode, AppMode::Normal));
+        assert_eq!(
+            app.toast.as_ref().unwrap().message,
+            format!("Can't reconstruct {path}: a Write replaced content from before the session")
+        );
+    }
+
+    #[test]
+    fn test_diff_viewer_hunk_headers_use_file_line_numbers() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("lib.rs");
+        std::fs::write(&file, "use x;\n\nfn a() {}\nfn renamed() {}\n").unwrap();
+        let mut app = test_app();
+        app.conversation.messages.push(crate::claude::conversation::Message {
+            role: Role::Assistant,
+            timestamp: None,
+            stop_reason: None,
+            content: vec![ContentBlock::ToolUse {
+                id: "t1".to_string(),
+                name: "Edit".to_string(),
+                input: serde_json::json!({
+                    "file_path": file.display().to_string(),
+                    "old_string": "fn b() {}",
+                    "new_string": "fn renamed() {}",
+                })
+                .to_string(),
+            }],
+        });
+
+        app.open_diff_viewer();
+        let AppMode::DiffViewer { sections, .. } = &app.mode else {
+            panic!("diff viewer not opened");
+        };
+        let (lines, _) = diff_viewer_lines(sections);
+        assert!(lines.contains(&"@@ -4,1 +4,1 @@".to_string()));
+        assert!(lines.contains(&"- fn b() {}".to_string()));
+
+        // Once the edit is gone from the file its hunk is marked, not numbered
+        std::fs::write(&file, "use x;\n").unwrap();
+        app.open_diff_viewer();
+        let AppMode::DiffViewer { sections, .. } = &app.mode else {
+            panic!("diff viewer not opened");
+        };
+        let (lines, _) = diff_viewer_lines(sections);
+        assert!(lines.contains(&"@@ -?,1 +?,1 @@ (not found in the current file)".to_string()));
+    }
+
+    #[test]
+    fn test_diff_viewer_folds_per_file_sections() {
+        let mut app = test_app();
+        let edit = |id: &str, name: &str, input: serde_json::Value| ContentBlock::ToolUse {
+            id: id.to_string(),
+            name: name.to_string(),
+            input: input.to_string(),
+        };
+        app.conversation.messages.push(crate::claude::conversation::Message {
+            role: Role::Assistant,
+            timestamp: None,
+            stop_reason: None,
+            content: vec![
+                edit("t1", "Edit", serde_json::json!({"file_path": "a.rs", "old_string": "x", "new_string": "y\nz"})),
+                edit("t2", "Write", serde_json::json!({"file_path": "b.rs", "content": "one\ntwo\n"})),
+                edit("t3", "Edit", serde_json::json!({"file_path": "a.rs", "old_string": "p", "new_string": "q"})),
+            ],
+        });
+        app.open_diff_viewer();
+        let lines = |app: &App| match &app.mode {
+            AppMode::DiffViewer { sections, .. } => diff_viewer_lines(sections).0,
+            _ => panic!("diff viewer not open"),
+        };
+        let all = lines(&app);
+        assert_eq!(all[0], "\u{25BC} a.rs [+3 -2]");
+        assert_eq!(all.iter().filter(|l| l.starts_with("\u{25BC}")).count(), 2);
+
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        rt.block_on(async {
+            app.update(key(KeyCode::Enter, KeyModifiers::NONE)).await.unwrap();
+            assert_eq!(lines(&app)[..3], ["\u{25B6} a.rs [+3 -2]", "", "\u{25BC} b.rs [+2 -0]"]);
+
+            // Moving to the next file brings its header to the top
+            app.update(key(KeyCode::Down, KeyModifiers::NONE)).await.unwrap();
+            assert!(matches!(app.mode, AppMode::DiffViewer { selected: 1, scroll: 2, .. }));
+
+            app.update(key(KeyCode::Char('a'), KeyModifiers::NONE)).await.unwrap();
+            assert_eq!(lines(&app), ["\u{25B6} a.rs [+3 -2]", "", "\u{25B6} b.rs [+2 -0]"]);
+        });
+    }
+
+    #[test]
+    fn test_text_input_edits_whole_graphemes() {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        rt.block_on(async {
+            let mut app = test_app();
+            app.mode = AppMode::TextInput {
+                prompt: "Session name".to_string(),
+                value: String::new(),
+                cursor: 0,
+                action: TextInputAction::RenameSession,
+            };
+            for c in ['o', 'k', '\u{1F44D}', '\u{1F3FD}'] {
+                app.update(key(KeyCode::Char(c), KeyModifiers::NONE)).await.unwrap();
+            }
+            app.update(key(KeyCode::Left, KeyModifiers::NONE)).await.unwrap();
+            app.update(key(KeyCode::Char('!'), KeyModifiers::NONE)).await.unwrap();
+            app.update(key(KeyCode::End, KeyModifiers::NONE)).await.unwrap();
+            app.update(key(KeyCode::Backspace, KeyModifiers::NONE)).await.unwrap();
+            match app.mode {
+                AppMode::TextInput { ref value, cursor, .. } => {
+                    assert_eq!(value, "ok!");
+                    assert_eq!(cursor, 3);
+                }
+                _ => panic!("expected the text input overlay"),
+            }
+        });
+    }
+
+    #[test]
+    fn test_split_auto_tools_controls_pane_switching() {
+        let grep_call = crate::claude::conversation::Message {
+            role: Role::Assistant,
+            timestamp: None,
+            stop_reason: None,
+            content: vec![ContentBlock::ToolUse {
+                id: "t1".to_string(),
+                name: "Grep".to_string(),
+                input: r#"{"pattern":"fn main"}"#.to_string(),
+            }],
+        };
+        let result = StreamEvent::ToolResult {
+            tool_use_id: "t1".to_string(),
+            content: "src/main.rs\nsrc/bin.rs".to_string(),
+            is_error: false,
+            images: Vec::new(),
+        };
+
+        // Grep is not auto-shown by default
+        let mut app = test_app();
+        app.conversation.messages.push(grep_call.clone());
+        app.update_split_content_from_event(&StreamEvent::MessageStop);
+        assert!(matches!(app.split_content, SplitContent::FileContext(_)));
+
+        app.config.split_auto_tools = vec!["Grep".to_string()];
+        app.update_split_content_from_event(&StreamEvent::MessageStop);
+        app.update_split_content_from_event(&result);
+        match app.split_content {
+            SplitContent::SearchResults(ref query, ref lines, selected) => {
+                assert_eq!(query, "Grep: fn main");
+                assert_eq!(lines, &vec!["src/main.rs".to_string(), "src/bin.rs".to_string()]);
+                assert_eq!(selected, 0);
+            }
+            _ => panic!("expected grep results in the split pane"),
+        }
+    }
+
+    #[test]
+    fn test_read_results_only_fill_their_own_preview() {
+        let read_call = |id: &str, path: &str| crate::claude::conversation::Message {
+            role: Role::Assistant,
+            timestamp: None,
+            stop_reason: None,
+            content: vec![ContentBlock::ToolUse {
+                id: id.to_string(),
+                name: "Read".to_string(),
+                input: serde_json::json!({ "file_path": path }).to_string(),
+            }],
+        };
+        let result = |id: &str, content: &str| StreamEvent::ToolResult {
+            tool_use_id: id.to_string(),
+            content: content.to_string(),
+            is_error: false,
+            images: Vec::new(),
+        };
+        let preview = |app: &App| match app.split_content {
+            SplitContent::FilePreview(ref path, ref lines) => (path.clone(), lines.join("\n")),
+            _ => panic!("expected a file preview"),
+        };
+
+        let mut app = test_app();
+        app.conversation.messages.push(read_call("t1", "/a.rs"));
+        app.update_split_content_from_event(&StreamEvent::MessageStop);
+        app.conversation.messages.push(read_call("t2", "/b.rs"));
+        // A result for another file leaves the preview alone
+        app.update_split_content_from_event(&result("t2", "b"));
+        assert_eq!(preview(&app), ("/a.rs".to_string(), "Reading file...".to_string()));
+        app.update_split_content_from_event(&result("t1", "a"));
+        assert_eq!(preview(&app), ("/a.rs".to_string(), "a".to_string()));
+
+        // Nor does any Read result once Read is not auto-shown
+        app.config.split_auto_tools.retain(|t| t != "Read");
+        app.update_split_content_from_event(&result("t1", "changed"));
+        assert_eq!(preview(&app).1, "a");
+    }
+
+    #[test]
+    fn test_search_results_navigate_and_open() {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        rt.block_on(async {
+            let mut app = test_app();
+            app.config.editor_command = Some("ed +{line} {file}".to_string());
+            app.split_pane = true;
+            app.visible_height = 4;
+            let lines = ["Found 3 matches", "src/app.rs:10:fn a()", "--", "src/app.rs:20:fn b()", "src/ui/mod.rs:5:fn c()"];
+            app.split_content = SplitContent::SearchResults(
+                "Grep: fn".to_string(),
+                lines.iter().map(|l| l.to_string()).collect(),
+                1,
+            );
+
+            app.update(key(KeyCode::Down, KeyModifiers::SHIFT)).await.unwrap();
+            assert!(matches!(app.split_content, SplitContent::SearchResults(_, _, 3)));
+            app.update(key(KeyCode::Down, KeyModifiers::SHIFT)).await.unwrap();
+            app.update(key(KeyCode::Down, KeyModifiers::SHIFT)).await.unwrap();
+            assert!(matches!(app.split_content, SplitContent::SearchResults(_, _, 4)));
+            assert_eq!(app.split_scroll, 3);
+
+            app.update(key(KeyCode::Char('o'), KeyModifiers::ALT)).await.unwrap();
+            assert_eq!(
+                app.pending_editor,
+                Some(vec!["ed".to_string(), "+5".to_string(), "src/ui/mod.rs".to_string()])
+            );
+
+            app.update(key(KeyCode::Up, KeyModifiers::SHIFT)).await.unwrap();
+            app.update(key(KeyCode::Up, KeyModifiers::SHIFT)).await.unwrap();
+            app.update(key(KeyCode::Up, KeyModifiers::SHIFT)).await.unwrap();
+            assert!(matches!(app.split_content, SplitContent::SearchResults(_, _, 1)));
+            assert_eq!(app.split_scroll, 1);
+        });
+    }
+
+    #[test]
+    fn test_pinned_split_pane_follows_only_its_file() {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        rt.block_on(async {
+            let dir = tempfile::tempdir().unwrap();
+            let pinned = dir.path().join("lib.rs").display().to_string();
+            std::fs::write(&pinned, "fn old() {}\n").unwrap();
+            let edit = |id: &str, path: &str| crate::claude::conversation::Message {
+                role: Role::Assistant,
+                timestamp: None,
+                stop_reason: None,
+                content: vec![ContentBlock::ToolUse {
+                    id: id.to_string(),
+                    name: "Edit".to_string(),
+                    input: serde_json::json!({"file_path": path, "old_string": "old", "new_string": "new"}).to_string(),
+                }],
+            };
+            let result = |id: &str| StreamEvent::ToolResult {
+                tool_use_id: id.to_string(),
+                content: "ok".to_string(),
+                is_error: false,
+                images: Vec::new(),
+            };
+            let shown = |app: &App| match app.split_content {
+                SplitContent::FilePreview(ref path, ref lines) => (path.clone(), lines.clone()),
+                _ => panic!("expected a file preview"),
+            };
+
+            let mut app = test_app();
+            app.split_content = SplitContent::FilePreview(pinned.clone(), Vec::new());
+            app.update(key(KeyCode::Char('p'), KeyModifiers::ALT)).await.unwrap();
+            assert_eq!(app.split_pinned.as_deref(), Some(pinned.as_str()));
+            assert_eq!(shown(&app), (pinned.clone(), vec!["fn old() {}".to_string()]));
+
+            // A call on another file doesn't take over the pane
+            app.conversation.messages.push(edit("t1", "/elsewhere/main.rs"));
+            app.update_split_content_from_event(&StreamEvent::MessageStop);
+            app.update_split_content_from_event(&result("t1"));
+            assert_eq!(shown(&app).0, pinned);
+
+            // A finished edit of the pinned file shows its new content
+            std::fs::write(&pinned, "fn new() {}\n").unwrap();
+            app.conversation.messages.push(edit("t2", &pinned));
+            app.update_split_content_from_event(&StreamEvent::MessageStop);
+            app.update_split_content_from_event(&result("t2"));
+            assert_eq!(shown(&app), (pinned.clone(), vec!["fn new() {}".to_string()]));
+
+            app.update(key(KeyCode::Char('p'), KeyModifiers::ALT)).await.unwrap();
+            assert_eq!(app.split_pinned, None);
+            app.update_split_content_from_event(&StreamEvent::MessageStop);
+            assert!(matches!(app.split_content, SplitContent::DiffView(_)));
+        });
+    }
+
+    #[test]
+    fn test_welcome_shortcut_items_open_their_views() {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        rt.block_on(async {
+            let mut app = test_app();
+            app.open_welcome();
+            match app.mode {
+                AppMode::Welcome(ref state) => {
+                    assert!(state.items.iter().any(|i| i.value == "dismiss"));
+                    assert!(state.items.iter().any(|i| i.hint == "Ctrl+K"));
+                }
+                _ => panic!("expected the welcome overlay"),
+            }
+
+            for c in "palette".chars() {
+                app.update(key(KeyCode::Char(c), KeyModifiers::NONE)).await.unwrap();
+            }
+            app.update(key(KeyCode::Enter, KeyModifiers::NONE)).await.unwrap();
+            assert!(matches!(app.mode, AppMode::ActionMenu(_)));
+        });
+    }
+
+    #[test]
+    fn test_code_block_picker_lists_last_response_blocks() {
+        let mut app = test_app();
+        app.open_code_block_picker();
+        assert!(matches!(app.mode, AppMode::Normal));
+
+        app.conversation.messages.push(crate::claude::conversation::Message {
+            role: Role::Assistant,
+            timestamp: None,
+            stop_reason: None,
+            content: vec![
+                ContentBlock::Text("```rust\nfn a() {}\n```".to_string()),
+                ContentBlock::Text("and\n\n```sh\n\ncargo test\n```".to_string()),
+            ],
+        });
+        app.open_code_block_picker();
+        match app.mode {
+            AppMode::CodeBlockPicker(ref state) => {
+                assert_eq!(state.items.len(), 2);
+                assert_eq!(state.items[0].label, "1. [rust] fn a() {}");
+                assert_eq!(state.items[1].label, "2. [sh] cargo test");
+                assert_eq!(state.items[1].value, "1");
+            }
+            _ => panic!("expected the code block picker"),
+        }
+    }
+
+    #[test]
+    fn test_summarize_visible_sends_on_screen_lines() {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        rt.block_on(async {
+            let mut app = test_app();
+            let log: Vec<String> = (1..=30).map(|i| format!("log line {i}")).collect();
+            app.conversation.push_user_message(log.join("\n"));
+            app.conversation_width = 80;
+            app.visible_height = 3;
+            app.auto_scroll = false;
+            app.scroll_offset = 10;
+
+            app.update(key(KeyCode::Char('s'), KeyModifiers::ALT)).await.unwrap();
+            let Some(ContentBlock::Text(prompt)) = app.conversation.messages.last().and_then(|m| m.content.first()) else {
+                panic!("expected a summarize prompt");
+            };
+            assert!(prompt.starts_with("Summarize this output:\n\n"));
+            let shown: Vec<&str> = prompt.lines().skip(2).map(str::trim).collect();
+            assert_eq!(shown.len(), 3);
+            assert!(shown.iter().all(|l| l.starts_with("log line")));
+            assert!(!prompt.contains("log line 1\n"));
+            assert_eq!(app.conversation.messages.len(), 2);
+        });
+    }
+
+    #[test]
+    fn test_undo_clear_restores_messages() {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        rt.block_on(async {
+            let mut app = test_app();
+            app.conversation.push_user_message("first question".to_string());
+            app.clear_conversation();
+            assert!(app.conversation.messages.is_empty());
+            assert_eq!(app.toast.as_ref().unwrap().message, "Cleared — press Ctrl+Z to undo");
+
+            app.conversation.push_user_message("after clear".to_string());
+            app.update(key(KeyCode::Char('z'), KeyModifiers::CONTROL)).await.unwrap();
+            assert_eq!(app.conversation.messages.len(), 2);
+            assert!(matches!(
+                app.conversation.messages[0].content.first(),
+                Some(ContentBlock::Text(t)) if t == "first question"
+            ));
+            assert!(app.cleared.is_none());
+
+            // Once the window has passed the snapshot is gone
+            app.clear_conversation();
+            let (messages, _) = app.cleared.take().unwrap();
+            app.cleared = Some((messages, std::time::Instant::now() - CLEAR_UNDO_WINDOW));
+            app.update(Msg::Tick).await.unwrap();
+            assert!(app.cleared.is_none());
+            app.update(key(KeyCode::Char('z'), KeyModifiers::CONTROL)).await.unwrap();
+            assert!(app.conversation.messages.is_empty());
+        });
+    }
+
+    #[test]
+    fn test_alt_e_inserts_last_tool_error() {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        rt.block_on(async {
+            let mut app = test_app();
+            app.update(key(KeyCode::Char('e'), KeyModifiers::ALT)).await.unwrap();
+            assert!(app.input.is_empty());
+            assert_eq!(app.toast.as_ref().unwrap().message, "No tool errors in this conversation");
+
+            let result = |id: &str, content: &str, is_error| ContentBlock::ToolResult {
+                tool_use_id: id.to_string(),
+                content: content.to_string(),
+                is_error,
+                collapsed: false,
+            };
+            app.conversation.messages.push(crate::claude::conversation::Message {
+                role: Role::User,
+                timestamp: None,
+                stop_reason: None,
+                content: vec![
+                    result("t1", "error[E0308]: old failure", true),
+                    result("t2", "error[E0425]: cannot find value `x`\n", true),
+                    result("t3", "all good", false),
+                ],
+            });
+            app.input.set_content("Why? ");
+            app.update(key(KeyCode::Char('e'), KeyModifiers::ALT)).await.unwrap();
+            assert_eq!(app.input.content(), "Why? error[E0425]: cannot find value `x`\n");
+            assert_eq!(app.conversation.messages.len(), 1);
+        });
+    }
+
+    #[test]
+    fn test_additional_dirs_are_passed_and_listed() {
+        let dir = tempfile::tempdir().unwrap();
+        let missing = dir.path().join("gone");
+        let config = Config {
+            additional_dirs: vec![dir.path().display().to_string(), missing.display().to_string()],
+            ..Default::default()
+        };
+        let mut app = App::new(
+            config,
+            Theme::default_theme(),
+            "catppuccin-mocha".to_string(),
+            "claude".to_string(),
+            false,
+            None,
+            None,
+            None,
+            None,
+        );
+        assert!(app.toast.as_ref().unwrap().message.contains("gone"));
+        assert_eq!(app.build_spawn_options().add_dirs, vec![dir.path().to_path_buf()]);
+
+        app.open_directories();
+        let AppMode::TextViewer { ref title, ref lines, .. } = app.mode else {
+            panic!("expected the directories viewer");
+        };
+        assert_eq!(title, "Directories in Scope");
+        assert!(lines[0].ends_with("(working directory)"));
+        assert_eq!(lines[1], format!("  ✓ {}", dir.path().display()));
+        assert_eq!(lines[2], format!("  ✗ {}  (not found, skipped)", missing.display()));
+    }
+
+    #[test]
+    fn test_ctrl_shift_k_reopens_last_overlay() {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        rt.block_on(async {
+            let mut app = test_app();
+            let reopen = || key(KeyCode::Char('K'), KeyModifiers::CONTROL | KeyModifiers::SHIFT);
+            app.update(reopen()).await.unwrap();
+            assert!(matches!(app.mode, AppMode::Normal));
+            assert_eq!(app.toast.as_ref().unwrap().message, "No overlay to reopen");
+
+            app.update(key(KeyCode::Char('w'), KeyModifiers::CONTROL)).await.unwrap();
+            assert!(matches!(app.mode, AppMode::WorkflowPicker(_)));
+            app.update(key(KeyCode::Esc, KeyModifiers::NONE)).await.unwrap();
+            app.update(reopen()).await.unwrap();
+            assert!(matches!(app.mode, AppMode::WorkflowPicker(_)));
+            app.update(key(KeyCode::Esc, KeyModifiers::NONE)).await.unwrap();
+
+            // Viewers come back at the same scroll position
+            app.show_help_viewer();
+            app.update(key(KeyCode::Down, KeyModifiers::NONE)).await.unwrap();
+            app.update(key(KeyCode::Esc, KeyModifiers::NONE)).await.unwrap();
+            app.update(reopen()).await.unwrap();
+            match app.mode {
+                AppMode::TextViewer { ref title, scroll, .. } => {
+                    assert_eq!(title, "Help");
+                    assert_eq!(scroll, 1);
+                }
+                _ => panic!("expected the help viewer"),
+            }
+            app.update(key(KeyCode::Esc, KeyModifiers::NONE)).await.unwrap();
+
+            // Reachable without Ctrl+Shift: Alt+K and the palette
+            app.update(key(KeyCode::Char('k'), KeyModifiers::ALT)).await.unwrap();
+            assert!(matches!(app.mode, AppMode::TextViewer { .. }));
+            app.update(key(KeyCode::Esc, KeyModifiers::NONE)).await.unwrap();
+            app.open_action_menu();
+            let AppMode::ActionMenu(ref mut state) = app.mode else { panic!("expected the palette") };
+            let index = state.items.iter().position(|i| i.value == "reopen").unwrap();
+            state.selected = index;
+            app.update(key(KeyCode::Enter, KeyModifiers::NONE)).await.unwrap();
+            match app.mode {
+                AppMode::TextViewer { ref title, .. } => assert_eq!(title, "Help"),
+                _ => panic!("expected the help viewer"),
+            }
+        });
+    }
+
+    #[test]
+    fn test_submit_key_ctrl_enter() {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        rt.block_on(async {
+            let mut app = test_app();
+            app.config.submit_key = "ctrl+enter".to_string();
+            app.input.set_content("first");
+            app.update(key(KeyCode::Enter, KeyModifiers::NONE)).await.unwrap();
+            app.input.insert_str("second");
+            assert_eq!(app.input.content(), "first\nsecond");
+            assert!(app.conversation.messages.is_empty());
+
+            app.update(key(KeyCode::Enter, KeyModifiers::CONTROL)).await.unwrap();
+            assert!(app.input.is_empty());
+            assert_eq!(app.conversation.messages.len(), 1);
+
+            // Alt+Enter sends too, where the terminal can't report Ctrl+Enter
+            app.input.set_content("again");
+            app.update(key(KeyCode::Enter, KeyModifiers::ALT)).await.unwrap();
+            assert!(app.input.is_empty());
+            assert_eq!(app.conversation.messages.len(), 2);
+
+            // The default keeps Enter to send and Shift+Enter for a newline
+            app.config.submit_key = "enter".to_string();
+            app.input.set_content("third");
+            app.update(key(KeyCode::Enter, KeyModifiers::SHIFT)).await.unwrap();
+            assert_eq!(app.input.content(), "third\n");
+            app.update(key(KeyCode::Enter, KeyModifiers::NONE)).await.unwrap();
+            assert_eq!(app.conversation.messages.len(), 3);
+        });
+    }
+
+    #[test]
+    fn test_agent_dashboard_details_and_cancel() {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        rt.block_on(async {
+            let mut app = test_app();
+            for (id, completed) in [("t1", true), ("t2", false)] {
+                app.agent_tasks.push(AgentTask {
+                    id: id.to_string(),
+                    description: format!("Task {id}"),
+                    agent_type: "Explore".to_string(),
+                    prompt: "Find every caller\nof parse_config".to_string(),
+                    started: std::time::Instant::now(),
+                    completed,
+                    cancel_requested: false,
+                });
+            }
+            app.open_agent_dashboard();
+            app.update(key(KeyCode::Char('x'), KeyModifiers::NONE)).await.unwrap();
+            assert_eq!(app.toast.as_ref().unwrap().message, "Agent already finished");
+            assert!(!app.agent_tasks[0].cancel_requested);
+
+            app.update(key(KeyCode::Down, KeyModifiers::NONE)).await.unwrap();
+            app.update(key(KeyCode::Char('x'), KeyModifiers::NONE)).await.unwrap();
+            assert!(app.agent_tasks[1].cancel_requested);
+            assert!(matches!(app.mode, AppMode::AgentDashboard { scroll: 1 }));
+
+            app.update(key(KeyCode::Enter, KeyModifiers::NONE)).await.unwrap();
+            match app.mode {
+                AppMode::TextViewer { ref title, ref lines, .. } => {
+                    assert_eq!(title, "Agent Details");
+                    assert!(lines.contains(&"  Status:     running (marked for cancellation)".to_string()));
+                    assert_eq!(lines.last().unwrap(), "of parse_config");
+                }
+                _ => panic!("expected the agent details viewer"),
+            }
+        });
+    }
+
+    #[test]
+    fn test_review_mode_disables_input_but_scrolls() {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        rt.block_on(async {
+            let mut app = test_app();
+            app.review_mode = true;
+            app.scroll_offset = 20;
+
+            app.update(key(KeyCode::Char('x'), KeyModifiers::NONE)).await.unwrap();
+            app.update(Msg::Paste("pasted".to_string())).await.unwrap();
+            assert!(app.input.is_empty());
+
+            app.update(key(KeyCode::Up, KeyModifiers::NONE)).await.unwrap();
+            assert_eq!(app.scroll_offset, 19);
+            assert!(!app.auto_scroll);
+
+            app.update(key(KeyCode::Enter, KeyModifiers::NONE)).await.unwrap();
+            assert!(app.conversation.messages.is_empty());
+
+            // Viewers stay available
+            app.update(key(KeyCode::Char('k'), KeyModifiers::CONTROL)).await.unwrap();
+            assert!(matches!(app.mode, AppMode::ActionMenu(_)));
+        });
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_review_mode_blocks_every_send() {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        rt.block_on(async {
+            let mut app = test_app();
+            let (process, _events) = ClaudeProcess::spawn("sh -c cat>/dev/null").unwrap();
+            app.claude = Some(process);
+            app.review_mode = true;
+
+            app.start_workflow("review", "Review the diff").await.unwrap();
+            app.continue_truncated_response().await.unwrap();
+            assert!(!app.submit_text("/compact".to_string()).await.unwrap());
+            assert!(app.conversation.messages.is_empty());
+            assert!(app.pending_slash_command.is_none());
+
+            app.pending_plan = Some(PermissionRequest {
+                request_id: "req-1".to_string(),
+                tool_name: "ExitPlanMode".to_string(),
+                input: serde_json::json!({"plan": "do it"}),
+            });
+            app.answer_plan(true).await.unwrap();
+            assert!(app.pending_plan.is_some());
+            assert_eq!(app.toast.as_ref().unwrap().message, "Review mode: input disabled (Ctrl+K to exit)");
+        });
+    }
+
+    #[test]
+    fn test_file_context_enter_opens_editor() {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        rt.block_on(async {
+            let mut app = test_app();
+            app.config.editor_command = Some("ed {file}".to_string());
+            app.conversation.messages.push(crate::claude::conversation::Message {
+                role: Role::Assistant,
+                timestamp: None,
+                stop_reason: None,
+                content: vec![ContentBlock::ToolUse {
+                    id: "t1".to_string(),
+                    name: "Read".to_string(),
+                    input: r#"{"file_path":"src/main.rs"}"#.to_string(),
+                }],
+            });
+
+            app.update(key(KeyCode::Char('f'), KeyModifiers::CONTROL)).await.unwrap();
+            match app.mode {
+                AppMode::FileContext(ref state) => {
+                    assert_eq!(state.selected_value().as_deref(), Some("src/main.rs"));
+                }
+                _ => panic!("expected the file context panel"),
+            }
+
+            app.update(key(KeyCode::Enter, KeyModifiers::NONE)).await.unwrap();
+            assert_eq!(app.pending_editor, Some(vec!["ed".to_string(), "src/main.rs".to_string()]));
+        });
+    }
+
+    #[test]
+    fn test_resume_dir_picker_offers_session_directory() {
+        let mut app = test_app();
+        let dir = std::path::PathBuf::from("/work/other-project");
+        app.open_resume_dir_picker("abc-123", dir.clone());
+
+        match app.mode {
+            AppMode::ResumeDirPicker(ref state) => {
+                assert_eq!(state.selected_value().as_deref(), Some("chdir"));
+                assert!(state.items[0].label.contains("/work/other-project"));
+            }
+            _ => panic!("expected the resume directory picker"),
+        }
+        assert_eq!(app.pending_resume, Some(("abc-123".to_string(), dir.clone())));
+
+        assert_eq!(app.build_spawn_options().cwd, None);
+        app.session_cwd = Some(dir.clone());
+        assert_eq!(app.build_spawn_options().cwd, Some(dir));
+    }
+
+    #[test]
+    fn test_permission_requests_skipped_when_bypassing() {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        rt.block_on(async {
+            let mut app = test_app();
+            app.config.permission_mode = Some("bypassPermissions".to_string());
+            assert!(!app.build_spawn_options().permission_prompt_stdio);
+            app.update(permission_event("req-1")).await.unwrap();
+            assert!(matches!(app.mode, AppMode::Normal));
+            assert!(app.permission_queue.is_empty());
+        });
+    }
 }
@@ -6,13 +6,13 @@ use crate::claude::events::{ContentBlockType, Delta, StreamEvent};
 // Public types
 // ---------------------------------------------------------------------------
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Hash)]
 pub enum Role {
     User,
     Assistant,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Hash)]
 #[allow(dead_code)]
 pub enum ContentBlock {
     Text(String),
@@ -32,6 +32,8 @@ pub enum ContentBlock {
     /// Image content block (rendered as placeholder in terminal).
     Image {
         media_type: String,
+        /// Pixel size, known for images returned by tools.
+        dimensions: Option<(u32, u32)>,
     },
     /// Document content block (rendered as placeholder in terminal).
     Document {
@@ -39,10 +41,21 @@ pub enum ContentBlock {
     },
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Hash)]
 pub struct Message {
     pub role: Role,
     pub content: Vec<ContentBlock>,
+    /// When the user submitted the message or the assistant started it.
+    pub timestamp: Option<Instant>,
+    /// Why the model stopped generating (e.g. "end_turn", "max_tokens").
+    pub stop_reason: Option<String>,
+}
+
+impl Message {
+    /// Whether the response was cut off by the output token limit.
+    pub fn is_truncated(&self) -> bool {
+        self.stop_reason.as_deref() == Some("max_tokens")
+    }
 }
 
 // ---------------------------------------------------------------------------
@@ -67,8 +80,20 @@ pub struct Conversation {
     active_tool_name: Option<String>,
     /// When the current tool execution started (for elapsed time display).
     tool_start_time: Option<Instant>,
+    /// Leading messages whose tool output and thinking have been dropped
+    /// to bound memory on long sessions (see `archive`).
+    archived: usize,
+    /// How many of the most recent archived messages are rendered in full
+    /// ("load older"); the rest render as one-line summaries.
+    expanded: usize,
+    /// Bumped whenever a tool result is folded or unfolded, so renders
+    /// cached by message count and last message notice older blocks changing.
+    fold_revision: u64,
 }
 
+/// Placeholder left in place of an archived tool result's output.
+pub const ARCHIVED_OUTPUT: &str = "[output archived]";
+
 impl Conversation {
     /// Create an empty conversation.
     pub fn new() -> Self {
@@ -81,13 +106,86 @@ impl Conversation {
             block_types: Vec::new(),
             active_tool_name: None,
             tool_start_time: None,
+            archived: 0,
+            expanded: 0,
+            fold_revision: 0,
+        }
+    }
+
+    /// Collapse or expand the output of tool call `tool_use_id`, returning
+    /// whether it is now collapsed. The flag lives on the stored block, so
+    /// it survives re-renders and later events.
+    pub fn toggle_tool_result(&mut self, tool_use_id: &str) -> Option<bool> {
+        let collapsed = self
+            .messages
+            .iter_mut()
+            .flat_map(|msg| msg.content.iter_mut())
+            .find_map(|block| match block {
+                ContentBlock::ToolResult { tool_use_id: id, collapsed, .. } if id == tool_use_id => Some(collapsed),
+                _ => None,
+            })?;
+        *collapsed = !*collapsed;
+        self.fold_revision += 1;
+        Some(*collapsed)
+    }
+
+    pub fn fold_revision(&self) -> u64 {
+        self.fold_revision
+    }
+
+    /// Archive all but the last `keep` messages: their tool output and
+    /// thinking are dropped, and they render as one-line summaries until
+    /// loaded with `load_older`. Does nothing while a response streams.
+    pub fn archive(&mut self, keep: usize) {
+        if self.streaming {
+            return;
+        }
+        let target = self.messages.len().saturating_sub(keep);
+        if target <= self.archived {
+            return;
+        }
+        for msg in &mut self.messages[self.archived..target] {
+            for block in &mut msg.content {
+                match block {
+                    ContentBlock::ToolResult { content, .. } if !content.is_empty() => {
+                        *content = ARCHIVED_OUTPUT.to_string();
+                    }
+                    ContentBlock::Thinking(text) => *text = String::new(),
+                    _ => {}
+                }
+            }
+        }
+        // Keep messages the user already loaded on screen
+        if self.expanded > 0 {
+            self.expanded += target - self.archived;
         }
+        self.archived = target;
+    }
+
+    /// Render up to `count` more archived messages in full. Returns how many
+    /// were loaded.
+    pub fn load_older(&mut self, count: usize) -> usize {
+        let loaded = count.min(self.summarized());
+        self.expanded += loaded;
+        loaded
+    }
+
+    /// Number of leading messages whose output has been archived.
+    pub fn archived(&self) -> usize {
+        self.archived
+    }
+
+    /// Number of leading messages rendered as one-line summaries.
+    pub fn summarized(&self) -> usize {
+        self.archived - self.expanded
     }
 
     /// Add a user message to the conversation.
     pub fn push_user_message(&mut self, text: String) {
         self.messages.push(Message {
             role: Role::User,
+            timestamp: Some(Instant::now()),
+            stop_reason: None,
             content: vec![ContentBlock::Text(text)],
         });
     }
@@ -96,6 +194,8 @@ impl Conversation {
     pub fn push_system_message(&mut self, text: String) {
         self.messages.push(Message {
             role: Role::Assistant,
+            timestamp: Some(Instant::now()),
+            stop_reason: None,
             content: vec![ContentBlock::Text(text)],
         });
     }
@@ -106,6 +206,8 @@ impl Conversation {
             StreamEvent::MessageStart { .. } => {
                 self.messages.push(Message {
                     role: Role::Assistant,
+                    timestamp: Some(Instant::now()),
+                    stop_reason: None,
                     content: Vec::new(),
                 });
                 self.streaming = true;
@@ -141,6 +243,7 @@ impl Conversation {
                         ContentBlockType::Image { ref media_type } => {
                             msg.content.push(ContentBlock::Image {
                                 media_type: media_type.clone(),
+                                dimensions: None,
                             });
                             self.block_types.push(block_type.clone());
                         }
@@ -190,8 +293,10 @@ impl Conversation {
                 // ContentBlockDelta, so nothing extra is needed here.
             }
 
-            StreamEvent::MessageDelta { .. } => {
-                // Could extract stop_reason if needed in the future.
+            StreamEvent::MessageDelta { stop_reason, .. } => {
+                if let (Some(reason), Some(msg)) = (stop_reason, self.messages.last_mut()) {
+                    msg.stop_reason = Some(reason.clone());
+                }
             }
 
             StreamEvent::MessageStop => {
@@ -221,6 +326,8 @@ impl Conversation {
                 if !text.is_empty() && !self.had_streaming_response {
                     self.messages.push(Message {
                         role: Role::Assistant,
+                        timestamp: Some(Instant::now()),
+                        stop_reason: None,
                         content: vec![ContentBlock::Text(text.clone())],
                     });
                 }
@@ -232,6 +339,7 @@ impl Conversation {
                 tool_use_id,
                 content,
                 is_error,
+                images,
             } => {
                 self.awaiting_tool_result = false;
                 self.active_tool_name = None;
@@ -246,11 +354,17 @@ impl Conversation {
                         is_error: *is_error,
                         collapsed,
                     });
+                    // Images follow the result so they render right below it
+                    msg.content.extend(images.iter().map(|image| ContentBlock::Image {
+                        media_type: image.media_type.clone(),
+                        dimensions: image.dimensions(),
+                    }));
                 }
             }
 
             StreamEvent::SystemInit { .. }
             | StreamEvent::SystemHook { .. }
+            | StreamEvent::PermissionRequest { .. }
             | StreamEvent::Unknown(_) => {
                 // Handled by App, not conversation state.
             }
@@ -267,6 +381,33 @@ impl Conversation {
         self.awaiting_tool_result
     }
 
+    /// Whether a thinking block is the block currently being streamed.
+    pub fn is_thinking(&self) -> bool {
+        self.streaming
+            && self
+                .messages
+                .last()
+                .filter(|m| m.role == Role::Assistant)
+                .and_then(|m| m.content.last())
+                .is_some_and(|block| matches!(block, ContentBlock::Thinking(_)))
+    }
+
+    /// Whether a text block is the block currently being streamed.
+    pub fn is_streaming_text(&self) -> bool {
+        self.streaming
+            && self
+                .messages
+                .last()
+                .filter(|m| m.role == Role::Assistant)
+                .and_then(|m| m.content.last())
+                .is_some_and(|block| matches!(block, ContentBlock::Text(_)))
+    }
+
+    /// Whether the latest message is an assistant response cut off by `max_tokens`.
+    pub fn last_response_truncated(&self) -> bool {
+        !self.streaming && self.messages.last().is_some_and(|m| m.is_truncated())
+    }
+
     /// Name of the tool currently being executed (if any).
     pub fn active_tool_name(&self) -> Option<&str> {
         self.active_tool_name.as_deref()
@@ -281,7 +422,6 @@ impl Conversation {
     ///
     /// This is useful for rendering the currently-streaming response. Returns
     /// an empty string if there is no assistant message or no text block.
-    #[allow(dead_code)]
     pub fn streaming_text(&self) -> &str {
         self.messages
             .last()
@@ -475,6 +615,67 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_archive_drops_old_output_and_loads_older() {
+        let mut conv = Conversation::new();
+        for i in 0..5 {
+            conv.push_user_message(format!("question {i}"));
+            conv.messages.push(Message {
+                role: Role::Assistant,
+                timestamp: None,
+                stop_reason: None,
+                content: vec![
+                    ContentBlock::Thinking("hmm".to_string()),
+                    ContentBlock::ToolResult {
+                        tool_use_id: format!("t{i}"),
+                        content: "lots of output".to_string(),
+                        is_error: false,
+                        collapsed: false,
+                    },
+                ],
+            });
+        }
+
+        conv.archive(4);
+        assert_eq!(conv.summarized(), 6);
+        assert!(matches!(&conv.messages[1].content[0], ContentBlock::Thinking(t) if t.is_empty()));
+        assert!(matches!(&conv.messages[1].content[1], ContentBlock::ToolResult { content, .. } if content == ARCHIVED_OUTPUT));
+        assert!(matches!(&conv.messages[7].content[1], ContentBlock::ToolResult { content, .. } if content == "lots of output"));
+        assert!(matches!(&conv.messages[0].content[0], ContentBlock::Text(t) if t == "question 0"));
+
+        assert_eq!(conv.load_older(4), 4);
+        assert_eq!(conv.summarized(), 2);
+        // Archiving more keeps the loaded messages on screen
+        conv.push_user_message("question 5".to_string());
+        conv.archive(4);
+        assert_eq!(conv.summarized(), 2);
+        assert_eq!(conv.load_older(10), 2);
+        assert_eq!(conv.summarized(), 0);
+    }
+
+    #[test]
+    fn test_max_tokens_stop_reason_marks_truncated() {
+        let mut conv = Conversation::new();
+        conv.push_user_message("Write a novel".to_string());
+        conv.apply_event(&StreamEvent::MessageStart {
+            message_id: "msg_001".to_string(),
+            model: "claude-opus-4-6".to_string(),
+            usage: None,
+        });
+        conv.apply_event(&StreamEvent::MessageDelta {
+            stop_reason: Some("max_tokens".to_string()),
+            usage: None,
+        });
+        assert!(!conv.last_response_truncated(), "still streaming");
+        conv.apply_event(&StreamEvent::MessageStop);
+
+        assert_eq!(conv.messages[1].stop_reason.as_deref(), Some("max_tokens"));
+        assert!(conv.last_response_truncated());
+
+        conv.push_user_message("continue".to_string());
+        assert!(!conv.last_response_truncated());
+    }
+
     #[test]
     fn test_result_after_streaming_does_not_duplicate() {
         let mut conv = Conversation::new();
@@ -540,6 +741,7 @@ mod tests {
             tool_use_id: "toolu_abc".to_string(),
             content: "hello world\n".to_string(),
             is_error: false,
+            images: Vec::new(),
         });
 
         let msg = &conv.messages[0];
@@ -584,6 +786,7 @@ mod tests {
             tool_use_id: "toolu_long".to_string(),
             content: long_output,
             is_error: false,
+            images: Vec::new(),
         });
 
         match &conv.messages[0].content[1] {
@@ -592,6 +795,49 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_toggled_tool_result_survives_later_events() {
+        let mut conv = Conversation::new();
+        let tool_turn = |conv: &mut Conversation, id: &str| {
+            conv.apply_event(&StreamEvent::MessageStart {
+                message_id: format!("msg_{id}"),
+                model: "claude-opus-4-6".to_string(),
+                usage: None,
+            });
+            conv.apply_event(&StreamEvent::ContentBlockStart {
+                index: 0,
+                block_type: ContentBlockType::ToolUse { id: id.to_string(), name: "Bash".to_string() },
+            });
+            conv.apply_event(&StreamEvent::ContentBlockStop { index: 0 });
+            conv.apply_event(&StreamEvent::MessageStop);
+            conv.apply_event(&StreamEvent::ToolResult {
+                tool_use_id: id.to_string(),
+                content: (0..30).map(|i| format!("line {i}")).collect::<Vec<_>>().join("\n"),
+                is_error: false,
+                images: Vec::new(),
+            });
+        };
+        let collapsed = |conv: &Conversation, id: &str| {
+            conv.messages.iter().flat_map(|m| &m.content).find_map(|b| match b {
+                ContentBlock::ToolResult { tool_use_id, collapsed, .. } if tool_use_id == id => Some(*collapsed),
+                _ => None,
+            })
+        };
+
+        tool_turn(&mut conv, "toolu_1");
+        assert_eq!(conv.toggle_tool_result("toolu_1"), Some(false));
+        assert_eq!(conv.fold_revision(), 1);
+
+        // A later turn with its own long result leaves the first one expanded
+        tool_turn(&mut conv, "toolu_2");
+        conv.push_user_message("next".to_string());
+        assert_eq!(collapsed(&conv, "toolu_1"), Some(false));
+        assert_eq!(collapsed(&conv, "toolu_2"), Some(true));
+
+        assert_eq!(conv.toggle_tool_result("toolu_1"), Some(true));
+        assert_eq!(conv.toggle_tool_result("missing"), None);
+    }
+
     #[test]
     fn test_slash_command_result_creates_message() {
         let mut conv = Conversation::new();
@@ -642,6 +888,29 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_is_thinking_while_thinking_block_streams() {
+        let mut conv = Conversation::new();
+        assert!(!conv.is_thinking());
+        conv.apply_event(&StreamEvent::MessageStart {
+            message_id: "msg_001".to_string(),
+            model: "claude-opus-4-6".to_string(),
+            usage: None,
+        });
+        conv.apply_event(&StreamEvent::ContentBlockStart {
+            index: 0,
+            block_type: ContentBlockType::Thinking,
+        });
+        assert!(conv.is_thinking());
+
+        conv.apply_event(&StreamEvent::ContentBlockStop { index: 0 });
+        conv.apply_event(&StreamEvent::ContentBlockStart {
+            index: 1,
+            block_type: ContentBlockType::Text,
+        });
+        assert!(!conv.is_thinking());
+    }
+
     #[test]
     fn test_awaiting_tool_result_lifecycle() {
         let mut conv = Conversation::new();
@@ -671,6 +940,7 @@ mod tests {
             tool_use_id: "toolu_abc".to_string(),
             content: "output".to_string(),
             is_error: false,
+            images: Vec::new(),
         });
         assert!(!conv.is_awaiting_tool_result());
     }
@@ -719,7 +989,7 @@ mod tests {
         let msg = &conv.messages[0];
         assert_eq!(msg.content.len(), 1);
         match &msg.content[0] {
-            ContentBlock::Image { media_type } => assert_eq!(media_type, "image/png"),
+            ContentBlock::Image { media_type, .. } => assert_eq!(media_type, "image/png"),
             other => panic!("Expected Image, got {:?}", other),
         }
     }
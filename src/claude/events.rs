@@ -24,6 +24,8 @@ pub enum StreamEvent {
     SystemInit {
         slash_commands: Vec<String>,
         session_id: Option<String>,
+        /// MCP servers and whether they connected.
+        mcp_servers: Vec<McpServer>,
     },
     /// System hook lifecycle event (hook_started, hook_completed).
     SystemHook {
@@ -42,10 +44,44 @@ pub enum StreamEvent {
         tool_use_id: String,
         content: String,
         is_error: bool,
+        /// Images returned by the tool (e.g. browser screenshots).
+        images: Vec<ToolImage>,
+    },
+    /// A `can_use_tool` control request: the CLI asks whether a tool may run.
+    /// Only emitted when spawned with `--permission-prompt-tool stdio`.
+    PermissionRequest {
+        request_id: String,
+        tool_name: String,
+        input: serde_json::Value,
     },
     Unknown(String),
 }
 
+/// An image block from a tool result, decoded from its base64 source.
+#[derive(Clone)]
+pub struct ToolImage {
+    pub media_type: String,
+    pub data: Vec<u8>,
+}
+
+/// Summarised rather than derived: screenshots run to megabytes, which would
+/// flood the raw event log.
+impl std::fmt::Debug for ToolImage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ToolImage")
+            .field("media_type", &self.media_type)
+            .field("bytes", &self.data.len())
+            .finish()
+    }
+}
+
+impl ToolImage {
+    /// Pixel size read from the PNG, GIF or JPEG header.
+    pub fn dimensions(&self) -> Option<(u32, u32)> {
+        image_dimensions(&self.data)
+    }
+}
+
 /// A tool that was denied permission during the session.
 #[derive(Debug, Clone, Deserialize)]
 #[allow(dead_code)]
@@ -55,6 +91,21 @@ pub struct PermissionDenial {
     pub tool_use_id: String,
 }
 
+/// An MCP server reported by `system.init`, with its connection state
+/// ("connected", "failed", "pending", ...).
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct McpServer {
+    pub name: String,
+    #[serde(default)]
+    pub status: String,
+}
+
+impl McpServer {
+    pub fn is_connected(&self) -> bool {
+        self.status == "connected"
+    }
+}
+
 /// Token usage data from message events.
 #[derive(Debug, Clone, Default)]
 pub struct Usage {
@@ -98,6 +149,8 @@ struct Envelope {
     slash_commands: Option<Vec<String>>,
     /// Session ID from system.init
     session_id: Option<String>,
+    /// MCP server connection states from system.init
+    mcp_servers: Option<Vec<McpServer>>,
     /// Hook ID for system hook events
     hook_id: Option<String>,
     /// Generic message field — used by both "assistant" and "user" envelopes.
@@ -111,6 +164,10 @@ struct Envelope {
     tool_use_result: Option<serde_json::Value>,
     /// Tools that were denied permission (in result events).
     permission_denials: Option<Vec<PermissionDenial>>,
+    /// Request ID for "control_request" envelopes.
+    request_id: Option<String>,
+    /// Request payload for "control_request" envelopes.
+    request: Option<serde_json::Value>,
 }
 
 #[derive(Deserialize)]
@@ -194,6 +251,7 @@ pub fn parse_event(line: &str) -> StreamEvent {
             StreamEvent::SystemInit {
                 slash_commands: envelope.slash_commands.unwrap_or_default(),
                 session_id: envelope.session_id,
+                mcp_servers: envelope.mcp_servers.unwrap_or_default(),
             }
         }
         // System hook lifecycle events (hook_started, hook_completed)
@@ -213,6 +271,8 @@ pub fn parse_event(line: &str) -> StreamEvent {
         }
         // Tool result from tool execution — emitted as {"type":"user","message":{...}}
         "user" => parse_tool_result(&envelope, line),
+        // Permission prompt: {"type":"control_request","request_id":"...","request":{"subtype":"can_use_tool",...}}
+        "control_request" => parse_permission_request(&envelope, line),
         // Full assistant message — we use streaming events instead
         "assistant" => StreamEvent::Unknown(line.to_string()),
         // Try parsing as a raw event directly (for backwards compatibility / tests)
@@ -226,6 +286,26 @@ pub fn parse_event(line: &str) -> StreamEvent {
     }
 }
 
+/// Parse a `can_use_tool` control request into a `PermissionRequest`.
+/// Other control request subtypes are returned as `Unknown`.
+fn parse_permission_request(envelope: &Envelope, line: &str) -> StreamEvent {
+    let (Some(request_id), Some(request)) = (envelope.request_id.as_ref(), envelope.request.as_ref()) else {
+        return StreamEvent::Unknown(line.to_string());
+    };
+    if request.get("subtype").and_then(|s| s.as_str()) != Some("can_use_tool") {
+        return StreamEvent::Unknown(line.to_string());
+    }
+    StreamEvent::PermissionRequest {
+        request_id: request_id.clone(),
+        tool_name: request
+            .get("tool_name")
+            .and_then(|n| n.as_str())
+            .unwrap_or("unknown")
+            .to_string(),
+        input: request.get("input").cloned().unwrap_or(serde_json::Value::Null),
+    }
+}
+
 /// Parse a tool result from a `{"type":"user"}` envelope.
 ///
 /// The envelope carries tool execution results:
@@ -263,6 +343,10 @@ fn parse_tool_result(envelope: &Envelope, line: &str) -> StreamEvent {
             .and_then(|e| e.as_bool())
             .unwrap_or(false);
 
+        // Content is a string, or an array of text/image blocks (MCP tools)
+        let blocks = item.get("content").and_then(|c| c.as_array());
+        let images = blocks.map(|b| b.iter().filter_map(parse_tool_image).collect()).unwrap_or_default();
+
         // Prefer clean content from tool_use_result metadata
         let content = extract_clean_content(envelope)
             .or_else(|| {
@@ -270,18 +354,74 @@ fn parse_tool_result(envelope: &Envelope, line: &str) -> StreamEvent {
                     .and_then(|c| c.as_str())
                     .map(String::from)
             })
+            .or_else(|| {
+                let texts: Vec<&str> = blocks?
+                    .iter()
+                    .filter(|b| b.get("type").and_then(|t| t.as_str()) == Some("text"))
+                    .filter_map(|b| b.get("text").and_then(|t| t.as_str()))
+                    .collect();
+                Some(texts.join("\n"))
+            })
             .unwrap_or_default();
 
         return StreamEvent::ToolResult {
             tool_use_id,
             content,
             is_error,
+            images,
         };
     }
 
     StreamEvent::Unknown(line.to_string())
 }
 
+/// Decode a `{"type":"image","source":{"type":"base64",...}}` tool result block.
+fn parse_tool_image(block: &serde_json::Value) -> Option<ToolImage> {
+    use base64::Engine;
+
+    if block.get("type").and_then(|t| t.as_str()) != Some("image") {
+        return None;
+    }
+    let source = block.get("source")?;
+    let data = source.get("data").and_then(|d| d.as_str())?;
+    Some(ToolImage {
+        media_type: source
+            .get("media_type")
+            .and_then(|m| m.as_str())
+            .unwrap_or("image")
+            .to_string(),
+        data: base64::engine::general_purpose::STANDARD.decode(data).ok()?,
+    })
+}
+
+/// Width and height from a PNG, GIF or JPEG header.
+fn image_dimensions(data: &[u8]) -> Option<(u32, u32)> {
+    let be16 = |i: usize| Some(u16::from_be_bytes([*data.get(i)?, *data.get(i + 1)?]) as u32);
+    let be32 = |i: usize| Some(u32::from_be_bytes(data.get(i..i + 4)?.try_into().ok()?));
+
+    if data.starts_with(b"\x89PNG\r\n\x1a\n") {
+        // IHDR is always the first chunk
+        return Some((be32(16)?, be32(20)?));
+    }
+    if data.starts_with(b"GIF8") {
+        let le16 = |i: usize| Some(u16::from_le_bytes([*data.get(i)?, *data.get(i + 1)?]) as u32);
+        return Some((le16(6)?, le16(8)?));
+    }
+    if data.starts_with(b"\xff\xd8") {
+        // Walk the segments to the first start-of-frame marker
+        let mut i = 2;
+        while data.get(i) == Some(&0xff) {
+            let marker = *data.get(i + 1)?;
+            let is_sof = matches!(marker, 0xc0..=0xcf) && !matches!(marker, 0xc4 | 0xc8 | 0xcc);
+            if is_sof {
+                return Some((be16(i + 7)?, be16(i + 5)?));
+            }
+            i += 2 + be16(i + 2)? as usize;
+        }
+    }
+    None
+}
+
 /// Extract clean content from the `tool_use_result` metadata field.
 /// This avoids line-number prefixes present in the raw content.
 fn extract_clean_content(envelope: &Envelope) -> Option<String> {
@@ -479,9 +619,26 @@ mod tests {
             StreamEvent::SystemInit {
                 slash_commands,
                 session_id,
+                mcp_servers,
             } => {
                 assert_eq!(slash_commands, vec!["commit", "review", "brainstorm"]);
                 assert_eq!(session_id, Some("abc-123".to_string()));
+                assert!(mcp_servers.is_empty());
+            }
+            other => panic!("Expected SystemInit, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_parse_system_init_mcp_servers() {
+        let line = r#"{"type":"system","subtype":"init","session_id":"abc","mcp_servers":[{"name":"github","status":"connected"},{"name":"linear","status":"failed"}]}"#;
+        match parse_event(line) {
+            StreamEvent::SystemInit { mcp_servers, .. } => {
+                assert_eq!(mcp_servers.len(), 2);
+                assert_eq!(mcp_servers[0].name, "github");
+                assert!(mcp_servers[0].is_connected());
+                assert_eq!(mcp_servers[1].status, "failed");
+                assert!(!mcp_servers[1].is_connected());
             }
             other => panic!("Expected SystemInit, got {:?}", other),
         }
@@ -500,6 +657,25 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_parse_permission_request() {
+        let line = r#"{"type":"control_request","request_id":"req-1","request":{"subtype":"can_use_tool","tool_name":"Bash","input":{"command":"rm -rf build"}}}"#;
+        match parse_event(line) {
+            StreamEvent::PermissionRequest { request_id, tool_name, input } => {
+                assert_eq!(request_id, "req-1");
+                assert_eq!(tool_name, "Bash");
+                assert_eq!(input["command"], "rm -rf build");
+            }
+            other => panic!("Expected PermissionRequest, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_parse_other_control_request_is_unknown() {
+        let line = r#"{"type":"control_request","request_id":"req-2","request":{"subtype":"interrupt"}}"#;
+        assert!(matches!(parse_event(line), StreamEvent::Unknown(_)));
+    }
+
     #[test]
     fn test_parse_result_event() {
         let line = r#"{"type":"result","subtype":"success","result":"Hello","session_id":"abc"}"#;
@@ -588,6 +764,7 @@ mod tests {
                 tool_use_id,
                 content,
                 is_error,
+                ..
             } => {
                 assert_eq!(tool_use_id, "toolu_abc");
                 // Should use clean content from metadata
@@ -607,6 +784,7 @@ mod tests {
                 tool_use_id,
                 content,
                 is_error,
+                ..
             } => {
                 assert_eq!(tool_use_id, "toolu_def");
                 assert_eq!(content, "command output here");
@@ -625,6 +803,7 @@ mod tests {
                 tool_use_id,
                 content,
                 is_error,
+                ..
             } => {
                 assert_eq!(tool_use_id, "toolu_err");
                 assert_eq!(content, "Error: file not found");
@@ -634,6 +813,43 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_parse_tool_result_with_image_blocks() {
+        use base64::Engine;
+
+        let mut png = b"\x89PNG\r\n\x1a\n\x00\x00\x00\x0dIHDR".to_vec();
+        png.extend_from_slice(&1280u32.to_be_bytes());
+        png.extend_from_slice(&720u32.to_be_bytes());
+        let data = base64::engine::general_purpose::STANDARD.encode(&png);
+        let line = format!(
+            r#"{{"type":"user","message":{{"role":"user","content":[{{"tool_use_id":"toolu_img","type":"tool_result","content":[{{"type":"text","text":"Took a screenshot"}},{{"type":"image","source":{{"type":"base64","media_type":"image/png","data":"{data}"}}}}]}}]}},"session_id":"abc"}}"#
+        );
+        match parse_event(&line) {
+            StreamEvent::ToolResult { content, images, .. } => {
+                assert_eq!(content, "Took a screenshot");
+                assert_eq!(images.len(), 1);
+                assert_eq!(images[0].media_type, "image/png");
+                assert_eq!(images[0].dimensions(), Some((1280, 720)));
+            }
+            other => panic!("Expected ToolResult, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_image_dimensions_gif_and_jpeg() {
+        assert_eq!(image_dimensions(b"GIF89a\x40\x01\xf0\x00"), Some((320, 240)));
+        // SOI, an APP0 segment, then SOF0 with height 480 and width 640
+        let jpeg = b"\xff\xd8\xff\xe0\x00\x04ab\xff\xc0\x00\x11\x08\x01\xe0\x02\x80";
+        assert_eq!(image_dimensions(jpeg), Some((640, 480)));
+        assert_eq!(image_dimensions(b"not an image"), None);
+    }
+
+    #[test]
+    fn test_tool_image_debug_omits_bytes() {
+        let image = ToolImage { media_type: "image/png".to_string(), data: vec![7; 4096] };
+        assert_eq!(format!("{image:?}"), r#"ToolImage { media_type: "image/png", bytes: 4096 }"#);
+    }
+
     #[test]
     fn test_parse_user_event_without_tool_result_is_unknown() {
         let line = r#"{"type":"user","message":{"role":"user","content":[{"type":"text","text":"hi"}]},"session_id":"abc"}"#;
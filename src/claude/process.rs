@@ -1,3 +1,6 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+
 use anyhow::{Context, Result};
 use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
 use tokio::process::{Child, Command};
@@ -24,6 +27,17 @@ pub struct SpawnOptions {
     pub permission_mode: Option<String>,
     /// Tools to auto-allow without prompting.
     pub allowed_tools: Option<Vec<String>>,
+    /// Route permission prompts over stdio as `control_request` events
+    /// (`--permission-prompt-tool stdio`) so the UI can answer them.
+    pub permission_prompt_stdio: bool,
+    /// Directories Claude may access besides the working directory (`--add-dir`).
+    pub add_dirs: Vec<PathBuf>,
+    /// Extra arguments appended verbatim after all other flags.
+    pub extra_args: Vec<String>,
+    /// Extra environment variables for the child process.
+    pub env: HashMap<String, String>,
+    /// Working directory for the child process (defaults to ours).
+    pub cwd: Option<PathBuf>,
 }
 
 pub struct ClaudeProcess {
@@ -108,6 +122,17 @@ impl ClaudeProcess {
                 cmd.args(["--allowedTools", tool]);
             }
         }
+        if options.permission_prompt_stdio {
+            cmd.args(["--permission-prompt-tool", "stdio"]);
+        }
+        for dir in &options.add_dirs {
+            cmd.arg("--add-dir").arg(dir);
+        }
+        cmd.args(&options.extra_args);
+        cmd.envs(&options.env);
+        if let Some(ref cwd) = options.cwd {
+            cmd.current_dir(cwd);
+        }
         // Prevent "cannot run inside another Claude Code session" error
         cmd.env_remove("CLAUDECODE");
         cmd.env_remove("CLAUDE_CODE_ENTRYPOINT");
@@ -139,14 +164,38 @@ impl ClaudeProcess {
 
     /// Send a user message as a stream-json input event.
     pub async fn send_message(&mut self, text: &str) -> Result<()> {
+        self.send_message_with_images(text, &[]).await
+    }
+
+    /// Send a user message with image attachments (PNG files on disk).
+    /// Images are sent as base64 image content blocks ahead of the text.
+    pub async fn send_message_with_images(&mut self, text: &str, images: &[PathBuf]) -> Result<()> {
         let event = serde_json::json!({
             "type": "user",
             "message": {
                 "role": "user",
-                "content": text,
+                "content": user_content(text, images)?,
             },
         });
-        let mut line = serde_json::to_string(&event)?;
+        self.write_event(&event).await
+    }
+
+    /// Answer a `can_use_tool` control request. Allowing passes the tool
+    /// input through unchanged; denying reports `message` back to Claude.
+    pub async fn send_permission_response(
+        &mut self,
+        request_id: &str,
+        allow: bool,
+        input: &serde_json::Value,
+        message: &str,
+    ) -> Result<()> {
+        self.write_event(&permission_response(request_id, allow, input, message))
+            .await
+    }
+
+    /// Write one NDJSON event to claude's stdin.
+    async fn write_event(&mut self, event: &serde_json::Value) -> Result<()> {
+        let mut line = serde_json::to_string(event)?;
         line.push('\n');
         self.stdin
             .write_all(line.as_bytes())
@@ -171,6 +220,51 @@ impl ClaudeProcess {
     }
 }
 
+/// Build a `control_response` for a permission request.
+fn permission_response(
+    request_id: &str,
+    allow: bool,
+    input: &serde_json::Value,
+    message: &str,
+) -> serde_json::Value {
+    let decision = if allow {
+        serde_json::json!({ "behavior": "allow", "updatedInput": input })
+    } else {
+        serde_json::json!({ "behavior": "deny", "message": message })
+    };
+    serde_json::json!({
+        "type": "control_response",
+        "response": {
+            "subtype": "success",
+            "request_id": request_id,
+            "response": decision,
+        },
+    })
+}
+
+/// Build the `content` field of a user message. Plain text is sent as a
+/// string; with attachments it becomes an array of image and text blocks.
+fn user_content(text: &str, images: &[PathBuf]) -> Result<serde_json::Value> {
+    if images.is_empty() {
+        return Ok(serde_json::Value::String(text.to_string()));
+    }
+    let mut blocks = Vec::with_capacity(images.len() + 1);
+    for path in images {
+        blocks.push(serde_json::json!({
+            "type": "image",
+            "source": {
+                "type": "base64",
+                "media_type": crate::clipboard::PNG_MEDIA_TYPE,
+                "data": crate::clipboard::read_base64(path)?,
+            },
+        }));
+    }
+    if !text.is_empty() {
+        blocks.push(serde_json::json!({ "type": "text", "text": text }));
+    }
+    Ok(serde_json::Value::Array(blocks))
+}
+
 impl Drop for ClaudeProcess {
     fn drop(&mut self) {
         let _ = self.child.start_kill();
@@ -189,4 +283,50 @@ mod tests {
             assert!(result.is_err());
         });
     }
+
+    #[test]
+    fn test_permission_response_allow() {
+        let input = serde_json::json!({ "command": "ls" });
+        let resp = permission_response("req-1", true, &input, "");
+        assert_eq!(resp["type"], "control_response");
+        assert_eq!(resp["response"]["request_id"], "req-1");
+        assert_eq!(resp["response"]["response"]["behavior"], "allow");
+        assert_eq!(resp["response"]["response"]["updatedInput"], input);
+    }
+
+    #[test]
+    fn test_permission_response_deny() {
+        let resp = permission_response("req-2", false, &serde_json::Value::Null, "Denied by user");
+        assert_eq!(resp["response"]["response"]["behavior"], "deny");
+        assert_eq!(resp["response"]["response"]["message"], "Denied by user");
+    }
+
+    #[test]
+    fn test_user_content_plain_text() {
+        let content = user_content("hello", &[]).unwrap();
+        assert_eq!(content, serde_json::json!("hello"));
+    }
+
+    #[test]
+    fn test_user_content_with_image() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("paste.png");
+        std::fs::write(&path, b"abc").unwrap();
+        let content = user_content("what is this?", &[path]).unwrap();
+        let blocks = content.as_array().unwrap();
+        assert_eq!(blocks.len(), 2);
+        assert_eq!(blocks[0]["type"], "image");
+        assert_eq!(blocks[0]["source"]["media_type"], "image/png");
+        assert_eq!(blocks[0]["source"]["data"], "YWJj");
+        assert_eq!(blocks[1]["text"], "what is this?");
+    }
+
+    #[test]
+    fn test_user_content_image_only() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("paste.png");
+        std::fs::write(&path, b"abc").unwrap();
+        let content = user_content("", &[path]).unwrap();
+        assert_eq!(content.as_array().unwrap().len(), 1);
+    }
 }
@@ -17,16 +17,20 @@ impl SessionInfo {
             .last_modified
             .elapsed()
             .unwrap_or(std::time::Duration::ZERO);
-        let secs = elapsed.as_secs();
-        if secs < 60 {
-            "just now".to_string()
-        } else if secs < 3600 {
-            format!("{}m ago", secs / 60)
-        } else if secs < 86400 {
-            format!("{}h ago", secs / 3600)
-        } else {
-            format!("{}d ago", secs / 86400)
-        }
+        format_age(elapsed.as_secs())
+    }
+}
+
+/// Format an age in seconds as "just now", "5m ago", "2h ago" or "3d ago".
+pub fn format_age(secs: u64) -> String {
+    if secs < 60 {
+        "just now".to_string()
+    } else if secs < 3600 {
+        format!("{}m ago", secs / 60)
+    } else if secs < 86400 {
+        format!("{}h ago", secs / 3600)
+    } else {
+        format!("{}d ago", secs / 86400)
     }
 }
 
@@ -62,6 +66,19 @@ pub fn discover_sessions() -> Vec<SessionInfo> {
     sessions
 }
 
+/// Whether `id` is a complete session UUID rather than a prefix of one.
+pub fn is_full_id(id: &str) -> bool {
+    id.len() == 36 && id.chars().filter(|&c| c == '-').count() == 4
+}
+
+/// Sessions whose ID starts with `prefix`, keeping their order.
+pub fn match_prefix(sessions: Vec<SessionInfo>, prefix: &str) -> Vec<SessionInfo> {
+    sessions
+        .into_iter()
+        .filter(|s| s.session_id.starts_with(prefix))
+        .collect()
+}
+
 /// Scan a single project directory for session JSONL files.
 fn scan_project_sessions(
     project_dir: &PathBuf,
@@ -110,23 +127,8 @@ fn extract_preview(path: &PathBuf) -> String {
     for line in content.lines().take(20) {
         if let Ok(value) = serde_json::from_str::<serde_json::Value>(line) {
             if value.get("type").and_then(|t| t.as_str()) == Some("user") {
-                if let Some(message) = value.get("message") {
-                    // Content can be a string or array of content blocks
-                    if let Some(content) = message.get("content") {
-                        if let Some(text) = content.as_str() {
-                            return truncate_preview(text);
-                        }
-                        if let Some(arr) = content.as_array() {
-                            for block in arr {
-                                if block.get("type").and_then(|t| t.as_str()) == Some("text") {
-                                    if let Some(text) = block.get("text").and_then(|t| t.as_str())
-                                    {
-                                        return truncate_preview(text);
-                                    }
-                                }
-                            }
-                        }
-                    }
+                if let Some(text) = message_text(&value) {
+                    return truncate_preview(text);
                 }
             }
         }
@@ -135,6 +137,122 @@ fn extract_preview(path: &PathBuf) -> String {
     String::new()
 }
 
+/// First text of a session entry's message. Content can be a string or an
+/// array of content blocks.
+fn message_text(value: &serde_json::Value) -> Option<&str> {
+    let content = value.get("message")?.get("content")?;
+    if let Some(text) = content.as_str() {
+        return Some(text);
+    }
+    content.as_array()?.iter().find_map(|block| {
+        if block.get("type").and_then(|t| t.as_str()) == Some("text") {
+            block.get("text").and_then(|t| t.as_str())
+        } else {
+            None
+        }
+    })
+}
+
+/// The first `max_messages` user/assistant messages of a session, one
+/// "You: ..." / "Claude: ..." line each, for previewing in the session picker.
+pub fn transcript_preview(session_id: &str, max_messages: usize) -> Vec<String> {
+    session_file(session_id)
+        .map(|path| read_transcript_preview(&path, max_messages))
+        .unwrap_or_default()
+}
+
+/// The full text of every assistant reply in a session, in order, for
+/// comparing two sessions.
+pub fn assistant_replies(session_id: &str) -> Vec<String> {
+    session_file(session_id)
+        .map(|path| read_assistant_replies(&path))
+        .unwrap_or_default()
+}
+
+/// The working directory a session was created in, as recorded in its transcript.
+pub fn session_cwd(session_id: &str) -> Option<PathBuf> {
+    session_file(session_id).and_then(|path| read_session_cwd(&path))
+}
+
+/// Locate `<session_id>.jsonl` under any project in `~/.claude/projects`.
+fn session_file(session_id: &str) -> Option<PathBuf> {
+    let home = dirs::home_dir()?;
+    let entries = std::fs::read_dir(home.join(".claude/projects")).ok()?;
+    entries
+        .flatten()
+        .map(|entry| entry.path().join(format!("{session_id}.jsonl")))
+        .find(|path| path.is_file())
+}
+
+fn read_session_cwd(path: &PathBuf) -> Option<PathBuf> {
+    use std::io::BufRead;
+
+    let file = std::fs::File::open(path).ok()?;
+    std::io::BufReader::new(file)
+        .lines()
+        .map_while(Result::ok)
+        .take(50)
+        .filter_map(|line| serde_json::from_str::<serde_json::Value>(&line).ok())
+        .find_map(|value| value.get("cwd")?.as_str().map(PathBuf::from))
+}
+
+fn read_transcript_preview(path: &PathBuf, max_messages: usize) -> Vec<String> {
+    use std::io::BufRead;
+
+    let Ok(file) = std::fs::File::open(path) else {
+        return Vec::new();
+    };
+    let mut lines = Vec::new();
+    for line in std::io::BufReader::new(file).lines().map_while(Result::ok).take(200) {
+        let Ok(value) = serde_json::from_str::<serde_json::Value>(&line) else {
+            continue;
+        };
+        let speaker = match value.get("type").and_then(|t| t.as_str()) {
+            Some("user") => "You",
+            Some("assistant") => "Claude",
+            _ => continue,
+        };
+        if let Some(text) = message_text(&value).map(str::trim).filter(|t| !t.is_empty()) {
+            lines.push(format!("{speaker}: {}", truncate_preview(text)));
+            if lines.len() >= max_messages {
+                break;
+            }
+        }
+    }
+    lines
+}
+
+fn read_assistant_replies(path: &PathBuf) -> Vec<String> {
+    use std::io::BufRead;
+
+    let Ok(file) = std::fs::File::open(path) else {
+        return Vec::new();
+    };
+    let mut replies = Vec::new();
+    for line in std::io::BufReader::new(file).lines().map_while(Result::ok) {
+        let Ok(value) = serde_json::from_str::<serde_json::Value>(&line) else {
+            continue;
+        };
+        if value.get("type").and_then(|t| t.as_str()) != Some("assistant") {
+            continue;
+        }
+        // Unlike the preview, keep every text block, not just the first
+        let Some(blocks) = value.get("message").and_then(|m| m.get("content")).and_then(|c| c.as_array()) else {
+            continue;
+        };
+        let text: Vec<&str> = blocks
+            .iter()
+            .filter(|block| block.get("type").and_then(|t| t.as_str()) == Some("text"))
+            .filter_map(|block| block.get("text").and_then(|t| t.as_str()))
+            .collect();
+        let text = text.join("\n").trim().to_string();
+        if !text.is_empty() {
+            replies.push(text);
+        }
+    }
+    replies
+}
+
 /// Truncate preview text to a reasonable length.
 fn truncate_preview(text: &str) -> String {
     let first_line = text.lines().next().unwrap_or(text);
@@ -191,6 +309,21 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_read_session_cwd() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("s.jsonl");
+        std::fs::write(
+            &path,
+            "{\"type\":\"summary\"}\n{\"type\":\"user\",\"cwd\":\"/work/app\",\"message\":{\"content\":\"hi\"}}\n",
+        )
+        .unwrap();
+        assert_eq!(read_session_cwd(&path), Some(PathBuf::from("/work/app")));
+
+        std::fs::write(&path, "{\"type\":\"summary\"}\n").unwrap();
+        assert_eq!(read_session_cwd(&path), None);
+    }
+
     #[test]
     fn test_slug_to_path() {
         assert_eq!(
@@ -204,6 +337,14 @@ mod tests {
         assert_eq!(slug_to_path("-Users-magnuspladsen"), "Users/magnuspladsen");
     }
 
+    #[test]
+    fn test_format_age() {
+        assert_eq!(format_age(59), "just now");
+        assert_eq!(format_age(60), "1m ago");
+        assert_eq!(format_age(2 * 3600), "2h ago");
+        assert_eq!(format_age(3 * 86400), "3d ago");
+    }
+
     #[test]
     fn test_age_string_just_now() {
         let info = SessionInfo {
@@ -215,6 +356,24 @@ mod tests {
         assert_eq!(info.age_string(), "just now");
     }
 
+    #[test]
+    fn test_match_prefix() {
+        let session = |id: &str| SessionInfo {
+            session_id: id.to_string(),
+            project_path: "test".to_string(),
+            last_modified: SystemTime::now(),
+            preview: String::new(),
+        };
+        let all = || vec![session("abc123-1"), session("abd456-2"), session("abc789-3")];
+        let ids = |matches: Vec<SessionInfo>| matches.into_iter().map(|s| s.session_id).collect::<Vec<_>>();
+        assert_eq!(ids(match_prefix(all(), "abc1")), vec!["abc123-1"]);
+        assert_eq!(ids(match_prefix(all(), "abc")), vec!["abc123-1", "abc789-3"]);
+        assert!(match_prefix(all(), "zzz").is_empty());
+
+        assert!(is_full_id("0f3c9a4e-5b1d-4c2e-9a7f-1e2d3c4b5a69"));
+        assert!(!is_full_id("0f3c9a4e"));
+    }
+
     #[test]
     fn test_discover_sessions_no_crash() {
         // Should not crash even if ~/.claude doesn't exist
@@ -247,6 +406,47 @@ mod tests {
         assert_eq!(extract_preview(&path.to_path_buf()), "Array content");
     }
 
+    #[test]
+    fn test_read_transcript_preview() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("test.jsonl");
+        std::fs::write(
+            &path,
+            [
+                r#"{"type":"summary","summary":"x"}"#,
+                r#"{"type":"user","message":{"role":"user","content":"Fix the bug"}}"#,
+                r#"{"type":"assistant","message":{"role":"assistant","content":[{"type":"text","text":"Looking now."}]}}"#,
+                r#"{"type":"user","message":{"role":"user","content":[{"type":"tool_result","content":"ok"}]}}"#,
+                r#"{"type":"assistant","message":{"role":"assistant","content":[{"type":"text","text":"Done."}]}}"#,
+            ]
+            .join("\n"),
+        )
+        .unwrap();
+        assert_eq!(
+            read_transcript_preview(&path, 2),
+            vec!["You: Fix the bug", "Claude: Looking now."]
+        );
+        assert_eq!(read_transcript_preview(&path, 10).len(), 3);
+    }
+
+    #[test]
+    fn test_read_assistant_replies() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("test.jsonl");
+        std::fs::write(
+            &path,
+            [
+                r#"{"type":"user","message":{"role":"user","content":"Fix the bug"}}"#,
+                r#"{"type":"assistant","message":{"role":"assistant","content":[{"type":"text","text":"Looking now."},{"type":"tool_use","id":"t1","name":"Read","input":{}},{"type":"text","text":"Found it."}]}}"#,
+                r#"{"type":"assistant","message":{"role":"assistant","content":[{"type":"tool_use","id":"t2","name":"Edit","input":{}}]}}"#,
+                r#"{"type":"assistant","message":{"role":"assistant","content":[{"type":"text","text":"Done."}]}}"#,
+            ]
+            .join("\n"),
+        )
+        .unwrap();
+        assert_eq!(read_assistant_replies(&path), vec!["Looking now.\nFound it.", "Done."]);
+    }
+
     #[test]
     fn test_extract_preview_empty_file() {
         let dir = tempfile::tempdir().unwrap();
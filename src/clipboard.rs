@@ -0,0 +1,79 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use base64::Engine;
+
+/// Media type of images written by [`paste_image`].
+pub const PNG_MEDIA_TYPE: &str = "image/png";
+
+/// Read an image from the system clipboard, encode it as PNG and write it
+/// to `paste-{index}.png` in `dir` (created if needed). Returns the path of
+/// the written file.
+///
+/// Fails if the clipboard is unavailable or does not currently hold an image.
+pub fn paste_image(dir: &Path, index: usize) -> Result<PathBuf> {
+    let mut clipboard = arboard::Clipboard::new().context("Clipboard unavailable")?;
+    let image = clipboard.get_image().context("No image in clipboard")?;
+    let png = encode_png(image.width as u32, image.height as u32, &image.bytes)?;
+
+    std::fs::create_dir_all(dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+    let path = dir.join(format!("paste-{index}.png"));
+    std::fs::write(&path, png)
+        .with_context(|| format!("Failed to write {}", path.display()))?;
+    Ok(path)
+}
+
+/// Put `text` on the system clipboard.
+pub fn copy_text(text: &str) -> Result<()> {
+    let mut clipboard = arboard::Clipboard::new().context("Clipboard unavailable")?;
+    clipboard
+        .set_text(text.to_string())
+        .context("Failed to write to clipboard")
+}
+
+/// Encode raw RGBA8 pixel data as a PNG.
+pub fn encode_png(width: u32, height: u32, rgba: &[u8]) -> Result<Vec<u8>> {
+    let mut out = Vec::new();
+    {
+        let mut encoder = png::Encoder::new(&mut out, width, height);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header().context("Failed to encode PNG")?;
+        writer
+            .write_image_data(rgba)
+            .context("Failed to encode PNG")?;
+    }
+    Ok(out)
+}
+
+/// Read an image file and return its contents base64-encoded.
+pub fn read_base64(path: &Path) -> Result<String> {
+    let bytes =
+        std::fs::read(path).with_context(|| format!("Failed to read {}", path.display()))?;
+    Ok(base64::engine::general_purpose::STANDARD.encode(bytes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode_png_signature() {
+        let rgba = vec![255u8; 2 * 2 * 4];
+        let png = encode_png(2, 2, &rgba).unwrap();
+        assert_eq!(&png[..8], b"\x89PNG\r\n\x1a\n");
+    }
+
+    #[test]
+    fn test_encode_png_rejects_short_buffer() {
+        assert!(encode_png(4, 4, &[0u8; 3]).is_err());
+    }
+
+    #[test]
+    fn test_read_base64() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("img.png");
+        std::fs::write(&path, b"abc").unwrap();
+        assert_eq!(read_base64(&path).unwrap(), "YWJj");
+    }
+}
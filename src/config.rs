@@ -1,12 +1,23 @@
 use anyhow::{Context, Result};
 use serde::Deserialize;
+use std::collections::HashMap;
 use std::path::PathBuf;
 
+use crate::termbg::Background;
+
 #[derive(Debug, Deserialize)]
 #[serde(default)]
 pub struct Config {
     pub command: String,
+    /// Theme name, or "auto" to pick `theme_dark`/`theme_light` from the
+    /// terminal's background colour.
     pub theme: String,
+    /// Theme used by `theme = "auto"` on dark terminals, and when the
+    /// background can't be detected.
+    pub theme_dark: String,
+    /// Theme used by `theme = "auto"` on light terminals.
+    pub theme_light: String,
+    /// Frame rate while streaming or animating; idle screens tick at 2fps.
     pub fps: u32,
     pub layout: LayoutConfig,
     /// Claude model to use (e.g. "claude-sonnet-4-5-20250929").
@@ -21,8 +32,105 @@ pub struct Config {
     pub permission_mode: Option<String>,
     /// Tools to auto-allow (e.g. ["Bash", "Read", "Write"]).
     pub allowed_tools: Option<Vec<String>>,
+    /// Command used to open `file:line` references, with `{file}` and `{line}`
+    /// placeholders (e.g. "code -g {file}:{line}"). Defaults to `$EDITOR +{line} {file}`.
+    pub editor_command: Option<String>,
+    /// Directories Claude may access besides the working directory, passed as
+    /// `--add-dir` (extended by the `--add-dir` flag). Missing ones are skipped.
+    pub additional_dirs: Vec<String>,
+    /// Extra arguments appended verbatim to the claude command (e.g. ["--strict-mcp-config"]).
+    pub extra_args: Vec<String>,
+    /// Extra environment variables set on the claude process.
+    pub env: HashMap<String, String>,
+    /// Bash command fragments that trigger a destructive-command warning
+    /// in the permission prompt (e.g. "rm -rf", "git reset --hard").
+    pub danger_patterns: Vec<String>,
+    /// Keep every non-JSON/unrecognised line from claude's stdout and print
+    /// them to stderr on exit (set by `--verbose-unknown`).
+    pub verbose_unknown: bool,
+    /// Maximum bytes of each `@file` mention sent to Claude; larger files are truncated.
+    pub max_mention_bytes: usize,
+    /// Show a relative timestamp ("2m ago") next to each message's role label.
+    pub show_timestamps: bool,
+    /// Show Claude's thinking blocks. When false each is reduced to a single
+    /// "reasoning hidden" line; toggle at runtime with Ctrl+B.
+    pub show_thinking: bool,
+    /// UI style: "full" (animated header, decorated status bar) or "minimal"
+    /// (plain one-line header and status; set by `--minimal`).
+    pub ui: String,
+    /// Scroll behaviour: "follow" re-engages auto-scroll when you scroll back to
+    /// the bottom; "manual" keeps the view pinned until End or the auto-scroll toggle.
+    pub auto_scroll: String,
+    /// Which lines a collapsed tool result shows: "head" (first lines), "tail"
+    /// (last lines), "both" (first and last), or "auto" (head for successful
+    /// results, both ends for errors, whose cause is usually at the bottom).
+    pub tool_preview: String,
+    /// Mark each tool call with a per-tool emoji (📖 Read, 💻 Bash, ...).
+    /// Turn off for fonts without emoji to get a plain `>`.
+    pub use_tool_glyphs: bool,
+    /// Maximum characters of the argument shown in a tool call header. Long
+    /// Bash commands keep their program name and tail (`git ... --force origin main`).
+    pub tool_header_width: usize,
+    /// Blank columns on each side of the conversation, for breathing room on
+    /// wide terminals. Shrinks automatically so text keeps at least 40 columns.
+    pub conversation_padding: usize,
+    /// Start in read-only review mode with the input box disabled (set by `--review`).
+    pub review: bool,
+    /// Tools whose calls switch the split pane to show them ("Edit" diffs,
+    /// "Read"/"Write" previews, "Grep"/"Glob" matches). Empty = never auto-switch.
+    pub split_auto_tools: Vec<String>,
+    /// Set once the first-run welcome overlay has been dismissed for good.
+    /// A config file without the key counts as welcomed: the overlay is for
+    /// people starting from scratch, not ones upgrading.
+    pub welcomed: bool,
+    /// Messages kept in full; older ones drop their tool output and render as
+    /// one-line summaries (Ctrl+L loads more). The dropped output is gone
+    /// for good, so this is opt-in: 0 (the default) keeps everything.
+    pub max_rendered_messages: usize,
+    /// Markdown shown as the first message on every launch (e.g. a compliance
+    /// notice or team tips), given inline or as the path to a `.md` file.
+    pub startup_banner: Option<String>,
+    /// Key that sends the message: "enter" (Shift+Enter inserts a newline,
+    /// which needs `/terminal-setup` on some terminals) or "ctrl+enter"
+    /// (Enter inserts a newline, as in many chat apps; Alt+Enter also sends).
+    pub submit_key: String,
+    /// Resend the last prompt when a turn fails with a transient network
+    /// error (connection reset, timeout, overloaded API).
+    pub auto_retry: bool,
+    /// How many times `auto_retry` resends a prompt before giving up.
+    pub max_retries: u32,
+    /// Briefly tint newly arrived messages, fading out over about a second
+    /// (shown when you scroll back down to them). Turn off to avoid the motion.
+    pub highlight_new_messages: bool,
+    /// Show the time of day and how long the session has run in the status bar.
+    pub show_clock: bool,
+    /// Named chains of prompts and commands, run from the command palette.
+    /// Each step is sent once the previous one's turn has finished.
+    pub macros: HashMap<String, Vec<String>>,
+    /// The file this config was loaded from (or would be created at); where
+    /// theme changes and the welcome screen write back to.
+    #[serde(skip)]
+    pub path: PathBuf,
 }
 
+/// Environment variable naming the config file to use, ahead of the XDG
+/// and home directory locations.
+const CONFIG_ENV: &str = "SEXY_CLAUDE_CONFIG";
+
+/// Default `danger_patterns`.
+const DEFAULT_DANGER_PATTERNS: &[&str] = &[
+    "rm -rf",
+    "rm -fr",
+    "git reset --hard",
+    "git clean -f",
+    "git push --force",
+    "git push -f",
+    "dd if=",
+    "mkfs",
+    ":(){ :|:& };:",
+    "> /dev/sd",
+];
+
 #[derive(Debug, Deserialize)]
 #[serde(default)]
 pub struct LayoutConfig {
@@ -34,6 +142,8 @@ impl Default for Config {
         Self {
             command: "claude".to_string(),
             theme: "catppuccin-mocha".to_string(),
+            theme_dark: "catppuccin-mocha".to_string(),
+            theme_light: "catppuccin-latte".to_string(),
             fps: 30,
             layout: LayoutConfig::default(),
             model: None,
@@ -42,6 +152,33 @@ impl Default for Config {
             mcp_config: None,
             permission_mode: None,
             allowed_tools: None,
+            editor_command: None,
+            additional_dirs: Vec::new(),
+            extra_args: Vec::new(),
+            env: HashMap::new(),
+            danger_patterns: DEFAULT_DANGER_PATTERNS.iter().map(|p| p.to_string()).collect(),
+            verbose_unknown: false,
+            max_mention_bytes: 100_000,
+            show_timestamps: false,
+            show_thinking: true,
+            ui: "full".to_string(),
+            auto_scroll: "follow".to_string(),
+            tool_preview: "auto".to_string(),
+            use_tool_glyphs: true,
+            tool_header_width: 60,
+            conversation_padding: 0,
+            review: false,
+            split_auto_tools: ["Edit", "Read", "Write"].iter().map(|t| t.to_string()).collect(),
+            welcomed: false,
+            max_rendered_messages: 0,
+            startup_banner: None,
+            submit_key: "enter".to_string(),
+            auto_retry: false,
+            max_retries: 2,
+            highlight_new_messages: true,
+            show_clock: true,
+            macros: HashMap::new(),
+            path: Self::default_path(),
         }
     }
 }
@@ -62,24 +199,77 @@ impl Config {
         };
 
         if !config_path.exists() {
-            return Ok(Self::default());
+            // Remember the path so writing a default config or a theme
+            // creates the file that was asked for
+            return Ok(Self { path: config_path, ..Self::default() });
         }
 
         let content = std::fs::read_to_string(&config_path)
             .with_context(|| format!("Failed to read config from {}", config_path.display()))?;
 
-        let config: Config =
+        let table: toml::Table =
             toml::from_str(&content).with_context(|| "Failed to parse config TOML")?;
+        let has_welcomed = table.contains_key("welcomed");
+        let mut config: Config = table.try_into().with_context(|| "Failed to parse config TOML")?;
+        if !has_welcomed {
+            config.welcomed = true;
+        }
 
         config.validate()?;
+        config.path = config_path;
         Ok(config)
     }
 
+    /// The config file used without `--config`: `$SEXY_CLAUDE_CONFIG`, then
+    /// `$XDG_CONFIG_HOME/sexy-claude/config.toml`, then
+    /// `~/.config/sexy-claude/config.toml`.
     pub fn default_path() -> PathBuf {
-        dirs::config_dir()
-            .unwrap_or_else(|| PathBuf::from("~/.config"))
-            .join("sexy-claude")
-            .join("config.toml")
+        resolve_path(std::env::var_os(CONFIG_ENV).map(PathBuf::from), config_dir())
+    }
+
+    /// The first danger pattern contained in `command`, if any.
+    /// Runs of whitespace are collapsed before matching.
+    pub fn danger_pattern(&self, command: &str) -> Option<&str> {
+        let normalize = |s: &str| s.split_whitespace().collect::<Vec<_>>().join(" ");
+        let command = normalize(command);
+        self.danger_patterns
+            .iter()
+            .find(|p| {
+                let p = normalize(p);
+                !p.is_empty() && command.contains(&p)
+            })
+            .map(|p| p.as_str())
+    }
+
+    /// `additional_dirs` split into the directories that exist and those
+    /// that don't. `~` is expanded and relative paths are resolved against
+    /// `base`, the directory Claude runs in (the process cwd when `None`).
+    pub fn partition_additional_dirs(&self, base: Option<&std::path::Path>) -> (Vec<PathBuf>, Vec<PathBuf>) {
+        self.additional_dirs
+            .iter()
+            .map(|dir| {
+                let dir = expand_home(dir.trim());
+                match base {
+                    Some(base) if dir.is_relative() => base.join(dir),
+                    _ => dir,
+                }
+            })
+            .partition(|dir| dir.is_dir())
+    }
+
+    /// The `startup_banner` markdown: the contents of the file it names, or
+    /// the value itself when it isn't a readable file.
+    pub fn startup_banner_text(&self) -> Option<String> {
+        let banner = self.startup_banner.as_deref()?.trim();
+        if banner.is_empty() {
+            return None;
+        }
+        let path = expand_home(banner);
+        let text = match std::fs::read_to_string(&path) {
+            Ok(content) if path.is_file() => content,
+            _ => banner.to_string(),
+        };
+        Some(text)
     }
 
     fn validate(&self) -> Result<()> {
@@ -88,13 +278,124 @@ impl Config {
             self.layout.claude_pane_percent >= 20 && self.layout.claude_pane_percent <= 100,
             "claude_pane_percent must be between 20 and 100"
         );
+        anyhow::ensure!(self.max_mention_bytes > 0, "max_mention_bytes must be greater than 0");
+        anyhow::ensure!(
+            matches!(self.ui.as_str(), "full" | "minimal"),
+            "ui must be \"full\" or \"minimal\""
+        );
+        anyhow::ensure!(
+            matches!(self.auto_scroll.as_str(), "follow" | "manual"),
+            "auto_scroll must be \"follow\" or \"manual\""
+        );
+        anyhow::ensure!(
+            matches!(self.tool_preview.as_str(), "auto" | "head" | "tail" | "both"),
+            "tool_preview must be \"auto\", \"head\", \"tail\" or \"both\""
+        );
+        anyhow::ensure!(
+            matches!(self.submit_key.as_str(), "enter" | "ctrl+enter"),
+            "submit_key must be \"enter\" or \"ctrl+enter\""
+        );
+        anyhow::ensure!(self.max_retries <= 10, "max_retries must be at most 10");
+        for (name, steps) in &self.macros {
+            anyhow::ensure!(
+                !steps.is_empty() && steps.iter().all(|step| !step.trim().is_empty()),
+                "macro \"{name}\" must have at least one step, and no empty ones"
+            );
+        }
+        anyhow::ensure!(self.tool_header_width >= 20, "tool_header_width must be at least 20");
+        anyhow::ensure!(self.conversation_padding <= 40, "conversation_padding must be at most 40");
+        anyhow::ensure!(
+            self.theme_dark != "auto" && self.theme_light != "auto",
+            "theme_dark and theme_light must name a theme, not \"auto\""
+        );
         Ok(())
     }
+
+    /// Resolve a theme name, turning "auto" into `theme_light` or `theme_dark`
+    /// according to `background` (dark when it couldn't be detected).
+    pub fn resolve_theme(&self, name: &str, background: impl FnOnce() -> Option<Background>) -> String {
+        if name != "auto" {
+            return name.to_string();
+        }
+        match background() {
+            Some(Background::Light) => self.theme_light.clone(),
+            Some(Background::Dark) | None => self.theme_dark.clone(),
+        }
+    }
+
+    /// Whether scrolling back to the bottom re-engages auto-scroll.
+    pub fn follow_on_scroll(&self) -> bool {
+        self.auto_scroll == "follow"
+    }
+
+    /// Whether the stripped-down UI (no header animation or status decorations) is enabled.
+    pub fn minimal_ui(&self) -> bool {
+        self.ui == "minimal"
+    }
+}
+
+/// Config file written by the first-run welcome, with the defaults spelled out.
+const DEFAULT_CONFIG: &str = r#"# sexy-claude configuration
+welcomed = true
+
+command = "claude"
+theme = "catppuccin-mocha"
+fps = 30
+
+# model = "claude-sonnet-4-5-20250929"
+# effort = "medium"
+# permission_mode = "default"
+# allowed_tools = ["Bash", "Read"]
+# additional_dirs = ["../shared-lib"]
+# editor_command = "code -g {file}:{line}"
+# show_timestamps = true
+# show_thinking = false
+# tool_preview = "both"
+# use_tool_glyphs = false
+# tool_header_width = 100
+# conversation_padding = 4
+# ui = "minimal"
+# max_rendered_messages = 200
+# startup_banner = "~/.config/sexy-claude/banner.md"
+# submit_key = "ctrl+enter"
+# auto_retry = true
+# max_retries = 2
+# highlight_new_messages = false
+# show_clock = false
+
+[layout]
+claude_pane_percent = 70
+
+# [macros]
+# review-and-test = ["/review", "Write tests for the issues you found"]
+"#;
+
+/// Write a commented default config file to `path`, creating parent dirs.
+/// Fails if a config file already exists there.
+pub fn write_default(path: &std::path::Path) -> Result<()> {
+    anyhow::ensure!(!path.exists(), "{} already exists", path.display());
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create config directory {}", parent.display()))?;
+    }
+    std::fs::write(path, DEFAULT_CONFIG)
+        .with_context(|| format!("Failed to write config to {}", path.display()))
+}
+
+/// Record in the config file that the welcome overlay was dismissed.
+/// Preserves all other config values.
+pub fn save_welcomed(path: &std::path::Path) -> Result<()> {
+    save_value("welcomed", toml::Value::Boolean(true), path)
 }
 
 /// Save the selected theme name to the config file.
 /// Preserves all other config values. Creates the file and parent dirs if needed.
 pub fn save_theme(theme_name: &str, path: &std::path::Path) -> Result<()> {
+    save_value("theme", toml::Value::String(theme_name.to_string()), path)
+}
+
+/// Set a single top-level `key` in the config file, preserving everything else.
+fn save_value(key: &str, value: toml::Value, path: &std::path::Path) -> Result<()> {
     use std::collections::BTreeMap;
 
     // Read existing config as a generic TOML table (preserves unknown fields)
@@ -106,10 +407,7 @@ pub fn save_theme(theme_name: &str, path: &std::path::Path) -> Result<()> {
         BTreeMap::new()
     };
 
-    table.insert(
-        "theme".to_string(),
-        toml::Value::String(theme_name.to_string()),
-    );
+    table.insert(key.to_string(), value);
 
     if let Some(parent) = path.parent() {
         std::fs::create_dir_all(parent)
@@ -124,6 +422,45 @@ pub fn save_theme(theme_name: &str, path: &std::path::Path) -> Result<()> {
     Ok(())
 }
 
+/// Directory holding the config, history, snippets, workflows and themes:
+/// `$XDG_CONFIG_HOME/sexy-claude`, else `~/.config/sexy-claude`.
+pub fn config_dir() -> PathBuf {
+    let dir = resolve_dir(std::env::var_os("XDG_CONFIG_HOME").map(PathBuf::from), dirs::home_dir());
+    // Files written by older versions to the platform config dir
+    // (~/Library/Application Support on macOS) keep being found
+    if !dir.exists() {
+        if let Some(legacy) = dirs::config_dir().map(|d| d.join("sexy-claude")).filter(|d| d.exists()) {
+            return legacy;
+        }
+    }
+    dir
+}
+
+/// The app directory under `XDG_CONFIG_HOME`, or under `~/.config` when it
+/// is unset or (against the spec) relative.
+fn resolve_dir(xdg_config_home: Option<PathBuf>, home: Option<PathBuf>) -> PathBuf {
+    xdg_config_home
+        .filter(|dir| dir.is_absolute())
+        .unwrap_or_else(|| home.unwrap_or_else(|| PathBuf::from("~")).join(".config"))
+        .join("sexy-claude")
+}
+
+/// The config file: the `SEXY_CLAUDE_CONFIG` value unless unset or empty,
+/// else `config.toml` in `dir`.
+fn resolve_path(env_path: Option<PathBuf>, dir: PathBuf) -> PathBuf {
+    env_path.filter(|p| !p.as_os_str().is_empty()).unwrap_or_else(|| dir.join("config.toml"))
+}
+
+/// `path` with a leading `~` replaced by the home directory.
+fn expand_home(path: &str) -> PathBuf {
+    match path.strip_prefix('~') {
+        Some(rest) if rest.is_empty() || rest.starts_with('/') => {
+            dirs::home_dir().unwrap_or_default().join(rest.trim_start_matches('/'))
+        }
+        _ => PathBuf::from(path),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -191,6 +528,166 @@ mod tests {
         assert_eq!(config.mcp_config.as_deref(), Some("~/.claude/mcp.json"));
     }
 
+    #[test]
+    fn test_editor_command_config() {
+        let toml = r#"editor_command = "code -g {file}:{line}""#;
+        let config: Config = toml::from_str(toml).unwrap();
+        assert_eq!(config.editor_command.as_deref(), Some("code -g {file}:{line}"));
+    }
+
+    #[test]
+    fn test_extra_args_and_env_config() {
+        let toml = r#"
+            extra_args = ["--add-dir", "../shared"]
+
+            [env]
+            ANTHROPIC_LOG = "debug"
+        "#;
+        let config: Config = toml::from_str(toml).unwrap();
+        assert_eq!(config.extra_args, vec!["--add-dir", "../shared"]);
+        assert_eq!(config.env.get("ANTHROPIC_LOG").map(String::as_str), Some("debug"));
+    }
+
+    #[test]
+    fn test_startup_banner_inline_or_file() {
+        assert_eq!(Config::default().startup_banner_text(), None);
+        let inline = Config { startup_banner: Some("**No secrets** in prompts".to_string()), ..Default::default() };
+        assert_eq!(inline.startup_banner_text().as_deref(), Some("**No secrets** in prompts"));
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("banner.md");
+        std::fs::write(&path, "# Tips\n\nPress Ctrl+K").unwrap();
+        let file = Config { startup_banner: Some(path.display().to_string()), ..Default::default() };
+        assert_eq!(file.startup_banner_text().as_deref(), Some("# Tips\n\nPress Ctrl+K"));
+    }
+
+    #[test]
+    fn test_additional_dirs_partition() {
+        let dir = tempfile::tempdir().unwrap();
+        let existing = dir.path().display().to_string();
+        let missing = dir.path().join("gone").display().to_string();
+        let config: Config = toml::from_str(&format!("additional_dirs = [{existing:?}, {missing:?}]")).unwrap();
+        let (found, not_found) = config.partition_additional_dirs(None);
+        assert_eq!(found, vec![PathBuf::from(existing)]);
+        assert_eq!(not_found, vec![PathBuf::from(missing)]);
+        assert!(Config::default().additional_dirs.is_empty());
+
+        // Relative paths belong to the session directory, `~` to home
+        std::fs::create_dir(dir.path().join("docs")).unwrap();
+        let config: Config = toml::from_str(r#"additional_dirs = ["docs", "~"]"#).unwrap();
+        let (found, not_found) = config.partition_additional_dirs(Some(dir.path()));
+        assert_eq!(found, vec![dir.path().join("docs"), dirs::home_dir().unwrap()]);
+        assert!(not_found.is_empty());
+    }
+
+    #[test]
+    fn test_danger_pattern_matching() {
+        let config = Config::default();
+        assert_eq!(config.danger_pattern("rm  -rf ./target"), Some("rm -rf"));
+        assert_eq!(config.danger_pattern("cd repo && git reset --hard"), Some("git reset --hard"));
+        assert_eq!(config.danger_pattern("cargo test"), None);
+
+        let config: Config = toml::from_str(r#"danger_patterns = ["DROP TABLE"]"#).unwrap();
+        assert_eq!(config.danger_pattern("psql -c 'DROP TABLE users'"), Some("DROP TABLE"));
+        assert_eq!(config.danger_pattern("rm -rf /"), None);
+    }
+
+    #[test]
+    fn test_resolve_auto_theme() {
+        let config = Config::default();
+        assert_eq!(config.resolve_theme("nord", || panic!("only auto detects")), "nord");
+        assert_eq!(config.resolve_theme("auto", || Some(Background::Light)), "catppuccin-latte");
+        assert_eq!(config.resolve_theme("auto", || Some(Background::Dark)), "catppuccin-mocha");
+
+        let config: Config = toml::from_str("theme = \"auto\"\ntheme_dark = \"nord\"").unwrap();
+        assert_eq!(config.resolve_theme(&config.theme, || None), "nord");
+
+        let config: Config = toml::from_str("theme_light = \"auto\"").unwrap();
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_max_mention_bytes() {
+        assert_eq!(Config::default().max_mention_bytes, 100_000);
+        let config: Config = toml::from_str("max_mention_bytes = 0").unwrap();
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_ui_mode() {
+        assert!(!Config::default().minimal_ui());
+        let config: Config = toml::from_str(r#"ui = "minimal""#).unwrap();
+        assert!(config.minimal_ui());
+        assert!(config.validate().is_ok());
+        let config: Config = toml::from_str(r#"ui = "fancy""#).unwrap();
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_tool_preview_validation() {
+        assert_eq!(Config::default().tool_preview, "auto");
+        let config: Config = toml::from_str(r#"tool_preview = "tail""#).unwrap();
+        assert!(config.validate().is_ok());
+        let config: Config = toml::from_str(r#"tool_preview = "middle""#).unwrap();
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_tool_header_width_validation() {
+        assert_eq!(Config::default().tool_header_width, 60);
+        let config: Config = toml::from_str("tool_header_width = 120").unwrap();
+        assert!(config.validate().is_ok());
+        let config: Config = toml::from_str("tool_header_width = 5").unwrap();
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_conversation_padding_validation() {
+        assert_eq!(Config::default().conversation_padding, 0);
+        let config: Config = toml::from_str("conversation_padding = 8").unwrap();
+        assert!(config.validate().is_ok());
+        let config: Config = toml::from_str("conversation_padding = 100").unwrap();
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_auto_scroll_mode() {
+        assert!(Config::default().follow_on_scroll());
+        let config: Config = toml::from_str(r#"auto_scroll = "manual""#).unwrap();
+        assert!(!config.follow_on_scroll());
+        assert!(config.validate().is_ok());
+        let config: Config = toml::from_str(r#"auto_scroll = "sometimes""#).unwrap();
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_split_auto_tools() {
+        assert_eq!(Config::default().split_auto_tools, vec!["Edit", "Read", "Write"]);
+        let config: Config = toml::from_str("split_auto_tools = []").unwrap();
+        assert!(config.split_auto_tools.is_empty());
+    }
+
+    #[test]
+    fn test_show_timestamps() {
+        assert!(!Config::default().show_timestamps);
+        let config: Config = toml::from_str("show_timestamps = true").unwrap();
+        assert!(config.show_timestamps);
+    }
+
+    #[test]
+    fn test_show_thinking() {
+        assert!(Config::default().show_thinking);
+        let config: Config = toml::from_str("show_thinking = false").unwrap();
+        assert!(!config.show_thinking);
+    }
+
+    #[test]
+    fn test_extra_args_and_env_default_empty() {
+        let config = Config::default();
+        assert!(config.extra_args.is_empty());
+        assert!(config.env.is_empty());
+    }
+
     #[test]
     fn test_permission_config() {
         let toml = r#"
@@ -225,12 +722,49 @@ mod tests {
         assert!(config.validate().is_err());
     }
 
+    #[test]
+    fn test_validation_macros() {
+        let mut config = Config::default();
+        config.macros.insert("empty".to_string(), Vec::new());
+        assert!(config.validate().is_err());
+        config.macros.insert("empty".to_string(), vec!["go".to_string(), " ".to_string()]);
+        assert!(config.validate().is_err());
+        config.macros.insert("empty".to_string(), vec!["go".to_string()]);
+        assert!(config.validate().is_ok());
+    }
+
     #[test]
     fn test_load_nonexistent_returns_default() {
         let config = Config::load(Some(&PathBuf::from("/nonexistent/config.toml"))).unwrap();
         assert_eq!(config.command, "claude");
     }
 
+    #[test]
+    fn test_load_remembers_path() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.toml");
+        // Also before the file exists, so it is created there
+        assert_eq!(Config::load(Some(&path)).unwrap().path, path);
+        std::fs::write(&path, "fps = 60\n").unwrap();
+        assert_eq!(Config::load(Some(&path)).unwrap().path, path);
+    }
+
+    #[test]
+    fn test_resolve_path_order() {
+        let home = Some(PathBuf::from("/home/me"));
+        let xdg_dir = resolve_dir(Some(PathBuf::from("/xdg")), home.clone());
+        assert_eq!(xdg_dir, PathBuf::from("/xdg/sexy-claude"));
+        // Unset or relative XDG_CONFIG_HOME falls back to ~/.config
+        let fallback = PathBuf::from("/home/me/.config/sexy-claude");
+        assert_eq!(resolve_dir(None, home.clone()), fallback);
+        assert_eq!(resolve_dir(Some(PathBuf::from("rel")), home), fallback);
+
+        let env = Some(PathBuf::from("/ci/sc.toml"));
+        assert_eq!(resolve_path(env, xdg_dir.clone()), PathBuf::from("/ci/sc.toml"));
+        assert_eq!(resolve_path(None, xdg_dir.clone()), PathBuf::from("/xdg/sexy-claude/config.toml"));
+        assert_eq!(resolve_path(Some(PathBuf::new()), xdg_dir), PathBuf::from("/xdg/sexy-claude/config.toml"));
+    }
+
     #[test]
     fn test_save_theme_creates_file() {
         let dir = tempfile::tempdir().unwrap();
@@ -262,4 +796,29 @@ mod tests {
         assert!(content.contains("theme = \"nord\""));
         assert!(content.contains("fps = 45"));
     }
+
+    #[test]
+    fn test_write_default_config_parses_and_is_welcomed() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("sexy-claude").join("config.toml");
+        write_default(&path).unwrap();
+        let config = Config::load(Some(&path)).unwrap();
+        assert!(config.welcomed);
+        assert_eq!(config.theme, "catppuccin-mocha");
+        assert!(write_default(&path).is_err());
+    }
+
+    #[test]
+    fn test_save_welcomed_preserves_other_fields() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.toml");
+        std::fs::write(&path, "fps = 45\n").unwrap();
+        assert!(Config::load(Some(&path)).unwrap().welcomed, "existing file without the key");
+        std::fs::write(&path, "welcomed = false\nfps = 45\n").unwrap();
+        assert!(!Config::load(Some(&path)).unwrap().welcomed);
+        save_welcomed(&path).unwrap();
+        let config = Config::load(Some(&path)).unwrap();
+        assert!(config.welcomed);
+        assert_eq!(config.fps, 45);
+    }
 }
@@ -40,6 +40,21 @@ pub fn pricing_for_model(model: &str) -> ModelPricing {
     }
 }
 
+/// Default context window size in tokens for current Claude models.
+pub const DEFAULT_CONTEXT_WINDOW: u64 = 200_000;
+
+/// Look up the context window size (in tokens) for a model name.
+/// Models selected with the `[1m]` suffix use the extended 1M-token window.
+pub fn context_window_for_model(model: &str) -> u64 {
+    let name = model.to_lowercase();
+    if name.ends_with("[1m]") {
+        1_000_000
+    } else {
+        // Opus, Sonnet and Haiku all ship with a 200k window
+        DEFAULT_CONTEXT_WINDOW
+    }
+}
+
 /// Extract a short display name from a full model identifier.
 /// e.g. "claude-sonnet-4-5-20250929" -> "sonnet 4.5"
 pub fn short_model_name(model: &str) -> String {
@@ -149,6 +164,14 @@ mod tests {
         assert_eq!(format_cost(150.0), "$150");
     }
 
+    #[test]
+    fn test_context_window_for_model() {
+        assert_eq!(context_window_for_model("claude-opus-4-6"), 200_000);
+        assert_eq!(context_window_for_model("claude-haiku-4-5-20251001"), 200_000);
+        assert_eq!(context_window_for_model("claude-sonnet-4-5-20250929[1m]"), 1_000_000);
+        assert_eq!(context_window_for_model("unknown"), DEFAULT_CONTEXT_WINDOW);
+    }
+
     #[test]
     fn test_short_model_name() {
         assert_eq!(short_model_name("claude-opus-4-6"), "opus");
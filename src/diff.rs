@@ -151,6 +151,298 @@ pub fn with_context<'a>(ops: &'a [DiffOp<'a>], context: usize) -> Vec<&'a DiffOp
         .collect()
 }
 
+/// Number of unchanged lines shown around each hunk in a patch.
+const PATCH_CONTEXT: usize = 3;
+
+/// A change made to a file by a tool call, in the order it was applied.
+#[derive(Debug, Clone, PartialEq)]
+pub enum FileEdit<'a> {
+    /// `old` replaced by `new` (every occurrence when `all` is set). `lines`
+    /// are the 0-based lines the change may have started on.
+    Replace { old: &'a str, new: &'a str, all: bool, lines: std::ops::Range<usize> },
+    /// The whole file written with this content; `created` when the file
+    /// didn't exist before.
+    Write { content: &'a str, created: bool },
+}
+
+/// `FileEdit::Replace::lines` for a change whose position isn't known.
+pub const ANY_LINE: std::ops::Range<usize> = 0..usize::MAX;
+
+/// Recover a file's content from before `edits` by undoing them, newest
+/// first, against its `current` content. Returns `(content, is_new)`, where
+/// `is_new` means the file was written from scratch and had no prior content.
+/// Returns `None` when an edit can't be undone (the file changed since) or
+/// a Write replaced content that nothing earlier accounts for.
+pub fn original_content(current: &str, edits: &[FileEdit<'_>]) -> Option<(String, bool)> {
+    // Unknown once a Write over an existing file is undone; only an earlier
+    // Write that created the file can still give the original
+    let mut text = Some(current.to_string());
+    for edit in edits.iter().rev() {
+        match edit {
+            FileEdit::Write { created: true, .. } => return Some((String::new(), true)),
+            FileEdit::Write { created: false, .. } => text = None,
+            FileEdit::Replace { old, new, all, lines } => {
+                let current = text.as_deref()?;
+                text = Some(if new.is_empty() {
+                    undo_deletion(current, old, *all, lines.clone())?
+                } else if !current.contains(new) {
+                    return None;
+                } else if *all {
+                    current.replace(new, old)
+                } else {
+                    current.replacen(new, old, 1)
+                });
+            }
+        }
+    }
+    text.map(|text| (text, false))
+}
+
+/// Put back `old`, which an edit starting on one of `lines` deleted from
+/// `text`. A deletion leaves nothing to search for, so every spot on those
+/// lines is tried (only line starts or ends when `old` ends or starts with a
+/// newline) and the undo succeeds when exactly one result has `old` once, as
+/// the edit required.
+fn undo_deletion(text: &str, old: &str, all: bool, lines: std::ops::Range<usize>) -> Option<String> {
+    if all || old.is_empty() {
+        return None;
+    }
+    let starts: Vec<usize> = std::iter::once(0)
+        .chain(text.match_indices('\n').map(|(i, _)| i + 1))
+        .collect();
+    let mut found: Option<String> = None;
+    for line in lines.start..lines.end.min(starts.len()) {
+        let start = starts[line];
+        let end = starts.get(line + 1).map_or(text.len(), |next| next - 1);
+        let spots: Vec<usize> = if old.ends_with('\n') {
+            vec![start]
+        } else if old.starts_with('\n') {
+            vec![end]
+        } else {
+            (start..=end).filter(|&i| text.is_char_boundary(i)).collect()
+        };
+        for at in spots {
+            let candidate = format!("{}{old}{}", &text[..at], &text[at..]);
+            if candidate.matches(old).count() != 1 {
+                continue;
+            }
+            if found.as_ref().is_some_and(|f| *f != candidate) {
+                return None;
+            }
+            found = Some(candidate);
+        }
+    }
+    found
+}
+
+/// Apply `edits` in order to `original`, returning the content after each
+/// one. Returns `None` when an edit's `old` text isn't in the content it
+/// was applied to.
+pub fn replay_edits(original: &str, edits: &[FileEdit<'_>]) -> Option<Vec<String>> {
+    let mut text = original.to_string();
+    edits
+        .iter()
+        .map(|edit| {
+            text = match edit {
+                FileEdit::Write { content, .. } => content.to_string(),
+                FileEdit::Replace { old, new, all, .. } => {
+                    if !text.contains(old) {
+                        return None;
+                    }
+                    if *all { text.replace(old, new) } else { text.replacen(old, new, 1) }
+                }
+            };
+            Some(text.clone())
+        })
+        .collect()
+}
+
+/// Line diff of two whole files. Only the region between their common
+/// prefix and suffix is run through the LCS, which keeps the table small
+/// for large files with local edits.
+fn diff_files<'a>(old_lines: &[&'a str], new_lines: &[&'a str]) -> Vec<DiffOp<'a>> {
+    let prefix = old_lines
+        .iter()
+        .zip(new_lines)
+        .take_while(|(a, b)| a == b)
+        .count();
+    let suffix = old_lines[prefix..]
+        .iter()
+        .rev()
+        .zip(new_lines[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let old_mid = &old_lines[prefix..old_lines.len() - suffix];
+    let new_mid = &new_lines[prefix..new_lines.len() - suffix];
+    let mut ops: Vec<DiffOp<'_>> = old_lines[..prefix].iter().map(|l| DiffOp::Equal(l)).collect();
+    ops.extend(build_diff(old_mid, new_mid, &lcs_table(old_mid, new_mid)));
+    ops.extend(old_lines[old_lines.len() - suffix..].iter().map(|l| DiffOp::Equal(l)));
+    ops
+}
+
+/// Largest LCS table (differing old lines × differing new lines) that
+/// [`diff_texts_bounded`] builds.
+pub const MAX_DIFF_CELLS: usize = 4_000_000;
+
+/// Line diff of two whole texts with their common start and end trimmed
+/// before comparing, or `None` when the lines in between are too many to
+/// compare (see [`MAX_DIFF_CELLS`]).
+pub fn diff_texts_bounded<'a>(old: &'a str, new: &'a str) -> Option<Vec<DiffOp<'a>>> {
+    let old_lines: Vec<&str> = old.lines().collect();
+    let new_lines: Vec<&str> = new.lines().collect();
+    let prefix = old_lines.iter().zip(&new_lines).take_while(|(a, b)| a == b).count();
+    let suffix = old_lines[prefix..]
+        .iter()
+        .rev()
+        .zip(new_lines[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let cells = (old_lines.len() - prefix - suffix).saturating_mul(new_lines.len() - prefix - suffix);
+    (cells <= MAX_DIFF_CELLS).then(|| diff_files(&old_lines, &new_lines))
+}
+
+/// Lines added and removed going from `old` to `new`.
+pub fn change_counts(old: &str, new: &str) -> (usize, usize) {
+    let old_lines: Vec<&str> = old.lines().collect();
+    let new_lines: Vec<&str> = new.lines().collect();
+    let ops = diff_files(&old_lines, &new_lines);
+    let added = ops.iter().filter(|op| matches!(op, DiffOp::Add(_))).count();
+    let removed = ops.iter().filter(|op| matches!(op, DiffOp::Remove(_))).count();
+    (added, removed)
+}
+
+/// The change from `old` to `new` (whole files) as diff viewer hunks, see
+/// [`format_hunks`].
+pub fn format_file_hunks(old: &str, new: &str, context: usize) -> String {
+    let old_lines: Vec<&str> = old.lines().collect();
+    let new_lines: Vec<&str> = new.lines().collect();
+    format_hunks(&diff_files(&old_lines, &new_lines), context, Some(0))
+}
+
+/// Format the change from `old` to `new` for `path` as a git-style patch
+/// (`diff --git` header plus `@@` hunks with real line numbers) that
+/// `git apply` accepts. `path` should be relative to the repository root.
+/// Returns an empty string when the contents are identical.
+pub fn format_patch(path: &str, old: &str, new: &str, is_new: bool) -> String {
+    if old == new && !is_new {
+        return String::new();
+    }
+    let old_lines: Vec<&str> = old.lines().collect();
+    let new_lines: Vec<&str> = new.lines().collect();
+    let mut ops = diff_files(&old_lines, &new_lines);
+
+    // A last line that only differs by its trailing newline is a change
+    let old_eol = old.is_empty() || old.ends_with('\n');
+    let new_eol = new.is_empty() || new.ends_with('\n');
+    if old_eol != new_eol {
+        if let Some(DiffOp::Equal(line)) = ops.last().cloned() {
+            ops.pop();
+            ops.push(DiffOp::Remove(line));
+            ops.push(DiffOp::Add(line));
+        }
+    }
+
+    let mut out = format!("diff --git a/{path} b/{path}\n");
+    if is_new {
+        out.push_str(&format!("new file mode 100644\n--- /dev/null\n+++ b/{path}\n"));
+    } else {
+        out.push_str(&format!("--- a/{path}\n+++ b/{path}\n"));
+    }
+
+    for (start, end) in hunk_ranges(&ops, PATCH_CONTEXT) {
+        out.push_str(&hunk_header(&ops, start, end, 0));
+        out.push('\n');
+
+        let mut old_seen = ops[..start].iter().filter(|op| !matches!(op, DiffOp::Add(_))).count();
+        let mut new_seen = ops[..start].iter().filter(|op| !matches!(op, DiffOp::Remove(_))).count();
+        for op in &ops[start..end] {
+            let (prefix, line) = match op {
+                DiffOp::Equal(line) => (' ', line),
+                DiffOp::Remove(line) => ('-', line),
+                DiffOp::Add(line) => ('+', line),
+            };
+            out.push(prefix);
+            out.push_str(line);
+            out.push('\n');
+            let ends_old = !matches!(op, DiffOp::Add(_)) && {
+                old_seen += 1;
+                old_seen == old_lines.len() && !old_eol
+            };
+            let ends_new = !matches!(op, DiffOp::Remove(_)) && {
+                new_seen += 1;
+                new_seen == new_lines.len() && !new_eol
+            };
+            if ends_old || ends_new {
+                out.push_str("\\ No newline at end of file\n");
+            }
+        }
+    }
+    out
+}
+
+/// Format `ops` for the diff viewer: `@@ -a,b +c,d @@` hunk headers followed
+/// by `"  "`, `"- "` and `"+ "` prefixed lines, with `context` unchanged lines
+/// around each change. `first_line` is the 0-based line in the file where
+/// the first op sits, so the headers carry real file line numbers; when it
+/// is unknown the headers only give line counts and say so.
+pub fn format_hunks(ops: &[DiffOp<'_>], context: usize, first_line: Option<usize>) -> String {
+    let mut out = String::new();
+    for (start, end) in hunk_ranges(ops, context) {
+        match first_line {
+            Some(first_line) => out.push_str(&hunk_header(ops, start, end, first_line)),
+            None => {
+                let old_count = ops[start..end].iter().filter(|op| !matches!(op, DiffOp::Add(_))).count();
+                let new_count = ops[start..end].iter().filter(|op| !matches!(op, DiffOp::Remove(_))).count();
+                out.push_str(&format!("@@ -?,{old_count} +?,{new_count} @@ (not found in the current file)"));
+            }
+        }
+        out.push('\n');
+        out.push_str(&format_unified(&ops[start..end]));
+    }
+    out
+}
+
+/// 0-based line on which `snippet` starts in `content`, if it occurs there.
+pub fn snippet_line(content: &str, snippet: &str) -> Option<usize> {
+    if snippet.is_empty() {
+        return None;
+    }
+    content
+        .find(snippet)
+        .map(|pos| content[..pos].matches('\n').count())
+}
+
+/// The `@@ -a,b +c,d @@` header for the hunk `ops[start..end]`, where the
+/// first op is on 0-based line `first_line` of both files.
+fn hunk_header(ops: &[DiffOp<'_>], start: usize, end: usize, first_line: usize) -> String {
+    // Line numbers (1-based) of the first line of the hunk on each side
+    let old_before = first_line + ops[..start].iter().filter(|op| !matches!(op, DiffOp::Add(_))).count();
+    let new_before = first_line + ops[..start].iter().filter(|op| !matches!(op, DiffOp::Remove(_))).count();
+    let old_count = ops[start..end].iter().filter(|op| !matches!(op, DiffOp::Add(_))).count();
+    let new_count = ops[start..end].iter().filter(|op| !matches!(op, DiffOp::Remove(_))).count();
+    let old_start = if old_count == 0 { old_before } else { old_before + 1 };
+    let new_start = if new_count == 0 { new_before } else { new_before + 1 };
+    format!("@@ -{old_start},{old_count} +{new_start},{new_count} @@")
+}
+
+/// Group changed ops into hunk ranges `[start, end)` with `context`
+/// unchanged lines around each change, merging hunks that touch.
+fn hunk_ranges(ops: &[DiffOp<'_>], context: usize) -> Vec<(usize, usize)> {
+    let mut ranges: Vec<(usize, usize)> = Vec::new();
+    for (i, op) in ops.iter().enumerate() {
+        if matches!(op, DiffOp::Equal(_)) {
+            continue;
+        }
+        let start = i.saturating_sub(context);
+        let end = (i + context + 1).min(ops.len());
+        match ranges.last_mut() {
+            Some(last) if start <= last.1 => last.1 = end,
+            _ => ranges.push((start, end)),
+        }
+    }
+    ranges
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -302,4 +594,122 @@ mod tests {
         let adds: Vec<_> = ops.iter().filter(|o| matches!(o, DiffOp::Add(_))).collect();
         assert!(!adds.is_empty());
     }
+
+    #[test]
+    fn test_format_patch_hunk_line_numbers() {
+        let old = "1\n2\n3\n4\n5\n6\n7\n8\n9\n10\n";
+        let new = "1\n2\n3\n4\n5\nsix\n7\n8\n9\n10\n";
+        let patch = format_patch("src/n.txt", old, new, false);
+        assert_eq!(
+            patch,
+            "diff --git a/src/n.txt b/src/n.txt\n\
+             --- a/src/n.txt\n\
+             +++ b/src/n.txt\n\
+             @@ -3,7 +3,7 @@\n 3\n 4\n 5\n-6\n+six\n 7\n 8\n 9\n"
+        );
+    }
+
+    #[test]
+    fn test_format_hunks_offsets_line_numbers() {
+        let ops = diff_lines("a\nb\nc", "a\nB\nc");
+        let out = format_hunks(&ops, 3, Some(9));
+        assert_eq!(out, "@@ -10,3 +10,3 @@\n  a\n- b\n+ B\n  c\n");
+        let out = format_hunks(&ops, 3, None);
+        assert_eq!(out, "@@ -?,3 +?,3 @@ (not found in the current file)\n  a\n- b\n+ B\n  c\n");
+    }
+
+    #[test]
+    fn test_format_hunks_limits_context() {
+        let old: String = (1..=10).map(|i| format!("{i}\n")).collect();
+        let new = old.replace("9\n", "nine\n");
+        let ops = diff_lines(&old, &new);
+        let out = format_hunks(&ops, 1, Some(0));
+        assert_eq!(out, "@@ -8,3 +8,3 @@\n  8\n- 9\n+ nine\n  10\n");
+    }
+
+    #[test]
+    fn test_snippet_line() {
+        assert_eq!(snippet_line("one\ntwo\nthree\n", "three"), Some(2));
+        assert_eq!(snippet_line("one\ntwo\n", "one"), Some(0));
+        assert_eq!(snippet_line("one\n", "missing"), None);
+        assert_eq!(snippet_line("one\n", ""), None);
+    }
+
+    #[test]
+    fn test_format_patch_separate_hunks() {
+        let old: String = (1..=20).map(|i| format!("{i}\n")).collect();
+        let new: String = (1..=20)
+            .filter(|&i| i != 19)
+            .map(|i| if i == 2 { "two\n".to_string() } else { format!("{i}\n") })
+            .collect();
+        let patch = format_patch("a.txt", &old, &new, false);
+        assert!(patch.contains("@@ -1,5 +1,5 @@\n"));
+        assert!(patch.contains("@@ -16,5 +16,4 @@\n"));
+    }
+
+    #[test]
+    fn test_format_patch_new_file_and_missing_newline() {
+        let patch = format_patch("new.rs", "", "fn main() {}", true);
+        assert_eq!(
+            patch,
+            "diff --git a/new.rs b/new.rs\nnew file mode 100644\n--- /dev/null\n+++ b/new.rs\n\
+             @@ -0,0 +1,1 @@\n+fn main() {}\n\\ No newline at end of file\n"
+        );
+        assert_eq!(format_patch("same.rs", "x\n", "x\n", false), "");
+    }
+
+    #[test]
+    fn test_original_content_undoes_edits() {
+        let replace = |old, new, all| FileEdit::Replace { old, new, all, lines: ANY_LINE };
+        let edits = [replace("a", "b", false), replace("b c", "d", false)];
+        assert_eq!(original_content("x d", &edits), Some(("x a c".to_string(), false)));
+        assert_eq!(original_content("gone", &edits), None);
+        let created = FileEdit::Write { content: "1 1", created: true };
+        let written = [created.clone(), replace("1", "2", true)];
+        assert_eq!(original_content("2 2", &written), Some((String::new(), true)));
+
+        // A Write over an existing file hides what was there before it
+        let overwrite = FileEdit::Write { content: "1 1", created: false };
+        assert_eq!(original_content("2 2", &[overwrite.clone(), replace("1", "2", true)]), None);
+        assert_eq!(original_content("1 1", &[replace("0", "1", true), overwrite.clone()]), None);
+        assert_eq!(original_content("1 1", &[created, overwrite]), Some((String::new(), true)));
+    }
+
+    #[test]
+    fn test_original_content_undoes_deletions() {
+        let text = "use a;\nuse b;\nfn main() {}\n";
+        let delete = |old, lines| FileEdit::Replace { old, new: "", all: false, lines };
+        // Whole lines go back at the start of the line the edit reported
+        assert_eq!(
+            original_content(text, &[delete("use c;\n", 1..2)]),
+            Some(("use a;\nuse c;\nuse b;\nfn main() {}\n".to_string(), false))
+        );
+        // Without a position only an unambiguous spot will do
+        assert_eq!(original_content(text, &[delete("use c;\n", ANY_LINE)]), None);
+        assert_eq!(original_content("f(a)\n", &[delete(", b", 0..1)]), None);
+        // Putting it back must leave it unique, as the edit needed
+        assert_eq!(original_content("f(a);\n", &[delete("f(a);\n", 0..5)]), None);
+        assert_eq!(
+            original_content("fn main() {}\n", &[delete("\n// done", 0..1)]),
+            Some(("fn main() {}\n// done\n".to_string(), false))
+        );
+    }
+
+    #[test]
+    fn test_replay_edits_and_file_hunks() {
+        let edits = [
+            FileEdit::Write { content: "a\nb\nc\n", created: true },
+            FileEdit::Replace { old: "b", new: "B\nB2", all: false, lines: ANY_LINE },
+            FileEdit::Replace { old: "c", new: "C", all: false, lines: ANY_LINE },
+        ];
+        let states = replay_edits("", &edits).unwrap();
+        assert_eq!(states, ["a\nb\nc\n", "a\nB\nB2\nc\n", "a\nB\nB2\nC\n"]);
+        assert_eq!(change_counts(&states[0], &states[1]), (2, 1));
+        assert_eq!(replay_edits("x", &edits[1..]), None);
+
+        assert_eq!(
+            format_file_hunks("a\nb\nc\n", &states[2], 1),
+            "@@ -1,3 +1,4 @@\n  a\n- b\n- c\n+ B\n+ B2\n+ C\n"
+        );
+    }
 }
@@ -0,0 +1,248 @@
+use std::ops::Range;
+
+/// A `path:line` reference found in assistant text or tool output.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileRef {
+    pub path: String,
+    pub line: usize,
+}
+
+impl FileRef {
+    pub fn display(&self) -> String {
+        format!("{}:{}", self.path, self.line)
+    }
+}
+
+/// Characters allowed in the path part of a reference.
+fn is_path_char(c: char) -> bool {
+    c.is_alphanumeric() || matches!(c, '/' | '.' | '_' | '-' | '~' | '+' | '@')
+}
+
+/// Whether `path` looks like a file: its last component needs an extension
+/// starting with a letter (rules out `localhost:8080`, `127.0.0.1:80`, etc).
+fn looks_like_file(path: &str) -> bool {
+    let name = path.rsplit('/').next().unwrap_or(path);
+    match name.rsplit_once('.') {
+        Some((stem, ext)) => {
+            !stem.is_empty() && ext.chars().next().is_some_and(|c| c.is_ascii_alphabetic())
+        }
+        None => false,
+    }
+}
+
+/// Find `path:line` and `path:line:col` references in `text`.
+/// Returns the byte range of each reference along with the parsed location.
+pub fn find_file_refs(text: &str) -> Vec<(Range<usize>, FileRef)> {
+    let mut refs = Vec::new();
+    let mut search_from = 0;
+
+    while let Some(offset) = text[search_from..].find(':') {
+        let colon = search_from + offset;
+        search_from = colon + 1;
+
+        // Line number directly after the colon
+        let digits_len = text[colon + 1..]
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(text.len() - colon - 1);
+        if digits_len == 0 {
+            continue;
+        }
+        let line_end = colon + 1 + digits_len;
+        let Ok(line) = text[colon + 1..line_end].parse::<usize>() else {
+            continue;
+        };
+
+        // Path directly before the colon
+        let start = text[..colon]
+            .char_indices()
+            .rev()
+            .take_while(|(_, c)| is_path_char(*c))
+            .last()
+            .map(|(i, _)| i)
+            .unwrap_or(colon);
+        if refs.last().is_some_and(|(r, _): &(Range<usize>, FileRef)| start < r.end) {
+            continue;
+        }
+        let path = text[start..colon].trim_end_matches('.');
+        if path.len() != colon - start || !looks_like_file(path) {
+            continue;
+        }
+
+        // Optional `:col` suffix
+        let mut end = line_end;
+        if text[end..].starts_with(':') {
+            let col_len = text[end + 1..]
+                .find(|c: char| !c.is_ascii_digit())
+                .unwrap_or(text.len() - end - 1);
+            if col_len > 0 {
+                end += 1 + col_len;
+            }
+        }
+
+        refs.push((
+            start..end,
+            FileRef {
+                path: path.to_string(),
+                line,
+            },
+        ));
+        search_from = end;
+    }
+
+    refs
+}
+
+/// One row of Grep/Glob output: a file, optionally with a line and the
+/// matching text.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SearchHit {
+    pub path: String,
+    pub line: Option<usize>,
+    /// The matched text; empty for file-only results.
+    pub text: String,
+}
+
+impl SearchHit {
+    /// Where to open the hit: its line, or the top of the file.
+    pub fn file_ref(&self) -> FileRef {
+        FileRef { path: self.path.clone(), line: self.line.unwrap_or(1) }
+    }
+}
+
+/// Whether `text` is a bare path as printed by ripgrep or Glob.
+fn is_result_path(text: &str) -> bool {
+    !text.is_empty() && text.chars().all(is_path_char) && (text.contains('/') || looks_like_file(text))
+}
+
+/// Parse one line of ripgrep-style Grep or Glob output: `path`,
+/// `path:line:text`, `path:line` or `path:text`. Summary lines such as
+/// "Found 3 files", context separators and blank lines give `None`.
+pub fn parse_search_line(line: &str) -> Option<SearchHit> {
+    let line = line.trim_end();
+    let Some((path, rest)) = line.split_once(':') else {
+        return is_result_path(line).then(|| SearchHit {
+            path: line.to_string(),
+            line: None,
+            text: String::new(),
+        });
+    };
+    if !is_result_path(path) {
+        return None;
+    }
+    let (number, text) = rest.split_once(':').unwrap_or((rest, ""));
+    let (line, text) = match number.parse::<usize>() {
+        Ok(n) => (Some(n), text),
+        Err(_) => (None, rest),
+    };
+    Some(SearchHit { path: path.to_string(), line, text: text.to_string() })
+}
+
+/// Build the editor command line for opening `file_ref`.
+///
+/// `template` may use `{file}` and `{line}` placeholders (e.g.
+/// `"code -g {file}:{line}"`). Without a template, `$VISUAL` / `$EDITOR`
+/// (falling back to `vi`) is invoked as `<editor> +{line} {file}`.
+pub fn editor_command(template: Option<&str>, file_ref: &FileRef) -> Vec<String> {
+    let template = match template {
+        Some(t) if !t.trim().is_empty() => t.to_string(),
+        _ => {
+            let editor = std::env::var("VISUAL")
+                .or_else(|_| std::env::var("EDITOR"))
+                .unwrap_or_else(|_| "vi".to_string());
+            format!("{editor} +{{line}} {{file}}")
+        }
+    };
+
+    let line = file_ref.line.to_string();
+    let mut argv: Vec<String> = template
+        .split_whitespace()
+        .map(|arg| arg.replace("{file}", &file_ref.path).replace("{line}", &line))
+        .collect();
+    if !template.contains("{file}") {
+        argv.push(file_ref.path.clone());
+    }
+    argv
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn paths(text: &str) -> Vec<String> {
+        find_file_refs(text)
+            .into_iter()
+            .map(|(_, r)| r.display())
+            .collect()
+    }
+
+    #[test]
+    fn test_find_simple_ref() {
+        let refs = find_file_refs("see src/app.rs:123 for details");
+        assert_eq!(refs.len(), 1);
+        assert_eq!(refs[0].0, 4..18);
+        assert_eq!(refs[0].1.path, "src/app.rs");
+        assert_eq!(refs[0].1.line, 123);
+    }
+
+    #[test]
+    fn test_find_ref_with_column() {
+        let text = "error at main.rs:10:5: oops";
+        let refs = find_file_refs(text);
+        assert_eq!(refs.len(), 1);
+        assert_eq!(&text[refs[0].0.clone()], "main.rs:10:5");
+        assert_eq!(refs[0].1.line, 10);
+    }
+
+    #[test]
+    fn test_find_multiple_refs() {
+        assert_eq!(
+            paths("`a.rs:1` and (lib/b.toml:22)"),
+            vec!["a.rs:1", "lib/b.toml:22"]
+        );
+    }
+
+    #[test]
+    fn test_ignores_non_file_colons() {
+        assert!(paths("localhost:8080").is_empty());
+        assert!(paths("127.0.0.1:80").is_empty());
+        assert!(paths("time 12:30").is_empty());
+        assert!(paths("https://example.com").is_empty());
+        assert!(paths("src/app.rs: no line").is_empty());
+    }
+
+    #[test]
+    fn test_parse_search_lines() {
+        let hit = parse_search_line("src/app.rs:42:    fn update(&mut self)").unwrap();
+        assert_eq!(hit.path, "src/app.rs");
+        assert_eq!(hit.line, Some(42));
+        assert_eq!(hit.text, "    fn update(&mut self)");
+        assert_eq!(hit.file_ref().display(), "src/app.rs:42");
+
+        let hit = parse_search_line("/repo/src/main.rs").unwrap();
+        assert_eq!((hit.line, hit.text.as_str()), (None, ""));
+        assert_eq!(hit.file_ref().line, 1);
+
+        let hit = parse_search_line("README.md:see the docs").unwrap();
+        assert_eq!((hit.line, hit.text.as_str()), (None, "see the docs"));
+
+        assert_eq!(parse_search_line("Found 3 files"), None);
+        assert_eq!(parse_search_line("No files found"), None);
+        assert_eq!(parse_search_line("--"), None);
+        assert_eq!(parse_search_line(""), None);
+    }
+
+    #[test]
+    fn test_editor_command_template() {
+        let r = FileRef { path: "src/app.rs".to_string(), line: 42 };
+        assert_eq!(
+            editor_command(Some("code -g {file}:{line}"), &r),
+            vec!["code", "-g", "src/app.rs:42"]
+        );
+    }
+
+    #[test]
+    fn test_editor_command_appends_file() {
+        let r = FileRef { path: "a.rs".to_string(), line: 1 };
+        assert_eq!(editor_command(Some("subl"), &r), vec!["subl", "a.rs"]);
+    }
+}
@@ -1,4 +1,23 @@
 use std::process::Command;
+use std::time::{Duration, Instant};
+
+/// How often git info is refreshed when `git status` is cheap.
+pub const REFRESH_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Refresh interval for repos where gathering is slow.
+pub const SLOW_REFRESH_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Gathering slower than this backs off to [`SLOW_REFRESH_INTERVAL`].
+const SLOW_GATHER: Duration = Duration::from_millis(500);
+
+/// Refresh interval for a repo whose last [`GitInfo::gather`] took `cost`.
+pub fn refresh_interval(cost: Duration) -> Duration {
+    if cost > SLOW_GATHER {
+        SLOW_REFRESH_INTERVAL
+    } else {
+        REFRESH_INTERVAL
+    }
+}
 
 /// Lightweight snapshot of git repo state.
 #[derive(Debug, Clone, Default)]
@@ -42,6 +61,13 @@ impl GitInfo {
         }
     }
 
+    /// Like [`GitInfo::gather`], also returning how long gathering took.
+    pub fn gather_timed() -> (Self, Duration) {
+        let start = Instant::now();
+        let info = Self::gather();
+        (info, start.elapsed())
+    }
+
     /// Format for display in status bar: " main" or " main *3"
     pub fn display(&self) -> Option<String> {
         self.branch.as_ref().map(|b| {
@@ -88,6 +114,13 @@ mod tests {
         assert_eq!(info.display(), None);
     }
 
+    #[test]
+    fn test_refresh_interval_backs_off_for_slow_repos() {
+        assert_eq!(refresh_interval(Duration::from_millis(40)), REFRESH_INTERVAL);
+        assert_eq!(refresh_interval(Duration::from_millis(500)), REFRESH_INTERVAL);
+        assert_eq!(refresh_interval(Duration::from_millis(1200)), SLOW_REFRESH_INTERVAL);
+    }
+
     #[test]
     fn test_gather_runs_in_git_repo() {
         // This test runs in the project repo, so should find a branch
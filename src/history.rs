@@ -12,10 +12,7 @@ pub struct InputHistory {
 impl InputHistory {
     /// Create a new history backed by the default file path.
     pub fn new() -> Self {
-        let path = dirs::config_dir()
-            .unwrap_or_else(|| PathBuf::from("~/.config"))
-            .join("sexy-claude")
-            .join("history.jsonl");
+        let path = crate::config::config_dir().join("history.jsonl");
         let mut h = Self {
             entries: Vec::new(),
             path,
@@ -24,6 +21,12 @@ impl InputHistory {
         h
     }
 
+    /// An empty history saved to `path`, for tests.
+    #[cfg(test)]
+    pub fn with_path(path: PathBuf) -> Self {
+        Self { entries: Vec::new(), path }
+    }
+
     /// Load history from disk. Silently ignores errors.
     fn load(&mut self) {
         let content = match std::fs::read_to_string(&self.path) {
@@ -121,10 +124,7 @@ mod tests {
 
     fn test_history() -> InputHistory {
         let dir = tempfile::tempdir().unwrap();
-        InputHistory {
-            entries: Vec::new(),
-            path: dir.into_path().join("history.jsonl"),
-        }
+        InputHistory::with_path(dir.into_path().join("history.jsonl"))
     }
 
     #[test]
@@ -1,16 +1,22 @@
 mod app;
 mod claude;
+mod clipboard;
 mod config;
 mod cost;
 mod diff;
+mod filerefs;
 mod git;
 mod history;
 mod keybindings;
+mod oneshot;
 mod pty;
+mod snippets;
+mod termbg;
 mod terminal;
 mod theme;
 mod todo;
 mod ui;
+mod workflows;
 
 use anyhow::{Context, Result};
 use clap::Parser;
@@ -20,7 +26,7 @@ use std::path::PathBuf;
 #[command(name = "sexy-claude", about = "A beautiful terminal wrapper for Claude Code")]
 #[command(version)]
 struct Cli {
-    /// Theme name (e.g., catppuccin-mocha, nord, dracula)
+    /// Theme name (e.g., catppuccin-mocha, nord, dracula), or "auto" to match the terminal background
     #[arg(short, long)]
     theme: Option<String>,
 
@@ -56,14 +62,50 @@ struct Cli {
     #[arg(long = "allowed-tools")]
     allowed_tools: Option<Vec<String>>,
 
+    /// Give Claude access to another directory (repeatable)
+    #[arg(long = "add-dir", value_name = "PATH")]
+    add_dirs: Vec<PathBuf>,
+
+    /// Extra argument passed verbatim to claude (repeatable, e.g. --claude-arg=--strict-mcp-config)
+    #[arg(long = "claude-arg", allow_hyphen_values = true)]
+    claude_args: Vec<String>,
+
+    /// Print every unrecognised line from claude's stdout to stderr on exit
+    #[arg(long)]
+    verbose_unknown: bool,
+
+    /// Stripped-down UI: no header animation or status bar decorations
+    #[arg(long)]
+    minimal: bool,
+
+    /// Read-only review mode: browse the session without sending messages
+    #[arg(long)]
+    review: bool,
+
     /// Continue the most recent session
     #[arg(long = "continue")]
     continue_session: bool,
 
-    /// Resume a specific session by ID
+    /// Resume a specific session by ID, or by a unique prefix of one
     #[arg(long)]
     resume: Option<String>,
 
+    /// Send a single prompt, print the final answer and exit without the TUI
+    #[arg(long, value_name = "PROMPT")]
+    oneshot: Option<String>,
+
+    /// With --oneshot, print the result as a JSON object instead of plain text
+    #[arg(long, requires = "oneshot")]
+    json_output: bool,
+
+    /// Print the available themes (name, tab, display name) and exit
+    #[arg(long)]
+    theme_list: bool,
+
+    /// Print the available workflows (name, tab, description) and exit
+    #[arg(long)]
+    list_workflows: bool,
+
     /// Command to run (default: claude)
     #[arg(trailing_var_arg = true)]
     command: Vec<String>,
@@ -73,6 +115,23 @@ struct Cli {
 async fn main() -> Result<()> {
     let cli = Cli::parse();
 
+    // Listings for scripts and shell completion, no terminal needed
+    if cli.theme_list {
+        print_listing(theme::Theme::list_available().into_iter().map(|name| {
+            let display = theme::Theme::load(&name).map(|t| t.name).unwrap_or_else(|_| name.clone());
+            format!("{name}\t{display}")
+        }));
+        return Ok(());
+    }
+    if cli.list_workflows {
+        let (workflows, errors) = app::all_workflows();
+        for e in errors {
+            eprintln!("Warning: workflows not loaded: {e}");
+        }
+        print_listing(workflows.into_iter().map(|w| format!("{}\t{}", w.name, w.description)));
+        return Ok(());
+    }
+
     let mut config = config::Config::load(cli.config.as_ref())
         .context("Failed to load configuration")?;
 
@@ -88,13 +147,15 @@ async fn main() -> Result<()> {
     if cli.allowed_tools.is_some() {
         config.allowed_tools = cli.allowed_tools;
     }
+    config.additional_dirs.extend(cli.add_dirs.iter().map(|d| d.display().to_string()));
+    config.extra_args.extend(cli.claude_args);
+    config.verbose_unknown |= cli.verbose_unknown;
+    config.review |= cli.review;
+    if cli.minimal {
+        config.ui = "minimal".to_string();
+    }
 
-    let theme_name = cli.theme.as_deref().unwrap_or(&config.theme);
-    let theme = theme::Theme::load(theme_name).unwrap_or_else(|e| {
-        eprintln!("Warning: Failed to load theme '{}': {}. Using default.", theme_name, e);
-        theme::Theme::default_theme()
-    });
-
+    let show_welcome = !config.welcomed;
     let command = if cli.command.is_empty() {
         config.command.clone()
     } else {
@@ -109,6 +170,63 @@ async fn main() -> Result<()> {
         );
     }
 
+    // A short --resume prefix resolves to the one session it matches; several
+    // matches open the session picker on just those
+    let mut resume_matches = Vec::new();
+    let resume = match cli.resume {
+        Some(id) if !claude::sessions::is_full_id(&id) => {
+            let mut matches = claude::sessions::match_prefix(claude::sessions::discover_sessions(), &id);
+            match matches.len() {
+                0 => anyhow::bail!("No session ID starts with '{id}'"),
+                1 => Some(matches.remove(0).session_id),
+                _ if cli.oneshot.is_some() => {
+                    let ids: Vec<String> = matches.into_iter().map(|s| s.session_id).collect();
+                    anyhow::bail!("'{id}' matches several sessions:\n  {}", ids.join("\n  "))
+                }
+                _ => {
+                    resume_matches = matches;
+                    None
+                }
+            }
+        }
+        other => other,
+    };
+
+    if let Some(prompt) = cli.oneshot {
+        let (add_dirs, missing_dirs) = config.partition_additional_dirs(None);
+        for dir in missing_dirs {
+            eprintln!("Warning: directory '{}' not found, not adding it", dir.display());
+        }
+        let options = claude::process::SpawnOptions {
+            continue_session: cli.continue_session,
+            model: cli.model.or(config.model),
+            effort: cli.effort.or(config.effort),
+            max_budget_usd: cli.max_budget_usd.or(config.max_budget_usd),
+            mcp_config: config.mcp_config,
+            permission_mode: config.permission_mode,
+            allowed_tools: config.allowed_tools,
+            add_dirs,
+            resume_session_id: resume,
+            extra_args: config.extra_args,
+            env: config.env,
+            ..Default::default()
+        };
+        let outcome = oneshot::run(&command, options, &prompt).await?;
+        if cli.json_output {
+            println!("{}", outcome.to_json());
+        } else {
+            println!("{}", outcome.text);
+        }
+        std::process::exit(if outcome.is_error { 1 } else { 0 });
+    }
+
+    let theme_name = config.resolve_theme(cli.theme.as_deref().unwrap_or(&config.theme), termbg::detect);
+    let theme_name = theme_name.as_str();
+    let theme = theme::Theme::load(theme_name).unwrap_or_else(|e| {
+        eprintln!("Warning: Failed to load theme '{}': {}. Using default.", theme_name, e);
+        theme::Theme::default_theme()
+    });
+
     let (cols, rows) = crossterm::terminal::size().context("Failed to get terminal size")?;
     if cols < 40 || rows < 10 {
         anyhow::bail!("Terminal too small ({}x{}). Need at least 40x10.", cols, rows);
@@ -121,10 +239,13 @@ async fn main() -> Result<()> {
         crossterm::terminal::SetTitle("sexy-claude"),
         crossterm::event::EnableBracketedPaste
     )?;
+    // Checked before the event reader starts, as it reads the terminal's reply
+    let keyboard_enhanced = crossterm::terminal::supports_keyboard_enhancement().unwrap_or(false)
+        && crossterm::execute!(std::io::stdout(), app::keyboard_enhancement()).is_ok();
 
     // Run the app — no more PTY setup needed, App handles process spawning
     let theme_name_owned = theme_name.to_string();
-    let continue_session = cli.continue_session || cli.resume.is_some();
+    let continue_session = cli.continue_session || resume.is_some();
     let mut app = app::App::new(
         config,
         theme,
@@ -134,19 +255,47 @@ async fn main() -> Result<()> {
         cli.model,
         cli.effort,
         cli.max_budget_usd,
-        cli.resume,
+        resume,
     );
+    app.set_keyboard_enhanced(keyboard_enhanced);
+    if show_welcome {
+        app.open_welcome();
+    }
+    if !resume_matches.is_empty() {
+        app.show_session_picker(resume_matches);
+    }
     let result = app.run(&mut terminal).await;
 
     let _ = crossterm::execute!(
         std::io::stdout(),
         crossterm::event::DisableBracketedPaste
     );
+    if keyboard_enhanced {
+        let _ = crossterm::execute!(std::io::stdout(), crossterm::event::PopKeyboardEnhancementFlags);
+    }
     ratatui::restore();
 
+    if app.verbose_unknown() {
+        for line in app.unknown_lines() {
+            eprintln!("{line}");
+        }
+    }
+
     result
 }
 
+/// Print one entry per line. A closed pipe (`| head`) is not an error.
+fn print_listing(entries: impl Iterator<Item = String>) {
+    use std::io::Write;
+
+    let mut out = String::new();
+    for entry in entries {
+        out.push_str(&entry);
+        out.push('\n');
+    }
+    let _ = std::io::stdout().write_all(out.as_bytes());
+}
+
 fn which(program: &str) -> Option<PathBuf> {
     std::env::var_os("PATH").and_then(|paths| {
         std::env::split_paths(&paths).find_map(|dir| {
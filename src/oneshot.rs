@@ -0,0 +1,114 @@
+/// Non-interactive mode (`--oneshot`): send a single prompt, print the final
+/// answer and exit without starting the TUI.
+use anyhow::Result;
+
+use crate::claude::conversation::Conversation;
+use crate::claude::events::StreamEvent;
+use crate::claude::process::{ClaudeProcess, SpawnOptions};
+
+/// The final answer of a one-shot run.
+pub struct Outcome {
+    pub text: String,
+    pub is_error: bool,
+    pub session_id: Option<String>,
+}
+
+impl Outcome {
+    /// The outcome as a single JSON object, for `--json-output`.
+    pub fn to_json(&self) -> String {
+        serde_json::json!({
+            "result": self.text,
+            "is_error": self.is_error,
+            "session_id": self.session_id,
+        })
+        .to_string()
+    }
+}
+
+/// Spawn `command`, send `prompt` and wait for the `result` event.
+pub async fn run(command: &str, options: SpawnOptions, prompt: &str) -> Result<Outcome> {
+    let (mut claude, mut events) = ClaudeProcess::spawn_with_options(command, options)?;
+    claude.send_message(prompt).await?;
+
+    let mut conversation = Conversation::new();
+    let mut session_id = None;
+    let outcome = loop {
+        let Some(event) = events.recv().await else {
+            break None;
+        };
+        if let StreamEvent::SystemInit { session_id: ref id, .. } = event {
+            session_id = id.clone();
+        }
+        conversation.apply_event(&event);
+        if let StreamEvent::Result { text, is_error, .. } = event {
+            // Streamed answers leave the result text empty; use the assembled reply
+            let text = if text.is_empty() { conversation.streaming_text().to_string() } else { text };
+            break Some(Outcome { text, is_error, session_id });
+        }
+    };
+    let _ = claude.kill().await;
+    outcome.ok_or_else(|| anyhow::anyhow!("{command} exited without a result"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A stand-in for claude that replies to one message with `lines`.
+    #[cfg(unix)]
+    fn fake_claude(dir: &std::path::Path, lines: &[&str]) -> String {
+        use std::os::unix::fs::PermissionsExt;
+
+        let path = dir.join("fake-claude");
+        let mut script = "#!/bin/sh\nread -r _prompt\n".to_string();
+        for line in lines {
+            script.push_str(&format!("echo '{line}'\n"));
+        }
+        std::fs::write(&path, script).unwrap();
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+        path.display().to_string()
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_oneshot_collects_streamed_answer() {
+        let dir = tempfile::tempdir().unwrap();
+        let command = fake_claude(
+            dir.path(),
+            &[
+                r#"{"type":"system","subtype":"init","session_id":"abc-123","slash_commands":[]}"#,
+                r#"{"type":"stream_event","event":{"type":"message_start","message":{"id":"m1","model":"claude","usage":null}}}"#,
+                r#"{"type":"stream_event","event":{"type":"content_block_start","index":0,"content_block":{"type":"text","text":""}}}"#,
+                r#"{"type":"stream_event","event":{"type":"content_block_delta","index":0,"delta":{"type":"text_delta","text":"4"}}}"#,
+                r#"{"type":"stream_event","event":{"type":"message_stop"}}"#,
+                r#"{"type":"result","subtype":"success","is_error":false,"result":""}"#,
+            ],
+        );
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        let outcome = rt.block_on(run(&command, SpawnOptions::default(), "2+2?")).unwrap();
+        assert_eq!(outcome.text, "4");
+        assert!(!outcome.is_error);
+        assert_eq!(outcome.session_id.as_deref(), Some("abc-123"));
+        assert_eq!(
+            outcome.to_json(),
+            r#"{"is_error":false,"result":"4","session_id":"abc-123"}"#
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_oneshot_reports_error_and_early_exit() {
+        let dir = tempfile::tempdir().unwrap();
+        let command = fake_claude(
+            dir.path(),
+            &[r#"{"type":"result","subtype":"error","is_error":true,"result":"Invalid API key"}"#],
+        );
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        let outcome = rt.block_on(run(&command, SpawnOptions::default(), "hi")).unwrap();
+        assert_eq!(outcome.text, "Invalid API key");
+        assert!(outcome.is_error);
+
+        let command = fake_claude(dir.path(), &[]);
+        assert!(rt.block_on(run(&command, SpawnOptions::default(), "hi")).is_err());
+    }
+}
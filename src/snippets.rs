@@ -0,0 +1,184 @@
+/// Named prompt snippets (`/snip`), persisted as a TOML table of name → text.
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+
+pub struct Snippets {
+    entries: BTreeMap<String, String>,
+    path: PathBuf,
+    /// Why the file could not be loaded, if it exists but failed to read or parse.
+    load_error: Option<String>,
+}
+
+impl Snippets {
+    /// Load snippets from the default file path.
+    pub fn new() -> Self {
+        let path = crate::config::config_dir().join("snippets.toml");
+        Self::load_from(path)
+    }
+
+    /// Load snippets from `path`. A missing file yields no snippets; one that
+    /// fails to parse yields none too and is reported by [`Self::load_error`].
+    pub fn load_from(path: PathBuf) -> Self {
+        let (entries, load_error) = match read_entries(&path) {
+            Ok(entries) => (entries, None),
+            Err(e) => (BTreeMap::new(), Some(format!("{e:#}"))),
+        };
+        Self { entries, path, load_error }
+    }
+
+    /// Why the snippets file could not be loaded at startup, if it couldn't.
+    pub fn load_error(&self) -> Option<&str> {
+        self.load_error.as_deref()
+    }
+
+    /// Re-read the file so edits made to it since startup are kept, apply
+    /// `change` and save if it reports a change. A file that fails to parse
+    /// is never overwritten.
+    fn update(&mut self, change: impl FnOnce(&mut BTreeMap<String, String>) -> bool) -> Result<bool> {
+        self.entries = read_entries(&self.path)
+            .with_context(|| format!("Not overwriting {}", self.path.display()))?;
+        self.load_error = None;
+        let changed = change(&mut self.entries);
+        if changed {
+            self.save()?;
+        }
+        Ok(changed)
+    }
+
+    /// Save snippets to disk. Creates parent directories if needed.
+    fn save(&self) -> Result<()> {
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create {}", parent.display()))?;
+        }
+        let content = toml::to_string(&self.entries).context("Failed to serialize snippets")?;
+        std::fs::write(&self.path, content)
+            .with_context(|| format!("Failed to write snippets to {}", self.path.display()))
+    }
+
+    /// The text stored under `name`.
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.entries.get(name).map(String::as_str)
+    }
+
+    /// Store `text` under `name`, replacing any existing snippet.
+    pub fn insert(&mut self, name: &str, text: &str) -> Result<()> {
+        self.update(|entries| {
+            entries.insert(name.to_string(), text.to_string());
+            true
+        })?;
+        Ok(())
+    }
+
+    /// Delete the snippet called `name`. Returns whether it existed.
+    pub fn remove(&mut self, name: &str) -> Result<bool> {
+        self.update(|entries| entries.remove(name).is_some())
+    }
+
+    /// All snippets as (name, text) pairs, sorted by name.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.entries.iter().map(|(k, v)| (k.as_str(), v.as_str()))
+    }
+
+    pub fn path(&self) -> &std::path::Path {
+        &self.path
+    }
+}
+
+/// The snippets stored in `path`; a missing file has none.
+fn read_entries(path: &Path) -> Result<BTreeMap<String, String>> {
+    let content = match std::fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(BTreeMap::new()),
+        Err(e) => return Err(e).with_context(|| format!("Failed to read {}", path.display())),
+    };
+    toml::from_str(&content).with_context(|| format!("Failed to parse {}", path.display()))
+}
+
+/// A parsed `/snip` command.
+#[derive(Debug, PartialEq)]
+pub enum SnipCommand {
+    /// `/snip` — list snippets and usage.
+    List,
+    /// `/snip save <name>` — store the last prompt.
+    Save(String),
+    /// `/snip delete <name>`
+    Delete(String),
+    /// `/snip <name>` — expand into the input box.
+    Expand(String),
+}
+
+/// Parse `/snip ...` input. Returns `None` for anything else, including
+/// commands that merely start with "snip" such as `/snippets`.
+pub fn parse_command(text: &str) -> Option<SnipCommand> {
+    let rest = text.trim().strip_prefix("/snip")?;
+    if !rest.is_empty() && !rest.starts_with(char::is_whitespace) {
+        return None;
+    }
+    let mut words = rest.split_whitespace();
+    let command = match (words.next(), words.next()) {
+        (None, _) => SnipCommand::List,
+        (Some("save"), Some(name)) => SnipCommand::Save(name.to_string()),
+        (Some("delete"), Some(name)) => SnipCommand::Delete(name.to_string()),
+        // "save"/"delete" without a name: show usage instead of expanding
+        (Some("save" | "delete"), None) => SnipCommand::List,
+        (Some(name), _) => SnipCommand::Expand(name.to_string()),
+    };
+    Some(command)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_command() {
+        assert_eq!(parse_command("/snip"), Some(SnipCommand::List));
+        assert_eq!(parse_command("/snip save review"), Some(SnipCommand::Save("review".to_string())));
+        assert_eq!(parse_command("/snip delete review"), Some(SnipCommand::Delete("review".to_string())));
+        assert_eq!(parse_command("/snip  review "), Some(SnipCommand::Expand("review".to_string())));
+        assert_eq!(parse_command("/snip save"), Some(SnipCommand::List));
+        assert_eq!(parse_command("/snippets"), None);
+        assert_eq!(parse_command("snip review"), None);
+    }
+
+    #[test]
+    fn test_toml_roundtrip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("snippets.toml");
+
+        let mut s = Snippets::load_from(path.clone());
+        s.insert("review", "Review this diff.\nBe \"strict\".").unwrap();
+        s.insert("tests", "Write tests").unwrap();
+
+        let mut s = Snippets::load_from(path.clone());
+        assert_eq!(s.get("review"), Some("Review this diff.\nBe \"strict\"."));
+        assert_eq!(s.iter().map(|(name, _)| name).collect::<Vec<_>>(), vec!["review", "tests"]);
+
+        assert!(s.remove("tests").unwrap());
+        assert!(!s.remove("tests").unwrap());
+        assert_eq!(Snippets::load_from(path).get("tests"), None);
+    }
+
+    #[test]
+    fn test_saving_keeps_outside_edits_and_broken_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("snippets.toml");
+
+        let mut s = Snippets::load_from(path.clone());
+        assert_eq!(s.load_error(), None);
+        std::fs::write(&path, "edited = \"by hand\"\n").unwrap();
+        s.insert("review", "Review this").unwrap();
+        let reloaded = Snippets::load_from(path.clone());
+        assert_eq!(reloaded.get("edited"), Some("by hand"));
+        assert_eq!(reloaded.get("review"), Some("Review this"));
+
+        std::fs::write(&path, "not = [valid").unwrap();
+        let mut s = Snippets::load_from(path.clone());
+        assert!(s.load_error().unwrap().starts_with("Failed to parse"));
+        assert!(s.insert("tests", "Write tests").is_err());
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "not = [valid");
+    }
+}
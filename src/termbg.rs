@@ -0,0 +1,122 @@
+/// Terminal background detection for the `auto` theme, via the OSC 11
+/// "report background color" query.
+#[cfg(unix)]
+use std::time::{Duration, Instant};
+
+/// How long to wait for the terminal to answer before giving up.
+#[cfg(unix)]
+const QUERY_TIMEOUT: Duration = Duration::from_millis(500);
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Background {
+    Light,
+    Dark,
+}
+
+/// Ask the terminal for its background colour. Returns `None` when there is
+/// no terminal, it doesn't support the query, or it doesn't answer in time.
+///
+/// Must run before the TUI starts reading input, since the reply arrives on
+/// the terminal's input stream.
+#[cfg(unix)]
+pub fn detect() -> Option<Background> {
+    let mut tty = std::fs::OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open("/dev/tty")
+        .ok()?;
+    crossterm::terminal::enable_raw_mode().ok()?;
+    let response = query(&mut tty);
+    let _ = crossterm::terminal::disable_raw_mode();
+    parse_response(&response?)
+}
+
+#[cfg(not(unix))]
+pub fn detect() -> Option<Background> {
+    None
+}
+
+/// Send the OSC 11 query followed by a Primary Device Attributes query. Every
+/// terminal answers the latter, so its reply marks the end of the response
+/// and unsupported terminals don't cost the full timeout.
+#[cfg(unix)]
+fn query(tty: &mut std::fs::File) -> Option<String> {
+    use std::io::{Read, Write};
+    use std::os::fd::AsRawFd;
+
+    tty.write_all(b"\x1b]11;?\x1b\\\x1b[c").ok()?;
+    tty.flush().ok()?;
+
+    let deadline = Instant::now() + QUERY_TIMEOUT;
+    let mut buf = Vec::new();
+    while !device_attributes_done(&buf) {
+        let Some(remaining) = deadline.checked_duration_since(Instant::now()) else {
+            break;
+        };
+        let mut pfd = libc::pollfd {
+            fd: tty.as_raw_fd(),
+            events: libc::POLLIN,
+            revents: 0,
+        };
+        // SAFETY: `pfd` is a valid pollfd for the duration of the call.
+        let ready = unsafe { libc::poll(&mut pfd, 1, remaining.as_millis() as libc::c_int) };
+        if ready <= 0 {
+            break;
+        }
+        let mut chunk = [0u8; 256];
+        match tty.read(&mut chunk) {
+            Ok(0) | Err(_) => break,
+            Ok(n) => buf.extend_from_slice(&chunk[..n]),
+        }
+    }
+    Some(String::from_utf8_lossy(&buf).into_owned())
+}
+
+/// Whether `buf` holds a complete `ESC [ ? ... c` device attributes reply.
+#[cfg(unix)]
+fn device_attributes_done(buf: &[u8]) -> bool {
+    buf.windows(3)
+        .position(|w| w == b"\x1b[?")
+        .is_some_and(|start| buf[start..].contains(&b'c'))
+}
+
+/// Classify an `ESC ] 11 ; rgb:RRRR/GGGG/BBBB` reply by perceived brightness.
+fn parse_response(response: &str) -> Option<Background> {
+    let start = response.find("]11;rgb:")? + "]11;rgb:".len();
+    let rest = &response[start..];
+    let end = rest.find(['\x1b', '\x07']).unwrap_or(rest.len());
+    let mut channels = rest[..end].split('/').map(parse_channel);
+    let (r, g, b) = (channels.next()??, channels.next()??, channels.next()??);
+    let luminance = 0.299 * r + 0.587 * g + 0.114 * b;
+    Some(if luminance > 0.5 { Background::Light } else { Background::Dark })
+}
+
+/// Parse a 1–4 digit hex colour channel into 0.0..=1.0.
+fn parse_channel(hex: &str) -> Option<f32> {
+    if hex.is_empty() || hex.len() > 4 {
+        return None;
+    }
+    let value = u32::from_str_radix(hex, 16).ok()?;
+    let max = (1u32 << (4 * hex.len())) - 1;
+    Some(value as f32 / max as f32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_dark_and_light_backgrounds() {
+        assert_eq!(parse_response("\x1b]11;rgb:1e1e/1e1e/2e2e\x1b\\\x1b[?62;c"), Some(Background::Dark));
+        assert_eq!(parse_response("\x1b]11;rgb:efef/f1f1/f5f5\x07"), Some(Background::Light));
+        assert_eq!(parse_response("\x1b]11;rgb:ff/ff/ff\x1b\\"), Some(Background::Light));
+    }
+
+    #[test]
+    fn test_parse_unsupported_terminal() {
+        // Only the device attributes reply came back
+        assert_eq!(parse_response("\x1b[?1;2c"), None);
+        assert_eq!(parse_response(""), None);
+        assert_eq!(parse_response("\x1b]11;rgb:zz/00/00\x07"), None);
+    }
+}
@@ -4,6 +4,8 @@ use ratatui::buffer::Buffer;
 use ratatui::layout::Rect;
 use ratatui::style::{Color, Modifier, Style};
 
+use crate::ui::claude_pane::{StyledLine, StyledSpan};
+
 /// Render the vt100 screen into a ratatui Buffer within the given area.
 /// `theme_bg` replaces all default/terminal backgrounds so the wrapper's
 /// theme dominates instead of the child process's own background.
@@ -28,23 +30,7 @@ pub fn render_screen(screen: &vt100::Screen, buf: &mut Buffer, area: Rect, theme
                 continue;
             }
 
-            let fg = convert_fg(cell.fgcolor(), theme_bg);
-            let bg = convert_bg(cell.bgcolor(), theme_bg);
-            let mut modifiers = Modifier::empty();
-            if cell.bold() {
-                modifiers |= Modifier::BOLD;
-            }
-            if cell.italic() {
-                modifiers |= Modifier::ITALIC;
-            }
-            if cell.underline() {
-                modifiers |= Modifier::UNDERLINED;
-            }
-            if cell.inverse() {
-                modifiers |= Modifier::REVERSED;
-            }
-
-            let style = Style::default().fg(fg).bg(bg).add_modifier(modifiers);
+            let style = cell_style(cell, theme_bg);
 
             let buf_cell = &mut buf[(x, y)];
             if contents.is_empty() {
@@ -57,6 +43,57 @@ pub fn render_screen(screen: &vt100::Screen, buf: &mut Buffer, area: Rect, theme
     }
 }
 
+/// Convert the vt100 screen into styled lines, one per screen row, merging
+/// runs of identically styled cells into a single span. When `show_cursor`
+/// is set the cell under the cursor is drawn reversed.
+pub fn screen_to_lines(screen: &vt100::Screen, theme_bg: Color, show_cursor: bool) -> Vec<StyledLine> {
+    let (rows, cols) = screen.size();
+    let cursor = (show_cursor && !screen.hide_cursor()).then(|| screen.cursor_position());
+
+    (0..rows)
+        .map(|row| {
+            let mut spans: Vec<StyledSpan> = Vec::new();
+            for col in 0..cols {
+                let Some(cell) = screen.cell(row, col) else { continue };
+                if cell.is_wide_continuation() {
+                    continue;
+                }
+                let mut style = cell_style(cell, theme_bg);
+                if cursor == Some((row, col)) {
+                    style = style.add_modifier(Modifier::REVERSED);
+                }
+                let contents = cell.contents();
+                let text = if contents.is_empty() { " " } else { contents.as_str() };
+                match spans.last_mut() {
+                    Some(last) if last.style == style => last.text.push_str(text),
+                    _ => spans.push(StyledSpan { text: text.to_string(), style }),
+                }
+            }
+            StyledLine { spans }
+        })
+        .collect()
+}
+
+/// Style of a single screen cell, with backgrounds mapped onto the theme.
+fn cell_style(cell: &vt100::Cell, theme_bg: Color) -> Style {
+    let fg = convert_fg(cell.fgcolor(), theme_bg);
+    let bg = convert_bg(cell.bgcolor(), theme_bg);
+    let mut modifiers = Modifier::empty();
+    if cell.bold() {
+        modifiers |= Modifier::BOLD;
+    }
+    if cell.italic() {
+        modifiers |= Modifier::ITALIC;
+    }
+    if cell.underline() {
+        modifiers |= Modifier::UNDERLINED;
+    }
+    if cell.inverse() {
+        modifiers |= Modifier::REVERSED;
+    }
+    Style::default().fg(fg).bg(bg).add_modifier(modifiers)
+}
+
 /// Convert foreground color. Default fg stays as Reset so terminal default applies.
 fn convert_fg(color: vt100::Color, _theme_bg: Color) -> Color {
     match color {
@@ -216,6 +253,33 @@ mod tests {
         assert_eq!(cell.style().bg.unwrap(), TEST_BG);
     }
 
+    #[test]
+    fn test_screen_to_lines_merges_styled_runs() {
+        let mut parser = vt100::Parser::new(2, 10, 0);
+        parser.process(b"ab\x1b[31mcd\x1b[0m");
+
+        let lines = screen_to_lines(parser.screen(), TEST_BG, false);
+        assert_eq!(lines.len(), 2);
+        let spans = &lines[0].spans;
+        assert_eq!(spans[0].text, "ab");
+        assert_eq!(spans[1].text, "cd");
+        assert_eq!(spans[1].style.fg, Some(Color::Indexed(1)));
+        assert_eq!(spans[2].text, "      ");
+        assert_eq!(spans[0].style.bg, Some(TEST_BG));
+    }
+
+    #[test]
+    fn test_screen_to_lines_marks_cursor() {
+        let mut parser = vt100::Parser::new(1, 5, 0);
+        parser.process(b"$ ");
+
+        let lines = screen_to_lines(parser.screen(), TEST_BG, true);
+        let texts: Vec<&str> = lines[0].spans.iter().map(|s| s.text.as_str()).collect();
+        assert_eq!(texts, vec!["$ ", " ", "  "]);
+        assert!(lines[0].spans[1].style.add_modifier.contains(Modifier::REVERSED));
+        assert!(!lines[0].spans[0].style.add_modifier.contains(Modifier::REVERSED));
+    }
+
     #[test]
     fn test_is_dark_bg() {
         assert!(is_dark_bg(0, 0, 0));       // pure black
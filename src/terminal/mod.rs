@@ -2,8 +2,19 @@
 
 pub mod converter;
 
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::style::Color;
 use vt100::Parser;
 
+use crate::ui::claude_pane::StyledLine;
+
+/// Longest output (in lines) played through an emulator by [`resolve_output`].
+const MAX_RESOLVED_ROWS: usize = 2000;
+/// Widest screen used by [`resolve_output`]; longer lines wrap onto the next row.
+const MAX_RESOLVED_COLS: usize = 500;
+/// Tab stops are every 8 columns.
+const TAB_WIDTH: usize = 8;
+
 pub struct TerminalEmulator {
     parser: Parser,
 }
@@ -27,13 +38,174 @@ impl TerminalEmulator {
         self.parser.set_size(rows, cols);
     }
 
-    #[allow(dead_code)]
     pub fn rows(&self) -> u16 {
         self.parser.screen().size().0
     }
 
-    #[allow(dead_code)]
     pub fn cols(&self) -> u16 {
         self.parser.screen().size().1
     }
 }
+
+/// Whether `output` contains escape sequences or bare carriage returns
+/// (progress bars, spinners) that only make sense once a terminal has
+/// interpreted them.
+pub fn has_control_sequences(output: &str) -> bool {
+    let bytes = output.as_bytes();
+    bytes.iter().enumerate().any(|(i, &b)| b == 0x1b || (b == b'\r' && bytes.get(i + 1) != Some(&b'\n')))
+}
+
+/// Play captured program `output` through an emulator and return what the
+/// screen would finally show: overwritten progress-bar frames collapse to
+/// their last state and colour codes become styles. Default colours are
+/// left unset so the caller's style shows through. Trailing blank rows and
+/// padding are dropped. Returns `None` for output too long to emulate.
+pub fn resolve_output(output: &str) -> Option<Vec<StyledLine>> {
+    // An upper bound on each line's width, so the screen is wide enough and,
+    // when lines still wrap, tall enough that nothing scrolls off the top
+    let widths: Vec<usize> = output.split('\n').map(max_line_width).collect();
+    let cols = widths.iter().copied().max().unwrap_or(0).clamp(1, MAX_RESOLVED_COLS);
+    let rows: usize = widths.iter().map(|w| w.div_ceil(cols).max(1)).sum();
+    if rows > MAX_RESOLVED_ROWS {
+        return None;
+    }
+    let mut emulator = TerminalEmulator::new(rows as u16, cols as u16);
+    // Captured output has bare line feeds, which a terminal only treats as
+    // "move down" without returning to the first column
+    emulator.process(output.replace("\r\n", "\n").replace('\n', "\r\n").as_bytes());
+
+    let mut lines = converter::screen_to_lines(emulator.screen(), Color::Reset, false);
+    for line in &mut lines {
+        for span in &mut line.spans {
+            if span.style.fg == Some(Color::Reset) {
+                span.style.fg = None;
+            }
+            if span.style.bg == Some(Color::Reset) {
+                span.style.bg = None;
+            }
+        }
+        // Drop the blank padding out to the screen edge
+        while line.spans.last().is_some_and(|s| s.text.trim_end().is_empty() && s.style.bg.is_none()) {
+            line.spans.pop();
+        }
+        if let Some(last) = line.spans.last_mut() {
+            let trimmed = last.text.trim_end().len();
+            if last.style.bg.is_none() {
+                last.text.truncate(trimmed);
+            }
+        }
+    }
+    while lines.last().is_some_and(|l| l.spans.is_empty()) {
+        lines.pop();
+    }
+    Some(lines)
+}
+
+/// Most columns `line` can take on screen: tabs padded to the next stop and
+/// escape sequences counted as if printed.
+fn max_line_width(line: &str) -> usize {
+    line.chars().fold(0, |width, c| match c {
+        '\t' => (width / TAB_WIDTH + 1) * TAB_WIDTH,
+        _ => width + unicode_width::UnicodeWidthChar::width(c).unwrap_or(0),
+    })
+}
+
+/// Encode a key press as the bytes a terminal would send to the program
+/// running in it. Returns `None` for keys with no terminal encoding.
+pub fn key_to_bytes(key: &KeyEvent) -> Option<Vec<u8>> {
+    let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+    let alt = key.modifiers.contains(KeyModifiers::ALT);
+
+    let bytes = match key.code {
+        KeyCode::Char(c) if ctrl => match c.to_ascii_lowercase() {
+            c @ 'a'..='z' => vec![c as u8 - b'a' + 1],
+            ' ' | '@' => vec![0],
+            '[' => vec![0x1b],
+            '\\' => vec![0x1c],
+            ']' => vec![0x1d],
+            _ => return None,
+        },
+        KeyCode::Char(c) => {
+            let mut buf = [0u8; 4];
+            c.encode_utf8(&mut buf).as_bytes().to_vec()
+        }
+        KeyCode::Enter => vec![b'\r'],
+        KeyCode::Backspace => vec![0x7f],
+        KeyCode::Tab => vec![b'\t'],
+        KeyCode::BackTab => b"\x1b[Z".to_vec(),
+        KeyCode::Esc => vec![0x1b],
+        KeyCode::Up => b"\x1b[A".to_vec(),
+        KeyCode::Down => b"\x1b[B".to_vec(),
+        KeyCode::Right => b"\x1b[C".to_vec(),
+        KeyCode::Left => b"\x1b[D".to_vec(),
+        KeyCode::Home => b"\x1b[H".to_vec(),
+        KeyCode::End => b"\x1b[F".to_vec(),
+        KeyCode::Insert => b"\x1b[2~".to_vec(),
+        KeyCode::Delete => b"\x1b[3~".to_vec(),
+        KeyCode::PageUp => b"\x1b[5~".to_vec(),
+        KeyCode::PageDown => b"\x1b[6~".to_vec(),
+        KeyCode::F(n @ 1..=4) => vec![0x1b, b'O', b'P' + (n - 1)],
+        KeyCode::F(n @ 5..=12) => {
+            let code = [15, 17, 18, 19, 20, 21, 23, 24][(n - 5) as usize];
+            format!("\x1b[{code}~").into_bytes()
+        }
+        _ => return None,
+    };
+
+    if alt && !ctrl {
+        let mut prefixed = vec![0x1b];
+        prefixed.extend(bytes);
+        return Some(prefixed);
+    }
+    Some(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bytes(code: KeyCode, modifiers: KeyModifiers) -> Option<Vec<u8>> {
+        key_to_bytes(&KeyEvent::new(code, modifiers))
+    }
+
+    #[test]
+    fn test_key_to_bytes_plain_and_control() {
+        assert_eq!(bytes(KeyCode::Char('a'), KeyModifiers::NONE), Some(b"a".to_vec()));
+        assert_eq!(bytes(KeyCode::Char('é'), KeyModifiers::NONE), Some("é".as_bytes().to_vec()));
+        assert_eq!(bytes(KeyCode::Char('c'), KeyModifiers::CONTROL), Some(vec![3]));
+        assert_eq!(bytes(KeyCode::Enter, KeyModifiers::NONE), Some(b"\r".to_vec()));
+        assert_eq!(bytes(KeyCode::Backspace, KeyModifiers::NONE), Some(vec![0x7f]));
+    }
+
+    #[test]
+    fn test_resolve_output_collapses_progress_frames() {
+        let output = "Pulling layer\n 10% [=>  ]\r 50% [==> ]\r100% [====]\n\x1b[32mDone\x1b[0m\n\n";
+        assert!(has_control_sequences(output));
+        assert!(!has_control_sequences("plain\r\nwindows line endings\n"));
+
+        let lines = resolve_output(output).unwrap();
+        let text: Vec<String> = lines.iter().map(|l| l.spans.iter().map(|s| s.text.as_str()).collect()).collect();
+        assert_eq!(text, ["Pulling layer", "100% [====]", "Done"]);
+        assert_eq!(lines[2].spans[0].style.fg, Some(Color::Indexed(2)));
+        assert_eq!(lines[0].spans[0].style.fg, None);
+    }
+
+    #[test]
+    fn test_resolve_output_keeps_wrapped_and_tabbed_lines() {
+        let wide = format!("\x1b[1m{}\x1b[0m", "x".repeat(MAX_RESOLVED_COLS + 20));
+        let output = format!("first\n{wide}\n\t\t\tlast\r\n");
+        let lines = resolve_output(&output).unwrap();
+        let text: Vec<String> = lines.iter().map(|l| l.spans.iter().map(|s| s.text.as_str()).collect()).collect();
+        assert_eq!(text.first().map(String::as_str), Some("first"));
+        assert_eq!(text.last().map(|l| l.trim_start()), Some("last"));
+    }
+
+    #[test]
+    fn test_key_to_bytes_escape_sequences() {
+        assert_eq!(bytes(KeyCode::Up, KeyModifiers::NONE), Some(b"\x1b[A".to_vec()));
+        assert_eq!(bytes(KeyCode::F(1), KeyModifiers::NONE), Some(b"\x1bOP".to_vec()));
+        assert_eq!(bytes(KeyCode::F(5), KeyModifiers::NONE), Some(b"\x1b[15~".to_vec()));
+        assert_eq!(bytes(KeyCode::Char('b'), KeyModifiers::ALT), Some(b"\x1bb".to_vec()));
+        assert_eq!(bytes(KeyCode::Null, KeyModifiers::NONE), None);
+    }
+}
@@ -37,6 +37,28 @@ pub struct ThemeColors {
     pub input_fg: String,
     pub input_cursor: String,
     pub input_placeholder: String,
+
+    // Conversation labels — optional, derived from the colors above when unset
+    #[serde(default)]
+    pub user_label_fg: Option<String>,
+    #[serde(default)]
+    pub user_label_bg: Option<String>,
+    #[serde(default)]
+    pub assistant_label_fg: Option<String>,
+    #[serde(default)]
+    pub assistant_label_bg: Option<String>,
+    #[serde(default)]
+    pub user_text: Option<String>,
+    #[serde(default)]
+    pub separator: Option<String>,
+
+    // Diff lines — optional, default to success/error/foreground
+    #[serde(default)]
+    pub diff_add: Option<String>,
+    #[serde(default)]
+    pub diff_remove: Option<String>,
+    #[serde(default)]
+    pub diff_context: Option<String>,
 }
 
 #[derive(Debug, Clone)]
@@ -67,6 +89,17 @@ pub struct Theme {
     pub input_fg: Color,
     pub input_cursor: Color,
     pub input_placeholder: Color,
+
+    pub user_label_fg: Color,
+    pub user_label_bg: Color,
+    pub assistant_label_fg: Color,
+    pub assistant_label_bg: Color,
+    pub user_text: Color,
+    pub separator: Color,
+
+    pub diff_add: Color,
+    pub diff_remove: Color,
+    pub diff_context: Color,
 }
 
 impl Theme {
@@ -80,9 +113,7 @@ impl Theme {
         }
 
         // Try loading from user config directory
-        let user_theme = dirs::config_dir()
-            .unwrap_or_else(|| PathBuf::from("~/.config"))
-            .join("sexy-claude")
+        let user_theme = crate::config::config_dir()
             .join("themes")
             .join(format!("{name}.toml"));
         if user_theme.exists() {
@@ -113,10 +144,7 @@ impl Theme {
         }
 
         // User themes: ~/.config/sexy-claude/themes/*.toml
-        let user_themes = dirs::config_dir()
-            .unwrap_or_else(|| PathBuf::from("~/.config"))
-            .join("sexy-claude")
-            .join("themes");
+        let user_themes = crate::config::config_dir().join("themes");
         Self::scan_theme_dir(&user_themes, &mut names);
 
         // Always include the embedded default
@@ -187,19 +215,25 @@ impl Theme {
         let file: ThemeFile =
             toml::from_str(content).with_context(|| "Failed to parse theme TOML")?;
         let c = &file.colors;
+        let background = parse_hex(&c.background)?;
+        let foreground = parse_hex(&c.foreground)?;
+        let overlay = parse_hex(&c.overlay)?;
+        let secondary = parse_hex(&c.secondary)?;
+        let success = parse_hex(&c.success)?;
+        let error = parse_hex(&c.error)?;
 
         Ok(Self {
             name: file.name,
-            background: parse_hex(&c.background)?,
-            foreground: parse_hex(&c.foreground)?,
+            background,
+            foreground,
             surface: parse_hex(&c.surface)?,
-            overlay: parse_hex(&c.overlay)?,
+            overlay,
             primary: parse_hex(&c.primary)?,
-            secondary: parse_hex(&c.secondary)?,
+            secondary,
             accent: parse_hex(&c.accent)?,
-            success: parse_hex(&c.success)?,
+            success,
             warning: parse_hex(&c.warning)?,
-            error: parse_hex(&c.error)?,
+            error,
             info: parse_hex(&c.info)?,
             border: parse_hex(&c.border)?,
             border_focused: parse_hex(&c.border_focused)?,
@@ -209,10 +243,24 @@ impl Theme {
             input_fg: parse_hex(&c.input_fg)?,
             input_cursor: parse_hex(&c.input_cursor)?,
             input_placeholder: parse_hex(&c.input_placeholder)?,
+            user_label_fg: parse_hex_or(c.user_label_fg.as_deref(), background)?,
+            user_label_bg: parse_hex_or(c.user_label_bg.as_deref(), secondary)?,
+            assistant_label_fg: parse_hex_or(c.assistant_label_fg.as_deref(), background)?,
+            assistant_label_bg: parse_hex_or(c.assistant_label_bg.as_deref(), success)?,
+            user_text: parse_hex_or(c.user_text.as_deref(), foreground)?,
+            separator: parse_hex_or(c.separator.as_deref(), overlay)?,
+            diff_add: parse_hex_or(c.diff_add.as_deref(), success)?,
+            diff_remove: parse_hex_or(c.diff_remove.as_deref(), error)?,
+            diff_context: parse_hex_or(c.diff_context.as_deref(), foreground)?,
         })
     }
 }
 
+/// Parse an optional color, using `fallback` when it's not set.
+fn parse_hex_or(hex: Option<&str>, fallback: Color) -> Result<Color> {
+    hex.map_or(Ok(fallback), parse_hex)
+}
+
 fn parse_hex(hex: &str) -> Result<Color> {
     let hex = hex.trim_start_matches('#');
     anyhow::ensure!(hex.len() == 6, "Invalid hex color: #{hex}");
@@ -252,6 +300,41 @@ mod tests {
         assert_eq!(theme.background, Color::Rgb(30, 30, 46));
     }
 
+    #[test]
+    fn test_label_colors_default_from_palette() {
+        let theme = Theme::default_theme();
+        assert_eq!(theme.user_label_bg, theme.secondary);
+        assert_eq!(theme.assistant_label_bg, theme.success);
+        assert_eq!(theme.user_label_fg, theme.background);
+        assert_eq!(theme.user_text, theme.foreground);
+        assert_eq!(theme.separator, theme.overlay);
+    }
+
+    #[test]
+    fn test_label_colors_override() {
+        let toml = DEFAULT_THEME.replace(
+            "[colors]",
+            "[colors]\nuser_label_bg = \"#ff0000\"\nseparator = \"#00ff00\"",
+        );
+        let theme = Theme::from_toml(&toml).unwrap();
+        assert_eq!(theme.user_label_bg, Color::Rgb(255, 0, 0));
+        assert_eq!(theme.separator, Color::Rgb(0, 255, 0));
+        assert_eq!(theme.assistant_label_bg, theme.success);
+    }
+
+    #[test]
+    fn test_diff_colors_default_and_override() {
+        let theme = Theme::default_theme();
+        assert_eq!(theme.diff_add, theme.success);
+        assert_eq!(theme.diff_remove, theme.error);
+        assert_eq!(theme.diff_context, theme.foreground);
+
+        let toml = DEFAULT_THEME.replace("[colors]", "[colors]\ndiff_add = \"#00ff00\"");
+        let theme = Theme::from_toml(&toml).unwrap();
+        assert_eq!(theme.diff_add, Color::Rgb(0, 255, 0));
+        assert_eq!(theme.diff_remove, theme.error);
+    }
+
     #[test]
     fn test_load_default_theme() {
         let theme = Theme::load("catppuccin-mocha").unwrap();
@@ -1,23 +1,109 @@
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::rc::Rc;
+
 use ratatui::buffer::Buffer;
 use ratatui::layout::Rect;
-use ratatui::style::{Color, Modifier, Style};
+use ratatui::style::{Modifier, Style};
 use ratatui::widgets::Widget;
 use unicode_width::UnicodeWidthChar;
 
 use crate::claude::conversation::{ContentBlock, Conversation, Message, Role};
+use crate::claude::sessions;
+use crate::filerefs;
 use crate::theme::Theme;
+use crate::ui::header::lerp_color;
 use crate::ui::markdown;
 
 /// Spinner frames for animated progress indicator.
 const SPINNER_FRAMES: &[char] = &['⠋', '⠙', '⠹', '⠸', '⠼', '⠴', '⠦', '⠧', '⠇', '⠏'];
 
+/// Shown beside code blocks that Ctrl+Y can copy.
+const COPY_HINT: &str = "Ctrl+Y to copy";
+
+/// Caret drawn after streaming text, and how many frames it stays on (then
+/// off) per blink.
+const STREAM_CARET: char = '▋';
+const CARET_BLINK_FRAMES: u64 = 15;
+
 /// A widget that renders the conversation as a scrollable chat.
 pub struct ClaudePane<'a> {
     conversation: &'a Conversation,
     theme: &'a Theme,
     scroll_offset: usize,
     frame_count: u64,
-    tools_expanded: bool,
+    options: RenderOptions,
+    highlight_line: Option<usize>,
+    fresh_messages: Option<(usize, f64)>,
+    horizontal_offset: usize,
+    cache: Option<&'a LineCache>,
+}
+
+/// Display toggles that change how the conversation is rendered.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RenderOptions {
+    /// Show full tool output instead of collapsed previews.
+    pub tools_expanded: bool,
+    /// Show a relative timestamp ("2m ago") on each message's role label.
+    pub show_timestamps: bool,
+    /// Show thinking blocks; when off each one is a single "reasoning hidden" line.
+    pub show_thinking: bool,
+    /// Which lines of a collapsed tool result to show.
+    pub tool_preview: ToolPreview,
+    /// Mark tool calls with a per-tool emoji instead of `>`.
+    pub tool_glyphs: bool,
+    /// Maximum characters of a tool call's argument in its header.
+    pub tool_header_width: usize,
+    /// Blank columns on each side of the conversation (see `effective_padding`).
+    pub padding: usize,
+    /// Focus mode: only text blocks, without tool calls, results or thinking.
+    pub prose_only: bool,
+    /// Word-wrap text and tool output to the pane; when off, wide lines run
+    /// past the edge and are panned instead (Shift+Left/Right).
+    pub wrap: bool,
+    /// A plan is waiting for the user's answer, so its accept prompt shows.
+    pub plan_pending: bool,
+}
+
+/// The part of a long tool result kept visible while it is collapsed.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ToolPreview {
+    /// The head for successful results, both ends for errors.
+    Auto,
+    Head,
+    Tail,
+    /// The first and last lines with the middle elided.
+    Both,
+}
+
+impl ToolPreview {
+    /// Parse the `tool_preview` config value (validated on load).
+    pub fn from_config(value: &str) -> Self {
+        match value {
+            "head" => Self::Head,
+            "tail" => Self::Tail,
+            "both" => Self::Both,
+            _ => Self::Auto,
+        }
+    }
+}
+
+impl Default for RenderOptions {
+    fn default() -> Self {
+        Self {
+            tools_expanded: false,
+            show_timestamps: false,
+            show_thinking: true,
+            tool_preview: ToolPreview::Auto,
+            tool_glyphs: true,
+            tool_header_width: 60,
+            padding: 0,
+            prose_only: false,
+            wrap: true,
+            plan_pending: false,
+        }
+    }
 }
 
 impl<'a> ClaudePane<'a> {
@@ -32,12 +118,43 @@ impl<'a> ClaudePane<'a> {
             theme,
             scroll_offset,
             frame_count,
-            tools_expanded: false,
+            options: RenderOptions::default(),
+            highlight_line: None,
+            fresh_messages: None,
+            horizontal_offset: 0,
+            cache: None,
         }
     }
 
-    pub fn with_tools_expanded(mut self, expanded: bool) -> Self {
-        self.tools_expanded = expanded;
+    /// Reuse wrapped lines from `cache` instead of re-rendering every frame.
+    pub fn with_cache(mut self, cache: &'a LineCache) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
+    /// Render with these display toggles; they must match the ones used
+    /// for scroll calculations so both agree on the line count.
+    pub fn with_options(mut self, options: RenderOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Highlight the rendered line at this index (e.g. a tool call just jumped to).
+    pub fn with_highlight_line(mut self, line: Option<usize>) -> Self {
+        self.highlight_line = line;
+        self
+    }
+
+    /// Tint the background of messages from this index on, blended toward
+    /// the surface color by the given strength (1.0 full, fading to 0.0).
+    pub fn with_fresh_messages(mut self, fresh: Option<(usize, f64)>) -> Self {
+        self.fresh_messages = fresh;
+        self
+    }
+
+    /// Skip this many columns at the start of every line (panning unwrapped content).
+    pub fn with_horizontal_offset(mut self, columns: usize) -> Self {
+        self.horizontal_offset = columns;
         self
     }
 }
@@ -61,33 +178,60 @@ impl Widget for ClaudePane<'_> {
             }
         }
 
+        let pad = effective_padding(area.width as usize, self.options.padding) as u16;
+        let area = Rect {
+            x: area.x + pad,
+            width: area.width - 2 * pad,
+            ..area
+        };
+
         // Convert conversation to wrapped lines
-        let mut lines = render_conversation_with_options(self.conversation, area.width as usize, self.theme, self.tools_expanded);
+        let rendered = match self.cache {
+            Some(cache) => cache.get(self.conversation, area.width as usize, self.theme, self.options),
+            None => Rc::new(render_conversation_with_offsets(
+                self.conversation,
+                area.width as usize,
+                self.theme,
+                self.options,
+            )),
+        };
+        let lines = &rendered.0;
 
         // Show spinner when waiting for tool execution or streaming
-        if self.conversation.is_awaiting_tool_result() || self.conversation.is_streaming() {
+        let mut spinner = None;
+        if let Some(activity) = activity(self.conversation) {
             let spinner_char =
                 SPINNER_FRAMES[(self.frame_count as usize / 2) % SPINNER_FRAMES.len()];
-            let label = if self.conversation.is_awaiting_tool_result() {
+            let label = if activity == Activity::Tool {
                 let tool = self.conversation.active_tool_name().unwrap_or("tool");
                 let elapsed = self.conversation.tool_elapsed_secs().unwrap_or(0);
                 format!("Running {tool}... ({elapsed}s)")
+            } else if self.conversation.is_thinking() {
+                "Reasoning...".to_string()
             } else {
                 "Thinking...".to_string()
             };
-            lines.push(StyledLine {
+            spinner = Some(StyledLine {
                 spans: vec![StyledSpan {
                     text: format!("  {spinner_char} {label}"),
                     style: Style::default()
-                        .fg(self.theme.info)
+                        .fg(activity.color(self.theme))
                         .add_modifier(Modifier::DIM),
                 }],
             });
         }
 
+        // Blinking caret where streamed text lands; hidden once the text block ends
+        let caret_line = (self.conversation.is_streaming_text()
+            && (self.frame_count / CARET_BLINK_FRAMES).is_multiple_of(2))
+        .then(|| lines.len().checked_sub(1))
+        .flatten();
+
         // Apply scroll offset
+        let total = lines.len() + spinner.iter().len();
         let visible_lines: Vec<&StyledLine> = lines
             .iter()
+            .chain(spinner.iter())
             .skip(self.scroll_offset)
             .take(area.height as usize)
             .collect();
@@ -97,13 +241,41 @@ impl Widget for ClaudePane<'_> {
             if y >= area.bottom() {
                 break;
             }
+            let line_idx = self.scroll_offset + row_idx;
+            let row_bg = if self.highlight_line == Some(line_idx) {
+                Some(self.theme.surface)
+            } else {
+                // Newly arrived messages get a tint that fades out
+                self.fresh_messages
+                    .filter(|&(first, _)| line_idx < lines.len() && message_at_line(&rendered.2, line_idx) >= Some(first))
+                    .map(|(_, strength)| lerp_color(bg, self.theme.surface, strength))
+            };
+            if let Some(row_bg) = row_bg {
+                let highlight = Style::default().bg(row_bg);
+                for x in area.left()..area.right() {
+                    if let Some(cell) = buf.cell_mut((x, y)) {
+                        cell.set_style(highlight);
+                    }
+                }
+            }
+            let bg = row_bg.unwrap_or(bg);
             let mut x = area.left();
+            let mut column = 0;
             for span in &line.spans {
                 for ch in span.text.chars() {
                     let ch_width = ch.width().unwrap_or(0);
                     if ch_width == 0 {
                         continue;
                     }
+                    column += ch_width;
+                    if column <= self.horizontal_offset {
+                        continue;
+                    }
+                    if column - ch_width < self.horizontal_offset {
+                        // A wide char cut by the left edge leaves a blank cell
+                        x += (column - self.horizontal_offset) as u16;
+                        continue;
+                    }
                     if x + ch_width as u16 > area.right() {
                         break;
                     }
@@ -124,6 +296,22 @@ impl Widget for ClaudePane<'_> {
                     x += ch_width as u16;
                 }
             }
+            if caret_line == Some(self.scroll_offset + row_idx) && x < area.right() {
+                if let Some(cell) = buf.cell_mut((x, y)) {
+                    cell.set_char(STREAM_CARET);
+                    cell.set_style(Style::default().fg(self.theme.primary).bg(bg));
+                }
+            }
+        }
+
+        // Scrolled back: say which message is at the top of the pane
+        if self.scroll_offset + (area.height as usize) < total {
+            if let Some(index) = message_at_line(&rendered.2, self.scroll_offset) {
+                let label = format!(" message {} of {} ", index + 1, self.conversation.messages.len());
+                let x = area.right().saturating_sub(label.len() as u16);
+                let style = Style::default().fg(self.theme.input_placeholder).bg(self.theme.surface);
+                crate::ui::put_str(buf, x.max(area.left()), area.top(), area.right(), &label, style);
+            }
         }
     }
 }
@@ -161,76 +349,221 @@ impl StyledLine {
 const USER_PREFIX: &str = "  You  ";
 const ASSISTANT_PREFIX: &str = " Claude ";
 
-fn user_label_style() -> Style {
+fn user_label_style(theme: &Theme) -> Style {
     Style::default()
-        .fg(Color::Rgb(30, 30, 46))
-        .bg(Color::Rgb(137, 180, 250))
+        .fg(theme.user_label_fg)
+        .bg(theme.user_label_bg)
         .add_modifier(Modifier::BOLD)
 }
 
-fn assistant_label_style() -> Style {
+fn assistant_label_style(theme: &Theme) -> Style {
     Style::default()
-        .fg(Color::Rgb(30, 30, 46))
-        .bg(Color::Rgb(166, 227, 161))
+        .fg(theme.assistant_label_fg)
+        .bg(theme.assistant_label_bg)
         .add_modifier(Modifier::BOLD)
 }
 
-fn user_text_style() -> Style {
-    Style::default().fg(Color::Rgb(205, 214, 244))
+fn user_text_style(theme: &Theme) -> Style {
+    Style::default().fg(theme.user_text)
 }
 
-fn separator_style() -> Style {
-    Style::default().fg(Color::Rgb(69, 71, 90))
+fn separator_style(theme: &Theme) -> Style {
+    Style::default().fg(theme.separator)
 }
 
 // ---------------------------------------------------------------------------
 // Conversation → lines
 // ---------------------------------------------------------------------------
 
+/// Whether a spinner line follows the conversation (streaming or running a tool).
+pub fn shows_spinner(conversation: &Conversation) -> bool {
+    activity(conversation).is_some()
+}
+
+/// What the conversation is waiting on.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Activity {
+    /// The model is generating (network).
+    Model,
+    /// A tool is running locally.
+    Tool,
+}
+
+impl Activity {
+    /// Colour of the spinner and header dot: `info` for the model,
+    /// `warning` for a running tool.
+    pub fn color(self, theme: &Theme) -> ratatui::style::Color {
+        match self {
+            Activity::Model => theme.info,
+            Activity::Tool => theme.warning,
+        }
+    }
+}
+
+pub fn activity(conversation: &Conversation) -> Option<Activity> {
+    if conversation.is_awaiting_tool_result() {
+        Some(Activity::Tool)
+    } else if conversation.is_streaming() {
+        Some(Activity::Model)
+    } else {
+        None
+    }
+}
+
 /// Convert the entire conversation into styled, wrapped lines for rendering.
 #[cfg(test)]
 fn render_conversation(conversation: &Conversation, width: usize, theme: &Theme) -> Vec<StyledLine> {
-    render_conversation_with_options(conversation, width, theme, false)
+    render_conversation_with_offsets(conversation, width, theme, RenderOptions::default()).0
 }
 
-fn render_conversation_with_options(conversation: &Conversation, width: usize, theme: &Theme, tools_expanded: bool) -> Vec<StyledLine> {
+/// Render the conversation, also returning the starting line of every
+/// tool call and the (starting line, message index) of every message shown,
+/// both in conversation order.
+fn render_conversation_with_offsets(
+    conversation: &Conversation,
+    width: usize,
+    theme: &Theme,
+    options: RenderOptions,
+) -> RenderedLines {
     let mut lines = Vec::new();
+    let mut tool_lines = Vec::new();
+    let mut message_lines = Vec::new();
     let content_width = width.saturating_sub(2); // 2-char left padding
 
-    for (i, msg) in conversation.messages.iter().enumerate() {
-        if i > 0 {
+    // Archived messages: one summary line each, behind a "load older" hint
+    let summarized = conversation.summarized();
+    if summarized > 0 {
+        let dim = Style::default().fg(theme.input_placeholder);
+        lines.push(StyledLine::plain(
+            &format!("  \u{25B2} {summarized} older messages archived \u{2014} Ctrl+L to load more"),
+            dim.add_modifier(Modifier::ITALIC),
+        ));
+        for (i, msg) in conversation.messages[..summarized].iter().enumerate() {
+            message_lines.push((lines.len(), i));
+            lines.push(render_summary(msg, content_width, dim));
+        }
+    }
+
+    let last = conversation.messages.len().saturating_sub(1);
+    // Ctrl+Y copies code blocks from the last response only
+    let last_response = conversation.messages.iter().rposition(|m| m.role == Role::Assistant);
+    for (i, msg) in conversation.messages.iter().enumerate().skip(summarized) {
+        // Focus mode drops messages made only of tool traffic entirely
+        if options.prose_only && !msg.content.iter().any(|b| matches!(b, ContentBlock::Text(_))) {
+            continue;
+        }
+        if !lines.is_empty() {
             // Separator line between messages
             let sep = "─".repeat(width.min(120));
-            lines.push(StyledLine::plain(&sep, separator_style()));
+            lines.push(StyledLine::plain(&sep, separator_style(theme)));
+        }
+        let start = lines.len();
+        message_lines.push((start, i));
+        let copy_hint = (last_response == Some(i)).then_some(COPY_HINT);
+        render_message(msg, &mut lines, &mut tool_lines, content_width, theme, options, copy_hint);
+        if i == last && conversation.is_streaming() {
+            // Skip the role label so it stays aligned with the others
+            accent_left_edge(&mut lines[start + 1..], theme);
         }
-        render_message(msg, &mut lines, content_width, theme, tools_expanded);
     }
 
-    lines
+    (lines, tool_lines, message_lines)
 }
 
-fn render_message(msg: &Message, lines: &mut Vec<StyledLine>, content_width: usize, theme: &Theme, tools_expanded: bool) {
-    // Role label line
-    match msg.role {
-        Role::User => {
-            lines.push(StyledLine {
-                spans: vec![StyledSpan {
-                    text: USER_PREFIX.to_string(),
-                    style: user_label_style(),
-                }],
-            });
-        }
-        Role::Assistant => {
-            lines.push(StyledLine {
-                spans: vec![StyledSpan {
-                    text: ASSISTANT_PREFIX.to_string(),
-                    style: assistant_label_style(),
-                }],
-            });
+/// Index of the message shown at rendered line `line`, from the message
+/// start offsets of `render_conversation_with_offsets`.
+fn message_at_line(message_lines: &[(usize, usize)], line: usize) -> Option<usize> {
+    let shown = message_lines.partition_point(|&(start, _)| start <= line);
+    message_lines.get(shown.checked_sub(1)?).map(|&(_, index)| index)
+}
+
+/// Mark the message still streaming in with a bar in its left padding, so
+/// it stands out from completed ones.
+fn accent_left_edge(lines: &mut [StyledLine], theme: &Theme) {
+    let bar = StyledSpan {
+        text: "\u{258E}".to_string(),
+        style: Style::default().fg(theme.primary),
+    };
+    for line in lines {
+        match line.spans.first_mut() {
+            Some(first) if first.text.starts_with(' ') => {
+                first.text.remove(0);
+            }
+            Some(first) if !first.text.is_empty() => continue,
+            _ => {}
         }
+        line.spans.insert(0, bar.clone());
+    }
+}
+
+/// One-line summary of an archived message: role, first line of text and
+/// the number of tool calls.
+fn render_summary(msg: &Message, content_width: usize, style: Style) -> StyledLine {
+    let role = match msg.role {
+        Role::User => "You",
+        Role::Assistant => "Claude",
+    };
+    let first_line = msg
+        .content
+        .iter()
+        .find_map(|block| match block {
+            ContentBlock::Text(text) => text.lines().map(str::trim).find(|l| !l.is_empty()),
+            _ => None,
+        })
+        .unwrap_or("");
+    let tools = msg
+        .content
+        .iter()
+        .filter(|block| matches!(block, ContentBlock::ToolUse { .. }))
+        .count();
+    let mut text = format!("  \u{00B7} {role}: {first_line}");
+    if tools > 0 {
+        text.push_str(&format!(" ({tools} tool{})", if tools == 1 { "" } else { "s" }));
     }
+    let (fits, rest) = split_at_width(&text, content_width);
+    let text = if rest.is_empty() {
+        fits.to_string()
+    } else {
+        let (fits, _) = split_at_width(&text, content_width.saturating_sub(1));
+        format!("{fits}\u{2026}")
+    };
+    StyledLine::plain(&text, style)
+}
+
+fn render_message(
+    msg: &Message,
+    lines: &mut Vec<StyledLine>,
+    tool_lines: &mut Vec<usize>,
+    content_width: usize,
+    theme: &Theme,
+    options: RenderOptions,
+    copy_hint: Option<&str>,
+) {
+    // Role label line
+    let mut label = match msg.role {
+        Role::User => StyledLine {
+            spans: vec![StyledSpan {
+                text: USER_PREFIX.to_string(),
+                style: user_label_style(theme),
+            }],
+        },
+        Role::Assistant => StyledLine {
+            spans: vec![StyledSpan {
+                text: ASSISTANT_PREFIX.to_string(),
+                style: assistant_label_style(theme),
+            }],
+        },
+    };
+    if let Some(at) = msg.timestamp.filter(|_| options.show_timestamps) {
+        label.spans.push(StyledSpan {
+            text: format!("  {}", sessions::format_age(at.elapsed().as_secs())),
+            style: Style::default().fg(theme.input_placeholder),
+        });
+    }
+    lines.push(label);
 
     let indent = "  ";
+    let wrap_width = if options.wrap { content_width } else { NOWRAP_WIDTH };
 
     // Build a lookup from tool_use_id → ToolResult for inline rendering
     let tool_results: std::collections::HashMap<&str, &ContentBlock> = msg
@@ -245,6 +578,9 @@ fn render_message(msg: &Message, lines: &mut Vec<StyledLine>, content_width: usi
         .collect();
 
     for block in &msg.content {
+        if options.prose_only && !matches!(block, ContentBlock::Text(_)) {
+            continue;
+        }
         match block {
             ContentBlock::Text(text) => {
                 // Trim leading blank lines to avoid whitespace gap after role label
@@ -253,19 +589,21 @@ fn render_message(msg: &Message, lines: &mut Vec<StyledLine>, content_width: usi
                 match msg.role {
                     Role::Assistant => {
                         // Use full markdown rendering for assistant messages
-                        let md_lines = markdown::render_markdown(trimmed, theme);
+                        let rule_width = content_width.saturating_sub(indent.len());
+                        let md_lines = markdown::render_markdown_with_copy_hint(trimmed, theme, rule_width, copy_hint);
                         for md_line in &md_lines {
                             if md_line.spans.is_empty() {
                                 lines.push(StyledLine::empty());
                             } else {
                                 // Word-wrap each markdown line with indent
-                                wrap_spans(&md_line.spans, indent, lines, content_width);
+                                let spans = linkify_spans(&md_line.spans, theme);
+                                wrap_spans(&spans, indent, lines, wrap_width);
                             }
                         }
                     }
                     Role::User => {
                         // User messages: plain text with wrapping
-                        let style = user_text_style();
+                        let style = user_text_style(theme);
                         for raw_line in trimmed.lines() {
                             if raw_line.is_empty() {
                                 lines.push(StyledLine::empty());
@@ -274,7 +612,7 @@ fn render_message(msg: &Message, lines: &mut Vec<StyledLine>, content_width: usi
                                     text: raw_line.to_string(),
                                     style,
                                 }];
-                                wrap_spans(&spans, indent, lines, content_width);
+                                wrap_spans(&spans, indent, lines, wrap_width);
                             }
                         }
                     }
@@ -286,7 +624,13 @@ fn render_message(msg: &Message, lines: &mut Vec<StyledLine>, content_width: usi
                     tool_results.get(id.as_str()),
                     Some(ContentBlock::ToolResult { is_error: true, .. })
                 );
-                render_tool_use(name, input, result_is_error, lines, theme);
+                tool_lines.push(lines.len());
+                if name == "ExitPlanMode" {
+                    let awaiting = options.plan_pending && !tool_results.contains_key(id.as_str());
+                    render_plan(input, awaiting, lines, content_width, theme);
+                } else {
+                    render_tool_use(name, input, result_is_error, options, lines, theme);
+                }
                 // Render matching tool result inline after the tool use
                 if let Some(ContentBlock::ToolResult {
                     content,
@@ -296,24 +640,78 @@ fn render_message(msg: &Message, lines: &mut Vec<StyledLine>, content_width: usi
                 }) = tool_results.get(id.as_str())
                 {
                     // When tools_expanded is true, force collapsed=false to show full output
-                    let effective_collapsed = if tools_expanded { false } else { *collapsed };
-                    render_tool_result(content, *is_error, effective_collapsed, lines, theme);
+                    let effective_collapsed = if options.tools_expanded { false } else { *collapsed };
+                    let preview = effective_collapsed.then_some(options.tool_preview);
+                    render_tool_result(content, *is_error, preview, wrap_width, lines, theme);
                 }
             }
             ContentBlock::ToolResult { .. } => {
                 // Rendered inline after the matching ToolUse above
             }
             ContentBlock::Thinking(text) => {
-                render_thinking(text, lines, theme);
-            }
-            ContentBlock::Image { media_type } => {
-                render_media_placeholder("Image", media_type, lines, theme);
+                render_thinking(text, options.show_thinking, lines, theme);
             }
+            ContentBlock::Image { media_type, dimensions } => match dimensions {
+                // Returned by a tool (e.g. a browser screenshot)
+                Some((w, h)) => render_media_placeholder("Screenshot", &format!("{w}x{h}"), lines, theme),
+                None => render_media_placeholder("Image", media_type, lines, theme),
+            },
             ContentBlock::Document { doc_type } => {
                 render_media_placeholder("Document", doc_type, lines, theme);
             }
         }
     }
+
+    if msg.is_truncated() {
+        lines.push(StyledLine {
+            spans: vec![StyledSpan {
+                text: format!("{indent}\u{26A0} response truncated \u{2014} ask to continue (Ctrl+N)"),
+                style: Style::default().fg(theme.warning),
+            }],
+        });
+    }
+}
+
+/// The emoji marking a tool call's header.
+pub fn tool_glyph(name: &str) -> &'static str {
+    // All double-width emoji without a variation selector (unlike e.g. ✏️),
+    // so terminals agree with `unicode-width` on how many cells they take
+    match name {
+        "Read" => "📖",
+        "Edit" | "MultiEdit" | "NotebookEdit" => "📝",
+        "Write" => "📄",
+        "Grep" => "🔍",
+        "Glob" => "📂",
+        "Bash" => "💻",
+        "WebFetch" => "🌐",
+        "WebSearch" => "🔎",
+        "Task" => "🤖",
+        "TodoWrite" => "✅",
+        _ => "🔧",
+    }
+}
+
+/// Shorten a tool header argument to `max` characters. Bash commands keep
+/// the program name and the tail, where the telling flags and operands
+/// usually are (`git ... --force origin main`); everything else keeps its head.
+fn truncate_tool_arg(name: &str, arg: &str, max: usize) -> String {
+    let len = arg.chars().count();
+    if len <= max {
+        return arg.to_string();
+    }
+    if name == "Bash" {
+        if let Some(program) = arg.split_whitespace().next() {
+            let head = format!("{program} ... ");
+            let head_len = head.chars().count();
+            // Only worth it if a useful amount of the tail still fits
+            if head_len + 10 <= max {
+                let tail: String = arg.chars().skip(len - (max - head_len)).collect();
+                return format!("{head}{}", tail.trim_start());
+            }
+        }
+    }
+    let head: String = arg.chars().take(max.saturating_sub(3)).collect();
+    format!("{head}...")
 }
 
 /// Render a tool use block with the tool name in accent color and a parsed primary argument.
@@ -322,6 +720,7 @@ fn render_tool_use(
     name: &str,
     input: &str,
     is_error: bool,
+    options: RenderOptions,
     lines: &mut Vec<StyledLine>,
     theme: &Theme,
 ) {
@@ -342,15 +741,11 @@ fn render_tool_use(
     let primary_arg = extract_primary_arg(name, input);
     let display = primary_arg.as_deref().unwrap_or("");
 
-    // Truncate long arguments
-    let truncated = if display.len() > 60 {
-        format!("{}...", &display[..57])
-    } else {
-        display.to_string()
-    };
+    let truncated = truncate_tool_arg(name, display, options.tool_header_width);
 
+    let marker = if options.tool_glyphs { tool_glyph(name) } else { ">" };
     let mut spans = vec![StyledSpan {
-        text: format!("  > {name}"),
+        text: format!("  {marker} {name}"),
         style: name_style,
     }];
     if !truncated.is_empty() {
@@ -379,6 +774,68 @@ fn render_tool_use(
     }
 }
 
+/// Render an ExitPlanMode plan as markdown inside a box drawn in
+/// `theme.primary`. While the plan is awaiting approval an accept prompt
+/// follows the box.
+fn render_plan(
+    input: &str,
+    awaiting_approval: bool,
+    lines: &mut Vec<StyledLine>,
+    content_width: usize,
+    theme: &Theme,
+) {
+    let plan = serde_json::from_str::<serde_json::Value>(input)
+        .ok()
+        .and_then(|v| v.get("plan").and_then(|p| p.as_str()).map(String::from))
+        .unwrap_or_default();
+    let border = Style::default().fg(theme.primary);
+    // "  │ " + text + " │"
+    let inner = content_width.saturating_sub(6).max(10);
+
+    lines.push(StyledLine {
+        spans: vec![
+            StyledSpan { text: "  ╭─ ".to_string(), style: border },
+            StyledSpan {
+                text: "Plan".to_string(),
+                style: border.add_modifier(Modifier::BOLD),
+            },
+            StyledSpan {
+                text: format!(" {}╮", "─".repeat(inner.saturating_sub(5))),
+                style: border,
+            },
+        ],
+    });
+
+    let mut body = Vec::new();
+    for md_line in markdown::render_markdown(plan.trim(), theme, inner) {
+        if md_line.spans.is_empty() {
+            body.push(StyledLine::empty());
+        } else {
+            wrap_spans(&md_line.spans, "", &mut body, inner);
+        }
+    }
+    for line in body {
+        let used: usize = line.spans.iter().map(|s| display_width(&s.text)).sum();
+        let mut spans = vec![StyledSpan { text: "  │ ".to_string(), style: border }];
+        spans.extend(line.spans);
+        spans.push(StyledSpan {
+            text: " ".repeat(inner.saturating_sub(used)),
+            style: Style::default(),
+        });
+        spans.push(StyledSpan { text: " │".to_string(), style: border });
+        lines.push(StyledLine { spans });
+    }
+
+    lines.push(StyledLine::plain(&format!("  ╰{}╯", "─".repeat(inner + 2)), border));
+
+    if awaiting_approval {
+        lines.push(StyledLine::plain(
+            "  Accept plan? [Alt+Y/Alt+N]",
+            Style::default().fg(theme.primary).add_modifier(Modifier::BOLD),
+        ));
+    }
+}
+
 /// Maximum diff lines to show inline before truncating.
 const DIFF_MAX_LINES: usize = 20;
 
@@ -403,31 +860,42 @@ fn render_edit_diff(input: &str, lines: &mut Vec<StyledLine>, theme: &Theme) {
         return;
     }
 
-    let removed_style = Style::default().fg(Color::Rgb(255, 100, 100));
-    let added_style = Style::default().fg(Color::Rgb(100, 255, 100));
+    let removed_style = Style::default().fg(theme.diff_remove);
+    let added_style = Style::default().fg(theme.diff_add);
     let context_style = Style::default()
-        .fg(theme.foreground)
+        .fg(theme.diff_context)
         .add_modifier(Modifier::DIM);
 
     let total = visible.len();
     let mut shown = 0;
+    let mut i = 0;
 
-    for op in &visible {
-        if shown >= DIFF_MAX_LINES {
-            break;
-        }
-        match op {
+    while i < visible.len() && shown < DIFF_MAX_LINES {
+        match visible[i] {
             crate::diff::DiffOp::Equal(line) => {
                 lines.push(StyledLine::plain(&format!("      {line}"), context_style));
             }
-            crate::diff::DiffOp::Remove(line) => {
-                lines.push(StyledLine::plain(&format!("    - {line}"), removed_style));
+            crate::diff::DiffOp::Remove(old_line) => {
+                // Adjacent Remove+Add pair: highlight the changed words within the lines
+                if let Some(crate::diff::DiffOp::Add(new_line)) = visible.get(i + 1) {
+                    let word_ops = crate::diff::diff_words(old_line, new_line);
+                    lines.push(word_diff_line("    - ", &word_ops, false, removed_style));
+                    shown += 1;
+                    if shown < DIFF_MAX_LINES {
+                        lines.push(word_diff_line("    + ", &word_ops, true, added_style));
+                        shown += 1;
+                    }
+                    i += 2;
+                    continue;
+                }
+                lines.push(StyledLine::plain(&format!("    - {old_line}"), removed_style));
             }
             crate::diff::DiffOp::Add(line) => {
                 lines.push(StyledLine::plain(&format!("    + {line}"), added_style));
             }
         }
         shown += 1;
+        i += 1;
     }
 
     if total > DIFF_MAX_LINES {
@@ -441,6 +909,34 @@ fn render_edit_diff(input: &str, lines: &mut Vec<StyledLine>, theme: &Theme) {
     }
 }
 
+/// Build one side of a paired remove/add line with word-level highlighting:
+/// unchanged words are dimmed, changed words are bold. `added` selects which
+/// side (the new line keeps `Add` ops, the old line keeps `Remove` ops).
+fn word_diff_line(
+    prefix: &str,
+    word_ops: &[crate::diff::DiffOp<'_>],
+    added: bool,
+    style: Style,
+) -> StyledLine {
+    let mut spans = vec![StyledSpan {
+        text: prefix.to_string(),
+        style,
+    }];
+    for op in word_ops {
+        let (text, span_style) = match op {
+            crate::diff::DiffOp::Equal(t) => (*t, style.add_modifier(Modifier::DIM)),
+            crate::diff::DiffOp::Remove(t) if !added => (*t, style.add_modifier(Modifier::BOLD)),
+            crate::diff::DiffOp::Add(t) if added => (*t, style.add_modifier(Modifier::BOLD)),
+            _ => continue,
+        };
+        spans.push(StyledSpan {
+            text: text.to_string(),
+            style: span_style,
+        });
+    }
+    StyledLine { spans }
+}
+
 /// Render a content preview for Write tool invocations.
 fn render_write_preview(input: &str, lines: &mut Vec<StyledLine>, theme: &Theme) {
     let value: serde_json::Value = match serde_json::from_str(input) {
@@ -475,11 +971,14 @@ fn render_write_preview(input: &str, lines: &mut Vec<StyledLine>, theme: &Theme)
 /// Maximum visible lines before collapsing tool result output.
 const TOOL_RESULT_COLLAPSE_PREVIEW: usize = 20;
 
-/// Render a tool result block inline below its tool use.
+/// Render a tool result block inline below its tool use, wrapped to
+/// `width`; `preview` is the visible part when collapsed, `None` shows it
+/// in full.
 fn render_tool_result(
     content: &str,
     is_error: bool,
-    collapsed: bool,
+    preview: Option<ToolPreview>,
+    width: usize,
     lines: &mut Vec<StyledLine>,
     theme: &Theme,
 ) {
@@ -507,43 +1006,97 @@ fn render_tool_result(
         });
     }
 
-    let total_lines = content.lines().count();
-
-    if collapsed {
-        // Show first N lines with a "more lines" indicator
-        for line_text in content.lines().take(TOOL_RESULT_COLLAPSE_PREVIEW) {
-            lines.push(StyledLine::plain(
-                &format!("    {line_text}"),
-                content_style,
-            ));
+    // JSON results (MCP tools, web APIs) tend to arrive crammed on one line
+    let pretty = pretty_json(content);
+    let content = pretty.as_deref().unwrap_or(content);
+    // Progress bars and coloured output (npm, docker, cargo) are played
+    // through a terminal emulator so only their final state shows
+    let styled = match pretty {
+        Some(ref json) if !is_error => Some(markdown::highlight_code(json, "json", theme)),
+        None if crate::terminal::has_control_sequences(content) => {
+            resolved_output(content).map(|screen| {
+                screen
+                    .into_iter()
+                    .map(|line| StyledLine {
+                        spans: line
+                            .spans
+                            .into_iter()
+                            .map(|span| StyledSpan { style: content_style.patch(span.style), ..span })
+                            .collect(),
+                    })
+                    .collect::<Vec<_>>()
+            })
         }
-        if total_lines > TOOL_RESULT_COLLAPSE_PREVIEW {
-            let dim_style = Style::default()
-                .fg(theme.info)
-                .add_modifier(Modifier::DIM);
-            lines.push(StyledLine::plain(
-                &format!(
-                    "    ... {} more lines",
-                    total_lines - TOOL_RESULT_COLLAPSE_PREVIEW
-                ),
-                dim_style,
-            ));
-        }
-    } else {
-        for line_text in content.lines() {
-            lines.push(StyledLine::plain(
-                &format!("    {line_text}"),
-                content_style,
-            ));
+        _ => None,
+    };
+
+    let content_lines: Vec<&str> = content.lines().collect();
+    let total_lines = styled.as_ref().map_or(content_lines.len(), Vec::len);
+    // Wide output (tables, long paths) wraps instead of being clipped at the edge
+    let push_line = |lines: &mut Vec<StyledLine>, i: usize| {
+        let spans = match styled.as_ref() {
+            Some(styled) => styled[i].spans.clone(),
+            None => linkify_spans(&StyledLine::plain(content_lines[i], content_style).spans, theme),
+        };
+        if spans.iter().all(|s| s.text.is_empty()) {
+            lines.push(StyledLine::plain("    ", content_style));
+            return;
         }
+        wrap_spans(&spans, "    ", lines, width);
+    };
+
+    let preview = match preview {
+        Some(_) if total_lines <= TOOL_RESULT_COLLAPSE_PREVIEW => None,
+        Some(ToolPreview::Auto) if is_error => Some(ToolPreview::Both),
+        Some(ToolPreview::Auto) => Some(ToolPreview::Head),
+        other => other,
+    };
+    // Lines shown from the start and the end; the rest is elided
+    let (head, tail) = match preview {
+        None => (total_lines, 0),
+        Some(ToolPreview::Tail) => (0, TOOL_RESULT_COLLAPSE_PREVIEW),
+        Some(ToolPreview::Both) => (TOOL_RESULT_COLLAPSE_PREVIEW / 2, TOOL_RESULT_COLLAPSE_PREVIEW / 2),
+        Some(ToolPreview::Head | ToolPreview::Auto) => (TOOL_RESULT_COLLAPSE_PREVIEW, 0),
+    };
+
+    for i in 0..head {
+        push_line(lines, i);
+    }
+    let hidden = total_lines - head - tail;
+    if hidden > 0 {
+        let dim_style = Style::default()
+            .fg(theme.info)
+            .add_modifier(Modifier::DIM);
+        let text = if tail == 0 {
+            format!("    ... {hidden} more lines")
+        } else if head == 0 {
+            format!("    ... {hidden} earlier lines")
+        } else {
+            format!("    ... {hidden} lines hidden ...")
+        };
+        lines.push(StyledLine::plain(&text, dim_style));
+    }
+    for i in total_lines - tail..total_lines {
+        push_line(lines, i);
+    }
+}
+
+/// `content` re-indented when it is a JSON object or array, else `None`.
+fn pretty_json(content: &str) -> Option<String> {
+    let trimmed = content.trim();
+    if !(trimmed.starts_with('{') || trimmed.starts_with('[')) {
+        return None;
     }
+    let value: serde_json::Value = serde_json::from_str(trimmed).ok()?;
+    serde_json::to_string_pretty(&value).ok()
 }
 
 /// Maximum visible lines before collapsing thinking block output.
 const THINKING_COLLAPSE_PREVIEW: usize = 4;
 
-/// Render a thinking block with dim styling and a "Thinking" header.
-fn render_thinking(text: &str, lines: &mut Vec<StyledLine>, theme: &Theme) {
+/// Render a thinking block with dim styling and a "Thinking" header, or a
+/// single "reasoning hidden" line when thinking is switched off.
+fn render_thinking(text: &str, show: bool, lines: &mut Vec<StyledLine>, theme: &Theme) {
     if text.is_empty() {
         return;
     }
@@ -551,6 +1104,10 @@ fn render_thinking(text: &str, lines: &mut Vec<StyledLine>, theme: &Theme) {
     let header_style = Style::default()
         .fg(theme.info)
         .add_modifier(Modifier::DIM | Modifier::ITALIC);
+    if !show {
+        lines.push(StyledLine::plain("  \u{1F4AD} reasoning hidden (Ctrl+B to show)", header_style));
+        return;
+    }
     let content_style = Style::default()
         .fg(theme.foreground)
         .add_modifier(Modifier::DIM | Modifier::ITALIC);
@@ -600,8 +1157,47 @@ fn render_media_placeholder(
     });
 }
 
+/// Highlight `path:line` references so they stand out as openable links
+/// (Ctrl+O lists them and opens the selected one in the editor).
+fn linkify_spans(spans: &[StyledSpan], theme: &Theme) -> Vec<StyledSpan> {
+    let mut out = Vec::with_capacity(spans.len());
+    for span in spans {
+        let refs = filerefs::find_file_refs(&span.text);
+        if refs.is_empty() {
+            out.push(span.clone());
+            continue;
+        }
+        let link_style = span
+            .style
+            .fg(theme.info)
+            .remove_modifier(Modifier::DIM)
+            .add_modifier(Modifier::UNDERLINED);
+        let mut pos = 0;
+        for (range, _) in refs {
+            if range.start > pos {
+                out.push(StyledSpan {
+                    text: span.text[pos..range.start].to_string(),
+                    style: span.style,
+                });
+            }
+            out.push(StyledSpan {
+                text: span.text[range.clone()].to_string(),
+                style: link_style,
+            });
+            pos = range.end;
+        }
+        if pos < span.text.len() {
+            out.push(StyledSpan {
+                text: span.text[pos..].to_string(),
+                style: span.style,
+            });
+        }
+    }
+    out
+}
+
 /// Extract the most relevant argument from a tool's JSON input.
-fn extract_primary_arg(tool_name: &str, input: &str) -> Option<String> {
+pub fn extract_primary_arg(tool_name: &str, input: &str) -> Option<String> {
     let value: serde_json::Value = serde_json::from_str(input).ok()?;
     let obj = value.as_object()?;
 
@@ -768,9 +1364,173 @@ fn split_at_width(s: &str, max_width: usize) -> (&str, &str) {
     (s, "")
 }
 
-/// Calculate total number of rendered lines for scroll calculations.
-pub fn total_lines_with_options(conversation: &Conversation, width: usize, theme: &Theme, tools_expanded: bool) -> usize {
-    render_conversation_with_options(conversation, width, theme, tools_expanded).len()
+/// Wrapped lines, tool call start lines and (start line, message index) pairs.
+type RenderedLines = (Vec<StyledLine>, Vec<usize>, Vec<(usize, usize)>);
+
+/// Number of (conversation state, options) renders kept. The pane and the
+/// scroll maths ask at different widths, so one entry would thrash.
+const LINE_CACHE_ENTRIES: usize = 4;
+
+/// Tool outputs kept already played through the terminal emulator.
+const RESOLVED_CACHE_ENTRIES: usize = 32;
+
+thread_local! {
+    /// [`crate::terminal::resolve_output`] results by hash of the output, so
+    /// a full re-render (on every streaming delta) doesn't replay them.
+    static RESOLVED_OUTPUTS: RefCell<VecDeque<(u64, Option<Vec<StyledLine>>)>> = const { RefCell::new(VecDeque::new()) };
+}
+
+/// Cached [`crate::terminal::resolve_output`].
+fn resolved_output(content: &str) -> Option<Vec<StyledLine>> {
+    let mut hasher = DefaultHasher::new();
+    content.hash(&mut hasher);
+    let key = hasher.finish();
+    RESOLVED_OUTPUTS.with(|cache| {
+        let mut cache = cache.borrow_mut();
+        if let Some((_, resolved)) = cache.iter().find(|(k, _)| *k == key) {
+            return resolved.clone();
+        }
+        let resolved = crate::terminal::resolve_output(content);
+        if cache.len() == RESOLVED_CACHE_ENTRIES {
+            cache.pop_front();
+        }
+        cache.push_back((key, resolved.clone()));
+        resolved
+    })
+}
+
+/// Layout width when wrapping is off: wide enough that no real line wraps.
+const NOWRAP_WIDTH: usize = 10_000;
+
+/// Narrowest the conversation text may get from padding alone.
+const MIN_PADDED_WIDTH: usize = 40;
+
+/// The padding actually applied on each side of a pane `width` columns wide:
+/// `padding`, reduced so the content keeps at least `MIN_PADDED_WIDTH`
+/// columns (none at all on terminals narrower than that).
+pub fn effective_padding(width: usize, padding: usize) -> usize {
+    padding.min(width.saturating_sub(MIN_PADDED_WIDTH) / 2)
+}
+
+/// Everything the wrapped lines depend on. Messages only change by being
+/// appended to, by the last message growing, or by being archived, so the
+/// count, the archive state and a hash of the last message identify them.
+#[derive(PartialEq)]
+struct CacheKey {
+    messages: usize,
+    archived: usize,
+    summarized: usize,
+    last_message: u64,
+    folds: u64,
+    width: usize,
+    theme: String,
+    options: RenderOptions,
+    /// The last message is drawn with an accent while it streams.
+    streaming: bool,
+    /// Wall-clock second while timestamps ("2m ago") are shown.
+    clock: Option<u64>,
+}
+
+impl CacheKey {
+    fn new(conversation: &Conversation, width: usize, theme: &Theme, options: RenderOptions) -> Self {
+        let last_message = conversation.messages.last().map_or(0, |msg| {
+            let mut hasher = DefaultHasher::new();
+            msg.hash(&mut hasher);
+            hasher.finish()
+        });
+        let clock = options.show_timestamps.then(|| {
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map_or(0, |d| d.as_secs())
+        });
+        Self {
+            messages: conversation.messages.len(),
+            archived: conversation.archived(),
+            summarized: conversation.summarized(),
+            last_message,
+            folds: conversation.fold_revision(),
+            width,
+            theme: theme.name.clone(),
+            options,
+            streaming: conversation.is_streaming(),
+            clock,
+        }
+    }
+}
+
+/// Wrapped conversation lines memoised across frames, so an unchanged
+/// conversation is not re-wrapped on every tick.
+#[derive(Default)]
+pub struct LineCache {
+    entries: RefCell<VecDeque<(CacheKey, Rc<RenderedLines>)>>,
+}
+
+impl LineCache {
+    fn get(
+        &self,
+        conversation: &Conversation,
+        width: usize,
+        theme: &Theme,
+        options: RenderOptions,
+    ) -> Rc<RenderedLines> {
+        let key = CacheKey::new(conversation, width, theme, options);
+        let mut entries = self.entries.borrow_mut();
+        if let Some((_, rendered)) = entries.iter().find(|(k, _)| *k == key) {
+            return rendered.clone();
+        }
+        let rendered = Rc::new(render_conversation_with_offsets(conversation, width, theme, options));
+        if entries.len() == LINE_CACHE_ENTRIES {
+            entries.pop_front();
+        }
+        entries.push_back((key, rendered.clone()));
+        rendered
+    }
+
+    /// Width of the widest rendered line, for clamping horizontal panning.
+    pub fn max_line_width(&self, conversation: &Conversation, width: usize, theme: &Theme, options: RenderOptions) -> usize {
+        self.get(conversation, width, theme, options)
+            .0
+            .iter()
+            .map(|line| line.spans.iter().map(|s| display_width(&s.text)).sum())
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Total number of rendered lines, for scroll calculations.
+    pub fn total_lines(&self, conversation: &Conversation, width: usize, theme: &Theme, options: RenderOptions) -> usize {
+        self.get(conversation, width, theme, options).0.len()
+    }
+
+    /// Plain text of the `height` rendered lines starting at `offset`: what
+    /// is on screen at that scroll position.
+    pub fn visible_text(
+        &self,
+        conversation: &Conversation,
+        width: usize,
+        theme: &Theme,
+        options: RenderOptions,
+        offset: usize,
+        height: usize,
+    ) -> Vec<String> {
+        self.get(conversation, width, theme, options)
+            .0
+            .iter()
+            .skip(offset)
+            .take(height)
+            .map(|line| line.spans.iter().map(|s| s.text.as_str()).collect::<String>().trim_end().to_string())
+            .collect()
+    }
+
+    /// Starting rendered line of every tool call, for jumping between them.
+    pub fn tool_use_lines(
+        &self,
+        conversation: &Conversation,
+        width: usize,
+        theme: &Theme,
+        options: RenderOptions,
+    ) -> Vec<usize> {
+        self.get(conversation, width, theme, options).1.clone()
+    }
 }
 
 #[cfg(test)]
@@ -805,6 +1565,8 @@ mod tests {
         let theme = crate::theme::Theme::default_theme();
         conv.messages.push(Message {
             role: Role::Assistant,
+            timestamp: None,
+            stop_reason: None,
             content: vec![ContentBlock::Text("Hi there".to_string())],
         });
         let lines = render_conversation(&conv, 80, &theme);
@@ -813,12 +1575,27 @@ mod tests {
         assert!(label.contains("Claude"));
     }
 
+    #[test]
+    fn test_timestamp_on_label_when_enabled() {
+        let mut conv = Conversation::new();
+        let theme = crate::theme::Theme::default_theme();
+        conv.push_user_message("Hello".to_string());
+        let label = |show: bool| -> String {
+            let (lines, ..) = render_conversation_with_offsets(&conv, 80, &theme, RenderOptions { show_timestamps: show, ..Default::default() });
+            lines[0].spans.iter().map(|s| s.text.as_str()).collect()
+        };
+        assert!(label(true).ends_with("just now"));
+        assert!(!label(false).contains("just now"));
+    }
+
     #[test]
     fn test_code_block_rendering() {
         let mut conv = Conversation::new();
         let theme = crate::theme::Theme::default_theme();
         conv.messages.push(Message {
             role: Role::Assistant,
+            timestamp: None,
+            stop_reason: None,
             content: vec![ContentBlock::Text(
                 "Here is code:\n```rust\nfn main() {}\n```\nDone.".to_string(),
             )],
@@ -834,6 +1611,8 @@ mod tests {
         let theme = crate::theme::Theme::default_theme();
         conv.messages.push(Message {
             role: Role::Assistant,
+            timestamp: None,
+            stop_reason: None,
             content: vec![ContentBlock::ToolUse {
                 id: "t1".to_string(),
                 name: "Bash".to_string(),
@@ -850,12 +1629,45 @@ mod tests {
         assert!(all_text.contains("ls"), "Expected command 'ls' in output");
     }
 
+    #[test]
+    fn test_tool_use_lines_point_at_tool_headers() {
+        let mut conv = Conversation::new();
+        let theme = crate::theme::Theme::default_theme();
+        conv.messages.push(Message {
+            role: Role::Assistant,
+            timestamp: None,
+            stop_reason: None,
+            content: vec![
+                ContentBlock::Text("Let me look.\n\nFirst the tree.".to_string()),
+                ContentBlock::ToolUse {
+                    id: "t1".to_string(),
+                    name: "Bash".to_string(),
+                    input: "{\"command\":\"ls\"}".to_string(),
+                },
+                ContentBlock::Text("Now the file.".to_string()),
+                ContentBlock::ToolUse {
+                    id: "t2".to_string(),
+                    name: "Read".to_string(),
+                    input: "{\"file_path\":\"src/main.rs\"}".to_string(),
+                },
+            ],
+        });
+        let (lines, tool_lines, _) = render_conversation_with_offsets(&conv, 80, &theme, RenderOptions::default());
+        assert_eq!(tool_lines.len(), 2);
+        let header = |i: usize| -> String { lines[i].spans.iter().map(|s| s.text.as_str()).collect() };
+        assert!(header(tool_lines[0]).contains("Bash"));
+        assert!(header(tool_lines[1]).contains("Read"));
+        assert_eq!(LineCache::default().tool_use_lines(&conv, 80, &theme, RenderOptions::default()), tool_lines);
+    }
+
     #[test]
     fn test_tool_use_read_rendering() {
         let mut conv = Conversation::new();
         let theme = crate::theme::Theme::default_theme();
         conv.messages.push(Message {
             role: Role::Assistant,
+            timestamp: None,
+            stop_reason: None,
             content: vec![ContentBlock::ToolUse {
                 id: "t2".to_string(),
                 name: "Read".to_string(),
@@ -878,6 +1690,8 @@ mod tests {
         let theme = crate::theme::Theme::default_theme();
         conv.messages.push(Message {
             role: Role::Assistant,
+            timestamp: None,
+            stop_reason: None,
             content: vec![
                 ContentBlock::ToolUse {
                     id: "t1".to_string(),
@@ -909,6 +1723,8 @@ mod tests {
         let long_output = (0..30).map(|i| format!("line {i}")).collect::<Vec<_>>().join("\n");
         conv.messages.push(Message {
             role: Role::Assistant,
+            timestamp: None,
+            stop_reason: None,
             content: vec![
                 ContentBlock::ToolUse {
                     id: "t1".to_string(),
@@ -935,12 +1751,156 @@ mod tests {
         assert!(all_text.contains("more lines"), "Expected 'more lines' indicator");
     }
 
+    #[test]
+    fn test_tool_result_preview_modes() {
+        let theme = crate::theme::Theme::default_theme();
+        let output = (0..30).map(|i| format!("line {i}")).collect::<Vec<_>>().join("\n");
+        let render = |is_error: bool, preview: ToolPreview| -> Vec<String> {
+            let mut lines = Vec::new();
+            render_tool_result(&output, is_error, Some(preview), 80, &mut lines, &theme);
+            lines
+                .iter()
+                .map(|l| l.spans.iter().map(|s| s.text.trim()).collect::<String>())
+                .filter(|t| t != "✗ Error")
+                .collect()
+        };
+
+        let tail = render(false, ToolPreview::Tail);
+        assert_eq!(tail[0], "... 10 earlier lines");
+        assert_eq!(tail[1], "line 10");
+        assert_eq!(tail.last().unwrap(), "line 29");
+
+        // Auto keeps both ends of errors, where the cause usually is
+        let both = render(true, ToolPreview::Auto);
+        assert_eq!(both.len(), 21);
+        assert_eq!(both[9], "line 9");
+        assert_eq!(both[10], "... 10 lines hidden ...");
+        assert_eq!(both[11], "line 20");
+        assert_eq!(both.last().unwrap(), "line 29");
+
+        let head = render(false, ToolPreview::Auto);
+        assert_eq!(head[19], "line 19");
+        assert_eq!(head[20], "... 10 more lines");
+
+        // Short results are never elided
+        let mut lines = Vec::new();
+        render_tool_result("one\ntwo", false, Some(ToolPreview::Tail), 80, &mut lines, &theme);
+        assert_eq!(lines.len(), 2);
+    }
+
+    #[test]
+    fn test_tool_result_wraps_wide_lines() {
+        let theme = crate::theme::Theme::default_theme();
+        let row = (0..12).map(|i| format!("col{i:02}")).collect::<Vec<_>>().join(" ");
+        let mut lines = Vec::new();
+        render_tool_result(&format!("{row}\n\nshort"), false, None, 40, &mut lines, &theme);
+        let text: Vec<String> = lines.iter().map(|l| l.spans.iter().map(|s| s.text.as_str()).collect()).collect();
+        assert!(text.iter().all(|t| display_width(t) <= 40), "{text:?}");
+        assert!(text.iter().all(|t| t.starts_with("    ")), "{text:?}");
+        // Nothing is lost to clipping: every column survives in order
+        let words: Vec<&str> = text.iter().flat_map(|t| t.split_whitespace()).collect();
+        assert_eq!(words.len(), 13);
+        assert_eq!(words[11], "col11");
+        assert_eq!(text.len(), 4);
+        assert_eq!(text[2], "    ");
+    }
+
+    #[test]
+    fn test_copy_hint_only_on_last_response() {
+        let mut conv = Conversation::new();
+        let theme = crate::theme::Theme::default_theme();
+        for code in ["```rust\nfn a() {}\n```", "```\nls\n```"] {
+            conv.messages.push(Message {
+                role: Role::Assistant,
+                timestamp: None,
+                stop_reason: None,
+                content: vec![ContentBlock::Text(code.to_string())],
+            });
+        }
+        let text: Vec<String> = render_conversation(&conv, 80, &theme)
+            .iter()
+            .map(|l| l.spans.iter().map(|s| s.text.as_str()).collect::<String>().trim().to_string())
+            .collect();
+        assert!(text.contains(&"rust".to_string()));
+        assert!(text.contains(&"code  [Ctrl+Y to copy]".to_string()));
+    }
+
+    #[test]
+    fn test_fresh_messages_tinted() {
+        let mut conv = Conversation::new();
+        let theme = crate::theme::Theme::default_theme();
+        conv.push_user_message("old".to_string());
+        conv.push_user_message("new".to_string());
+        let area = Rect::new(0, 0, 40, 10);
+        let render = |fresh: Option<(usize, f64)>| {
+            let mut buf = Buffer::empty(area);
+            ClaudePane::new(&conv, &theme, 0, 0).with_fresh_messages(fresh).render(area, &mut buf);
+            buf
+        };
+        let row_bg = |fresh, y: u16| render(fresh)[(30, y)].bg;
+        let plain = render(None);
+        let new_row = (0..10)
+            .find(|&y| (0..40).map(|x| plain[(x, y)].symbol()).collect::<String>().contains("new"))
+            .unwrap();
+
+        assert_eq!(row_bg(Some((1, 1.0)), new_row), theme.surface);
+        assert_eq!(row_bg(Some((1, 1.0)), 0), theme.background, "older message untouched");
+        assert_eq!(row_bg(Some((1, 0.0)), new_row), theme.background);
+    }
+
+    #[test]
+    fn test_tool_result_resolves_progress_output() {
+        let theme = crate::theme::Theme::default_theme();
+        let output = "added 3 packages\r\x1b[2K[1/3] fetch\r\x1b[2K[3/3] link\n\x1b[31mwarn\x1b[0m deprecated\n";
+        let mut lines = Vec::new();
+        render_tool_result(output, false, None, 80, &mut lines, &theme);
+        let text: Vec<String> = lines.iter().map(|l| l.spans.iter().map(|s| s.text.as_str()).collect()).collect();
+        assert_eq!(text, ["    [3/3] link", "    warn deprecated"]);
+        let warn = lines[1].spans.iter().find(|s| s.text == "warn").unwrap();
+        assert_eq!(warn.style.fg, Some(ratatui::style::Color::Indexed(1)));
+        // Uncoloured text keeps the dimmed tool output style
+        assert!(lines[0].spans[1].style.add_modifier.contains(Modifier::DIM));
+    }
+
+    #[test]
+    fn test_tool_result_json_pretty_printed() {
+        let theme = crate::theme::Theme::default_theme();
+        let text = |lines: &[StyledLine]| -> Vec<String> {
+            lines.iter().map(|l| l.spans.iter().map(|s| s.text.as_str()).collect()).collect()
+        };
+
+        let mut lines = Vec::new();
+        render_tool_result(r#"{"name":"sc","tags":["tui","rust"]}"#, false, None, 80, &mut lines, &theme);
+        let rendered = text(&lines);
+        assert_eq!(rendered.len(), 7);
+        assert_eq!(rendered[1], r#"      "name": "sc","#);
+        assert_eq!(rendered[3], r#"        "tui","#);
+        // Highlighted with several colours, not one plain content style
+        let colours: std::collections::HashSet<_> = lines[1].spans.iter().filter_map(|s| s.style.fg).collect();
+        assert!(colours.len() > 1, "Expected syntax highlighting: {:?}", lines[1].spans);
+
+        // Collapsing counts the pretty-printed lines
+        let big = serde_json::to_string(&(0..30).collect::<Vec<_>>()).unwrap();
+        let mut lines = Vec::new();
+        render_tool_result(&big, false, Some(ToolPreview::Auto), 80, &mut lines, &theme);
+        assert_eq!(text(&lines).last().unwrap().trim(), "... 12 more lines");
+
+        // Not JSON, or just a JSON scalar: left verbatim
+        for content in ["{not json}", "42"] {
+            let mut lines = Vec::new();
+            render_tool_result(content, false, None, 80, &mut lines, &theme);
+            assert_eq!(text(&lines), vec![format!("    {content}")]);
+        }
+    }
+
     #[test]
     fn test_tool_result_error_styling() {
         let mut conv = Conversation::new();
         let theme = crate::theme::Theme::default_theme();
         conv.messages.push(Message {
             role: Role::Assistant,
+            timestamp: None,
+            stop_reason: None,
             content: vec![
                 ContentBlock::ToolUse {
                     id: "t1".to_string(),
@@ -997,6 +1957,8 @@ mod tests {
         let theme = crate::theme::Theme::default_theme();
         conv.messages.push(Message {
             role: Role::Assistant,
+            timestamp: None,
+            stop_reason: None,
             content: vec![
                 ContentBlock::ToolUse {
                     id: "t1".to_string(),
@@ -1023,6 +1985,8 @@ mod tests {
         let theme = crate::theme::Theme::default_theme();
         conv.messages.push(Message {
             role: Role::Assistant,
+            timestamp: None,
+            stop_reason: None,
             content: vec![ContentBlock::Text(long_text.to_string())],
         });
         // Narrow width to force wrapping
@@ -1031,12 +1995,308 @@ mod tests {
         assert!(lines.len() > 3, "Expected wrapping, got {} lines", lines.len());
     }
 
+    #[test]
+    fn test_unwrapped_lines_pan_horizontally() {
+        let mut conv = Conversation::new();
+        let theme = crate::theme::Theme::default_theme();
+        let row = "| col | ".to_string() + &"x".repeat(60) + " | end |";
+        conv.messages.push(Message {
+            role: Role::User,
+            timestamp: None,
+            stop_reason: None,
+            content: vec![ContentBlock::Text(row.clone())],
+        });
+        let options = RenderOptions { wrap: false, ..RenderOptions::default() };
+        let (lines, ..) = render_conversation_with_offsets(&conv, 40, &theme, options);
+        assert_eq!(lines.len(), 2);
+        let cache = LineCache::default();
+        assert_eq!(cache.max_line_width(&conv, 40, &theme, options), row.len() + 2);
+
+        let area = Rect::new(0, 0, 40, 3);
+        let mut buf = Buffer::empty(area);
+        ClaudePane::new(&conv, &theme, 0, 0)
+            .with_options(options)
+            .with_horizontal_offset(row.len() + 2 - 40)
+            .render(area, &mut buf);
+        let second: String = (0..area.width).map(|x| buf.cell((x, 1)).unwrap().symbol().to_string()).collect();
+        assert!(second.ends_with("x | end |"), "got {second:?}");
+    }
+
+    #[test]
+    fn test_inline_code_survives_wrapping() {
+        let mut conv = Conversation::new();
+        let theme = crate::theme::Theme::default_theme();
+        conv.messages.push(Message {
+            role: Role::Assistant,
+            timestamp: None,
+            stop_reason: None,
+            content: vec![ContentBlock::Text(
+                "a fairly long sentence that mentions `render_markdown` near the end".to_string(),
+            )],
+        });
+        let lines = render_conversation(&conv, 40, &theme);
+        let code = lines
+            .iter()
+            .flat_map(|l| l.spans.iter())
+            .find(|s| s.text.contains("render_markdown"))
+            .expect("inline code span");
+        assert!(!code.text.contains('`'));
+        assert_eq!(code.style.fg, Some(theme.accent));
+        assert_eq!(code.style.bg, Some(theme.surface));
+    }
+
+    #[test]
+    fn test_line_cache_rebuilds_only_on_change() {
+        let theme = crate::theme::Theme::default_theme();
+        let cache = LineCache::default();
+        let mut conv = Conversation::new();
+        conv.push_user_message("hello".to_string());
+
+        let first = cache.get(&conv, 80, &theme, RenderOptions::default());
+        assert!(Rc::ptr_eq(&first, &cache.get(&conv, 80, &theme, RenderOptions::default())));
+        assert!(!Rc::ptr_eq(&first, &cache.get(&conv, 40, &theme, RenderOptions::default())));
+        // Both widths stay cached
+        assert!(Rc::ptr_eq(&first, &cache.get(&conv, 80, &theme, RenderOptions::default())));
+
+        // A streamed delta grows the last message
+        if let Some(ContentBlock::Text(text)) = conv.messages[0].content.last_mut() {
+            text.push_str(" world");
+        }
+        let grown = cache.get(&conv, 80, &theme, RenderOptions::default());
+        assert!(!Rc::ptr_eq(&first, &grown));
+        assert_eq!(grown.0.len(), render_conversation(&conv, 80, &theme).len());
+        assert!(grown.0.iter().flat_map(|l| &l.spans).any(|s| s.text.contains("hello world")));
+    }
+
+    #[test]
+    fn test_streaming_message_has_left_accent() {
+        use crate::claude::events::{ContentBlockType, Delta, StreamEvent};
+
+        let theme = crate::theme::Theme::default_theme();
+        let cache = LineCache::default();
+        let mut conv = Conversation::new();
+        conv.push_user_message("hello".to_string());
+        conv.apply_event(&StreamEvent::MessageStart {
+            message_id: "msg_1".to_string(),
+            model: "claude-sonnet-4-5".to_string(),
+            usage: None,
+        });
+        conv.apply_event(&StreamEvent::ContentBlockStart { index: 0, block_type: ContentBlockType::Text });
+        conv.apply_event(&StreamEvent::ContentBlockDelta {
+            index: 0,
+            delta: Delta::TextDelta("first\n\nsecond".to_string()),
+        });
+        let starts_with_bar = |line: &StyledLine| line.spans.first().is_some_and(|s| s.text == "\u{258E}");
+
+        let streaming = cache.get(&conv, 80, &theme, RenderOptions::default());
+        let label = streaming.0.iter().rposition(|l| l.spans.iter().any(|s| s.text.contains("Claude"))).unwrap();
+        assert!(!starts_with_bar(&streaming.0[label]));
+        assert!(streaming.0[label + 1..].iter().all(starts_with_bar));
+        // The bar replaces padding, so the text stays in place
+        let text = |line: &StyledLine| line.spans.iter().map(|s| s.text.as_str()).collect::<String>();
+        assert!(streaming.0[label + 1..].iter().any(|l| text(l) == "\u{258E} first"));
+        assert!(!streaming.0[..label].iter().any(starts_with_bar));
+
+        conv.apply_event(&StreamEvent::MessageStop);
+        let done = cache.get(&conv, 80, &theme, RenderOptions::default());
+        assert!(!done.0.iter().any(starts_with_bar));
+    }
+
+    #[test]
+    fn test_archived_messages_render_as_summaries() {
+        let theme = crate::theme::Theme::default_theme();
+        let mut conv = Conversation::new();
+        conv.push_user_message("\nfirst question\nmore detail".to_string());
+        conv.messages.push(Message {
+            role: Role::Assistant,
+            timestamp: None,
+            stop_reason: None,
+            content: vec![
+                ContentBlock::Text("first answer".to_string()),
+                ContentBlock::ToolUse {
+                    id: "t1".to_string(),
+                    name: "Bash".to_string(),
+                    input: "{\"command\":\"ls\"}".to_string(),
+                },
+            ],
+        });
+        conv.push_user_message("latest question".to_string());
+        conv.archive(1);
+
+        let (lines, tool_lines, _) = render_conversation_with_offsets(&conv, 80, &theme, RenderOptions::default());
+        let text: Vec<String> = lines
+            .iter()
+            .map(|l| l.spans.iter().map(|s| s.text.as_str()).collect())
+            .collect();
+        assert!(text[0].contains("2 older messages archived"));
+        assert_eq!(text[1], "  \u{00B7} You: first question");
+        assert_eq!(text[2], "  \u{00B7} Claude: first answer (1 tool)");
+        assert!(text.iter().any(|t| t.contains("latest question")));
+        assert!(tool_lines.is_empty());
+
+        conv.load_older(1);
+        let lines = render_conversation(&conv, 80, &theme);
+        assert!(lines.iter().flat_map(|l| &l.spans).any(|s| s.text.contains("💻 Bash")));
+    }
+
+    #[test]
+    fn test_plan_renders_in_box_until_answered() {
+        let theme = crate::theme::Theme::default_theme();
+        let mut conv = Conversation::new();
+        conv.messages.push(Message {
+            role: Role::Assistant,
+            timestamp: None,
+            stop_reason: None,
+            content: vec![ContentBlock::ToolUse {
+                id: "p1".to_string(),
+                name: "ExitPlanMode".to_string(),
+                input: "{\"plan\":\"1. Add the parser\\n2. Write tests\"}".to_string(),
+            }],
+        });
+        let text = |l: &StyledLine| l.spans.iter().map(|s| s.text.as_str()).collect::<String>();
+        // No prompt without a pending plan (e.g. in a resumed session)
+        let lines = render_conversation(&conv, 40, &theme);
+        assert!(!lines.iter().any(|l| text(l).contains("Accept plan?")));
+
+        let pending = RenderOptions { plan_pending: true, ..Default::default() };
+        let (lines, ..) = render_conversation_with_offsets(&conv, 40, &theme, pending);
+        let top = lines.iter().position(|l| text(l).contains("╭─ Plan")).unwrap();
+        assert_eq!(lines[top].spans[0].style.fg, Some(theme.primary));
+        let width = display_width(&text(&lines[top]));
+        let body: Vec<String> = lines[top + 1..]
+            .iter()
+            .map(text)
+            .take_while(|t| t.starts_with("  │"))
+            .collect();
+        assert!(body.iter().any(|t| t.contains("Add the parser")));
+        assert!(body.iter().all(|t| display_width(t) == width && t.ends_with(" │")));
+        assert!(text(&lines[top + body.len() + 1]).starts_with("  ╰"));
+        assert!(text(lines.last().unwrap()).contains("Accept plan? [Alt+Y/Alt+N]"));
+
+        conv.messages[0].content.push(ContentBlock::ToolResult {
+            tool_use_id: "p1".to_string(),
+            content: "User has approved your plan.".to_string(),
+            is_error: false,
+            collapsed: false,
+        });
+        let (lines, ..) = render_conversation_with_offsets(&conv, 40, &theme, pending);
+        assert!(!lines.iter().any(|l| text(l).contains("Accept plan?")));
+    }
+
+    #[test]
+    fn test_truncated_response_shows_indicator() {
+        let theme = crate::theme::Theme::default_theme();
+        let mut conv = Conversation::new();
+        conv.messages.push(Message {
+            role: Role::Assistant,
+            timestamp: None,
+            stop_reason: Some("max_tokens".to_string()),
+            content: vec![ContentBlock::Text("Once upon a".to_string())],
+        });
+        let lines = render_conversation(&conv, 80, &theme);
+        let last = lines.last().unwrap();
+        assert!(last.spans[0].text.contains("response truncated"));
+        assert_eq!(last.spans[0].style.fg, Some(theme.warning));
+
+        conv.messages[0].stop_reason = Some("end_turn".to_string());
+        let lines = render_conversation(&conv, 80, &theme);
+        assert!(!lines.iter().flat_map(|l| &l.spans).any(|s| s.text.contains("truncated")));
+    }
+
     #[test]
     fn test_display_width() {
         assert_eq!(display_width("hello"), 5);
         assert_eq!(display_width(""), 0);
     }
 
+    #[test]
+    fn test_tool_glyphs() {
+        for name in ["Read", "Edit", "Write", "Grep", "Glob", "Bash", "WebFetch", "WebSearch", "Task", "TodoWrite", "mcp__x"] {
+            assert_eq!(display_width(tool_glyph(name)), 2, "{name}");
+        }
+        let theme = crate::theme::Theme::default_theme();
+        let header = |glyphs| {
+            let mut lines = Vec::new();
+            let options = RenderOptions { tool_glyphs: glyphs, ..Default::default() };
+            render_tool_use("Read", r#"{"file_path":"src/main.rs"}"#, false, options, &mut lines, &theme);
+            lines[0].spans.iter().map(|s| s.text.as_str()).collect::<String>()
+        };
+        assert_eq!(header(true), "  📖 Read: src/main.rs");
+        assert_eq!(header(false), "  > Read: src/main.rs");
+    }
+
+    #[test]
+    fn test_activity_distinguishes_model_and_tool() {
+        use crate::claude::events::{ContentBlockType, StreamEvent};
+
+        let mut conv = Conversation::new();
+        assert_eq!(activity(&conv), None);
+        conv.apply_event(&StreamEvent::MessageStart {
+            message_id: "m1".to_string(),
+            model: "claude".to_string(),
+            usage: None,
+        });
+        assert_eq!(activity(&conv), Some(Activity::Model));
+        conv.apply_event(&StreamEvent::ContentBlockStart {
+            index: 0,
+            block_type: ContentBlockType::ToolUse { id: "t1".to_string(), name: "Bash".to_string() },
+        });
+        conv.apply_event(&StreamEvent::ContentBlockStop { index: 0 });
+        conv.apply_event(&StreamEvent::MessageStop);
+        assert_eq!(activity(&conv), Some(Activity::Tool));
+
+        let theme = crate::theme::Theme::default_theme();
+        let area = Rect::new(0, 0, 40, 5);
+        let mut buf = Buffer::empty(area);
+        ClaudePane::new(&conv, &theme, 0, 0).render(area, &mut buf);
+        let spinner_row = (0..area.height)
+            .find(|&y| {
+                (0..area.width).map(|x| buf.cell((x, y)).unwrap().symbol().to_string()).collect::<String>().contains("Running Bash")
+            })
+            .expect("spinner line");
+        assert_eq!(buf.cell((4, spinner_row)).unwrap().fg, theme.warning);
+    }
+
+    #[test]
+    fn test_conversation_padding() {
+        assert_eq!(effective_padding(120, 8), 8);
+        assert_eq!(effective_padding(50, 8), 5);
+        assert_eq!(effective_padding(30, 8), 0);
+
+        let mut conv = Conversation::new();
+        conv.push_user_message("hello".to_string());
+        let theme = crate::theme::Theme::default_theme();
+        let area = Rect::new(0, 0, 100, 4);
+        let first_text_column = |padding| {
+            let mut buf = Buffer::empty(area);
+            ClaudePane::new(&conv, &theme, 0, 0)
+                .with_options(RenderOptions { padding, ..Default::default() })
+                .render(area, &mut buf);
+            (0..area.height)
+                .flat_map(|y| (0..area.width).map(move |x| (x, y)))
+                .filter(|&(x, y)| buf.cell((x, y)).unwrap().symbol() == "h")
+                .map(|(x, _)| x)
+                .min()
+                .unwrap()
+        };
+        assert_eq!(first_text_column(6), first_text_column(0) + 6);
+    }
+
+    #[test]
+    fn test_truncate_tool_arg() {
+        let command = "git push --force-with-lease --set-upstream origin feature/long-branch-name";
+        assert_eq!(truncate_tool_arg("Bash", command, 100), command);
+        let short = truncate_tool_arg("Bash", command, 40);
+        assert_eq!(short, "git ... origin feature/long-branch-name");
+        assert!(short.chars().count() <= 40);
+        assert_eq!(
+            truncate_tool_arg("Read", "/very/long/path/to/some/deeply/nested/file.rs", 20),
+            "/very/long/path/t..."
+        );
+        // Multi-byte text is cut on character boundaries
+        assert_eq!(truncate_tool_arg("Grep", &"é".repeat(30), 20), format!("{}...", "é".repeat(17)));
+    }
+
     #[test]
     fn test_separator_between_messages() {
         let mut conv = Conversation::new();
@@ -1044,6 +2304,8 @@ mod tests {
         conv.push_user_message("Hi".to_string());
         conv.messages.push(Message {
             role: Role::Assistant,
+            timestamp: None,
+            stop_reason: None,
             content: vec![ContentBlock::Text("Hello!".to_string())],
         });
         let lines = render_conversation(&conv, 80, &theme);
@@ -1068,6 +2330,60 @@ mod tests {
         pane.render(area, &mut buf);
     }
 
+    #[test]
+    fn test_message_position_indicator() {
+        let mut conv = Conversation::new();
+        for i in 0..30 {
+            conv.push_user_message(format!("Message {}", i));
+        }
+        let theme = crate::theme::Theme::default_theme();
+        let (_, _, message_lines) = render_conversation_with_offsets(&conv, 80, &theme, RenderOptions::default());
+        assert_eq!(message_lines.len(), 30);
+        // Each message is a label, its text and a separator before the next
+        assert_eq!(message_lines[1], (3, 1));
+        assert_eq!(message_at_line(&message_lines, 0), Some(0));
+        assert_eq!(message_at_line(&message_lines, 31), Some(10));
+        assert_eq!(message_at_line(&[], 5), None);
+
+        let top_row = |scroll: usize| {
+            let pane = ClaudePane::new(&conv, &theme, scroll, 0);
+            let area = Rect::new(0, 0, 80, 10);
+            let mut buf = Buffer::empty(area);
+            pane.render(area, &mut buf);
+            (0..80).map(|x| buf[(x, 0)].symbol().to_string()).collect::<String>()
+        };
+        assert!(top_row(31).ends_with(" message 11 of 30 "));
+        // At the bottom there's nothing to orient
+        assert!(!top_row(89 - 10).contains("message"));
+    }
+
+    #[test]
+    fn test_stream_caret_blinks_at_text_tail() {
+        use crate::claude::events::{ContentBlockType, Delta, StreamEvent};
+
+        let mut conv = Conversation::new();
+        conv.apply_event(&StreamEvent::MessageStart { message_id: "m1".to_string(), model: "claude".to_string(), usage: None });
+        conv.apply_event(&StreamEvent::ContentBlockStart { index: 0, block_type: ContentBlockType::Text });
+        conv.apply_event(&StreamEvent::ContentBlockDelta { index: 0, delta: Delta::TextDelta("Hello".to_string()) });
+        let theme = crate::theme::Theme::default_theme();
+
+        let row_with_hello = |conv: &Conversation, frame: u64| {
+            let pane = ClaudePane::new(conv, &theme, 0, frame);
+            let area = Rect::new(0, 0, 40, 6);
+            let mut buf = Buffer::empty(area);
+            pane.render(area, &mut buf);
+            (0..6)
+                .map(|y| (0..40).map(|x| buf[(x, y)].symbol().to_string()).collect::<String>())
+                .find(|row| row.contains("Hello"))
+                .unwrap()
+        };
+        assert!(row_with_hello(&conv, 0).contains("Hello▋"));
+        assert!(!row_with_hello(&conv, CARET_BLINK_FRAMES).contains('▋'));
+
+        conv.apply_event(&StreamEvent::MessageStop);
+        assert!(!row_with_hello(&conv, 0).contains('▋'));
+    }
+
     #[test]
     fn test_zero_area() {
         let conv = Conversation::new();
@@ -1102,6 +2418,8 @@ mod tests {
         let theme = crate::theme::Theme::default_theme();
         conv.messages.push(Message {
             role: Role::Assistant,
+            timestamp: None,
+            stop_reason: None,
             content: vec![ContentBlock::Thinking(
                 "Let me analyze this.\nFirst step.\nSecond step.".to_string(),
             )],
@@ -1122,6 +2440,8 @@ mod tests {
         let theme = crate::theme::Theme::default_theme();
         conv.messages.push(Message {
             role: Role::Assistant,
+            timestamp: None,
+            stop_reason: None,
             content: vec![ContentBlock::Thinking(String::new())],
         });
         let lines = render_conversation(&conv, 80, &theme);
@@ -1143,6 +2463,8 @@ mod tests {
             .join("\n");
         conv.messages.push(Message {
             role: Role::Assistant,
+            timestamp: None,
+            stop_reason: None,
             content: vec![ContentBlock::Thinking(long_thinking)],
         });
         let lines = render_conversation(&conv, 80, &theme);
@@ -1154,12 +2476,82 @@ mod tests {
         assert!(all_text.contains("... 6 more lines"), "Expected collapse indicator");
     }
 
+    #[test]
+    fn test_thinking_hidden_when_disabled() {
+        let mut conv = Conversation::new();
+        let theme = crate::theme::Theme::default_theme();
+        conv.messages.push(Message {
+            role: Role::Assistant,
+            timestamp: None,
+            stop_reason: None,
+            content: vec![
+                ContentBlock::Thinking("secret plan\nstep two".to_string()),
+                ContentBlock::Text("Done".to_string()),
+            ],
+        });
+        let options = RenderOptions { show_thinking: false, ..Default::default() };
+        let (lines, ..) = render_conversation_with_offsets(&conv, 80, &theme, options);
+        let texts: Vec<String> = lines
+            .iter()
+            .map(|l| l.spans.iter().map(|s| s.text.as_str()).collect())
+            .collect();
+        assert!(texts.iter().any(|t| t.contains("reasoning hidden")));
+        assert!(!texts.iter().any(|t| t.contains("secret plan") || t.contains("Thinking...")));
+        assert!(texts.iter().any(|t| t.contains("Done")));
+
+        let cache = LineCache::default();
+        let shown = cache.total_lines(&conv, 80, &theme, RenderOptions::default());
+        assert_eq!(cache.total_lines(&conv, 80, &theme, options), shown - 2);
+    }
+
+    #[test]
+    fn test_prose_only_hides_tool_traffic() {
+        let mut conv = Conversation::new();
+        let theme = crate::theme::Theme::default_theme();
+        conv.push_user_message("Why does it fail?".to_string());
+        conv.messages.push(Message {
+            role: Role::Assistant,
+            timestamp: None,
+            stop_reason: None,
+            content: vec![
+                ContentBlock::Thinking("let me look".to_string()),
+                ContentBlock::ToolUse {
+                    id: "t1".to_string(),
+                    name: "Bash".to_string(),
+                    input: r#"{"command":"cargo test"}"#.to_string(),
+                },
+            ],
+        });
+        conv.messages.push(Message {
+            role: Role::Assistant,
+            timestamp: None,
+            stop_reason: None,
+            content: vec![ContentBlock::Text("The fixture is missing.".to_string())],
+        });
+        let options = RenderOptions { prose_only: true, ..Default::default() };
+        let (lines, tool_lines, _) = render_conversation_with_offsets(&conv, 80, &theme, options);
+        let texts: Vec<String> = lines
+            .iter()
+            .map(|l| l.spans.iter().map(|s| s.text.as_str()).collect())
+            .collect();
+        assert!(tool_lines.is_empty());
+        assert!(!texts.iter().any(|t| t.contains("cargo test") || t.contains("let me look")));
+        assert!(texts.iter().any(|t| t.contains("The fixture is missing.")));
+        assert_eq!(texts.iter().filter(|t| t.starts_with('─')).count(), 1);
+
+        let cache = LineCache::default();
+        assert_eq!(cache.total_lines(&conv, 80, &theme, options), lines.len());
+        assert!(cache.total_lines(&conv, 80, &theme, RenderOptions::default()) > lines.len());
+    }
+
     #[test]
     fn test_edit_diff_preview() {
         let mut conv = Conversation::new();
         let theme = crate::theme::Theme::default_theme();
         conv.messages.push(Message {
             role: Role::Assistant,
+            timestamp: None,
+            stop_reason: None,
             content: vec![ContentBlock::ToolUse {
                 id: "t1".to_string(),
                 name: "Edit".to_string(),
@@ -1177,12 +2569,37 @@ mod tests {
         assert!(all_text.contains("+ let x = 42;"), "Expected added line");
     }
 
+    #[test]
+    fn test_edit_diff_word_highlighting() {
+        let theme = crate::theme::Theme::default_theme();
+        let mut lines = Vec::new();
+        render_edit_diff(
+            r#"{"old_string":"let x = 1;","new_string":"let x = 42;"}"#,
+            &mut lines,
+            &theme,
+        );
+        assert_eq!(lines.len(), 2);
+        let bold = |line: &StyledLine| -> Vec<String> {
+            line.spans
+                .iter()
+                .filter(|s| s.style.add_modifier.contains(Modifier::BOLD))
+                .map(|s| s.text.clone())
+                .collect()
+        };
+        assert_eq!(bold(&lines[0]), vec!["1;"]);
+        assert_eq!(bold(&lines[1]), vec!["42;"]);
+        let added: String = lines[1].spans.iter().map(|s| s.text.as_str()).collect();
+        assert_eq!(added, "    + let x = 42;");
+    }
+
     #[test]
     fn test_write_content_preview() {
         let mut conv = Conversation::new();
         let theme = crate::theme::Theme::default_theme();
         conv.messages.push(Message {
             role: Role::Assistant,
+            timestamp: None,
+            stop_reason: None,
             content: vec![ContentBlock::ToolUse {
                 id: "t1".to_string(),
                 name: "Write".to_string(),
@@ -1206,8 +2623,11 @@ mod tests {
         let theme = crate::theme::Theme::default_theme();
         conv.messages.push(Message {
             role: Role::Assistant,
+            timestamp: None,
+            stop_reason: None,
             content: vec![ContentBlock::Image {
                 media_type: "image/png".to_string(),
+                dimensions: None,
             }],
         });
         let lines = render_conversation(&conv, 80, &theme);
@@ -1223,12 +2643,49 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_tool_result_image_shows_dimensions() {
+        let mut conv = Conversation::new();
+        let theme = crate::theme::Theme::default_theme();
+        conv.messages.push(Message {
+            role: Role::Assistant,
+            timestamp: None,
+            stop_reason: None,
+            content: vec![ContentBlock::ToolUse {
+                id: "t1".to_string(),
+                name: "mcp__browser__screenshot".to_string(),
+                input: "{}".to_string(),
+            }],
+        });
+        let mut png = b"\x89PNG\r\n\x1a\n\x00\x00\x00\x0dIHDR".to_vec();
+        png.extend_from_slice(&1280u32.to_be_bytes());
+        png.extend_from_slice(&720u32.to_be_bytes());
+        conv.apply_event(&crate::claude::events::StreamEvent::ToolResult {
+            tool_use_id: "t1".to_string(),
+            content: String::new(),
+            is_error: false,
+            images: vec![crate::claude::events::ToolImage {
+                media_type: "image/png".to_string(),
+                data: png,
+            }],
+        });
+        let lines = render_conversation(&conv, 80, &theme);
+        let texts: Vec<String> = lines
+            .iter()
+            .map(|l| l.spans.iter().map(|s| s.text.as_str()).collect())
+            .collect();
+        let header = texts.iter().position(|t| t.contains("mcp__browser__screenshot")).unwrap();
+        assert_eq!(texts[header + 1], "  [Screenshot: 1280x720]");
+    }
+
     #[test]
     fn test_document_placeholder_renders() {
         let mut conv = Conversation::new();
         let theme = crate::theme::Theme::default_theme();
         conv.messages.push(Message {
             role: Role::Assistant,
+            timestamp: None,
+            stop_reason: None,
             content: vec![ContentBlock::Document {
                 doc_type: "application/pdf".to_string(),
             }],
@@ -1245,4 +2702,20 @@ mod tests {
             all_text
         );
     }
+
+    #[test]
+    fn test_file_refs_highlighted_in_tool_result() {
+        let theme = crate::theme::Theme::default_theme();
+        let mut lines = Vec::new();
+        render_tool_result("error in src/app.rs:42 here", false, None, 80, &mut lines, &theme);
+        let link = lines[0]
+            .spans
+            .iter()
+            .find(|s| s.text == "src/app.rs:42")
+            .expect("reference should be its own span");
+        assert_eq!(link.style.fg, Some(theme.info));
+        assert!(link.style.add_modifier.contains(Modifier::UNDERLINED));
+        let text: String = lines[0].spans.iter().map(|s| s.text.as_str()).collect();
+        assert_eq!(text, "    error in src/app.rs:42 here");
+    }
 }
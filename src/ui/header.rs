@@ -4,6 +4,7 @@ use ratatui::style::{Color, Modifier, Style};
 use ratatui::widgets::Widget;
 
 use crate::theme::Theme;
+use crate::ui::claude_pane::Activity;
 
 /// Height of the full header area in terminal rows.
 pub const HEADER_HEIGHT: u16 = 10;
@@ -21,27 +22,53 @@ const LOGO: [&str; 6] = [
     r"|_____/|______/_/ \_\   |_|     \_____|______/_/    \_\____/|_____/|______|",
 ];
 
+/// Narrowest area the logo fits in. Below this the header is always the
+/// compact single line, since a clipped logo just looks garbled.
+pub const MIN_FULL_HEADER_WIDTH: u16 = 76;
+
+/// Whether a header `width` columns wide shows the compact line instead of
+/// the full logo: when asked to, or when the logo doesn't fit.
+pub fn is_compact(width: u16, compact: bool) -> bool {
+    compact || width < MIN_FULL_HEADER_WIDTH
+}
+
 /// Sparkle characters — cycled through for the particle effect.
 const SPARKLES: [char; 6] = ['✦', '✧', '⋆', '·', '∘', '⊹'];
 
 /// Animated header widget displaying a big sexy-claude brand with
 /// gradient wave, sparkle particles, and shimmer sweep effects.
 /// In compact mode, shows a single-line header with name + version.
+/// In minimal mode, that single line is plain and static (no animation).
 pub struct Header<'a> {
     theme: &'a Theme,
     frame_count: u64,
     compact: bool,
+    minimal: bool,
+    activity: Option<Activity>,
 }
 
 impl<'a> Header<'a> {
     pub fn new(theme: &'a Theme, frame_count: u64) -> Self {
-        Self { theme, frame_count, compact: false }
+        Self { theme, frame_count, compact: false, minimal: false, activity: None }
     }
 
     pub fn compact(mut self, compact: bool) -> Self {
         self.compact = compact;
         self
     }
+
+    /// Render a plain one-line name without gradient or sparkle animation.
+    pub fn minimal(mut self, minimal: bool) -> Self {
+        self.minimal = minimal;
+        self
+    }
+
+    /// Show a dot in the top-right corner while the model or a tool is
+    /// busy, visible even when scrolled away from the spinner.
+    pub fn activity(mut self, activity: Option<Activity>) -> Self {
+        self.activity = activity;
+        self
+    }
 }
 
 impl Widget for Header<'_> {
@@ -49,7 +76,13 @@ impl Widget for Header<'_> {
         if area.height == 0 || area.width == 0 {
             return;
         }
+        self.render_body(area, buf);
+        self.render_activity_dot(area, buf);
+    }
+}
 
+impl Header<'_> {
+    fn render_body(&self, area: Rect, buf: &mut Buffer) {
         let bg = self.theme.background;
         let frame = self.frame_count;
 
@@ -62,8 +95,24 @@ impl Widget for Header<'_> {
             }
         }
 
+        // Minimal mode: static, dimmed name on a single line
+        if self.minimal {
+            let text = format!("sexy-claude v{}", env!("CARGO_PKG_VERSION"));
+            let start_x = area.left() + area.width.saturating_sub(text.len() as u16) / 2;
+            let style = Style::default().fg(self.theme.input_placeholder).bg(bg);
+            for (i, ch) in text.chars().enumerate() {
+                let x = start_x + i as u16;
+                if x >= area.right() { break; }
+                if let Some(cell) = buf.cell_mut((x, area.top())) {
+                    cell.set_char(ch);
+                    cell.set_style(style);
+                }
+            }
+            return;
+        }
+
         // Compact mode: single line with "sexy-claude vX.Y.Z" centered
-        if self.compact {
+        if is_compact(area.width, self.compact) {
             let text = format!("sexy-claude v{}", env!("CARGO_PKG_VERSION"));
             let text_len = text.len() as u16;
             let start_x = area.left() + area.width.saturating_sub(text_len) / 2;
@@ -178,9 +227,21 @@ impl Widget for Header<'_> {
             }
         }
     }
-}
 
-impl Header<'_> {
+    fn render_activity_dot(&self, area: Rect, buf: &mut Buffer) {
+        let Some(activity) = self.activity else {
+            return;
+        };
+        if area.width < 2 {
+            return;
+        }
+        let style = Style::default().fg(activity.color(self.theme)).bg(self.theme.background);
+        if let Some(cell) = buf.cell_mut((area.right() - 2, area.top())) {
+            cell.set_char('●');
+            cell.set_style(style);
+        }
+    }
+
     /// Render a row of animated sparkle particles.
     fn render_sparkle_row(&self, y: u16, area: Rect, buf: &mut Buffer, seed: u64) {
         if y >= area.bottom() {
@@ -362,6 +423,54 @@ mod tests {
         header.render(area, &mut buf);
     }
 
+    #[test]
+    fn test_narrow_header_falls_back_to_compact_line() {
+        assert!(LOGO.iter().all(|line| line.chars().count() <= MIN_FULL_HEADER_WIDTH as usize));
+        assert!(!is_compact(MIN_FULL_HEADER_WIDTH, false));
+        assert!(is_compact(MIN_FULL_HEADER_WIDTH - 1, false));
+
+        let theme = test_theme();
+        let area = Rect::new(0, 0, 50, HEADER_HEIGHT);
+        let mut buf = Buffer::empty(area);
+        Header::new(&theme, 0).render(area, &mut buf);
+        let row = |y| (0..50).map(|x| buf.cell((x, y)).unwrap().symbol().to_string()).collect::<String>();
+        assert!(row(0).contains("sexy-claude"));
+        assert!(!row(1).contains('_') && !row(1).contains('|'));
+    }
+
+    #[test]
+    fn test_minimal_header_is_static() {
+        let theme = test_theme();
+        let area = Rect::new(0, 0, 40, COMPACT_HEADER_HEIGHT);
+        let mut a = Buffer::empty(area);
+        let mut b = Buffer::empty(area);
+        Header::new(&theme, 0).minimal(true).render(area, &mut a);
+        Header::new(&theme, 97).minimal(true).render(area, &mut b);
+        assert_eq!(a, b);
+
+        let row: String = (0..40)
+            .map(|x| a.cell((x, 0)).unwrap().symbol().to_string())
+            .collect();
+        assert!(row.contains("sexy-claude"));
+    }
+
+    #[test]
+    fn test_activity_dot_in_top_right_corner() {
+        let theme = test_theme();
+        let area = Rect::new(0, 0, 40, COMPACT_HEADER_HEIGHT);
+        for (activity, color) in [(Activity::Model, theme.info), (Activity::Tool, theme.warning)] {
+            let mut buf = Buffer::empty(area);
+            Header::new(&theme, 0).compact(true).activity(Some(activity)).render(area, &mut buf);
+            let cell = buf.cell((38, 0)).unwrap();
+            assert_eq!(cell.symbol(), "●");
+            assert_eq!(cell.fg, color);
+        }
+
+        let mut buf = Buffer::empty(area);
+        Header::new(&theme, 0).compact(true).render(area, &mut buf);
+        assert_ne!(buf.cell((38, 0)).unwrap().symbol(), "●");
+    }
+
     #[test]
     fn test_header_zero_size() {
         let theme = test_theme();
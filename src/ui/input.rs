@@ -2,11 +2,34 @@
 
 use ratatui::buffer::Buffer;
 use ratatui::layout::Rect;
-use ratatui::style::Style;
+use ratatui::style::{Modifier, Style};
 use ratatui::widgets::Widget;
 
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
 use crate::theme::Theme;
 
+/// Byte offset of the grapheme cluster boundary before `cursor` in `text`,
+/// so emoji with modifiers and combining accents move and delete as one unit.
+pub fn prev_grapheme(text: &str, cursor: usize) -> usize {
+    text[..cursor]
+        .grapheme_indices(true)
+        .next_back()
+        .map(|(i, _)| i)
+        .unwrap_or(0)
+}
+
+/// Byte offset of the grapheme cluster boundary after `cursor` in `text`.
+pub fn next_grapheme(text: &str, cursor: usize) -> usize {
+    text[cursor..]
+        .graphemes(true)
+        .next()
+        .map(|g| cursor + g.len())
+        .unwrap_or(text.len())
+}
+
+#[derive(Clone)]
 pub struct InputEditor {
     content: String,
     cursor: usize,
@@ -37,12 +60,7 @@ impl InputEditor {
 
     pub fn backspace(&mut self) {
         if self.cursor > 0 {
-            // Find the previous character boundary
-            let prev = self.content[..self.cursor]
-                .char_indices()
-                .next_back()
-                .map(|(i, _)| i)
-                .unwrap_or(0);
+            let prev = prev_grapheme(&self.content, self.cursor);
             self.content.drain(prev..self.cursor);
             self.cursor = prev;
         }
@@ -50,33 +68,45 @@ impl InputEditor {
 
     pub fn delete(&mut self) {
         if self.cursor < self.content.len() {
-            let next = self.content[self.cursor..]
-                .char_indices()
-                .nth(1)
-                .map(|(i, _)| self.cursor + i)
-                .unwrap_or(self.content.len());
+            let next = next_grapheme(&self.content, self.cursor);
             self.content.drain(self.cursor..next);
         }
     }
 
-    pub fn move_left(&mut self) {
-        if self.cursor > 0 {
-            self.cursor = self.content[..self.cursor]
-                .char_indices()
-                .next_back()
-                .map(|(i, _)| i)
-                .unwrap_or(0);
+    /// Delete from the start of the current line to the cursor (Alt+U). At
+    /// the start of a line it joins with the previous one, so repeated
+    /// presses clear a multi-line input.
+    pub fn delete_to_line_start(&mut self) {
+        let start = self.content[..self.cursor]
+            .rfind('\n')
+            .map(|i| i + 1)
+            .unwrap_or(0);
+        if start == self.cursor {
+            self.backspace();
+            return;
         }
+        self.content.drain(start..self.cursor);
+        self.cursor = start;
+    }
+
+    /// Delete the word before the cursor along with any whitespace after it
+    /// (Alt+Backspace), like readline's `unix-word-rubout`.
+    pub fn delete_prev_word(&mut self) {
+        let before = self.content[..self.cursor].trim_end();
+        let start = before
+            .rfind(char::is_whitespace)
+            .map(|i| i + before[i..].chars().next().map_or(1, char::len_utf8))
+            .unwrap_or(0);
+        self.content.drain(start..self.cursor);
+        self.cursor = start;
+    }
+
+    pub fn move_left(&mut self) {
+        self.cursor = prev_grapheme(&self.content, self.cursor);
     }
 
     pub fn move_right(&mut self) {
-        if self.cursor < self.content.len() {
-            self.cursor = self.content[self.cursor..]
-                .char_indices()
-                .nth(1)
-                .map(|(i, _)| self.cursor + i)
-                .unwrap_or(self.content.len());
-        }
+        self.cursor = next_grapheme(&self.content, self.cursor);
     }
 
     pub fn move_home(&mut self) {
@@ -126,7 +156,7 @@ impl InputEditor {
         let col = before_cursor
             .rsplit('\n')
             .next()
-            .map(|s| s.len() as u16)
+            .map(|s| s.width() as u16)
             .unwrap_or(0);
         (col, row)
     }
@@ -135,11 +165,25 @@ impl InputEditor {
 pub struct InputWidget<'a> {
     editor: &'a InputEditor,
     theme: &'a Theme,
+    disabled: bool,
+    ctrl_enter_submits: bool,
 }
 
 impl<'a> InputWidget<'a> {
     pub fn new(editor: &'a InputEditor, theme: &'a Theme) -> Self {
-        Self { editor, theme }
+        Self { editor, theme, disabled: false, ctrl_enter_submits: false }
+    }
+
+    /// Describe Ctrl+Enter as the send key in the placeholder (`submit_key = "ctrl+enter"`).
+    pub fn ctrl_enter_submits(mut self, ctrl_enter_submits: bool) -> Self {
+        self.ctrl_enter_submits = ctrl_enter_submits;
+        self
+    }
+
+    /// Grey out the input and show a notice instead of the cursor (review mode).
+    pub fn disabled(mut self, disabled: bool) -> Self {
+        self.disabled = disabled;
+        self
     }
 }
 
@@ -160,6 +204,23 @@ impl<'a> Widget for InputWidget<'a> {
             }
         }
 
+        if self.disabled {
+            let notice_style = Style::default()
+                .fg(self.theme.input_placeholder)
+                .bg(self.theme.input_bg)
+                .add_modifier(Modifier::DIM);
+            let notice = "Review mode \u{2014} input disabled (Ctrl+K to exit)";
+            for (i, ch) in notice.chars().enumerate() {
+                let x = area.x + 1 + i as u16;
+                if x >= area.right() {
+                    break;
+                }
+                buf[(x, area.y)].set_symbol(&ch.to_string());
+                buf[(x, area.y)].set_style(notice_style);
+            }
+            return;
+        }
+
         if self.editor.is_empty() {
             // Show cursor at position 0
             if let Some(cell) = buf.cell_mut((area.x, area.y)) {
@@ -169,7 +230,11 @@ impl<'a> Widget for InputWidget<'a> {
             let placeholder_style = Style::default()
                 .fg(self.theme.input_placeholder)
                 .bg(self.theme.input_bg);
-            let placeholder = "Type a message... (Enter to send, Shift+Enter for newline)";
+            let placeholder = if self.ctrl_enter_submits {
+                "Type a message... (Ctrl/Alt+Enter to send, Enter for newline)"
+            } else {
+                "Type a message... (Enter to send, Shift+Enter for newline)"
+            };
             for (i, ch) in placeholder.chars().enumerate() {
                 let x = area.x + 1 + i as u16;
                 if x >= area.right() {
@@ -187,13 +252,13 @@ impl<'a> Widget for InputWidget<'a> {
         let mut y = area.y;
         let mut byte_offset = 0usize;
 
-        for ch in self.editor.content().chars() {
+        for g in self.editor.content().graphemes(true) {
             if y >= area.bottom() {
                 break;
             }
             let is_cursor = byte_offset == cursor_pos;
 
-            if ch == '\n' {
+            if g == "\n" || g == "\r\n" {
                 // Show cursor on the newline position (as a block at end of line)
                 if is_cursor && x < area.right() && y < area.bottom() {
                     if let Some(cell) = buf.cell_mut((x, y)) {
@@ -203,10 +268,11 @@ impl<'a> Widget for InputWidget<'a> {
                 }
                 x = area.x;
                 y += 1;
-                byte_offset += ch.len_utf8();
+                byte_offset += g.len();
                 continue;
             }
-            if x >= area.right() {
+            let width = g.width().max(1) as u16;
+            if x + width > area.right() {
                 x = area.x;
                 y += 1;
                 if y >= area.bottom() {
@@ -214,10 +280,16 @@ impl<'a> Widget for InputWidget<'a> {
                 }
             }
             let char_style = if is_cursor { cursor_style } else { style };
-            buf[(x, y)].set_symbol(&ch.to_string());
+            buf[(x, y)].set_symbol(g);
             buf[(x, y)].set_style(char_style);
-            x += 1;
-            byte_offset += ch.len_utf8();
+            for offset in 1..width {
+                if let Some(cell) = buf.cell_mut((x + offset, y)) {
+                    cell.set_symbol(" ");
+                    cell.set_style(char_style);
+                }
+            }
+            x += width;
+            byte_offset += g.len();
         }
 
         // If cursor is at end of content, show cursor block after last char
@@ -325,4 +397,70 @@ mod tests {
         editor.move_end();
         assert_eq!(editor.cursor_position(), 5);
     }
+
+    #[test]
+    fn test_delete_to_line_start() {
+        let mut e = InputEditor::new();
+        e.set_content("first\nsecond line");
+        e.move_left();
+        e.move_left();
+        e.delete_to_line_start();
+        assert_eq!(e.content(), "first\nne");
+        assert_eq!(e.cursor_position(), 6);
+        e.delete_to_line_start();
+        assert_eq!(e.content(), "firstne");
+        e.delete_to_line_start();
+        assert_eq!(e.content(), "ne");
+        assert_eq!(e.cursor_position(), 0);
+    }
+
+    #[test]
+    fn test_delete_prev_word() {
+        let mut e = InputEditor::new();
+        e.set_content("fix the   café bug  ");
+        e.delete_prev_word();
+        assert_eq!(e.content(), "fix the   café ");
+        e.delete_prev_word();
+        assert_eq!(e.content(), "fix the   ");
+        e.delete_prev_word();
+        e.delete_prev_word();
+        assert!(e.is_empty());
+        e.delete_prev_word();
+        assert!(e.is_empty());
+
+        e.set_content("one\ntwo");
+        e.delete_prev_word();
+        assert_eq!(e.content(), "one\n");
+    }
+
+    #[test]
+    fn test_grapheme_cursor_and_deletion() {
+        // Thumbs-up with a skin-tone modifier, and "e" + combining acute accent
+        let text = "a\u{1F44D}\u{1F3FD}e\u{301}";
+        let mut editor = InputEditor::new();
+        editor.insert_str(text);
+
+        editor.move_left();
+        assert_eq!(&editor.content()[editor.cursor_position()..], "e\u{301}");
+        editor.move_left();
+        assert_eq!(editor.cursor_position(), 1);
+        editor.move_right();
+        assert_eq!(&editor.content()[editor.cursor_position()..], "e\u{301}");
+
+        editor.backspace();
+        assert_eq!(editor.content(), "ae\u{301}");
+        editor.delete();
+        assert_eq!(editor.content(), "a");
+    }
+
+    #[test]
+    fn test_grapheme_boundaries() {
+        let text = "x\u{1F468}\u{200D}\u{1F469}y";
+        let after_x = 1;
+        let before_y = text.len() - 1;
+        assert_eq!(next_grapheme(text, after_x), before_y);
+        assert_eq!(prev_grapheme(text, before_y), after_x);
+        assert_eq!(next_grapheme(text, text.len()), text.len());
+        assert_eq!(prev_grapheme(text, 0), 0);
+    }
 }
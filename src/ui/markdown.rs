@@ -1,4 +1,4 @@
-use pulldown_cmark::{Event, Options, Parser, Tag, TagEnd};
+use pulldown_cmark::{Event, LinkType, Options, Parser, Tag, TagEnd};
 use ratatui::style::{Color, Modifier, Style};
 use syntect::easy::HighlightLines;
 use syntect::highlighting::ThemeSet;
@@ -12,10 +12,25 @@ use super::claude_pane::{StyledLine, StyledSpan};
 // Public API
 // ---------------------------------------------------------------------------
 
+/// Parser extensions used for all rendering and extraction.
+const PARSER_OPTIONS: Options = Options::ENABLE_STRIKETHROUGH.union(Options::ENABLE_FOOTNOTES);
+
 /// Convert a markdown string into styled lines ready for rendering.
 ///
 /// Lines are NOT wrapped — the caller should run them through `wrap_spans()`.
-pub fn render_markdown(text: &str, theme: &Theme) -> Vec<StyledLine> {
+/// Horizontal rules are drawn `rule_width` columns wide.
+pub fn render_markdown(text: &str, theme: &Theme, rule_width: usize) -> Vec<StyledLine> {
+    render_markdown_with_copy_hint(text, theme, rule_width, None)
+}
+
+/// `render_markdown`, with `copy_hint` (e.g. "Ctrl+Y to copy") shown beside
+/// each code block's language label.
+pub fn render_markdown_with_copy_hint(
+    text: &str,
+    theme: &Theme,
+    rule_width: usize,
+    copy_hint: Option<&str>,
+) -> Vec<StyledLine> {
     let ss = SyntaxSet::load_defaults_newlines();
     let ts = ThemeSet::load_defaults();
     let syntax_theme_name = theme.syntax_theme_name();
@@ -35,29 +50,169 @@ pub fn render_markdown(text: &str, theme: &Theme) -> Vec<StyledLine> {
         in_code_block: false,
         code_block_lang: String::new(),
         code_block_buf: String::new(),
+        copy_hint,
         ss: &ss,
         syntax_theme,
         theme,
         base_style,
+        rule_width,
+        notes: Vec::new(),
+        footnote_bodies: std::collections::HashMap::new(),
+        footnote: None,
+        pending_note: None,
     };
 
-    let opts = Options::ENABLE_STRIKETHROUGH;
-    let parser = Parser::new_ext(text, opts);
-
-    for event in parser {
+    let parser = Parser::new_ext(text, PARSER_OPTIONS);
+
+    for (event, range) in parser.into_offset_iter() {
+        // Only draw a rule when it stands on its own between blank lines;
+        // otherwise keep the literal `---` / `***` text.
+        if matches!(event, Event::Rule) && !is_isolated_line(text, range.clone()) {
+            ctx.flush_line();
+            let style = ctx.current_style();
+            ctx.push_text(text[range].trim(), style);
+            ctx.flush_line();
+            continue;
+        }
         ctx.process_event(event);
     }
 
     // Flush any remaining spans
     ctx.flush_line();
+    ctx.emit_notes();
 
     ctx.lines
 }
 
+/// Syntax-highlight `code` as `lang` (a fence token such as "json"), one
+/// styled line per source line, as code blocks are in [`render_markdown`].
+pub fn highlight_code(code: &str, lang: &str, theme: &Theme) -> Vec<StyledLine> {
+    let ss = SyntaxSet::load_defaults_newlines();
+    let ts = ThemeSet::load_defaults();
+    let syntax_theme = ts
+        .themes
+        .get(theme.syntax_theme_name())
+        .unwrap_or_else(|| ts.themes.values().next().unwrap());
+    highlight_lines(code, lang, &ss, syntax_theme)
+}
+
+/// Highlight `code` with the syntax for `lang`, or in a plain code colour
+/// when there is none.
+fn highlight_lines(
+    code: &str,
+    lang: &str,
+    ss: &SyntaxSet,
+    syntax_theme: &syntect::highlighting::Theme,
+) -> Vec<StyledLine> {
+    let fallback_style = Style::default().fg(Color::Rgb(180, 190, 220));
+
+    let syntax = if !lang.is_empty() {
+        ss.find_syntax_by_token(lang)
+    } else {
+        None
+    };
+
+    match syntax {
+        Some(syn) => {
+            let mut h = HighlightLines::new(syn, syntax_theme);
+            code.lines()
+                .map(|line| {
+                    let ranges = h.highlight_line(line, ss).unwrap_or_default();
+                    let spans = ranges
+                        .iter()
+                        .map(|(style, text)| {
+                            let fg = Color::Rgb(style.foreground.r, style.foreground.g, style.foreground.b);
+                            StyledSpan {
+                                text: text.to_string(),
+                                style: Style::default().fg(fg),
+                            }
+                        })
+                        .collect();
+                    StyledLine { spans }
+                })
+                .collect()
+        }
+        // No syntax found — plain code style
+        None => code.lines().map(|line| StyledLine::plain(line, fallback_style)).collect(),
+    }
+}
+
+/// Label shown above a code block: the first word of the fence info string
+/// (`rust` for "```rust,ignore"), or `code` when there is none.
+fn code_block_label(info: &str) -> &str {
+    info.split(|c: char| c.is_whitespace() || c == ',')
+        .find(|word| !word.is_empty())
+        .unwrap_or("code")
+}
+
+/// A fenced or indented code block extracted from markdown source.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CodeBlock {
+    /// Fence info string (e.g. "rust"); empty for indented or unlabeled blocks.
+    pub lang: String,
+    pub code: String,
+}
+
+/// Extract every code block from `text`, in order, using the same parser
+/// settings as [`render_markdown`].
+pub fn code_blocks(text: &str) -> Vec<CodeBlock> {
+    let mut blocks = Vec::new();
+    let mut current: Option<CodeBlock> = None;
+    for event in Parser::new_ext(text, PARSER_OPTIONS) {
+        match event {
+            Event::Start(Tag::CodeBlock(kind)) => {
+                let lang = match kind {
+                    pulldown_cmark::CodeBlockKind::Fenced(lang) => lang.to_string(),
+                    pulldown_cmark::CodeBlockKind::Indented => String::new(),
+                };
+                current = Some(CodeBlock { lang, code: String::new() });
+            }
+            Event::Text(code) => {
+                if let Some(block) = current.as_mut() {
+                    block.code.push_str(&code);
+                }
+            }
+            Event::End(TagEnd::CodeBlock) => blocks.extend(current.take()),
+            _ => {}
+        }
+    }
+    blocks
+}
+
+/// Whether the source lines spanned by `range` have a blank line (or the
+/// start/end of the text) directly before and after them.
+fn is_isolated_line(text: &str, range: std::ops::Range<usize>) -> bool {
+    let before = &text[..range.start];
+    let blank_before = match before.strip_suffix('\n') {
+        None => before.trim().is_empty(),
+        Some(prev) => prev.rsplit('\n').next().is_none_or(|line| line.trim().is_empty()),
+    };
+
+    let after = &text[range.end..];
+    let after = if text[..range.end].ends_with('\n') {
+        after
+    } else {
+        after.strip_prefix('\n').unwrap_or(after)
+    };
+    let blank_after = after.split('\n').next().is_none_or(|line| line.trim().is_empty());
+
+    blank_before && blank_after
+}
+
 // ---------------------------------------------------------------------------
 // Render context
 // ---------------------------------------------------------------------------
 
+/// An entry in the notes section appended below a message, numbered by
+/// first reference.
+#[derive(PartialEq)]
+enum Note {
+    /// A `[^label]` footnote.
+    Footnote(String),
+    /// The target of a `[text][ref]` reference link.
+    Link(String),
+}
+
 struct RenderContext<'a> {
     lines: Vec<StyledLine>,
     current_spans: Vec<StyledSpan>,
@@ -70,11 +225,24 @@ struct RenderContext<'a> {
     in_code_block: bool,
     code_block_lang: String,
     code_block_buf: String,
+    /// Shown after each code block's label, when the block can be copied.
+    copy_hint: Option<&'a str>,
 
     ss: &'a SyntaxSet,
     syntax_theme: &'a syntect::highlighting::Theme,
     theme: &'a Theme,
     base_style: Style,
+    rule_width: usize,
+
+    /// Footnotes and reference-link targets, in order of first reference.
+    notes: Vec<Note>,
+    /// Rendered `[^label]: ...` definitions, by label.
+    footnote_bodies: std::collections::HashMap<String, Vec<StyledLine>>,
+    /// Label of the footnote definition being rendered, and the message lines
+    /// set aside meanwhile.
+    footnote: Option<(String, Vec<StyledLine>)>,
+    /// Number of the reference link being rendered, marked after its text.
+    pending_note: Option<usize>,
 }
 
 impl<'a> RenderContext<'a> {
@@ -143,16 +311,19 @@ impl<'a> RenderContext<'a> {
                     pulldown_cmark::CodeBlockKind::Fenced(lang) => lang.to_string(),
                     pulldown_cmark::CodeBlockKind::Indented => String::new(),
                 };
-                // Render the opening fence line
-                let fence_label = if self.code_block_lang.is_empty() {
-                    "```".to_string()
-                } else {
-                    format!("```{}", self.code_block_lang)
-                };
+                // Label the block with its language (the fence's first word)
+                let label = code_block_label(&self.code_block_lang);
                 let fence_style = Style::default()
                     .fg(Color::Rgb(127, 132, 156))
                     .add_modifier(Modifier::DIM);
-                self.lines.push(StyledLine::plain(&fence_label, fence_style));
+                let mut line = StyledLine::plain(label, fence_style);
+                if let Some(hint) = self.copy_hint {
+                    line.spans.push(StyledSpan {
+                        text: format!("  [{hint}]"),
+                        style: fence_style.add_modifier(Modifier::ITALIC),
+                    });
+                }
+                self.lines.push(line);
             }
 
             Event::End(TagEnd::CodeBlock) => {
@@ -283,23 +454,50 @@ impl<'a> RenderContext<'a> {
                 self.pop_style();
             }
 
-            Event::Start(Tag::Link { dest_url, .. }) => {
+            Event::Start(Tag::Link { link_type, dest_url, .. }) => {
                 let link_style = Style::default()
                     .fg(self.theme.info)
                     .add_modifier(Modifier::UNDERLINED);
                 self.push_style(link_style);
-                // Store URL for later (we'll show it after the text)
-                // For now, just style the text
-                let _ = dest_url; // URL available if we want to show it
+                // Reference-style links list their target in the notes section
+                if matches!(link_type, LinkType::Reference | LinkType::Collapsed | LinkType::Shortcut) {
+                    self.pending_note = Some(self.note_number(Note::Link(dest_url.to_string())));
+                }
             }
 
             Event::End(TagEnd::Link) => {
                 self.pop_style();
+                if let Some(n) = self.pending_note.take() {
+                    self.push_note_marker(n);
+                }
+            }
+
+            // --- Footnotes ---
+            Event::FootnoteReference(label) => {
+                let n = self.note_number(Note::Footnote(label.to_string()));
+                self.push_note_marker(n);
+            }
+
+            Event::Start(Tag::FootnoteDefinition(label)) => {
+                self.flush_line();
+                let message_lines = std::mem::take(&mut self.lines);
+                self.footnote = Some((label.to_string(), message_lines));
+            }
+
+            Event::End(TagEnd::FootnoteDefinition) => {
+                self.flush_line();
+                if let Some((label, message_lines)) = self.footnote.take() {
+                    let body = std::mem::replace(&mut self.lines, message_lines);
+                    self.footnote_bodies.insert(label, body);
+                }
             }
 
             // Inline code
             Event::Code(text) => {
-                let code_style = Style::default().fg(Color::Rgb(166, 227, 161));
+                let code_style = self
+                    .current_style()
+                    .fg(self.theme.accent)
+                    .bg(self.theme.surface);
                 self.current_spans.push(StyledSpan {
                     text: text.to_string(),
                     style: code_style,
@@ -336,58 +534,76 @@ impl<'a> RenderContext<'a> {
 
             Event::Rule => {
                 self.flush_line();
-                let sep_style = Style::default().fg(Color::Rgb(69, 71, 90));
+                let rule_style = Style::default().fg(self.theme.border);
                 self.lines
-                    .push(StyledLine::plain(&"─".repeat(40), sep_style));
+                    .push(StyledLine::plain(&"─".repeat(self.rule_width.max(1)), rule_style));
             }
 
-            // Ignore everything else (HTML, footnotes, etc.)
+            // Ignore everything else (HTML, etc.)
             _ => {}
         }
     }
 
-    /// Highlight the buffered code block using syntect and emit styled lines.
-    fn emit_highlighted_code(&mut self) {
-        let fallback_style = Style::default().fg(Color::Rgb(180, 190, 220));
+    /// The 1-based number of `note`, adding it if this is its first reference.
+    fn note_number(&mut self, note: Note) -> usize {
+        match self.notes.iter().position(|n| *n == note) {
+            Some(i) => i + 1,
+            None => {
+                self.notes.push(note);
+                self.notes.len()
+            }
+        }
+    }
 
-        let syntax = if !self.code_block_lang.is_empty() {
-            self.ss.find_syntax_by_token(&self.code_block_lang)
-        } else {
-            None
-        };
+    fn push_note_marker(&mut self, n: usize) {
+        self.current_spans.push(StyledSpan {
+            text: format!("[{n}]"),
+            style: Style::default().fg(self.theme.info),
+        });
+    }
 
-        match syntax {
-            Some(syn) => {
-                let mut h = HighlightLines::new(syn, self.syntax_theme);
-                for line in self.code_block_buf.lines() {
-                    let ranges = h
-                        .highlight_line(line, self.ss)
-                        .unwrap_or_default();
-                    let spans: Vec<StyledSpan> = ranges
-                        .iter()
-                        .map(|(style, text)| {
-                            let fg = Color::Rgb(
-                                style.foreground.r,
-                                style.foreground.g,
-                                style.foreground.b,
-                            );
-                            StyledSpan {
-                                text: text.to_string(),
-                                style: Style::default().fg(fg),
-                            }
-                        })
-                        .collect();
-                    self.lines.push(StyledLine { spans });
-                }
-            }
-            None => {
-                // No syntax found — plain code style
-                for line in self.code_block_buf.lines() {
-                    self.lines.push(StyledLine::plain(line, fallback_style));
-                }
+    /// Append the footnotes and reference-link targets below a short rule.
+    /// Footnotes defined but never referenced come last.
+    fn emit_notes(&mut self) {
+        let mut unreferenced: Vec<String> = self
+            .footnote_bodies
+            .keys()
+            .filter(|label| !self.notes.contains(&Note::Footnote(label.to_string())))
+            .cloned()
+            .collect();
+        unreferenced.sort();
+        self.notes.extend(unreferenced.into_iter().map(Note::Footnote));
+        if self.notes.is_empty() {
+            return;
+        }
+
+        self.lines.push(StyledLine::empty());
+        self.lines.push(StyledLine::plain(&"─".repeat(self.rule_width.clamp(1, 10)), Style::default().fg(self.theme.border)));
+        let marker_style = Style::default().fg(self.theme.info);
+        for (i, note) in std::mem::take(&mut self.notes).into_iter().enumerate() {
+            let marker = StyledSpan { text: format!("[{}] ", i + 1), style: marker_style };
+            let body = match note {
+                Note::Link(url) => vec![StyledLine::plain(&url, marker_style.add_modifier(Modifier::UNDERLINED))],
+                Note::Footnote(label) => self.footnote_bodies.remove(&label).unwrap_or_else(|| {
+                    vec![StyledLine::plain(&format!("[^{label}] (not defined)"), self.base_style)]
+                }),
+            };
+            // Continuation lines line up under the first line's text
+            let indent = " ".repeat(marker.text.len());
+            let body = body.into_iter().filter(|line| !line.spans.is_empty());
+            for (j, mut line) in body.enumerate() {
+                let prefix = if j == 0 { marker.clone() } else { StyledSpan { text: indent.clone(), style: Style::default() } };
+                line.spans.insert(0, prefix);
+                self.lines.push(line);
             }
         }
     }
+
+    /// Highlight the buffered code block using syntect and emit styled lines.
+    fn emit_highlighted_code(&mut self) {
+        let highlighted = highlight_lines(&self.code_block_buf, &self.code_block_lang, self.ss, self.syntax_theme);
+        self.lines.extend(highlighted);
+    }
 }
 
 // ---------------------------------------------------------------------------
@@ -403,9 +619,27 @@ mod tests {
         Theme::default_theme()
     }
 
+    #[test]
+    fn test_footnotes_and_reference_links() {
+        let md = "See the [docs][d] and [docs][d] again.[^why]\n\nAnother point.[^2]\n\n[d]: https://example.com/docs\n[^why]: Because **it** matters.\n[^2]: Second note.\n";
+        let lines = render_markdown(md, &test_theme(), 40);
+        let text: Vec<String> = lines.iter().map(|l| l.spans.iter().map(|s| s.text.as_str()).collect()).collect();
+        assert_eq!(text[0], "See the docs[1] and docs[1] again.[2]");
+        assert_eq!(text[2], "Another point.[3]");
+        let notes = &text[text.len() - 3..];
+        assert_eq!(notes, ["[1] https://example.com/docs", "[2] Because it matters.", "[3] Second note."]);
+        // Footnote bodies keep their inline formatting
+        let bold = lines[lines.len() - 2].spans.iter().find(|s| s.text == "it").unwrap();
+        assert!(bold.style.add_modifier.contains(Modifier::BOLD));
+
+        // Inline links and messages without notes are unchanged
+        let lines = render_markdown("A [link](https://x.dev).", &test_theme(), 40);
+        assert_eq!(lines.len(), 1);
+    }
+
     #[test]
     fn test_plain_text() {
-        let lines = render_markdown("Hello world", &test_theme());
+        let lines = render_markdown("Hello world", &test_theme(), 40);
         let all_text: String = lines
             .iter()
             .flat_map(|l| l.spans.iter())
@@ -416,7 +650,7 @@ mod tests {
 
     #[test]
     fn test_bold_text() {
-        let lines = render_markdown("**bold text**", &test_theme());
+        let lines = render_markdown("**bold text**", &test_theme(), 40);
         let bold_span = lines
             .iter()
             .flat_map(|l| l.spans.iter())
@@ -428,7 +662,7 @@ mod tests {
 
     #[test]
     fn test_italic_text() {
-        let lines = render_markdown("*italic text*", &test_theme());
+        let lines = render_markdown("*italic text*", &test_theme(), 40);
         let italic_span = lines
             .iter()
             .flat_map(|l| l.spans.iter())
@@ -440,33 +674,58 @@ mod tests {
 
     #[test]
     fn test_inline_code() {
-        let lines = render_markdown("Use `cargo build` to compile", &test_theme());
+        let lines = render_markdown("Use `cargo build` to compile", &test_theme(), 40);
         let code_span = lines
             .iter()
             .flat_map(|l| l.spans.iter())
             .find(|s| s.text.contains("cargo build"));
         assert!(code_span.is_some());
         let span = code_span.unwrap();
-        assert_eq!(span.style.fg, Some(Color::Rgb(166, 227, 161)));
+        assert_eq!(span.text, "cargo build");
+        assert_eq!(span.style.fg, Some(test_theme().accent));
+        assert_eq!(span.style.bg, Some(test_theme().surface));
+    }
+
+    #[test]
+    fn test_inline_code_mid_line() {
+        let lines = render_markdown("call `foo()` then `bar`.", &test_theme(), 40);
+        assert_eq!(lines.len(), 1);
+        let texts: Vec<&str> = lines[0].spans.iter().map(|s| s.text.as_str()).collect();
+        assert_eq!(texts, vec!["call ", "foo()", " then ", "bar", "."]);
+        assert_eq!(lines[0].spans[0].style.bg, None);
     }
 
     #[test]
     fn test_code_block() {
         let md = "```rust\nfn main() {}\n```";
-        let lines = render_markdown(md, &test_theme());
-        // Should have: fence, highlighted code, fence
+        let lines = render_markdown(md, &test_theme(), 40);
+        // Should have: label, highlighted code, fence
         assert!(lines.len() >= 3);
-        // First and last lines should be fences
+        // Labelled with the language, closed by a fence
         let first_text: String = lines[0].spans.iter().map(|s| s.text.as_str()).collect();
-        assert!(first_text.contains("```rust"));
+        assert_eq!(first_text, "rust");
         let last_text: String = lines.last().unwrap().spans.iter().map(|s| s.text.as_str()).collect();
         assert!(last_text.contains("```"));
     }
 
+    #[test]
+    fn test_code_block_label_and_copy_hint() {
+        let label = |md: &str, hint| -> String {
+            render_markdown_with_copy_hint(md, &test_theme(), 40, hint)[0]
+                .spans
+                .iter()
+                .map(|s| s.text.as_str())
+                .collect()
+        };
+        assert_eq!(label("```\nls\n```", None), "code");
+        assert_eq!(label("```rust,ignore\nfn a() {}\n```", None), "rust");
+        assert_eq!(label("```python title=x\npass\n```", Some("Ctrl+Y to copy")), "python  [Ctrl+Y to copy]");
+    }
+
     #[test]
     fn test_code_block_unknown_language() {
         let md = "```\nsome code\n```";
-        let lines = render_markdown(md, &test_theme());
+        let lines = render_markdown(md, &test_theme(), 40);
         let all_text: String = lines
             .iter()
             .flat_map(|l| l.spans.iter())
@@ -477,7 +736,7 @@ mod tests {
 
     #[test]
     fn test_headers() {
-        let lines = render_markdown("# Title\n## Subtitle", &test_theme());
+        let lines = render_markdown("# Title\n## Subtitle", &test_theme(), 40);
         let all_text: String = lines
             .iter()
             .flat_map(|l| l.spans.iter())
@@ -489,7 +748,7 @@ mod tests {
 
     #[test]
     fn test_unordered_list() {
-        let lines = render_markdown("- item one\n- item two", &test_theme());
+        let lines = render_markdown("- item one\n- item two", &test_theme(), 40);
         let all_text: String = lines
             .iter()
             .flat_map(|l| l.spans.iter())
@@ -501,7 +760,7 @@ mod tests {
 
     #[test]
     fn test_ordered_list() {
-        let lines = render_markdown("1. first\n2. second", &test_theme());
+        let lines = render_markdown("1. first\n2. second", &test_theme(), 40);
         let all_text: String = lines
             .iter()
             .flat_map(|l| l.spans.iter())
@@ -513,13 +772,13 @@ mod tests {
 
     #[test]
     fn test_empty_input() {
-        let lines = render_markdown("", &test_theme());
+        let lines = render_markdown("", &test_theme(), 40);
         assert!(lines.is_empty());
     }
 
     #[test]
     fn test_horizontal_rule() {
-        let lines = render_markdown("---", &test_theme());
+        let lines = render_markdown("---", &test_theme(), 40);
         let all_text: String = lines
             .iter()
             .flat_map(|l| l.spans.iter())
@@ -527,4 +786,42 @@ mod tests {
             .collect();
         assert!(all_text.contains("─"));
     }
+
+    #[test]
+    fn test_isolated_rule_spans_width_in_border_color() {
+        let theme = test_theme();
+        let lines = render_markdown("above\n\n***\n\nbelow", &theme, 60);
+        let rule = lines
+            .iter()
+            .find(|l| l.spans.iter().any(|s| s.text.contains('─')))
+            .expect("rule line");
+        assert_eq!(rule.spans[0].text, "─".repeat(60));
+        assert_eq!(rule.spans[0].style.fg, Some(theme.border));
+    }
+
+    #[test]
+    fn test_rule_without_blank_lines_stays_literal() {
+        let lines = render_markdown("- item\n---\n- next", &test_theme(), 40);
+        let all_text: String = lines
+            .iter()
+            .flat_map(|l| l.spans.iter())
+            .map(|s| s.text.as_str())
+            .collect();
+        assert!(!all_text.contains("─"));
+        assert!(all_text.contains("---"));
+    }
+
+    #[test]
+    fn test_code_blocks_in_order() {
+        let text = "Intro\n\n```rust\nfn main() {}\n```\n\nThen:\n\n```\nls -la\ncd ..\n```\n";
+        let blocks = code_blocks(text);
+        assert_eq!(
+            blocks,
+            vec![
+                CodeBlock { lang: "rust".to_string(), code: "fn main() {}\n".to_string() },
+                CodeBlock { lang: String::new(), code: "ls -la\ncd ..\n".to_string() },
+            ]
+        );
+        assert!(code_blocks("no code here, just `inline`").is_empty());
+    }
 }
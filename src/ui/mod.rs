@@ -9,10 +9,12 @@ pub mod toast;
 
 use ratatui::buffer::Buffer;
 use ratatui::layout::{Constraint, Direction, Layout, Rect};
-use ratatui::style::{Modifier, Style};
+use ratatui::style::{Color, Modifier, Style};
 use ratatui::symbols::border;
 use ratatui::widgets::{Block, Borders, Clear, Widget};
 use ratatui::Frame;
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
 
 use crate::app::{AgentTask, CompletionState, PluginInfo, SplitContent};
 use crate::claude::conversation::Conversation;
@@ -20,7 +22,7 @@ use crate::diff::{self, DiffOp};
 use crate::git::GitInfo;
 use crate::theme::Theme;
 use crate::ui::toast::Toast;
-use claude_pane::ClaudePane;
+use claude_pane::{ClaudePane, LineCache, RenderOptions};
 use header::{Header, HEADER_HEIGHT, COMPACT_HEADER_HEIGHT};
 use input::{InputEditor, InputWidget};
 use overlay::{OverlayState, OverlayWidget};
@@ -44,48 +46,40 @@ pub fn render(
     todo_summary: Option<&str>,
     model_name: Option<&str>,
     permission_mode: Option<&str>,
-    tools_expanded: bool,
+    effort: Option<&str>,
+    options: RenderOptions,
     active_tool: Option<(&str, u64)>,
     split_content: Option<&SplitContent>,
     split_scroll: usize,
+    highlight_line: Option<usize>,
+    fresh_messages: Option<(usize, f64)>,
+    minimal: bool,
+    review_mode: bool,
+    session_elapsed: Option<u64>,
+    ctrl_enter_submits: bool,
+    horizontal_offset: usize,
+    line_cache: &LineCache,
 ) {
     let size = frame.area();
 
-    let input_height = if input.is_empty() {
-        1
-    } else {
-        // Allow input to grow up to 10 lines for multi-line content (e.g. paste)
-        let line_count = input.content().lines().count() as u16 + 1;
-        let max_height = (size.height / 3).max(3).min(10);
-        max_height.min(line_count)
-    };
-
     // Collapse header to single line once conversation has messages
-    let compact_header = !conversation.messages.is_empty();
-    let header_height = if compact_header { COMPACT_HEADER_HEIGHT } else { HEADER_HEIGHT };
-
-    let chunks = Layout::default()
-        .direction(Direction::Vertical)
-        .constraints([
-            Constraint::Length(header_height),
-            Constraint::Min(3),
-            Constraint::Length(input_height + 2), // +2 for border
-            Constraint::Length(1),
-        ])
-        .split(size);
+    // (minimal mode and terminals too narrow for the logo never show the
+    // full animated header)
+    let compact_header = header::is_compact(size.width, minimal || !conversation.messages.is_empty());
+    let chunks = main_layout(size, input, compact_header);
 
     // Animated header (compact when conversation has content)
-    frame.render_widget(Header::new(theme, frame_count).compact(compact_header), chunks[0]);
+    frame.render_widget(
+        Header::new(theme, frame_count)
+            .compact(compact_header)
+            .minimal(minimal)
+            .activity(claude_pane::activity(conversation)),
+        chunks[0],
+    );
 
     // Claude pane (optionally split horizontally with right pane)
     if let Some(content) = split_content {
-        let pane_chunks = Layout::default()
-            .direction(Direction::Horizontal)
-            .constraints([
-                Constraint::Percentage(60),
-                Constraint::Percentage(40),
-            ])
-            .split(chunks[1]);
+        let pane_chunks = split_layout(chunks[1]);
 
         // Left: conversation
         let left_block = borders::themed_block("", true, theme);
@@ -93,7 +87,11 @@ pub fn render(
         frame.render_widget(left_block, pane_chunks[0]);
         frame.render_widget(
             ClaudePane::new(conversation, theme, scroll_offset, frame_count)
-                .with_tools_expanded(tools_expanded),
+                .with_options(options)
+                .with_highlight_line(highlight_line)
+                .with_fresh_messages(fresh_messages)
+                .with_horizontal_offset(horizontal_offset)
+                .with_cache(line_cache),
             left_inner,
         );
 
@@ -105,7 +103,11 @@ pub fn render(
         frame.render_widget(claude_block, chunks[1]);
         frame.render_widget(
             ClaudePane::new(conversation, theme, scroll_offset, frame_count)
-                .with_tools_expanded(tools_expanded),
+                .with_options(options)
+                .with_highlight_line(highlight_line)
+                .with_fresh_messages(fresh_messages)
+                .with_horizontal_offset(horizontal_offset)
+                .with_cache(line_cache),
             claude_inner,
         );
     }
@@ -115,7 +117,7 @@ pub fn render(
     let input_block = borders::themed_block(input_title, !is_streaming, theme);
     let input_inner = input_block.inner(chunks[2]);
     frame.render_widget(input_block, chunks[2]);
-    frame.render_widget(InputWidget::new(input, theme), input_inner);
+    frame.render_widget(InputWidget::new(input, theme).disabled(review_mode).ctrl_enter_submits(ctrl_enter_submits), input_inner);
 
     // Completion popup (rendered above input area)
     if let Some(state) = completion {
@@ -124,7 +126,11 @@ pub fn render(
 
     // Status bar
     frame.render_widget(
-        StatusBar::new(theme, token_usage.0, token_usage.1, git_info, todo_summary, model_name, permission_mode, active_tool),
+        StatusBar::new(theme, token_usage.0, token_usage.1, git_info, todo_summary, model_name, permission_mode, active_tool)
+            .with_effort(effort)
+            .with_review(review_mode)
+            .with_clock(session_elapsed)
+            .minimal(minimal),
         chunks[3],
     );
 
@@ -134,19 +140,106 @@ pub fn render(
     }
 }
 
+/// Header, conversation, input and status bar rows for a terminal of `size`.
+fn main_layout(size: Rect, input: &InputEditor, compact_header: bool) -> std::rc::Rc<[Rect]> {
+    let input_height = if input.is_empty() {
+        1
+    } else {
+        // Allow input to grow up to 10 lines for multi-line content (e.g. paste)
+        let line_count = input.content().lines().count() as u16 + 1;
+        let max_height = (size.height / 3).max(3).min(10);
+        max_height.min(line_count)
+    };
+    let header_height = if compact_header { COMPACT_HEADER_HEIGHT } else { HEADER_HEIGHT };
+
+    Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(header_height),
+            Constraint::Min(3),
+            Constraint::Length(input_height + 2), // +2 for border
+            Constraint::Length(1),
+        ])
+        .split(size)
+}
+
+/// Conversation (60%) and split pane (40%) side by side in `area`.
+fn split_layout(area: Rect) -> std::rc::Rc<[Rect]> {
+    Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([
+            Constraint::Percentage(60),
+            Constraint::Percentage(40),
+        ])
+        .split(area)
+}
+
+/// Inner areas (inside the borders) of the conversation pane and, when
+/// `split`, the split pane, exactly as `render` lays them out.
+pub fn pane_areas(size: Rect, input: &InputEditor, compact_header: bool, split: bool) -> (Rect, Option<Rect>) {
+    let margin = ratatui::layout::Margin::new(1, 1);
+    let middle = main_layout(size, input, compact_header)[1];
+    if split {
+        let panes = split_layout(middle);
+        (panes[0].inner(margin), Some(panes[1].inner(margin)))
+    } else {
+        (middle.inner(margin), None)
+    }
+}
+
+/// Render one Grep/Glob result line: matches as path, line number and text,
+/// with the selected one highlighted; anything else dimmed.
+fn render_search_line(buf: &mut Buffer, row: Rect, line: &str, selected: bool, theme: &Theme) {
+    let Some(hit) = crate::filerefs::parse_search_line(line) else {
+        let style = Style::default().fg(theme.foreground).add_modifier(Modifier::DIM);
+        put_str(buf, row.x, row.y, row.right(), line, style);
+        return;
+    };
+    let base = if selected {
+        Style::default().bg(theme.overlay).add_modifier(Modifier::BOLD)
+    } else {
+        Style::default()
+    };
+    if selected {
+        for x in row.x..row.right() {
+            if let Some(cell) = buf.cell_mut((x, row.y)) {
+                cell.set_char(' ');
+                cell.set_style(base);
+            }
+        }
+    }
+    let mut x = put_str(buf, row.x, row.y, row.right(), &hit.path, base.fg(theme.info));
+    if let Some(number) = hit.line {
+        x = put_str(buf, x, row.y, row.right(), &format!(":{number}"), base.fg(theme.secondary));
+    }
+    if !hit.text.is_empty() {
+        let text = format!("  {}", hit.text.trim());
+        put_str(buf, x, row.y, row.right(), &text, base.fg(theme.foreground));
+    }
+}
+
 /// Render the right split pane with contextual content.
 fn render_split_pane(frame: &mut Frame, area: Rect, content: &SplitContent, scroll: usize, theme: &Theme) {
-    let (title, lines) = match content {
+    let wrapped: Vec<String>;
+    let (title, lines): (String, Vec<&String>) = match content {
         SplitContent::FilePreview(path, lines) => {
             // Show just the filename in the title
             let name = std::path::Path::new(path)
                 .file_name()
                 .and_then(|n| n.to_str())
                 .unwrap_or(path);
-            (format!(" {} ", name), lines.as_slice())
+            (format!(" {} ", name), lines.iter().collect())
+        }
+        SplitContent::DiffView(lines) => (" Diff ".to_string(), lines.iter().collect()),
+        SplitContent::SearchResults(query, lines, _) => (format!(" {query} "), lines.iter().collect()),
+        SplitContent::FileContext(lines) => (" Context ".to_string(), lines.iter().collect()),
+        SplitContent::EventLog(lines) => (" Event Log ".to_string(), lines.iter().collect()),
+        SplitContent::AltAnswer(model, text, finished) => {
+            wrapped = wrap_plain(text, area.width.saturating_sub(2) as usize);
+            let status = if *finished { "" } else { " (answering...)" };
+            (format!(" {model}{status} "), wrapped.iter().collect())
         }
-        SplitContent::DiffView(lines) => (" Diff ".to_string(), lines.as_slice()),
-        SplitContent::FileContext(lines) => (" Context ".to_string(), lines.as_slice()),
+        SplitContent::Terminal(_) => (" Terminal ".to_string(), Vec::new()),
     };
 
     let block = Block::default()
@@ -159,6 +252,19 @@ fn render_split_pane(frame: &mut Frame, area: Rect, content: &SplitContent, scro
     frame.render_widget(block, area);
 
     let buf = frame.buffer_mut();
+
+    // The terminal screen is already laid out and styled cell by cell
+    if let SplitContent::Terminal(screen) = content {
+        for (i, line) in screen.iter().take(inner.height as usize).enumerate() {
+            let y = inner.y + i as u16;
+            let mut x = inner.x;
+            for span in &line.spans {
+                x = put_str(buf, x, y, inner.right(), &span.text, span.style);
+            }
+        }
+        return;
+    }
+
     let visible_height = inner.height as usize;
     let clamped_scroll = scroll.min(lines.len().saturating_sub(visible_height));
 
@@ -167,19 +273,24 @@ fn render_split_pane(frame: &mut Frame, area: Rect, content: &SplitContent, scro
         let x = inner.x;
         let max_x = inner.right();
 
+        if let SplitContent::SearchResults(_, _, selected) = content {
+            render_search_line(buf, Rect::new(x, y, inner.width, 1), line, clamped_scroll + i == *selected, theme);
+            continue;
+        }
+
         // Determine style based on content type and line prefix
         let style = match content {
             SplitContent::DiffView(_) => {
                 if line.starts_with('+') && !line.starts_with("+++") {
-                    Style::default().fg(theme.success)
+                    Style::default().fg(theme.diff_add)
                 } else if line.starts_with('-') && !line.starts_with("---") {
-                    Style::default().fg(theme.error)
+                    Style::default().fg(theme.diff_remove)
                 } else if line.starts_with("@@") {
                     Style::default().fg(theme.info)
                 } else if line.starts_with("---") || line.starts_with("+++") {
                     Style::default().fg(theme.primary).add_modifier(Modifier::BOLD)
                 } else {
-                    Style::default().fg(theme.foreground)
+                    Style::default().fg(theme.diff_context)
                 }
             }
             SplitContent::FilePreview(_, _) => {
@@ -189,17 +300,25 @@ fn render_split_pane(frame: &mut Frame, area: Rect, content: &SplitContent, scro
             SplitContent::FileContext(_) => {
                 Style::default().fg(theme.foreground)
             }
+            SplitContent::SearchResults(..) => Style::default().fg(theme.foreground),
+            SplitContent::EventLog(_) => {
+                if line.starts_with("Unknown") {
+                    Style::default().fg(theme.warning)
+                } else {
+                    Style::default().fg(theme.foreground).add_modifier(Modifier::DIM)
+                }
+            }
+            SplitContent::AltAnswer(..) => {
+                if line.starts_with('[') && line.ends_with(']') {
+                    Style::default().fg(theme.info).add_modifier(Modifier::DIM)
+                } else {
+                    Style::default().fg(theme.foreground)
+                }
+            }
+            SplitContent::Terminal(_) => Style::default().fg(theme.foreground),
         };
 
-        let mut cx = x;
-        for ch in line.chars() {
-            if cx >= max_x {
-                break;
-            }
-            buf[(cx, y)].set_symbol(&ch.to_string());
-            buf[(cx, y)].set_style(style);
-            cx += 1;
-        }
+        put_str(buf, x, y, max_x, line, style);
     }
 
     // Scroll indicator
@@ -223,6 +342,73 @@ fn render_split_pane(frame: &mut Frame, area: Rect, content: &SplitContent, scro
     }
 }
 
+/// Display width of a character in terminal cells (0 for control characters).
+fn char_width(ch: char) -> u16 {
+    ch.width().unwrap_or(0) as u16
+}
+
+/// Write `text` into `buf` from (`x`, `y`), stopping before `max_x`.
+/// Advances by each character's display width so emoji and CJK text take
+/// two cells; the cell covered by the right half of a wide character is
+/// blanked. Zero-width characters are skipped. Returns the next free column.
+pub(crate) fn put_str(buf: &mut Buffer, x: u16, y: u16, max_x: u16, text: &str, style: Style) -> u16 {
+    let mut col = x;
+    for ch in text.chars() {
+        let width = char_width(ch);
+        if width == 0 {
+            continue;
+        }
+        if col + width > max_x {
+            break;
+        }
+        if let Some(cell) = buf.cell_mut((col, y)) {
+            cell.set_char(ch);
+            cell.set_style(style);
+        }
+        for offset in 1..width {
+            if let Some(cell) = buf.cell_mut((col + offset, y)) {
+                cell.set_char(' ');
+                cell.set_style(style);
+            }
+        }
+        col += width;
+    }
+    col
+}
+
+/// Word-wrap plain text to `width` display columns, breaking overlong words.
+fn wrap_plain(text: &str, width: usize) -> Vec<String> {
+    let width = width.max(1);
+    let mut out = Vec::new();
+    for line in text.lines() {
+        let mut current = String::new();
+        let mut current_width = 0;
+        for word in line.split(' ') {
+            let word_width = word.width();
+            let needed = if current.is_empty() { word_width } else { word_width + 1 };
+            if current_width + needed > width && !current.is_empty() {
+                out.push(std::mem::take(&mut current));
+                current_width = 0;
+            } else if !current.is_empty() {
+                current.push(' ');
+                current_width += 1;
+            }
+            // Hard-break words wider than a whole line
+            for ch in word.chars() {
+                let w = ch.width().unwrap_or(0);
+                if current_width + w > width {
+                    out.push(std::mem::take(&mut current));
+                    current_width = 0;
+                }
+                current.push(ch);
+                current_width += w;
+            }
+        }
+        out.push(current);
+    }
+    out
+}
+
 /// Render the slash command completion popup just above the input area.
 fn render_completion_popup(buf: &mut Buffer, state: &CompletionState, input_area: Rect, theme: &Theme) {
     if state.matches.is_empty() {
@@ -320,16 +506,7 @@ fn render_completion_popup(buf: &mut Buffer, state: &CompletionState, input_area
         let marker = if is_selected { " \u{25b8} " } else { "   " };
         let name_text = format!("{marker}/{}", item.name);
         let mut col = inner.x;
-        for ch in name_text.chars() {
-            if col >= inner.right() {
-                break;
-            }
-            if let Some(cell) = buf.cell_mut((col, y)) {
-                cell.set_char(ch);
-                cell.set_style(name_style);
-            }
-            col += 1;
-        }
+        col = put_str(buf, col, y, inner.right(), &name_text, name_style);
 
         // Write description (dim) if available
         if !item.description.is_empty() && col + 2 < inner.right() {
@@ -340,16 +517,7 @@ fn render_completion_popup(buf: &mut Buffer, state: &CompletionState, input_area
                 }
                 col += 1;
             }
-            for ch in item.description.chars() {
-                if col >= inner.right() {
-                    break;
-                }
-                if let Some(cell) = buf.cell_mut((col, y)) {
-                    cell.set_char(ch);
-                    cell.set_style(desc_style);
-                }
-                col += 1;
-            }
+            put_str(buf, col, y, inner.right(), &item.description, desc_style);
         }
     }
 }
@@ -360,12 +528,14 @@ pub fn render_overlay(frame: &mut Frame, title: &str, state: &OverlayState, them
     frame.render_widget(widget, frame.area());
 }
 
-/// Render a scrollable text viewer overlay on top of the UI.
+/// Render a scrollable text viewer overlay on top of the UI, highlighting
+/// line `selected` (the focused file in the diff viewer).
 pub fn render_text_viewer(
     frame: &mut Frame,
     title: &str,
     lines: &[String],
     scroll: usize,
+    selected: Option<usize>,
     theme: &Theme,
 ) {
     let area = frame.area();
@@ -412,12 +582,12 @@ pub fn render_text_viewer(
         .bg(theme.surface)
         .add_modifier(Modifier::BOLD);
     let code_style = Style::default().fg(theme.accent).bg(theme.surface);
-    let diff_add_style = Style::default()
-        .fg(ratatui::style::Color::Rgb(100, 255, 100))
-        .bg(theme.surface);
-    let diff_remove_style = Style::default()
-        .fg(ratatui::style::Color::Rgb(255, 100, 100))
-        .bg(theme.surface);
+    let diff_add_style = Style::default().fg(theme.diff_add).bg(theme.surface);
+    let diff_remove_style = Style::default().fg(theme.diff_remove).bg(theme.surface);
+    let diff_context_style = Style::default()
+        .fg(theme.diff_context)
+        .bg(theme.surface)
+        .add_modifier(Modifier::DIM);
     let diff_header_style = Style::default()
         .fg(theme.info)
         .bg(theme.surface)
@@ -449,56 +619,28 @@ pub fn render_text_viewer(
             // Render remove line with word-level highlighting
             let mut col = inner.x;
             // Write "- " prefix
-            for ch in "- ".chars() {
-                if col >= inner.right() { break; }
-                if let Some(cell) = buf.cell_mut((col, row_y)) {
-                    cell.set_char(ch);
-                    cell.set_style(diff_remove_style);
-                }
-                col += 1;
-            }
+            col = put_str(buf, col, row_y, inner.right(), "- ", diff_remove_style);
             for op in &word_ops {
                 let (text, style) = match op {
-                    DiffOp::Equal(t) => (*t, text_style.add_modifier(Modifier::DIM)),
+                    DiffOp::Equal(t) => (*t, diff_context_style),
                     DiffOp::Remove(t) => (*t, diff_remove_style),
                     DiffOp::Add(_) => continue, // skip adds on the remove line
                 };
-                for ch in text.chars() {
-                    if col >= inner.right() { break; }
-                    if let Some(cell) = buf.cell_mut((col, row_y)) {
-                        cell.set_char(ch);
-                        cell.set_style(style);
-                    }
-                    col += 1;
-                }
+                col = put_str(buf, col, row_y, inner.right(), text, style);
             }
 
             // Render add line with word-level highlighting
             let next_row_y = inner.y + (i + 1) as u16;
             if next_row_y < inner.bottom() {
                 let mut col = inner.x;
-                for ch in "+ ".chars() {
-                    if col >= inner.right() { break; }
-                    if let Some(cell) = buf.cell_mut((col, next_row_y)) {
-                        cell.set_char(ch);
-                        cell.set_style(diff_add_style);
-                    }
-                    col += 1;
-                }
+                col = put_str(buf, col, next_row_y, inner.right(), "+ ", diff_add_style);
                 for op in &word_ops {
                     let (text, style) = match op {
-                        DiffOp::Equal(t) => (*t, text_style.add_modifier(Modifier::DIM)),
+                        DiffOp::Equal(t) => (*t, diff_context_style),
                         DiffOp::Add(t) => (*t, diff_add_style),
                         DiffOp::Remove(_) => continue, // skip removes on the add line
                     };
-                    for ch in text.chars() {
-                        if col >= inner.right() { break; }
-                        if let Some(cell) = buf.cell_mut((col, next_row_y)) {
-                            cell.set_char(ch);
-                            cell.set_style(style);
-                        }
-                        col += 1;
-                    }
+                    col = put_str(buf, col, next_row_y, inner.right(), text, style);
                 }
             }
             skip_next = true;
@@ -512,7 +654,7 @@ pub fn render_text_viewer(
             diff_remove_style
         } else if line.starts_with("@@ ") {
             diff_header_style
-        } else if line.starts_with('#') {
+        } else if line.starts_with(['#', '\u{25B6}', '\u{25BC}']) {
             heading_style
         } else if line.starts_with("```") || line.starts_with('\t') {
             code_style
@@ -520,14 +662,10 @@ pub fn render_text_viewer(
             text_style
         };
 
-        for (j, ch) in line.chars().enumerate() {
-            let col_x = inner.x + j as u16;
-            if col_x >= inner.right() {
-                break;
-            }
-            if let Some(cell) = buf.cell_mut((col_x, row_y)) {
-                cell.set_char(ch);
-                cell.set_style(style);
+        put_str(buf, inner.x, row_y, inner.right(), line, style);
+        if selected == Some(scroll + i) {
+            for x in inner.x..inner.right() {
+                buf[(x, row_y)].set_bg(theme.overlay);
             }
         }
     }
@@ -618,17 +756,7 @@ pub fn render_history_search(
             format!("{marker}{first_line}")
         };
 
-        let mut col = inner.x;
-        for ch in display.chars() {
-            if col >= inner.right() {
-                break;
-            }
-            if let Some(cell) = buf.cell_mut((col, row_y)) {
-                cell.set_char(ch);
-                cell.set_style(entry_style);
-            }
-            col += 1;
-        }
+        put_str(buf, inner.x, row_y, inner.right(), &display, entry_style);
     }
 }
 
@@ -684,17 +812,11 @@ pub fn render_text_input(
     let text_style = Style::default().fg(theme.foreground).bg(theme.surface);
     let cursor_style = Style::default().fg(theme.surface).bg(theme.primary);
 
+    // `cursor` is a byte offset on a grapheme boundary
     let mut col = inner.x;
-    for (i, ch) in value.chars().enumerate() {
-        if col >= inner.right() {
-            break;
-        }
+    for (i, g) in value.grapheme_indices(true) {
         let style = if i == cursor { cursor_style } else { text_style };
-        if let Some(cell) = buf.cell_mut((col, text_y)) {
-            cell.set_char(ch);
-            cell.set_style(style);
-        }
-        col += 1;
+        col = put_str(buf, col, text_y, inner.right(), g, style);
     }
 
     // Show cursor at end if cursor == value length
@@ -772,18 +894,14 @@ pub fn render_user_question(
     let mut col = inner.x;
     let mut row = inner.y;
     for ch in question.chars() {
-        if col >= inner.right() {
+        if col + char_width(ch) > inner.right() {
             col = inner.x;
             row += 1;
         }
         if row >= inner.bottom() {
             break;
         }
-        if let Some(cell) = buf.cell_mut((col, row)) {
-            cell.set_char(ch);
-            cell.set_style(question_style);
-        }
-        col += 1;
+        col = put_str(buf, col, row, inner.right(), ch.encode_utf8(&mut [0; 4]), question_style);
     }
 
     // Render options starting 2 rows after question start
@@ -839,52 +957,28 @@ pub fn render_user_question(
 
         // Write marker + label
         let mut c = inner.x;
-        for ch in marker.chars() {
-            if c >= inner.right() { break; }
-            if let Some(cell) = buf.cell_mut((c, opt_y)) {
-                cell.set_char(ch);
-                cell.set_style(label_style);
-            }
-            c += 1;
-        }
-        for ch in label.chars() {
-            if c >= inner.right() { break; }
-            if let Some(cell) = buf.cell_mut((c, opt_y)) {
-                cell.set_char(ch);
-                cell.set_style(label_style);
-            }
-            c += 1;
-        }
+        c = put_str(buf, c, opt_y, inner.right(), marker, label_style);
+        c = put_str(buf, c, opt_y, inner.right(), label, label_style);
 
         // Write description (if room)
         if !description.is_empty() && c + 3 < inner.right() {
             // Separator
-            for ch in " - ".chars() {
-                if c >= inner.right() { break; }
-                if let Some(cell) = buf.cell_mut((c, opt_y)) {
-                    cell.set_char(ch);
-                    cell.set_style(desc_style);
-                }
-                c += 1;
-            }
-            for ch in description.chars() {
-                if c >= inner.right() { break; }
-                if let Some(cell) = buf.cell_mut((c, opt_y)) {
-                    cell.set_char(ch);
-                    cell.set_style(desc_style);
-                }
-                c += 1;
-            }
+            c = put_str(buf, c, opt_y, inner.right(), " - ", desc_style);
+            put_str(buf, c, opt_y, inner.right(), description, desc_style);
         }
     }
 }
 
 /// Render a plugin browser overlay showing available/installed/enabled plugins.
+/// `plugins` are those matching `query`, out of `total`; `searching` while
+/// the query is being typed.
 pub fn render_plugin_browser(
     frame: &mut Frame,
     plugins: &[PluginInfo],
+    total: usize,
+    query: &str,
+    searching: bool,
     cursor: usize,
-    _scroll: usize,
     theme: &Theme,
 ) {
     let area = frame.area();
@@ -900,8 +994,17 @@ pub fn render_plugin_browser(
     Clear.render(popup, buf);
 
     let enabled_count = plugins.iter().filter(|p| p.enabled).count();
-    let title = format!(" Plugins ({} available, {} enabled) ", plugins.len(), enabled_count);
-    let hint = " Enter:readme  Space:toggle  i:install  u:uninstall  Esc:close ";
+    let title = if query.is_empty() && !searching {
+        format!(" Plugins ({} available, {} enabled) ", plugins.len(), enabled_count)
+    } else {
+        let caret = if searching { "\u{2588}" } else { "" };
+        format!(" Plugins /{query}{caret} ({} of {total} match) ", plugins.len())
+    };
+    let hint = if searching {
+        " type to filter  Enter:done  Esc:clear "
+    } else {
+        " Enter:readme  Space:toggle  i:install  u:uninstall  /:search  a-z:jump  Esc:close "
+    };
 
     let block = Block::default()
         .title(title)
@@ -962,58 +1065,23 @@ pub fn render_plugin_browser(
         let mut col = inner.x;
         // Write " [+] "
         let icon_text = format!(" {} ", icon);
-        for ch in icon_text.chars() {
-            if col >= inner.right() { break; }
-            if let Some(cell) = buf.cell_mut((col, row_y)) {
-                cell.set_char(ch);
-                cell.set_style(icon_style);
-            }
-            col += 1;
-        }
+        col = put_str(buf, col, row_y, inner.right(), &icon_text, icon_style);
 
         // Write plugin name
-        for ch in plugin.name.chars() {
-            if col >= inner.right() { break; }
-            if let Some(cell) = buf.cell_mut((col, row_y)) {
-                cell.set_char(ch);
-                cell.set_style(name_style);
-            }
-            col += 1;
-        }
+        col = put_str(buf, col, row_y, inner.right(), &plugin.name, name_style);
 
         // Write MCP tag if applicable
         if plugin.is_mcp {
             let tag = " [MCP]";
-            for ch in tag.chars() {
-                if col >= inner.right() { break; }
-                if let Some(cell) = buf.cell_mut((col, row_y)) {
-                    cell.set_char(ch);
-                    cell.set_style(tag_style);
-                }
-                col += 1;
-            }
+            col = put_str(buf, col, row_y, inner.right(), tag, tag_style);
         }
 
         // Write " — description"
         let sep = " — ";
-        for ch in sep.chars() {
-            if col >= inner.right() { break; }
-            if let Some(cell) = buf.cell_mut((col, row_y)) {
-                cell.set_char(ch);
-                cell.set_style(desc_style);
-            }
-            col += 1;
-        }
+        col = put_str(buf, col, row_y, inner.right(), sep, desc_style);
 
         // Truncate description to fit
-        for ch in plugin.description.chars() {
-            if col >= inner.right() { break; }
-            if let Some(cell) = buf.cell_mut((col, row_y)) {
-                cell.set_char(ch);
-                cell.set_style(desc_style);
-            }
-            col += 1;
-        }
+        put_str(buf, col, row_y, inner.right(), &plugin.description, desc_style);
     }
 }
 
@@ -1037,7 +1105,7 @@ pub fn render_agent_dashboard(
 
     let active_count = tasks.iter().filter(|t| !t.completed).count();
     let title = format!(" Agent Dashboard ({} active / {} total) ", active_count, tasks.len());
-    let hint = " j/k:scroll  Esc:close ";
+    let hint = " j/k:scroll  Enter:details  x:mark cancel  a-z:jump  Esc:close ";
 
     let block = Block::default()
         .title(title)
@@ -1058,15 +1126,7 @@ pub fn render_agent_dashboard(
     // Header row
     let header = "  STATUS   TYPE             ELAPSED  DESCRIPTION";
     let header_style = Style::default().fg(theme.primary).bg(theme.surface).add_modifier(Modifier::BOLD);
-    let mut hx = inner.x;
-    for ch in header.chars() {
-        if hx >= inner.right() { break; }
-        if let Some(cell) = buf.cell_mut((hx, inner.y)) {
-            cell.set_char(ch);
-            cell.set_style(header_style);
-        }
-        hx += 1;
-    }
+    put_str(buf, inner.x, inner.y, inner.right(), header, header_style);
 
     // Separator line
     if inner.height > 1 {
@@ -1102,6 +1162,8 @@ pub fn render_agent_dashboard(
         // Status indicator
         let (status_icon, status_color) = if task.completed {
             ("  DONE  ", theme.success)
+        } else if task.cancel_requested {
+            ("  MARKED ", theme.error)
         } else {
             ("  RUNNING", theme.warning)
         };
@@ -1125,52 +1187,77 @@ pub fn render_agent_dashboard(
 
         let status_style = Style::default().fg(status_color).bg(row_bg);
         let type_style = Style::default().fg(theme.info).bg(row_bg);
-        let elapsed_style = Style::default().fg(theme.input_placeholder).bg(row_bg);
+        // Running agents shift towards red the longer they take, so stuck ones stand out
+        let elapsed_color = if task.completed { theme.input_placeholder } else { agent_elapsed_color(elapsed, theme) };
+        let elapsed_style = Style::default().fg(elapsed_color).bg(row_bg);
         let desc_style = Style::default().fg(theme.foreground).bg(row_bg);
 
         let mut col = inner.x;
 
         // Status
-        for ch in status_icon.chars() {
-            if col >= inner.right() { break; }
-            if let Some(cell) = buf.cell_mut((col, row_y)) {
-                cell.set_char(ch);
-                cell.set_style(status_style);
-            }
-            col += 1;
-        }
+        col = put_str(buf, col, row_y, inner.right(), status_icon, status_style);
         col += 1; // gap
 
         // Agent type
-        for ch in agent_type.chars() {
-            if col >= inner.right() { break; }
-            if let Some(cell) = buf.cell_mut((col, row_y)) {
-                cell.set_char(ch);
-                cell.set_style(type_style);
-            }
-            col += 1;
-        }
+        col = put_str(buf, col, row_y, inner.right(), &agent_type, type_style);
         col += 1; // gap
 
         // Elapsed
         let elapsed_padded = format!("{:>6}  ", elapsed_str);
-        for ch in elapsed_padded.chars() {
-            if col >= inner.right() { break; }
-            if let Some(cell) = buf.cell_mut((col, row_y)) {
-                cell.set_char(ch);
-                cell.set_style(elapsed_style);
-            }
-            col += 1;
-        }
+        col = put_str(buf, col, row_y, inner.right(), &elapsed_padded, elapsed_style);
 
         // Description
-        for ch in task.description.chars() {
-            if col >= inner.right() { break; }
-            if let Some(cell) = buf.cell_mut((col, row_y)) {
-                cell.set_char(ch);
-                cell.set_style(desc_style);
-            }
-            col += 1;
-        }
+        put_str(buf, col, row_y, inner.right(), &task.description, desc_style);
+    }
+}
+
+/// Colour of a running agent's elapsed time: info under a minute, warning
+/// under five, error after that.
+fn agent_elapsed_color(elapsed_secs: u64, theme: &Theme) -> Color {
+    match elapsed_secs {
+        0..60 => theme.info,
+        60..300 => theme.warning,
+        _ => theme.error,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_agent_elapsed_color_thresholds() {
+        let theme = Theme::default_theme();
+        assert_eq!(agent_elapsed_color(59, &theme), theme.info);
+        assert_eq!(agent_elapsed_color(60, &theme), theme.warning);
+        assert_eq!(agent_elapsed_color(299, &theme), theme.warning);
+        assert_eq!(agent_elapsed_color(300, &theme), theme.error);
+    }
+
+    fn row_symbols(buf: &Buffer, y: u16) -> Vec<String> {
+        (0..buf.area.width).map(|x| buf[(x, y)].symbol().to_string()).collect()
+    }
+
+    #[test]
+    fn test_put_str_advances_by_display_width() {
+        let mut buf = Buffer::empty(Rect::new(0, 0, 8, 1));
+        let end = put_str(&mut buf, 0, 0, 8, "a日b", Style::default());
+        assert_eq!(end, 4);
+        assert_eq!(row_symbols(&buf, 0)[..4], ["a", "日", " ", "b"]);
+    }
+
+    #[test]
+    fn test_put_str_stops_before_split_wide_char() {
+        let mut buf = Buffer::empty(Rect::new(0, 0, 4, 1));
+        let end = put_str(&mut buf, 0, 0, 4, "abc🎉", Style::default());
+        assert_eq!(end, 3);
+        assert_eq!(buf[(3, 0)].symbol(), " ");
+    }
+
+    #[test]
+    fn test_wrap_plain_uses_display_width() {
+        assert_eq!(wrap_plain("hello world", 5), vec!["hello", "world"]);
+        assert_eq!(wrap_plain("日本語テキスト", 6), vec!["日本語", "テキス", "ト"]);
+        assert_eq!(wrap_plain("a\n\nb", 10), vec!["a", "", "b"]);
     }
 }
@@ -1,9 +1,14 @@
+use fuzzy_matcher::skim::SkimMatcherV2;
+use fuzzy_matcher::FuzzyMatcher;
 use ratatui::buffer::Buffer;
 use ratatui::layout::Rect;
 use ratatui::style::{Modifier, Style};
 use ratatui::symbols::border;
 use ratatui::widgets::{Block, Borders, Clear, Widget};
 
+use unicode_width::UnicodeWidthStr;
+
+use super::put_str;
 use crate::theme::Theme;
 
 #[derive(Debug, Clone)]
@@ -11,6 +16,20 @@ pub struct OverlayItem {
     pub label: String,
     pub value: String,
     pub hint: String,
+    /// Non-selectable category header row (e.g. "Session" in the command palette).
+    pub is_header: bool,
+}
+
+impl OverlayItem {
+    /// A category header row.
+    pub fn header(label: &str) -> Self {
+        Self {
+            label: label.to_string(),
+            value: String::new(),
+            hint: String::new(),
+            is_header: true,
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -19,63 +38,136 @@ pub struct OverlayState {
     pub selected: usize,
     pub filter: String,
     pub original_theme: Option<String>,
+    /// Fuzzy-match the filter against labels and hints instead of substring matching.
+    pub fuzzy: bool,
+    /// Preview of the selected item, shown in a side panel (set by the owner
+    /// whenever the selection changes).
+    pub preview: Option<Vec<String>>,
 }
 
 impl OverlayState {
     pub fn new(items: Vec<OverlayItem>, original_theme: Option<String>) -> Self {
-        Self {
+        let mut state = Self {
             items,
             selected: 0,
             filter: String::new(),
             original_theme,
-        }
+            fuzzy: false,
+            preview: None,
+        };
+        state.select_first();
+        state
+    }
+
+    /// Create a fuzzy-filtered state (used by the command palette).
+    pub fn fuzzy(items: Vec<OverlayItem>) -> Self {
+        let mut state = Self::new(items, None);
+        state.fuzzy = true;
+        state
     }
 
     pub fn filtered_items(&self) -> Vec<(usize, &OverlayItem)> {
-        self.items
-            .iter()
-            .enumerate()
-            .filter(|(_, item)| {
-                if self.filter.is_empty() {
-                    return true;
+        if self.filter.is_empty() {
+            return self.items.iter().enumerate().collect();
+        }
+        let matcher = SkimMatcherV2::default();
+        let filter = self.filter.to_lowercase();
+        let matches = |item: &OverlayItem| {
+            if self.fuzzy {
+                let haystack = format!("{} {}", item.label, item.hint);
+                matcher.fuzzy_match(&haystack, &self.filter).is_some()
+            } else {
+                item.label.to_lowercase().contains(&filter)
+            }
+        };
+
+        // Keep a header only if at least one item in its section matches.
+        let mut result = Vec::new();
+        let mut pending_header = None;
+        for (i, item) in self.items.iter().enumerate() {
+            if item.is_header {
+                pending_header = Some((i, item));
+            } else if matches(item) {
+                if let Some(header) = pending_header.take() {
+                    result.push(header);
                 }
-                let lower = item.label.to_lowercase();
-                let filter = self.filter.to_lowercase();
-                lower.contains(&filter)
-            })
-            .collect()
+                result.push((i, item));
+            }
+        }
+        result
+    }
+
+    /// Select the first non-header row of the filtered list.
+    fn select_first(&mut self) {
+        self.selected = self
+            .filtered_items()
+            .iter()
+            .position(|(_, item)| !item.is_header)
+            .unwrap_or(0);
     }
 
     pub fn move_up(&mut self) {
-        let count = self.filtered_items().len();
-        if count > 0 {
-            self.selected = self.selected.checked_sub(1).unwrap_or(count - 1);
+        let filtered = self.filtered_items();
+        let count = filtered.len();
+        if !filtered.iter().any(|(_, item)| !item.is_header) {
+            return;
+        }
+        let mut idx = self.selected;
+        loop {
+            idx = idx.checked_sub(1).unwrap_or(count - 1);
+            if !filtered[idx].1.is_header {
+                break;
+            }
         }
+        self.selected = idx;
     }
 
     pub fn move_down(&mut self) {
-        let count = self.filtered_items().len();
-        if count > 0 {
-            self.selected = (self.selected + 1) % count;
+        let filtered = self.filtered_items();
+        let count = filtered.len();
+        if !filtered.iter().any(|(_, item)| !item.is_header) {
+            return;
+        }
+        let mut idx = self.selected;
+        loop {
+            idx = (idx + 1) % count;
+            if !filtered[idx].1.is_header {
+                break;
+            }
         }
+        self.selected = idx;
     }
 
     pub fn selected_value(&self) -> Option<String> {
         let filtered = self.filtered_items();
-        filtered.get(self.selected).map(|(_, item)| item.value.clone())
+        filtered
+            .get(self.selected)
+            .filter(|(_, item)| !item.is_header)
+            .map(|(_, item)| item.value.clone())
     }
 
     pub fn type_char(&mut self, c: char) {
         self.filter.push(c);
-        self.selected = 0;
+        self.select_first();
     }
 
     pub fn backspace(&mut self) {
         self.filter.pop();
-        self.selected = 0;
+        self.select_first();
     }
 }
 
+/// Type-ahead find for lists without a filter: the next entry after
+/// `current` (wrapping around) whose name starts with `c`, ignoring case.
+/// Repeated presses of the same letter cycle through the matches.
+pub fn find_by_initial<'a>(names: impl IntoIterator<Item = &'a str>, current: usize, c: char) -> Option<usize> {
+    let names: Vec<&str> = names.into_iter().collect();
+    let len = names.len();
+    (1..=len)
+        .map(|step| (current + step) % len)
+        .find(|&i| names[i].chars().next().is_some_and(|first| first.to_lowercase().eq(c.to_lowercase())))
+}
+
 /// Reusable overlay popup widget.
 pub struct OverlayWidget<'a> {
     pub title: &'a str,
@@ -104,11 +196,55 @@ impl<'a> OverlayWidget<'a> {
 
         Rect::new(x, y, width, height)
     }
+
+    /// Popup area plus the side panel for the preview, if there is one and
+    /// the screen is wide enough. The pair is centered together.
+    pub fn layout(&self, screen: Rect) -> (Rect, Option<Rect>) {
+        let popup = self.popup_area(screen);
+        if self.state.preview.is_none() {
+            return (popup, None);
+        }
+        let preview_width = popup.width.min(screen.width.saturating_sub(popup.width + 5));
+        if preview_width < 20 {
+            return (popup, None);
+        }
+        let total = popup.width + 1 + preview_width;
+        let x = screen.x + (screen.width.saturating_sub(total)) / 2;
+        (
+            Rect::new(x, popup.y, popup.width, popup.height),
+            Some(Rect::new(x + popup.width + 1, popup.y, preview_width, popup.height)),
+        )
+    }
+
+    fn render_preview(&self, area: Rect, lines: &[String], buf: &mut Buffer) {
+        Clear.render(area, buf);
+        let block = Block::default()
+            .title(" Preview ")
+            .title_style(Style::default().fg(self.theme.secondary))
+            .borders(Borders::ALL)
+            .border_set(border::ROUNDED)
+            .border_style(Style::default().fg(self.theme.border))
+            .style(Style::default().bg(self.theme.surface).fg(self.theme.foreground));
+        let inner = block.inner(area);
+        block.render(area, buf);
+
+        let style = Style::default().fg(self.theme.foreground).bg(self.theme.surface);
+        let wrapped: Vec<String> = lines
+            .iter()
+            .flat_map(|line| super::wrap_plain(line, inner.width as usize))
+            .collect();
+        for (row, line) in wrapped.iter().take(inner.height as usize).enumerate() {
+            put_str(buf, inner.x, inner.y + row as u16, inner.right(), line, style);
+        }
+    }
 }
 
 impl Widget for OverlayWidget<'_> {
     fn render(self, area: Rect, buf: &mut Buffer) {
-        let popup = self.popup_area(area);
+        let (popup, preview_area) = self.layout(area);
+        if let (Some(preview_area), Some(lines)) = (preview_area, self.state.preview.as_ref()) {
+            self.render_preview(preview_area, lines, buf);
+        }
 
         // Clear the area behind the popup
         Clear.render(popup, buf);
@@ -134,18 +270,8 @@ impl Widget for OverlayWidget<'_> {
         let prompt = "> ";
         let filter_text = format!("{}{}", prompt, self.state.filter);
         let filter_style = Style::default().fg(self.theme.accent).bg(self.theme.surface);
-        for (i, ch) in filter_text.chars().enumerate() {
-            let x = inner.x + i as u16;
-            if x >= inner.right() {
-                break;
-            }
-            if let Some(cell) = buf.cell_mut((x, filter_y)) {
-                cell.set_char(ch);
-                cell.set_style(filter_style);
-            }
-        }
         // Cursor indicator
-        let cursor_x = inner.x + filter_text.len() as u16;
+        let cursor_x = put_str(buf, inner.x, filter_y, inner.right(), &filter_text, filter_style);
         if cursor_x < inner.right() {
             if let Some(cell) = buf.cell_mut((cursor_x, filter_y)) {
                 cell.set_char('_');
@@ -189,6 +315,22 @@ impl Widget for OverlayWidget<'_> {
                 break;
             }
 
+            if item.is_header {
+                let style = Style::default()
+                    .fg(self.theme.accent)
+                    .bg(self.theme.surface)
+                    .add_modifier(Modifier::BOLD);
+                for x in inner.x..inner.right() {
+                    if let Some(cell) = buf.cell_mut((x, y)) {
+                        cell.set_char(' ');
+                        cell.set_style(style);
+                    }
+                }
+                let text = format!(" {}", item.label);
+                put_str(buf, inner.x, y, inner.right(), &text, style);
+                continue;
+            }
+
             let is_selected = vi + scroll == self.state.selected;
             let marker = if is_selected { " ▸ " } else { "   " };
             let label = &item.label;
@@ -213,16 +355,7 @@ impl Widget for OverlayWidget<'_> {
 
             // Write marker + label
             let text = format!("{}{}", marker, label);
-            for (i, ch) in text.chars().enumerate() {
-                let x = inner.x + i as u16;
-                if x >= inner.right() {
-                    break;
-                }
-                if let Some(cell) = buf.cell_mut((x, y)) {
-                    cell.set_char(ch);
-                    cell.set_style(style);
-                }
-            }
+            let text_end = put_str(buf, inner.x, y, inner.right(), &text, style);
 
             // Write hint on the right side
             if !hint.is_empty() {
@@ -231,16 +364,9 @@ impl Widget for OverlayWidget<'_> {
                 } else {
                     Style::default().fg(self.theme.border).bg(self.theme.surface)
                 };
-                let hint_start = inner.right().saturating_sub(hint.len() as u16 + 1);
-                for (i, ch) in hint.chars().enumerate() {
-                    let x = hint_start + i as u16;
-                    if x >= inner.right() || x <= inner.x + text.len() as u16 {
-                        continue;
-                    }
-                    if let Some(cell) = buf.cell_mut((x, y)) {
-                        cell.set_char(ch);
-                        cell.set_style(hint_style);
-                    }
+                let hint_start = inner.right().saturating_sub(hint.width() as u16 + 1);
+                if hint_start > text_end {
+                    put_str(buf, hint_start, y, inner.right(), hint, hint_style);
                 }
             }
         }
@@ -256,9 +382,22 @@ mod tests {
             label: label.to_string(),
             value: value.to_string(),
             hint: hint.to_string(),
+            is_header: false,
         }
     }
 
+    #[test]
+    fn test_find_by_initial_cycles_matches() {
+        let names = ["alpha", "Beta", "apex", "gamma"];
+        assert_eq!(find_by_initial(names, 0, 'a'), Some(2));
+        assert_eq!(find_by_initial(names, 2, 'a'), Some(0));
+        assert_eq!(find_by_initial(names, 0, 'b'), Some(1));
+        // The only match stays selected
+        assert_eq!(find_by_initial(names, 3, 'G'), Some(3));
+        assert_eq!(find_by_initial(names, 0, 'z'), None);
+        assert_eq!(find_by_initial([], 0, 'a'), None);
+    }
+
     #[test]
     fn test_overlay_state_navigation() {
         let mut state = OverlayState::new(
@@ -313,6 +452,51 @@ mod tests {
         assert_eq!(state.filter, "");
     }
 
+    #[test]
+    fn test_overlay_state_skips_headers() {
+        let mut state = OverlayState::fuzzy(vec![
+            OverlayItem::header("Session"),
+            item("Resume Session", "resume", ""),
+            OverlayItem::header("View"),
+            item("Split Pane", "split", "Ctrl+S"),
+        ]);
+        assert_eq!(state.selected, 1);
+        assert_eq!(state.selected_value(), Some("resume".to_string()));
+        state.move_down();
+        assert_eq!(state.selected_value(), Some("split".to_string()));
+        state.move_down();
+        assert_eq!(state.selected_value(), Some("resume".to_string()));
+        state.move_up();
+        assert_eq!(state.selected_value(), Some("split".to_string()));
+    }
+
+    #[test]
+    fn test_overlay_state_fuzzy_filter_keeps_headers() {
+        let mut state = OverlayState::fuzzy(vec![
+            OverlayItem::header("Session"),
+            item("Resume Session", "resume", ""),
+            OverlayItem::header("View"),
+            item("Split Pane", "split", "Ctrl+S"),
+            item("Diff Viewer", "diff", "Ctrl+G"),
+        ]);
+        for c in "splt".chars() {
+            state.type_char(c);
+        }
+        let filtered = state.filtered_items();
+        assert_eq!(filtered.len(), 2);
+        assert!(filtered[0].1.is_header);
+        assert_eq!(filtered[0].1.label, "View");
+        assert_eq!(state.selected_value(), Some("split".to_string()));
+    }
+
+    #[test]
+    fn test_overlay_state_only_headers_has_no_selection() {
+        let mut state = OverlayState::fuzzy(vec![OverlayItem::header("Empty")]);
+        state.move_down();
+        state.move_up();
+        assert_eq!(state.selected_value(), None);
+    }
+
     #[test]
     fn test_overlay_widget_renders_without_panic() {
         let theme = crate::theme::Theme::default_theme();
@@ -325,4 +509,24 @@ mod tests {
         let mut buf = Buffer::empty(area);
         widget.render(area, &mut buf);
     }
+
+    #[test]
+    fn test_overlay_layout_with_preview() {
+        let theme = crate::theme::Theme::default_theme();
+        let mut state = OverlayState::new(vec![item("A", "a", "")], None);
+        let screen = Rect::new(0, 0, 120, 30);
+        let (popup, preview) = OverlayWidget::new("Test", &state, &theme).layout(screen);
+        assert!(preview.is_none());
+        assert_eq!(popup, OverlayWidget::new("Test", &state, &theme).popup_area(screen));
+
+        state.preview = Some(vec!["line one".to_string()]);
+        let (popup, preview) = OverlayWidget::new("Test", &state, &theme).layout(screen);
+        let preview = preview.expect("wide screen fits a preview");
+        assert_eq!(preview.x, popup.right() + 1);
+        assert!(preview.right() <= screen.right());
+
+        let narrow = Rect::new(0, 0, 50, 30);
+        let (_, preview) = OverlayWidget::new("Test", &state, &theme).layout(narrow);
+        assert!(preview.is_none());
+    }
 }
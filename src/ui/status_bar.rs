@@ -1,15 +1,12 @@
 use ratatui::buffer::Buffer;
 use ratatui::layout::Rect;
-use ratatui::style::Style;
+use ratatui::style::{Color, Style};
 use ratatui::widgets::Widget;
 
 use crate::cost;
 use crate::git::GitInfo;
 use crate::theme::Theme;
 
-/// Default context window size in tokens (Claude's 200k window).
-const CONTEXT_WINDOW_TOKENS: u64 = 200_000;
-
 pub struct StatusBar<'a> {
     theme: &'a Theme,
     input_tokens: u64,
@@ -20,6 +17,14 @@ pub struct StatusBar<'a> {
     permission_mode: Option<&'a str>,
     /// Active tool name and elapsed seconds, if a tool is currently running.
     active_tool: Option<(&'a str, u64)>,
+    /// Effort level passed to claude (`--effort`), if any.
+    effort: Option<&'a str>,
+    /// Render a single plain line without segments, colours or key hints.
+    minimal: bool,
+    /// Show the REVIEW badge (read-only mode).
+    review: bool,
+    /// Seconds since the session started, shown after the wall clock.
+    session_elapsed: Option<u64>,
 }
 
 impl<'a> StatusBar<'a> {
@@ -42,7 +47,62 @@ impl<'a> StatusBar<'a> {
             model_name,
             permission_mode,
             active_tool,
+            effort: None,
+            minimal: false,
+            review: false,
+            session_elapsed: None,
+        }
+    }
+
+    pub fn with_review(mut self, review: bool) -> Self {
+        self.review = review;
+        self
+    }
+
+    pub fn with_effort(mut self, effort: Option<&'a str>) -> Self {
+        self.effort = effort;
+        self
+    }
+
+    /// Show the time of day and how long the session has run (`show_clock`).
+    pub fn with_clock(mut self, session_elapsed: Option<u64>) -> Self {
+        self.session_elapsed = session_elapsed;
+        self
+    }
+
+    pub fn minimal(mut self, minimal: bool) -> Self {
+        self.minimal = minimal;
+        self
+    }
+
+    /// Plain status text for minimal mode: model, token usage, cost and
+    /// the running tool, joined by dots.
+    fn minimal_text(&self) -> String {
+        let mut parts = Vec::new();
+        if self.review {
+            parts.push("REVIEW".to_string());
+        }
+        if let Some(model) = self.model_name {
+            parts.push(cost::short_model_name(model));
+        }
+        if self.input_tokens + self.output_tokens > 0 {
+            let pricing = cost::pricing_for_model(self.model_name.unwrap_or("sonnet"));
+            parts.push(format!(
+                "{} in / {} out",
+                format_tokens(self.input_tokens),
+                format_tokens(self.output_tokens),
+            ));
+            parts.push(cost::format_cost(
+                pricing.calculate_cost(self.input_tokens, self.output_tokens),
+            ));
+        }
+        if let Some((tool_name, elapsed)) = self.active_tool {
+            parts.push(format!("{tool_name} ({elapsed}s)"));
         }
+        if let Some(elapsed) = self.session_elapsed {
+            parts.push(clock_text(local_time(), elapsed));
+        }
+        format!(" {}", parts.join(" · "))
     }
 }
 
@@ -57,16 +117,69 @@ fn format_tokens(count: u64) -> String {
     }
 }
 
+/// Wall clock and session length, e.g. "12:34 · 0:45:10".
+fn clock_text((hour, minute): (u32, u32), elapsed: u64) -> String {
+    format!(
+        "{hour:02}:{minute:02} · {}:{:02}:{:02}",
+        elapsed / 3600,
+        (elapsed % 3600) / 60,
+        elapsed % 60
+    )
+}
+
+/// Current local hour and minute.
+#[cfg(unix)]
+fn local_time() -> (u32, u32) {
+    // SAFETY: a null argument makes `time` only return the value, and
+    // `localtime_r` writes into the zeroed `tm` we own.
+    let tm = unsafe {
+        let now = libc::time(std::ptr::null_mut());
+        let mut tm: libc::tm = std::mem::zeroed();
+        libc::localtime_r(&now, &mut tm);
+        tm
+    };
+    (tm.tm_hour as u32, tm.tm_min as u32)
+}
+
+/// Current hour and minute (UTC: no time zone lookup off unix).
+#[cfg(not(unix))]
+fn local_time() -> (u32, u32) {
+    let secs = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs());
+    (((secs / 3600) % 24) as u32, ((secs / 60) % 60) as u32)
+}
+
 /// Build a context budget bar string like "▓▓▓▓▓░░░░░" for the given usage ratio.
 /// Returns (bar_string, fill_ratio) where fill_ratio is 0.0..=1.0.
-fn context_bar(total_tokens: u64, bar_width: usize) -> (String, f64) {
-    let ratio = (total_tokens as f64 / CONTEXT_WINDOW_TOKENS as f64).min(1.0);
+fn context_bar(tokens: u64, context_window: u64, bar_width: usize) -> (String, f64) {
+    let ratio = (tokens as f64 / context_window.max(1) as f64).min(1.0);
     let filled = (ratio * bar_width as f64).round() as usize;
     let empty = bar_width.saturating_sub(filled);
     let bar = format!("{}{}", "█".repeat(filled), "░".repeat(empty));
     (bar, ratio)
 }
 
+/// Gauge colour for a context usage ratio: warning from 75%, error from 90%.
+fn context_color(ratio: f64, theme: &Theme) -> Color {
+    if ratio >= 0.9 {
+        theme.error
+    } else if ratio >= 0.75 {
+        theme.warning
+    } else {
+        theme.success
+    }
+}
+
+/// Indicator colour for an effort level: higher effort means longer thinking.
+fn effort_color(level: &str, theme: &Theme) -> Color {
+    match level {
+        "high" => theme.warning,
+        "low" => theme.success,
+        _ => theme.info,
+    }
+}
+
 /// Write a string into the buffer at (start_x, y) with the given style.
 /// Returns the x position after the last written character.
 fn write_str(buf: &mut Buffer, text: &str, x_start: u16, y: u16, x_limit: u16, style: Style) -> u16 {
@@ -88,6 +201,18 @@ impl<'a> Widget for StatusBar<'a> {
             .fg(self.theme.status_fg)
             .bg(self.theme.status_bg);
 
+        if self.minimal {
+            let plain = Style::default()
+                .fg(self.theme.input_placeholder)
+                .bg(self.theme.background);
+            for x in area.x..area.right() {
+                buf[(x, area.y)].set_style(plain);
+                buf[(x, area.y)].set_symbol(" ");
+            }
+            write_str(buf, &self.minimal_text(), area.x, area.y, area.right(), plain);
+            return;
+        }
+
         // Fill entire bar with background
         for x in area.x..area.right() {
             buf[(x, area.y)].set_style(style);
@@ -101,6 +226,15 @@ impl<'a> Widget for StatusBar<'a> {
             .bg(self.theme.status_bg);
         let mut left_end = write_str(buf, left, area.x, area.y, area.right(), left_style);
 
+        // Read-only review badge (after app name)
+        if self.review {
+            left_end = write_str(buf, " | ", left_end, area.y, area.right(), style);
+            let review_style = Style::default()
+                .fg(self.theme.status_bg)
+                .bg(self.theme.info);
+            left_end = write_str(buf, " REVIEW ", left_end, area.y, area.right(), review_style);
+        }
+
         // Permission mode indicator (after app name)
         if let Some(mode) = self.permission_mode {
             let (label, color) = match mode {
@@ -120,6 +254,17 @@ impl<'a> Widget for StatusBar<'a> {
             left_end = write_str(buf, label, left_end, area.y, area.right(), mode_style);
         }
 
+        // Effort level indicator (after permission mode)
+        if let Some(level) = self.effort {
+            let sep = " | ";
+            left_end = write_str(buf, sep, left_end, area.y, area.right(), style);
+            let effort_text = format!("EFFORT:{}", level.to_uppercase());
+            let effort_style = Style::default()
+                .fg(effort_color(level, self.theme))
+                .bg(self.theme.status_bg);
+            left_end = write_str(buf, &effort_text, left_end, area.y, area.right(), effort_style);
+        }
+
         // Git branch info (right after app name)
         if let Some(display) = self.git_info.display() {
             let sep = " | ";
@@ -165,12 +310,16 @@ impl<'a> Widget for StatusBar<'a> {
             .map(|m| cost::short_model_name(m))
             .unwrap_or_default();
 
+        let context_window = self.model_name
+            .map(cost::context_window_for_model)
+            .unwrap_or(cost::DEFAULT_CONTEXT_WINDOW);
+
         let center_text = if has_usage {
             let pricing = self.model_name
                 .map(|m| cost::pricing_for_model(m))
                 .unwrap_or_else(|| cost::pricing_for_model("sonnet"));
             let session_cost = pricing.calculate_cost(self.input_tokens, self.output_tokens);
-            let pct = ((total_tokens as f64 / CONTEXT_WINDOW_TOKENS as f64) * 100.0).min(100.0);
+            let pct = ((self.input_tokens as f64 / context_window as f64) * 100.0).min(100.0);
             format!(
                 " {} | {} in / {} out | {} | {:.0}% ",
                 short_model,
@@ -192,27 +341,36 @@ impl<'a> Widget for StatusBar<'a> {
 
         // Write center text
         let after_text = write_str(buf, &center_text, center_start, area.y, area.right(), style);
+        let mut center_end = if center_text.is_empty() { area.x } else { after_text };
 
         // Write context bar with color coding
         if has_usage {
-            let (bar, ratio) = context_bar(total_tokens, bar_width);
-            let bar_color = if ratio < 0.5 {
-                self.theme.success
-            } else if ratio < 0.8 {
-                self.theme.warning
-            } else {
-                self.theme.error
-            };
+            let (bar, ratio) = context_bar(self.input_tokens, context_window, bar_width);
+            let bar_color = context_color(ratio, self.theme);
             let bar_style = Style::default()
                 .fg(bar_color)
                 .bg(self.theme.status_bg);
-            write_str(buf, &bar, after_text, area.y, area.right(), bar_style);
+            center_end = write_str(buf, &bar, after_text, area.y, area.right(), bar_style);
         }
 
         // Right: help hint
-        let right = "^K:menu | ^S:split | ^D:diff | ^Q:quit ";
+        let right = "^K:menu | ^S:split | ^G:diff | ^Q:quit ";
         let right_start = area.right().saturating_sub(right.len() as u16);
         write_str(buf, right, right_start, area.y, area.right(), style);
+
+        // Clock and session length, just left of the help hint, left out
+        // when it would cover the center
+        if let Some(elapsed) = self.session_elapsed {
+            let clock = format!("{} | ", clock_text(local_time(), elapsed));
+            let clock_start = right_start.saturating_sub(clock.chars().count() as u16);
+            if clock_start < center_end {
+                return;
+            }
+            let clock_style = Style::default()
+                .fg(self.theme.info)
+                .bg(self.theme.status_bg);
+            write_str(buf, &clock, clock_start, area.y, right_start, clock_style);
+        }
     }
 }
 
@@ -242,29 +400,128 @@ mod tests {
 
     #[test]
     fn test_context_bar_empty() {
-        let (bar, ratio) = context_bar(0, 10);
+        let (bar, ratio) = context_bar(0, 200_000, 10);
         assert_eq!(bar, "░░░░░░░░░░");
         assert!((ratio - 0.0).abs() < f64::EPSILON);
     }
 
     #[test]
     fn test_context_bar_half() {
-        let (bar, ratio) = context_bar(100_000, 10);
+        let (bar, ratio) = context_bar(100_000, 200_000, 10);
         assert_eq!(bar, "█████░░░░░");
         assert!((ratio - 0.5).abs() < f64::EPSILON);
     }
 
     #[test]
     fn test_context_bar_full() {
-        let (bar, ratio) = context_bar(200_000, 10);
+        let (bar, ratio) = context_bar(200_000, 200_000, 10);
         assert_eq!(bar, "██████████");
         assert!((ratio - 1.0).abs() < f64::EPSILON);
     }
 
     #[test]
     fn test_context_bar_over_limit_capped() {
-        let (bar, ratio) = context_bar(300_000, 10);
+        let (bar, ratio) = context_bar(300_000, 200_000, 10);
         assert_eq!(bar, "██████████");
         assert!((ratio - 1.0).abs() < f64::EPSILON);
     }
+
+    #[test]
+    fn test_context_bar_large_window() {
+        let (bar, ratio) = context_bar(500_000, 1_000_000, 10);
+        assert_eq!(bar, "█████░░░░░");
+        assert!((ratio - 0.5).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn test_context_color_thresholds() {
+        let theme = Theme::default_theme();
+        assert_eq!(context_color(0.5, &theme), theme.success);
+        assert_eq!(context_color(0.75, &theme), theme.warning);
+        assert_eq!(context_color(0.89, &theme), theme.warning);
+        assert_eq!(context_color(0.9, &theme), theme.error);
+    }
+
+    #[test]
+    fn test_effort_color() {
+        let theme = Theme::default_theme();
+        assert_eq!(effort_color("high", &theme), theme.warning);
+        assert_eq!(effort_color("medium", &theme), theme.info);
+        assert_eq!(effort_color("low", &theme), theme.success);
+    }
+
+    #[test]
+    fn test_review_badge() {
+        let theme = Theme::default_theme();
+        let git = GitInfo::default();
+        let area = Rect::new(0, 0, 100, 1);
+        let mut buf = Buffer::empty(area);
+        StatusBar::new(&theme, 0, 0, &git, None, None, None, None)
+            .with_review(true)
+            .render(area, &mut buf);
+        let row: String = (0..100).map(|x| buf[(x, 0)].symbol().to_string()).collect();
+        assert!(row.starts_with(" sexy-claude |  REVIEW "));
+    }
+
+    #[test]
+    fn test_clock_text() {
+        assert_eq!(clock_text((9, 5), 0), "09:05 · 0:00:00");
+        assert_eq!(clock_text((12, 34), 45 * 60 + 10), "12:34 · 0:45:10");
+        assert_eq!(clock_text((23, 59), 3 * 3600 + 7), "23:59 · 3:00:07");
+    }
+
+    #[test]
+    fn test_clock_before_help_hint() {
+        let theme = Theme::default_theme();
+        let git = GitInfo::default();
+        let area = Rect::new(0, 0, 100, 1);
+        let row = |clock: Option<u64>| {
+            let mut buf = Buffer::empty(area);
+            StatusBar::new(&theme, 0, 0, &git, None, None, None, None)
+                .with_clock(clock)
+                .render(area, &mut buf);
+            (0..100).map(|x| buf[(x, 0)].symbol().to_string()).collect::<String>()
+        };
+        assert!(row(Some(2710)).contains(" · 0:45:10 | ^K:menu"));
+        assert!(row(None).ends_with("^K:menu | ^S:split | ^G:diff | ^Q:quit "));
+        assert!(!row(None).contains("0:45:10"));
+    }
+
+    #[test]
+    fn test_clock_never_covers_the_center() {
+        let theme = Theme::default_theme();
+        let git = GitInfo::default();
+        let row = |width: u16| {
+            let area = Rect::new(0, 0, width, 1);
+            let mut buf = Buffer::empty(area);
+            StatusBar::new(&theme, 52_800, 1_200, &git, None, Some("claude-sonnet-4"), None, None)
+                .with_clock(Some(2710))
+                .render(area, &mut buf);
+            (0..width).map(|x| buf[(x, 0)].symbol().to_string()).collect::<String>()
+        };
+        let narrow = row(120);
+        assert!(narrow.contains("52.8k in / 1.2k out"));
+        assert!(narrow.contains("% ██"));
+        assert!(!narrow.contains("0:45:10"));
+        assert!(row(200).contains(" · 0:45:10 | ^K:menu"));
+    }
+
+    #[test]
+    fn test_minimal_status_is_plain_one_line() {
+        let theme = Theme::default_theme();
+        let git = GitInfo::default();
+        let bar = StatusBar::new(&theme, 1500, 200, &git, Some("1/3"), Some("claude-sonnet-4"), Some("plan"), Some(("Bash", 4)))
+            .minimal(true);
+        let text = bar.minimal_text();
+        assert!(text.contains("1.5k in / 200 out"));
+        assert!(text.contains("Bash (4s)"));
+        assert!(!text.contains("PLAN"));
+
+        let area = Rect::new(0, 0, 80, 1);
+        let mut buf = Buffer::empty(area);
+        bar.render(area, &mut buf);
+        let row: String = (0..80).map(|x| buf[(x, 0)].symbol().to_string()).collect();
+        assert!(!row.contains("^K:menu"));
+        assert_eq!(buf[(0, 0)].bg, theme.background);
+    }
 }
@@ -4,7 +4,9 @@ use ratatui::style::Style;
 use ratatui::symbols::border;
 use ratatui::widgets::{Block, Borders, Clear, Widget};
 use std::time::Instant;
+use unicode_width::UnicodeWidthStr;
 
+use super::put_str;
 use crate::theme::Theme;
 
 /// Duration the toast is visible (total).
@@ -16,6 +18,8 @@ const FADE_DURATION_MS: u128 = 500;
 pub struct Toast {
     pub message: String,
     pub created_at: Instant,
+    /// Rendered in muted colours, for passive system notices.
+    pub dim: bool,
 }
 
 impl Toast {
@@ -23,6 +27,15 @@ impl Toast {
         Self {
             message,
             created_at: Instant::now(),
+            dim: false,
+        }
+    }
+
+    /// A muted toast for system notices that don't need attention.
+    pub fn dim(message: String) -> Self {
+        Self {
+            dim: true,
+            ..Self::new(message)
         }
     }
 
@@ -86,7 +99,7 @@ impl<'a> Widget for ToastWidget<'a> {
 
         // Toast dimensions: pad the message with some margin
         let text = &self.toast.message;
-        let content_width = text.len() as u16 + 2; // 1 padding each side
+        let content_width = text.width() as u16 + 2; // 1 padding each side
         let popup_width = content_width + 2; // +2 for border
         let popup_height: u16 = 3; // border + content line + border
 
@@ -101,8 +114,13 @@ impl<'a> Widget for ToastWidget<'a> {
 
         // Fade colors toward background
         let fade = 1.0 - opacity;
-        let fg = Self::lerp_color(self.theme.foreground, self.theme.surface, fade);
-        let border_color = Self::lerp_color(self.theme.border_focused, self.theme.surface, fade);
+        let (text_color, border_color) = if self.toast.dim {
+            (self.theme.input_placeholder, self.theme.border)
+        } else {
+            (self.theme.foreground, self.theme.border_focused)
+        };
+        let fg = Self::lerp_color(text_color, self.theme.surface, fade);
+        let border_color = Self::lerp_color(border_color, self.theme.surface, fade);
         let bg = self.theme.surface;
 
         // Clear area behind popup
@@ -126,16 +144,7 @@ impl<'a> Widget for ToastWidget<'a> {
         let y = inner.y;
         // Prefix with checkmark
         let display = format!(" {text}");
-        for (i, ch) in display.chars().enumerate() {
-            let x = inner.x + i as u16;
-            if x >= inner.right() {
-                break;
-            }
-            if let Some(cell) = buf.cell_mut((x, y)) {
-                cell.set_char(ch);
-                cell.set_style(style);
-            }
-        }
+        put_str(buf, inner.x, y, inner.right(), &display, style);
     }
 }
 
@@ -149,6 +158,12 @@ mod tests {
         assert!(!toast.is_expired());
     }
 
+    #[test]
+    fn test_toast_dim() {
+        assert!(!Toast::new("test".to_string()).dim);
+        assert!(Toast::dim("test".to_string()).dim);
+    }
+
     #[test]
     fn test_toast_opacity_starts_at_one() {
         let toast = Toast::new("test".to_string());
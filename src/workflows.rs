@@ -0,0 +1,117 @@
+/// User-defined workflow templates, loaded from `workflows.toml` files and
+/// shown in the workflow picker alongside the built-in ones.
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use serde::Deserialize;
+
+/// A named prompt in the workflow picker (Ctrl+W).
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Workflow {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    pub prompt: String,
+    /// Loaded from a file rather than built in.
+    #[serde(skip)]
+    pub custom: bool,
+}
+
+#[derive(Deserialize)]
+struct WorkflowFile {
+    #[serde(default)]
+    workflow: Vec<Workflow>,
+}
+
+/// Load custom workflows from the project (`.claude/workflows.toml`, so a
+/// team can share them in the repo) and from the user's config directory.
+/// Project workflows take precedence over user ones with the same name.
+/// Files that fail to load are skipped and their errors returned.
+pub fn load_all() -> (Vec<Workflow>, Vec<String>) {
+    let mut workflows = Vec::new();
+    let user_file = crate::config::config_dir().join("workflows.toml");
+    let errors = [Path::new(".claude/workflows.toml"), user_file.as_path()]
+        .into_iter()
+        .filter_map(|path| load_from(path, &mut workflows).err())
+        .map(|e| format!("{e:#}"))
+        .collect();
+    (workflows, errors)
+}
+
+/// Append the workflows in `path`, skipping names already loaded. A missing
+/// file adds nothing; one that fails to read or parse is an error.
+fn load_from(path: &Path, workflows: &mut Vec<Workflow>) -> Result<()> {
+    let content = match std::fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+        Err(e) => return Err(e).with_context(|| format!("Failed to read {}", path.display())),
+    };
+    let file: WorkflowFile =
+        toml::from_str(&content).with_context(|| format!("Invalid {}", path.display()))?;
+    for mut workflow in file.workflow {
+        if workflows.iter().any(|w| w.name == workflow.name) {
+            continue;
+        }
+        workflow.custom = true;
+        workflows.push(workflow);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_load_from_merges_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let project = dir.path().join("project.toml");
+        let user = dir.path().join("user.toml");
+        std::fs::write(
+            &project,
+            r#"
+[[workflow]]
+name = "Release Notes"
+description = "Draft release notes"
+prompt = "Write release notes for {version}."
+"#,
+        )
+        .unwrap();
+        std::fs::write(
+            &user,
+            r#"
+[[workflow]]
+name = "Release Notes"
+prompt = "Shadowed by the project file"
+
+[[workflow]]
+name = "Standup"
+prompt = "Summarize what I did yesterday."
+"#,
+        )
+        .unwrap();
+
+        let mut workflows = Vec::new();
+        load_from(&project, &mut workflows).unwrap();
+        load_from(&user, &mut workflows).unwrap();
+        load_from(&dir.path().join("missing.toml"), &mut workflows).unwrap();
+
+        assert_eq!(workflows.len(), 2);
+        assert_eq!(workflows[0].prompt, "Write release notes for {version}.");
+        assert_eq!(workflows[1].name, "Standup");
+        assert_eq!(workflows[1].description, "");
+        assert!(workflows.iter().all(|w| w.custom));
+    }
+
+    #[test]
+    fn test_invalid_file_is_reported() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("workflows.toml");
+        std::fs::write(&path, "[[workflow]]\nname = \"No prompt\"\n").unwrap();
+        let mut workflows = Vec::new();
+        let err = load_from(&path, &mut workflows).unwrap_err();
+        assert!(format!("{err:#}").starts_with(&format!("Invalid {}", path.display())));
+        assert!(format!("{err:#}").contains("prompt"));
+        assert!(workflows.is_empty());
+    }
+}